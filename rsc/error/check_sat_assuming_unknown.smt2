@@ -0,0 +1,10 @@
+(set-logic HORN)
+
+(declare-fun inv (Int) Bool)
+
+(assert (! (forall ((x Int)) (=> (= x 0) (inv x))) :named init))
+
+; Error, there is no clause named `step`.
+(check-sat-assuming (init (not step)))
+
+(exit)
@@ -0,0 +1,15 @@
+(set-logic HORN)
+
+(declare-fun inv (Int) Bool)
+
+(assert (! (forall ((x Int)) (=> (= x 0) (inv x))) :named init))
+(assert (! (forall ((x Int)) (=> (and (inv x) (< x 10)) (inv (+ x 1)))) :named step))
+(assert (! (forall ((x Int)) (=> (and (inv x) (> x 5)) false)) :named bad))
+
+; Unsat, `inv` reaches 10.
+(check-sat)
+
+; Sat without the `bad` clause.
+(check-sat-assuming (init step (not bad)))
+
+(exit)
@@ -0,0 +1,40 @@
+(set-logic HORN)
+
+(declare-datatypes ((ResetPair 0)) (
+  ((reset-pair (reset-fst Int) (reset-snd Int)))
+) )
+(define-fun reset_double ((x Int)) Int (* 2 x))
+(declare-fun inv (ResetPair) Bool)
+
+(assert (forall ((x Int)) (=> (= x 0) (inv (reset-pair x (reset_double x))))))
+(assert (forall ((p ResetPair))
+  (=> (and (inv p) (< (reset-fst p) 10))
+      (inv (reset-pair (+ (reset-fst p) 1) (reset_double (+ (reset-fst p) 1)))))
+))
+(assert (forall ((p ResetPair)) (=> (and (inv p) (> (reset-snd p) 10)) false)))
+
+; Unsat, `(reset-snd p)` reaches 20.
+(check-sat)
+
+(reset)
+
+; Same names, different declarations.
+(set-logic HORN)
+
+(declare-datatypes ((ResetPair 0)) (
+  ((reset-pair (reset-fst Int) (reset-snd Bool)))
+) )
+(define-fun reset_double ((x Int)) Int (+ x x))
+(declare-fun inv (ResetPair Int) Bool)
+
+(assert (forall ((x Int)) (=> (= x 0) (inv (reset-pair x true) (reset_double x)))))
+(assert (forall ((p ResetPair) (n Int))
+  (=> (and (inv p n) (< (reset-fst p) 10))
+      (inv (reset-pair (+ (reset-fst p) 1) (reset-snd p)) (reset_double n)))
+))
+(assert (forall ((p ResetPair) (n Int)) (=> (and (inv p n) (not (reset-snd p))) false)))
+
+; Sat, the result of the first check does not carry over.
+(check-sat)
+
+(exit)
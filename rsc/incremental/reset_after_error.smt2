@@ -0,0 +1,27 @@
+(set-logic HORN)
+
+; Fails to parse, `y` is not defined. The signature of `twice` is registered nonetheless.
+(define-funs-rec ((twice ((x Int)) Int)) ((* 2 y)))
+(declare-fun inv (Int) Bool)
+
+(assert (forall ((x Int)) (=> (= x 0) (inv x))))
+(assert (forall ((x Int)) (=> (and (inv x) (< x 0)) false)))
+
+; Sat.
+(check-sat)
+
+(reset)
+
+; Nothing from the first problem carries over, `twice` can be defined again.
+(set-logic HORN)
+
+(define-funs-rec ((twice ((x Int)) Int)) ((* 2 x)))
+(declare-fun inv (Int) Bool)
+
+(assert (forall ((x Int)) (=> (= x 1) (inv x))))
+(assert (forall ((x Int)) (=> (and (inv x) (= (twice x) 2)) false)))
+
+; Unsat, but sat if the definition of `twice` fails.
+(check-sat)
+
+(exit)
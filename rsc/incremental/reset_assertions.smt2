@@ -0,0 +1,22 @@
+(set-logic HORN)
+
+(declare-fun inv (Int) Bool)
+
+(assert (forall ((x Int)) (=> (= x 0) (inv x))))
+(assert (forall ((x Int)) (=> (and (inv x) (< x 10)) (inv (+ x 1)))))
+(assert (forall ((x Int)) (=> (and (inv x) (> x 5)) false)))
+
+; Unsat, `inv` reaches 10.
+(check-sat)
+
+(reset-assertions)
+
+; `inv` is still declared.
+(assert (forall ((x Int)) (=> (= x 0) (inv x))))
+(assert (forall ((x Int)) (=> (and (inv x) (< x 10)) (inv (+ x 1)))))
+(assert (forall ((x Int)) (=> (and (inv x) (> x 10)) false)))
+
+; Sat, the clauses of the first check are gone.
+(check-sat)
+
+(exit)
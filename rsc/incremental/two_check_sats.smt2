@@ -0,0 +1,15 @@
+(set-logic HORN)
+
+(declare-fun inv (Int) Bool)
+
+(assert (forall ((x Int)) (=> (= x 0) (inv x))))
+(assert (forall ((x Int)) (=> (and (inv x) (< x 10)) (inv (+ x 1)))))
+
+; Trivially sat.
+(check-sat)
+
+(assert (forall ((x Int)) (=> (and (inv x) (> x 10)) false)))
+
+(check-sat)
+
+(exit)
@@ -0,0 +1,33 @@
+(set-logic HORN)
+
+; Invariant: `b => x > 0`.
+(declare-fun inv ( Bool Int ) Bool)
+
+(assert
+  (forall ( (b Bool) (x Int) )
+    (=> (not b) (inv b x))
+  )
+)
+(assert
+  (forall ( (b Bool) (x Int) (b2 Bool) (x2 Int) )
+    (=>
+      (and (inv b x) (not b) b2 (= x2 1))
+      (inv b2 x2)
+    )
+  )
+)
+(assert
+  (forall ( (b Bool) (x Int) (b2 Bool) (x2 Int) )
+    (=>
+      (and (inv b x) b (= b2 b) (= x2 (+ x 1)))
+      (inv b2 x2)
+    )
+  )
+)
+(assert
+  (forall ( (b Bool) (x Int) )
+    (=> (and (inv b x) b (<= x 0)) false)
+  )
+)
+
+(check-sat)
@@ -0,0 +1,14 @@
+(set-logic HORN)
+
+; `P` appears in several rhs and several lhs, only `cfg_red` can inline it.
+(declare-fun P (Int Int) Bool)
+(declare-fun Q (Int Int) Bool)
+
+(assert (forall ((x Int) (y Int)) (=> (and (= x 0) (= y 0)) (P x y))))
+(assert (forall ((x Int) (y Int)) (=> (and (= x 1) (= y 1)) (P x y))))
+(assert (forall ((x Int) (y Int)) (=> (and (P x y) (> x 5)) false)))
+(assert (forall ((x Int) (y Int) (z Int)) (=> (and (P x y) (= z (+ x y))) (Q z z))))
+(assert (forall ((x Int) (y Int)) (=> (and (Q x y) (< x 0)) false)))
+
+(check-sat)
+(get-model)
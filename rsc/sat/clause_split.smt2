@@ -0,0 +1,31 @@
+(set-logic HORN)
+
+(declare-fun inv ( Int Int ) Bool)
+
+(assert (forall ( (x Int) (y Int) )
+  (=>
+    (and (or (= x 0) (= x 1)) (= y 0))
+    (inv x y)
+  )
+))
+
+(assert (forall ( (x Int) (y Int) (x2 Int) (y2 Int) )
+  (=>
+    (and
+      (inv x y)
+      (or (= x2 (+ x 2)) (= x2 (+ x 4)))
+      (or (= y2 (+ y 1)) (= y2 y))
+    )
+    (inv x2 y2)
+  )
+))
+
+(assert (forall ( (x Int) (y Int) )
+  (=>
+    (and (inv x y) (or (< x 0) (< y 0)))
+    false
+  )
+))
+
+(check-sat)
+(get-model)
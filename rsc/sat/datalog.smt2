@@ -0,0 +1,14 @@
+(set-logic HORN)
+
+(declare-fun cnt (Int Int) Bool)
+(declare-fun err () Bool)
+
+(declare-var x Int)
+(declare-var y Int)
+
+(rule (=> (= y 0) (cnt 0 y)) init)
+(rule (=> (and (cnt x y) (< x 10)) (cnt (+ x 1) (+ y 2))) step)
+(assert (forall ((n Int) (m Int)) (=> (and (cnt n m) (not (= m (* 2 n)))) err)))
+(query err)
+
+(check-sat)
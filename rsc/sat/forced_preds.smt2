@@ -0,0 +1,18 @@
+(set-logic HORN)
+
+(declare-fun inv (Int) Bool)
+; Never in a rhs, forced to false by pre-processing.
+(declare-fun src (Int) Bool)
+; Never in a lhs, forced to true by pre-processing.
+(declare-fun snk (Int) Bool)
+; Not mentioned at all.
+(declare-fun unused (Int Int) Bool)
+
+(assert (forall ((x Int)) (=> (= x 0) (inv x))))
+(assert (forall ((x Int)) (=> (and (inv x) (< x 10)) (inv (+ x 1)))))
+(assert (forall ((x Int)) (=> (and (src x) (inv x)) (inv (- x 1)))))
+(assert (forall ((x Int)) (=> (inv x) (snk x))))
+(assert (forall ((x Int)) (=> (and (inv x) (> x 10)) false)))
+
+(check-sat)
+(get-model)
@@ -0,0 +1,21 @@
+(set-logic HORN)
+
+(declare-fun edge (Int Int) Bool)
+(declare-fun path (Int Int) Bool)
+
+(assert (edge 0 1))
+(assert (edge 1 2))
+(assert (edge 2 3))
+(assert (edge 3 1))
+
+(assert (forall ((x Int) (y Int)) (=> (edge x y) (path x y))))
+(assert
+  (forall ((x Int) (y Int) (z Int))
+    (=> (and (path x y) (edge y z)) (path x z))
+  )
+)
+
+(assert (forall ((x Int)) (=> (path x 0) false)))
+(assert (=> (path 3 0) false))
+
+(check-sat)
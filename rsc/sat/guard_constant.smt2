@@ -0,0 +1,25 @@
+(set-logic HORN)
+
+; Invariant: `x <= 4096`, the constant only appears in the loop guard.
+(declare-fun inv ( Int ) Bool)
+
+(assert
+  (forall ( (x Int) )
+    (=> (= x 0) (inv x))
+  )
+)
+(assert
+  (forall ( (x Int) (x2 Int) )
+    (=>
+      (and (inv x) (< x 4096) (= x2 (+ x 1)))
+      (inv x2)
+    )
+  )
+)
+(assert
+  (forall ( (x Int) )
+    (=> (and (inv x) (> x 4096)) false)
+  )
+)
+
+(check-sat)
@@ -0,0 +1,21 @@
+; Safe on mathematical integers, unsafe on 8-bit machine integers (`--int_width 8`): `127 + 1`
+; wraps around to `-128`.
+(set-logic HORN)
+
+(declare-fun inv ( Int ) Bool)
+
+(assert
+  (forall ( (x Int) )
+    (=> (and (>= x 0) (<= x 127)) (inv x))
+  )
+)
+(assert
+  (forall ( (x Int) )
+    (=>
+      (and (inv x) (< (+ x 1) 0))
+      false
+    )
+  )
+)
+
+(check-sat)
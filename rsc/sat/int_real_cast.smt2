@@ -0,0 +1,24 @@
+(set-logic HORN)
+
+(declare-fun inv ( Int Real ) Bool)
+
+(assert
+  (forall ( (x Int) (r Real) )
+    (=> (and (= x 0) (>= r 0.0)) (inv x r))
+  )
+)
+(assert
+  (forall ( (x Int) (r Real) (x2 Int) (r2 Real) )
+    (=>
+      (and (inv x r) (= x2 (+ x 1)) (>= r2 (+ r 1.0)))
+      (inv x2 r2)
+    )
+  )
+)
+(assert
+  (forall ( (x Int) (r Real) )
+    (=> (and (inv x r) (> (to_real x) r)) false)
+  )
+)
+
+(check-sat)
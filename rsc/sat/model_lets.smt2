@@ -0,0 +1,15 @@
+(set-logic HORN)
+
+; Only in the rhs of a clause without predicates, its definition is forced by pre-processing and
+; mentions `(>= x 7)` twice.
+(declare-fun p (Int Bool Bool) Bool)
+
+(assert (forall ((x Int) (b Bool) (c Bool))
+  (=> (and (or (>= x 7) b) (or (>= x 7) c)) (p x b c))
+))
+(assert (forall ((x Int) (b Bool) (c Bool))
+  (=> (and (p x b c) (< x (- 100)) (not b) (not c)) false)
+))
+
+(check-sat)
+(get-model)
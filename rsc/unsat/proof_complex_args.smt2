@@ -0,0 +1,30 @@
+(set-option :produce-proofs true)
+
+(set-logic HORN)
+
+(declare-fun inv (Int Int) Bool)
+
+(assert
+  (forall ((x Int))
+    (=> (= x 0) (inv x (* 2 x)))
+  )
+)
+(assert
+  (forall ((x Int) (y Int))
+    (=>
+      (and (inv x y) (< x 5))
+      (inv (+ x 1) (+ y 2))
+    )
+  )
+)
+(assert
+  (forall ((x Int) (y Int))
+    (=>
+      (and (inv x y) (>= x 5))
+      (not (= y (* 2 x)))
+    )
+  )
+)
+
+(check-sat)
+(get-proof)
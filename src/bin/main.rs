@@ -14,22 +14,32 @@ use hoice::common::*;
 //     debug_assert_eq!(result, 0)
 // }
 
+/// Exit code when the solver cannot be spawned.
+const SOLVER_EXIT_CODE: i32 = 3;
+
 /// Entry point.
 fn main() {
     // renice();
     // Work and report error if any.
     if let Err(errs) = ::hoice::work() {
-        let errs = match *errs.kind() {
-            ErrorKind::Z3SpawnError => format!(
-                "could not spawn z3 using command `{}`\n\
+        if let Some(index) = errs.z3_spawn_index() {
+            let cause = errs
+                .iter()
+                .nth(index + 1)
+                .map(|cause| format!(": {}", cause))
+                .unwrap_or_else(String::new);
+            let errs: Error = format!(
+                "could not run `{}`{}\n\
                  make sure the z3 binary has that name and is in your path,\n\
-                 or specify a different z3 command with option `{}`",
+                 or specify a different z3 command with option `{} <path>`",
                 conf.emph(&conf.solver.conf().get_cmd()),
+                cause,
                 conf.emph("--z3")
             )
-            .into(),
-            _ => errs,
-        };
+            .into();
+            print_err(&errs);
+            ::std::process::exit(SOLVER_EXIT_CODE)
+        }
         print_err(&errs);
         ::std::process::exit(2)
     } else {
@@ -62,6 +62,9 @@ lazy_static! {
     static ref version_string: String = crate_version!().into();
     /// Version with revision info.
     pub static ref version: & 'static str = & version_string;
+    /// File the profiling information goes to, see `--profile_to`. `None` for stdout.
+    static ref profile_out: ::std::sync::Mutex<Option<::std::fs::File>> =
+        ::std::sync::Mutex::new(None);
 }
 
 // |===| Helpers.
@@ -77,17 +80,82 @@ impl<T> Discard for T {}
 #[cfg(feature = "bench")]
 pub fn print_stats(_: &'static str, _: Profiler) {}
 /// Prints the stats if asked. Does nothing in bench mode.
+///
+/// The stats go to the file opened by [`open_profile_out`] if any, without styling, and to stdout
+/// otherwise (stderr with `--chc_comp`).
+///
+/// [`open_profile_out`]: fn.open_profile_out.html (open_profile_out function)
 #[cfg(not(feature = "bench"))]
 pub fn print_stats(name: &str, profiler: Profiler) {
-    if conf.stats {
-        let others = profiler.drain_others();
-        println!();
-        profiler.print(name, "", &["data"]);
-        println!();
-        for (name, other) in others {
-            print_stats(&name, other)
+    if conf.show_stats {
+        // A panic while writing poisons the lock, the output itself is still usable.
+        let mut out = profile_out
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let res = if let Some(file) = out.as_mut() {
+            let mut bytes = vec![];
+            write_stats(&mut bytes, name, profiler).and_then(|()| {
+                file.write_all(strip_styles(&String::from_utf8_lossy(&bytes)).as_bytes())
+            })
+        } else if conf.chc_comp {
+            write_stats(&mut ::std::io::stderr(), name, profiler)
+        } else {
+            write_stats(&mut stdout(), name, profiler)
+        };
+        if let Err(e) = res {
+            warn!("failed to write profiling information: {}", e)
+        }
+    }
+}
+
+/// Writes a profiler and its others (not subs) profilers.
+#[cfg(not(feature = "bench"))]
+fn write_stats<W: Write>(w: &mut W, name: &str, profiler: Profiler) -> IoRes<()> {
+    let others = profiler.drain_others();
+    writeln!(w)?;
+    profiler.write_to(w, name, "", &["data"])?;
+    writeln!(w)?;
+    for (name, other) in others {
+        write_stats(w, &name, other)?
+    }
+    Ok(())
+}
+
+/// Removes the ANSI escape sequences of a string, used to style terminal output.
+#[cfg(not(feature = "bench"))]
+fn strip_styles(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip the sequence, its last character is a letter.
+            for c in &mut chars {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            res.push(c)
+        }
+    }
+    res
+}
+
+/// Opens the file the profiling information goes to, see `--profile_to`.
+///
+/// Does nothing if `--profile_to` is not set or is `-`, in which case the profiling information
+/// goes to stdout.
+pub fn open_profile_out() -> Res<()> {
+    if let Some(path) = conf.profile_to.as_ref() {
+        if path != "-" {
+            let file = ::std::fs::File::create(path)
+                .chain_err(|| format!("while creating profiling file `{}`", path))?;
+            *profile_out
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(file)
         }
     }
+    Ok(())
 }
 
 /// Lock corrupted error.
@@ -98,8 +166,8 @@ pub fn corrupted_err<T>(_: T) -> Error {
 /// Notifies the user and reads a line from stdin.
 pub fn pause(s: &str, _profiler: &Profiler) {
     let mut dummy = String::new();
-    println!();
-    println!("; {} {}...", conf.emph("press return"), s);
+    println_side!();
+    println_side!("; {} {}...", conf.emph("press return"), s);
     let _ = profile!(
       |_profiler| wrap {
         ::std::io::stdin().read_line(& mut dummy)
@@ -114,6 +182,39 @@ pub fn pause_msg(core: &msg::MsgCore, s: &str) {
     let _ = ::std::io::stdin().read_line(&mut dummy);
 }
 
+/// Creates a random number generator.
+///
+/// All the random number generators of hoice are created by this function, so that runs are
+/// reproducible: two runs with the same seed (`--seed`) make the same choices. The `salt`
+/// distinguishes the different generators of a run. Without a seed, the generator only depends on
+/// the salt.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::common::new_rng;
+/// use rand::RngCore;
+///
+/// let (mut rng_1, mut rng_2) = (new_rng(Some(7), 42), new_rng(Some(7), 42));
+/// assert_eq! { rng_1.next_u64(), rng_2.next_u64() }
+///
+/// let (mut rng_1, mut rng_2) = (new_rng(Some(7), 42), new_rng(Some(8), 42));
+/// assert_ne! { rng_1.next_u64(), rng_2.next_u64() }
+///
+/// let (mut rng_1, mut rng_2) = (new_rng(None, 42), new_rng(None, 42));
+/// assert_eq! { rng_1.next_u64(), rng_2.next_u64() }
+/// ```
+pub fn new_rng(seed: Option<u64>, salt: u8) -> Rng {
+    use rand::SeedableRng;
+    let mut bytes = [salt; 16];
+    if let Some(seed) = seed {
+        for (byte, seed_byte) in bytes.iter_mut().zip(seed.to_le_bytes().iter().cycle()) {
+            *byte ^= seed_byte
+        }
+    }
+    Rng::from_seed(bytes)
+}
+
 /// Identity function.
 pub fn identity<T>(t: T) -> T {
     t
@@ -255,6 +356,10 @@ pub enum TeachRes {
     Model(Candidates),
     /// An unsat result.
     Unsat(crate::unsat_core::UnsatRes),
+    /// The iteration limit was reached, last candidates of the learner.
+    ///
+    /// These candidates are **not** validated.
+    Partial(Candidates),
 }
 
 /// Quantified variables for a top term.
@@ -458,7 +563,7 @@ impl CanBEvaled for Term {
 
 /// Information returned by preprocessing techniques.
 #[must_use]
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct RedInfo {
     /// Number of predicates eliminated.
     pub preds: usize,
@@ -468,6 +573,13 @@ pub struct RedInfo {
     pub clauses_added: usize,
     /// Number of arguments removed.
     pub args_rmed: usize,
+    /// Number of predicate applications removed from clauses' LHS.
+    pub apps_rmed: usize,
+    /// Number of clause terms re-simplified after forcing a predicate.
+    ///
+    /// Not taken into account by [`non_zero`](#method.non_zero), as it does not change the
+    /// structure of the instance.
+    pub terms_simplified: usize,
 }
 impl RedInfo {
     /// Basic constructor.
@@ -477,6 +589,8 @@ impl RedInfo {
             clauses_rmed: 0,
             clauses_added: 0,
             args_rmed: 0,
+            apps_rmed: 0,
+            terms_simplified: 0,
         }
     }
     /// Constructor from the number of predicates eliminated.
@@ -499,7 +613,11 @@ impl RedInfo {
     }
     /// True if one or more fields are non-zero.
     pub fn non_zero(&self) -> bool {
-        self.preds > 0 || self.clauses_rmed > 0 || self.clauses_added > 0 || self.args_rmed > 0
+        self.preds > 0
+            || self.clauses_rmed > 0
+            || self.clauses_added > 0
+            || self.args_rmed > 0
+            || self.apps_rmed > 0
     }
 
     /// True if `clause_added > clause_rmed`.
@@ -524,6 +642,8 @@ impl From<(usize, usize, usize)> for RedInfo {
             clauses_rmed,
             clauses_added,
             args_rmed: 0,
+            apps_rmed: 0,
+            terms_simplified: 0,
         }
     }
 }
@@ -535,20 +655,25 @@ impl std::ops::AddAssign for RedInfo {
             clauses_rmed,
             clauses_added,
             args_rmed,
+            apps_rmed,
+            terms_simplified,
         }: Self,
     ) {
         self.preds += preds;
         self.clauses_rmed += clauses_rmed;
         self.clauses_added += clauses_added;
-        self.args_rmed += args_rmed
+        self.args_rmed += args_rmed;
+        self.apps_rmed += apps_rmed;
+        self.terms_simplified += terms_simplified
     }
 }
 impl_fmt! {
   RedInfo(self, fmt) {
     write!(
       fmt, "\
-        prd: {}, cls rm: {}, cls add: {}, args rm: {}\
-      ", self.preds, self.clauses_rmed, self.clauses_added, self.args_rmed
+        prd: {}, cls rm: {}, cls add: {}, args rm: {}, apps rm: {}, terms simpl: {}\
+      ", self.preds, self.clauses_rmed, self.clauses_added, self.args_rmed,
+      self.apps_rmed, self.terms_simplified
     )
   }
 }
@@ -195,6 +195,41 @@ make_conf! {
             self.conf.clone()
         }
 
+        /// True if the solver is z3.
+        ///
+        /// Some of the definitions hoice gives to the solver, such as the overloaded `dtyp-size`
+        /// function, are z3 extensions.
+        pub fn is_z3(&self) -> bool {
+            self.conf.style() == ::rsmt2::SmtStyle::Z3
+        }
+
+        /// Creates a solver, the only place where solver processes are spawned.
+        ///
+        /// Failing to spawn the solver yields a [`Z3SpawnError`].
+        ///
+        /// [`Z3SpawnError`]: ../../errors/enum.ErrorKind.html#variant.Z3SpawnError
+        /// (Z3SpawnError error kind)
+        fn new_solver<Parser>(
+            &self,
+            smt_conf: SolverConf,
+            parser: Parser,
+        ) -> Res<::rsmt2::Solver<Parser>> {
+            ::rsmt2::Solver::new(smt_conf, parser)
+                .chain_err(|| crate::errors::ErrorKind::Z3SpawnError)
+        }
+
+        /// Checks that the solver can be spawned and answers queries.
+        ///
+        /// Called once at startup so that a bad solver command is reported right away.
+        pub fn probe(&self) -> Res<()> {
+            let mut solver = self.new_solver(self.conf.clone(), ())?;
+            solver
+                .check_sat()
+                .chain_err(|| crate::errors::ErrorKind::Z3SpawnError)?;
+            solver.kill()?;
+            Ok(())
+        }
+
         /// Spawns a solver.
         ///
         /// Performs the solver initialization step given by `common::smt::init`.
@@ -214,8 +249,14 @@ make_conf! {
             if let Some(timeout) = crate::common::conf.until_timeout() {
                 smt_conf.option(format!("-T:{}", timeout.as_secs() + 1));
             }
+            // The teacher's checks, and thus the counterexamples, depend on z3's own choices.
+            if let Some(seed) = crate::common::conf.seed {
+                let seed = seed as u32;
+                smt_conf.option(format!("smt.random_seed={}", seed));
+                smt_conf.option(format!("sat.random_seed={}", seed));
+            }
 
-            let mut solver = ::rsmt2::Solver::new(smt_conf, parser)?;
+            let mut solver = self.new_solver(smt_conf, parser)?;
             if let Some(log) = self
                 .log_file(name, instance.as_ref())
                 .chain_err(|| format!("While opening log file for {}", crate::common::conf.emph(name)))?
@@ -224,7 +265,7 @@ make_conf! {
             }
 
             if preproc {
-                crate::smt::preproc_init(&mut solver)?
+                crate::smt::preproc_init(&mut solver, instance.as_ref())?
             } else {
                 crate::smt::init(&mut solver, instance)?
             }
@@ -313,6 +354,10 @@ pub struct InstanceConf {
     pub clause_capa: usize,
     /// Initial capacity of the predicate vector.
     pub pred_capa: usize,
+    /// Width of machine integers, `None` for mathematical integers.
+    pub int_width: Option<usize>,
+    /// Maximum number of clauses of the instances hoice works on, `None` for no limit.
+    pub max_clauses: Option<usize>,
 }
 impl SubConf for InstanceConf {
     fn need_out_dir(&self) -> bool {
@@ -321,16 +366,64 @@ impl SubConf for InstanceConf {
 }
 impl InstanceConf {
     /// Adds clap options to a clap App.
-    pub fn add_args(app: App, _: usize) -> App {
-        app
+    pub fn add_args(app: App, order: usize) -> App {
+        app.arg(
+            Arg::with_name("int_width")
+                .long("--int_width")
+                .help(
+                    "emulates machine integers of the given width (in bits) by wrapping \
+                     additions, subtractions and multiplications, `0` for none",
+                )
+                .long_help(
+                    "\
+                     Emulates signed machine integers of the given width (in bits), `0` for \
+                     none. Integer literals, and the results of additions, subtractions and \
+                     multiplications are wrapped modulo 2^width in the range [-2^(width-1), \
+                     2^(width-1)). The wrap-around is encoded with `mod` in the clauses when \
+                     parsing, so that the SMT solver, the simplifier and the evaluator agree \
+                     on it. Only this wrap-around is modeled: variables and the results of \
+                     other operators are mathematical integers.\
+                     ",
+                )
+                .validator(int_validator)
+                .value_name("int")
+                .default_value("0")
+                .takes_value(true)
+                .number_of_values(1)
+                .display_order(order + 1),
+        )
+        .arg(
+            Arg::with_name("max_clauses")
+                .long("--max_clauses")
+                .help(
+                    "answers `unknown` right away on instances with more clauses than this, \
+                     `0` for no limit",
+                )
+                .validator(int_validator)
+                .value_name("int")
+                .default_value("0")
+                .takes_value(true)
+                .number_of_values(1)
+                .display_order(order + 2),
+        )
     }
 
     /// Creates itself from some matches.
-    pub fn new(_: &Matches) -> Self {
+    pub fn new(matches: &Matches) -> Self {
+        let int_width = match int_of_matches(matches, "int_width") {
+            0 => None,
+            n => Some(n),
+        };
+        let max_clauses = match int_of_matches(matches, "max_clauses") {
+            0 => None,
+            n => Some(n),
+        };
         InstanceConf {
             term_capa: 3_000,
             clause_capa: 42,
             pred_capa: 42,
+            int_width,
+            max_clauses,
         }
     }
 }
@@ -373,6 +466,49 @@ make_conf! {
             |val| bool_of_match(val)
         }
 
+        explain_preproc, explain_preproc: bool {
+            help "Explains why pre-processors did not reduce predicates.",
+            long_help "\
+                If active, pre-processors record why they did not reduce each predicate they \
+                looked at, and a table of these reasons with their count is printed after \
+                pre-processing reaches its fixed point.\
+            ",
+            long "--explain_preproc",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "no",
+        } {
+            |val| bool_of_match(val)
+        }
+
+        max_clause_vars, max_clause_vars: Option<usize> {
+            help "Maximum number of variables of a clause produced by unfolding, 0 for none.",
+            long_help "\
+                Inlining and unfolding a predicate can produce clauses with a very large number \
+                of quantified variables. When this is not zero, `one_rhs`, `one_lhs` and \
+                `cfg_red` will not unfold a predicate if one of the resulting clauses would have more variables \
+                than this value. The predicate is then left as is.\
+            ",
+            long "--max_clause_vars",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| {
+                let value = int_of_match(mtch);
+                if value == 0 {
+                    None
+                } else {
+                    Some(value)
+                }
+            }
+        }
+
         prune_terms, prune_terms: bool {
             help "(De)activates expensive clause term pruning when simplifying clauses.",
             long_help "\
@@ -390,6 +526,41 @@ make_conf! {
             |val| bool_of_match(val)
         }
 
+        unit_prop, unit_prop: bool {
+            help "(De)activates fact propagation.",
+            long_help "\
+                If active, applications `(p c_1 ... c_n)` of a predicate to constants appearing \
+                as the consequent of a clause with no antecedent are removed from the \
+                antecedents of all the other clauses.\
+            ",
+            long "--unit_prop",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "on",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+
+        clause_split, clause_split: bool {
+            help "(De)activates clause splitting.",
+            long_help "\
+                If active, clauses with a top-level disjunction in their antecedent are split \
+                into one clause per disjunct, unless that would create too many clauses.\
+            ",
+            long "--clause_split",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "on",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+
         one_rhs, one_rhs: bool {
             help "(De)activates one rhs reduction.",
             long_help "\
@@ -922,6 +1093,75 @@ make_conf! {
             |mtch| bool_of_match(mtch)
         }
 
+        synth_max_size, synth_max_size: Option<usize> {
+            help "Maximum size of synthesized qualifiers, `0` for none.",
+            long_help "\
+                Synthesized qualifiers with more nodes than this value are discarded without \
+                being evaluated. The higher synthesis increment levels can produce very large \
+                qualifiers, this caps their size.\
+            ",
+            long "--synth_max_size",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| {
+                let value = int_of_match(mtch);
+                if value > 0 {
+                    Some(value)
+                } else {
+                    None
+                }
+            }
+        }
+
+        no_synth_preds, no_synth_preds: ::std::collections::HashSet<String> {
+            help "Predicates for which qualifier synthesis is deactivated.",
+            long_help "\
+                Comma-separated list of predicate names. The learner does not synthesize \
+                qualifiers for these predicates, it only uses the qualifiers mined from the \
+                instance and the ones provided by the user (hints). Also available as \
+                `(set-option :no-synth-preds \"P1 P2 ... Pn\")`.\
+            ",
+            long "--no_synth_preds",
+            val_name "preds",
+            default "",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| mtch
+                .split(',')
+                .map(|pred| pred.trim())
+                .filter(|pred| !pred.is_empty())
+                .map(|pred| pred.to_string())
+                .collect()
+        }
+
+        qual_hints, qual_hints: Option<String> {
+            help "File of qualifier hints, one term per line.",
+            long_help "\
+                Each non-empty line of the file that does not start with `;` is a boolean term \
+                where variable `v_i` is the `i`th argument of a predicate. The term is used as a \
+                qualifier for all the predicates whose signature is compatible with it. Hints \
+                that are malformed or compatible with no predicate are ignored with a warning.\
+            ",
+            long "--qual_hints",
+            val_name "file",
+            default "",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
+        }
+
         add_synth, add_synth: bool {
             help "Add synthesized qualifiers as normal qualifiers.",
             long_help "\
@@ -956,6 +1196,25 @@ make_conf! {
             |mtch| bool_of_match(mtch)
         }
 
+        share_cands, share_cands: bool {
+            help "Shares candidates between predicates with the same signature.",
+            long_help "\
+                If active, when the candidate of a predicate received no counterexample, the \
+                learner tries it first for the other predicates with the same signature, and \
+                seeds their qualifiers with the ones of the predicate. Useful when many \
+                predicates come from the same unrolled loop.\
+            ",
+            long "--share_cands",
+            validator bool_validator,
+            val_name bool_format,
+            default "off",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
         qual_step, qual_step: bool {
             help "Wait for user input on each (non-synthesis) qualifier.",
             long_help "\
@@ -1101,6 +1360,77 @@ make_conf! {
         } {
             |mtch| bool_of_match(mtch)
         }
+
+        max_iter, max_iter: Option<usize> {
+            help "Maximum number of learning iterations, `0` for none.",
+            long_help "\
+                Caps the number of candidates the teacher checks. When the limit is reached, \
+                hoice answers `unknown` and prints the last candidates of the learner as a \
+                partial model. This partial model is **not** validated: it is not a solution \
+                of the clauses.\
+            ",
+            long "--max_iter",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| {
+                let value = int_of_match(mtch);
+                if value > 0 {
+                    Some(value)
+                } else {
+                    None
+                }
+            }
+        }
+
+        mem_budget, mem_budget: Option<usize> {
+            help "Soft memory budget, in thousands of stored items, `0` for none.",
+            long_help "\
+                Soft memory budget, `0` for none. The memory footprint is estimated by the number \
+                of samples, constraints and qualifiers stored, in thousands. When over budget, \
+                the teacher drops old constraints with unclassified samples and the learner \
+                drops the qualifiers it has not used recently (see `--mem_budget_age`). This \
+                is a best-effort bound on the number of items stored, not on the memory used by \
+                the process: the SMT solvers and the term factory are not accounted for.\
+            ",
+            long "--mem_budget",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| {
+                let value = int_of_match(mtch);
+                if value > 0 {
+                    Some(value * 1000)
+                } else {
+                    None
+                }
+            }
+        }
+
+        mem_budget_age, mem_budget_age: usize {
+            help "Number of rounds after which constraints can be dropped when over budget.",
+            long_help "\
+                When over the memory budget (see `--mem_budget`), the teacher drops the \
+                constraints created more than this number of learning rounds ago, if all their \
+                samples are still unclassified, and the learner drops the qualifiers it has not \
+                created or selected in this number of learning rounds.\
+            ",
+            long "--mem_budget_age",
+            validator int_validator,
+            val_name "int",
+            default "20",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| int_of_match(mtch)
+        }
     }
 
     impl SubConf for TeacherConf {
@@ -1117,10 +1447,36 @@ pub struct Config {
     file: Option<String>,
     /// Verbosity.
     pub verb: usize,
-    /// Statistics flag.
+    /// Statistics flag: profiling information is collected.
+    ///
+    /// Set by `--stats`, and implied by `--profile_to` and `--profile_json`.
     pub stats: bool,
+    /// Print the profiling information, set by `--stats` and `--profile_to`.
+    pub show_stats: bool,
     /// Inference flag.
     pub infer: bool,
+    /// Parse-only flag.
+    pub parse_only: bool,
+    /// Dry-run flag: stop after pre-processing.
+    pub dry_run: bool,
+    /// Keep the datatypes declared when resetting.
+    pub keep_dtyps_on_reset: bool,
+    /// File to write the profiling information to, as JSON.
+    pub profile_json: Option<String>,
+    /// File to write the profiling information to, `-` for stdout.
+    pub profile_to: Option<String>,
+    /// Seed of the random number generators, see [`new_rng`](../fn.new_rng.html).
+    ///
+    /// Also passed to the solvers, so that the counterexamples of the teacher are reproducible.
+    pub seed: Option<u64>,
+    /// Model simplification flag.
+    pub simplify_model: bool,
+    /// Factor repeated subterms in model definitions with let-bindings.
+    pub model_lets: bool,
+    /// Report on the predicates of the model after solving.
+    pub model_stats: bool,
+    /// CHC-COMP output: only results on stdout, everything else on stderr.
+    pub chc_comp: bool,
     /// Reason on each negative clause separately.
     pub split: bool,
     /// Pause between negative clauses when in split mode.
@@ -1136,6 +1492,8 @@ pub struct Config {
     check: Option<String>,
     /// Eldarica result checking flag.
     pub check_eld: bool,
+    /// Check results against the status declared with `(set-info :status ...)`.
+    pub check_status: bool,
     /// If true, SMT-check all simplifications.
     pub check_simpl: bool,
     /// Level of term simplification.
@@ -1232,8 +1590,12 @@ impl Config {
             }
         }
 
+        // CHC-COMP output flag.
+        let chc_comp = bool_of_matches(&matches, "chc_comp");
+
         // Colors.
-        let color = ::atty::is(::atty::Stream::Stdout) && bool_of_matches(&matches, "color");
+        let color =
+            !chc_comp && ::atty::is(::atty::Stream::Stdout) && bool_of_matches(&matches, "color");
         let styles = Styles::new(color);
 
         // Output directory.
@@ -1243,11 +1605,44 @@ impl Config {
             .to_string();
 
         // Profiling.
-        let stats = bool_of_matches(&matches, "stats");
+        let show_stats = bool_of_matches(&matches, "stats");
 
         // Inference flag.
         let infer = bool_of_matches(&matches, "infer");
 
+        // Parse-only flag.
+        let parse_only = bool_of_matches(&matches, "parse_only");
+
+        // Dry-run flag.
+        let dry_run = bool_of_matches(&matches, "dry_run");
+
+        // Keep datatypes on reset flag.
+        let keep_dtyps_on_reset = bool_of_matches(&matches, "keep_dtyps_on_reset");
+
+        // JSON profiling output.
+        let profile_json = matches.value_of("profile_json").map(|s| s.to_string());
+
+        // Profiling output.
+        let profile_to = matches.value_of("profile_to").map(|s| s.to_string());
+
+        // Both profiling outputs need the profiling information to be collected.
+        let show_stats = show_stats || profile_to.is_some();
+        let stats = show_stats || profile_json.is_some();
+
+        // Seed for random number generators.
+        let seed = matches
+            .value_of("seed")
+            .map(|seed| int_of_match(seed) as u64);
+
+        // Model simplification flag.
+        let simplify_model = bool_of_matches(&matches, "simplify_model");
+
+        // Let-bindings in models flag.
+        let model_lets = bool_of_matches(&matches, "model_lets");
+
+        // Model report flag.
+        let model_stats = bool_of_matches(&matches, "model_stats");
+
         // Inference flag.
         let split_step = bool_of_matches(&matches, "split_step");
 
@@ -1262,6 +1657,7 @@ impl Config {
         // Result checking.
         let check = matches.value_of("check").map(|s| s.to_string());
         let check_eld = bool_of_matches(&matches, "check_eld");
+        let check_status = bool_of_matches(&matches, "check_status");
         let check_simpl = bool_of_matches(&matches, "check_simpl");
 
         // Timeout.
@@ -1277,7 +1673,18 @@ impl Config {
             file,
             verb,
             stats,
+            show_stats,
             infer,
+            parse_only,
+            dry_run,
+            keep_dtyps_on_reset,
+            profile_json,
+            profile_to,
+            seed,
+            simplify_model,
+            model_lets,
+            model_stats,
+            chc_comp,
             split,
             split_step,
             timeout,
@@ -1285,6 +1692,7 @@ impl Config {
             styles,
             check,
             check_eld,
+            check_status,
             check_simpl,
             term_simpl,
             instance,
@@ -1374,6 +1782,126 @@ impl Config {
                     .number_of_values(1)
                     .display_order(order()),
             )
+            .arg(
+                Arg::with_name("parse_only")
+                    .long("--parse_only")
+                    .help("only parse the input and print it back in canonical form")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("dry_run")
+                    .long("--dry_run")
+                    .help("stop after pre-processing and print statistics about the instance")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("keep_dtyps_on_reset")
+                    .long("--keep_dtyps_on_reset")
+                    .help("keep the datatypes declared so far on `(reset)`")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("profile_json")
+                    .long("--profile_json")
+                    .help(
+                        "writes the profiling information as JSON to a file after solving \
+                         (collects profiling information even without `--stats`)",
+                    )
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("profile_to")
+                    .long("--profile_to")
+                    .help(
+                        "writes the profiling information to a file instead of stdout, \
+                         `-` for stdout (implies profiling output even without `--stats`)",
+                    )
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("seed")
+                    .long("--seed")
+                    .help(
+                        "seed for the random choices of the learner and the solvers, \
+                         runs with the same seed are reproducible",
+                    )
+                    .validator(int_validator)
+                    .value_name("int")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("simplify_model")
+                    .long("--simplify_model")
+                    .help("(de)activates model simplification before printing")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("on")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("model_lets")
+                    .long("--model_lets")
+                    .help("factors repeated subterms in model definitions with let-bindings")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("model_stats")
+                    .long("--model_stats")
+                    .help(
+                        "reports sample counts, clause counts, origin and definition size \
+                         for each predicate of the model",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("chc_comp")
+                    .long("--chc_comp")
+                    .help(
+                        "CHC-COMP output: only `sat`, `unsat`, `unknown` and models on stdout, \
+                         everything else on stderr, no colors",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
             .arg(
                 Arg::with_name("timeout")
                     .long("--timeout")
@@ -1461,6 +1989,20 @@ impl Config {
                 .number_of_values(1)
                 .display_order(order()),
         )
+        .arg(
+            Arg::with_name("check_status")
+                .long("--check_status")
+                .help(
+                    "fail if a result contradicts the status declared with \
+                     `(set-info :status ...)`",
+                )
+                .validator(bool_validator)
+                .value_name(bool_format)
+                .default_value("no")
+                .takes_value(true)
+                .number_of_values(1)
+                .display_order(order()),
+        )
     }
 }
 
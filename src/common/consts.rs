@@ -148,6 +148,8 @@ pub mod keywords {
                 store_ ("store", doc = "Updater for arrays.")
                 select_ ("select", doc = "Accessor for arrays.")
 
+                dtyp_size_ ("dtyp-size", doc = "Structural size of a datatype value.")
+
                 match_ ("match", doc = "Match operator.")
 
                 lambda_ ("_", doc = "Lambda abstraction.")
@@ -179,11 +181,16 @@ pub mod keywords {
                 assert ("assert", doc = "Assertion keyword.")
 
                 check_sat ("check-sat", doc = "Check-sat keyword.")
+                check_sat_assuming (
+                    "check-sat-assuming",
+                    doc = "Check-sat-assuming keyword."
+                )
                 get_model ("get-model", doc = "Get-model keyword.")
                 get_unsat_core ("get-unsat-core", doc = "Get-unsat-core keyword.")
                 get_proof ("get-proof", doc = "Get-proof keyword.")
 
                 reset ("reset", doc = "Reset keyword.")
+                reset_assertions ("reset-assertions", doc = "Reset-assertions keyword.")
                 exit  ("exit", doc = "Exit keyword.")
             }
         }
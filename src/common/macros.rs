@@ -158,13 +158,13 @@ macro_rules! log {
 
   ( > $pref:expr => $( $str:expr $(, $args:expr)* $(,)* );* ) => ({
     $(
-        println!("{}{}", $pref, format!($str $(, $args)*)) ;
+        println_side!("{}{}", $pref, format!($str $(, $args)*)) ;
     )*
     ()
   }) ;
   ( > $( $str:expr $(, $args:expr)* $(,)* );* ) => ({
     $(
-      println!("; {}", format!($str $(, $args)*))
+      println_side!("; {}", format!($str $(, $args)*))
     )*
     ()
   }) ;
@@ -173,9 +173,9 @@ macro_rules! log {
     $(
       for line in format!($str $(, $args)*).lines() {
         if line != "" {
-          println!("{}{}", $pref, line)
+          println_side!("{}{}", $pref, line)
         } else {
-          println!()
+          println_side!()
         }
       }
     )*
@@ -185,9 +185,9 @@ macro_rules! log {
     $(
       for line in format!($str $(, $args)*).lines() {
         if line != "" {
-          println!("; {}", line)
+          println_side!("; {}", line)
         } else {
-          println!()
+          println_side!()
         }
       }
     )*
@@ -230,20 +230,45 @@ macro_rules! log_debug {
   ) ;
 }
 
+/// Same as `println!` for output that is not a result.
+///
+/// Prints to stderr with `--chc_comp`, so that stdout only contains results and models.
+#[macro_export]
+macro_rules! println_side {
+  ( $($stuff:tt)* ) => (
+    if $crate::common::conf.chc_comp {
+      eprintln!( $($stuff)* )
+    } else {
+      println!( $($stuff)* )
+    }
+  ) ;
+}
+/// Same as `print!` for output that is not a result, see `println_side`.
+#[macro_export]
+macro_rules! print_side {
+  ( $($stuff:tt)* ) => (
+    if $crate::common::conf.chc_comp {
+      eprint!( $($stuff)* )
+    } else {
+      print!( $($stuff)* )
+    }
+  ) ;
+}
+
 /// Prints a warning SMT-LIB-style.
 ///
 /// **Active in bench mode.**
 #[macro_export]
 macro_rules! warn {
   ( $( $str:expr $(, $args:expr)* $(,)* );* ) => ({
-    println!(
+    println_side!(
       "; {}", $crate::common::conf.sad("|===| Warning:")
     ) ;
     $(
-      print!("; {} ", $crate::common::conf.sad("|")) ;
-      println!( $str $(, $args)* ) ;
+      print_side!("; {} ", $crate::common::conf.sad("|")) ;
+      println_side!( $str $(, $args)* ) ;
     )*
-    println!("; {}", $crate::common::conf.sad("|===|"))
+    println_side!("; {}", $crate::common::conf.sad("|===|"))
   }) ;
 }
 
@@ -22,6 +22,21 @@ impl DurationExt for Duration {
     }
 }
 
+/// Escapes a string so that it can appear in a JSON string literal.
+pub fn json_escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            c if c.is_control() => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
 /// Profile Tree.
 #[derive(PartialEq, Eq)]
 pub struct ProfileTree {
@@ -50,24 +65,29 @@ impl ProfileTree {
     /// Debug printing (multi-line).
     #[cfg(feature = "bench")]
     #[allow(dead_code)]
-    fn print<S>(&self, _: S, _: &[&'static str]) {}
+    fn write_to<W: Write, S>(&self, _: &mut W, _: S, _: &[&'static str]) -> IoRes<()> {
+        Ok(())
+    }
     #[cfg(not(feature = "bench"))]
     #[cfg_attr(feature = "cargo-clippy", allow(print_literal))]
-    fn print<S>(&self, pref: S, set_sum: &[&'static str])
+    fn write_to<W, S>(&self, w: &mut W, pref: S, set_sum: &[&'static str]) -> IoRes<()>
     where
+        W: Write,
         S: Into<String>,
     {
         let pref = pref.into();
         self.fold(
-            None,
+            Ok(None),
             |prev, scope, time, sub_time| {
+                let prev = prev?;
                 if let Some(last) = scope.last() {
                     debug_assert! { ! scope.is_empty() }
                     let art = match prev {
                         Some(n) if n < scope.len() => "\\",
                         Some(_) | None => "|",
                     };
-                    println!(
+                    writeln!(
+                        w,
                         "; {5}{0: >1$}{6}- {2}s {3}{4}",
                         "",
                         // Can't be negative because `scope` contains `last`.
@@ -81,10 +101,11 @@ impl ProfileTree {
                         },
                         pref,
                         art
-                    );
-                    Some(scope.len())
+                    )?;
+                    Ok(Some(scope.len()))
                 } else {
-                    println!(
+                    writeln!(
+                        w,
                         "; {}{} {}s{}",
                         pref,
                         conf.happy("total"),
@@ -94,12 +115,13 @@ impl ProfileTree {
                         } else {
                             "".into()
                         }
-                    );
-                    None
+                    )?;
+                    Ok(None)
                 }
             },
             set_sum,
-        );
+        )
+        .map(|_| ())
     }
 
     /// Inserts something in the tree.
@@ -123,9 +145,9 @@ impl ProfileTree {
     /// Iterator on the tree.
     ///
     /// Scopes are guaranteed to follow the topological order.
-    pub fn fold<F, T>(&self, init: T, f: F, set_sum: &[&'static str])
+    pub fn fold<F, T>(&self, init: T, mut f: F, set_sum: &[&'static str]) -> T
     where
-        F: Fn(T, &[&'static str], &Duration, Duration) -> T,
+        F: FnMut(T, &[&'static str], &Duration, Duration) -> T,
     {
         let mut prev = init;
         if let Some(duration) = self.duration.as_ref() {
@@ -175,6 +197,7 @@ impl ProfileTree {
                 ))
             }
         }
+        prev
     }
 }
 
@@ -185,9 +208,18 @@ pub trait CanPrint {
     /// True if at least one value is not `0`.
     fn has_non_zero(&self) -> bool;
     /// Debug print (multi-line).
-    fn print<S>(&self, s: S)
+    fn write_to<W, S>(&self, w: &mut W, s: S) -> IoRes<()>
     where
+        W: Write,
         S: Into<String>;
+    /// Debug print (multi-line) on stdout.
+    fn print<S>(&self, s: S)
+    where
+        S: Into<String>,
+    {
+        self.write_to(&mut stdout(), s)
+            .expect("failed to write to stdout")
+    }
 }
 static STAT_LEN: usize = 29;
 impl CanPrint for Stats {
@@ -195,8 +227,9 @@ impl CanPrint for Stats {
         self.values().any(|n| *n > 0)
     }
     #[cfg_attr(feature = "cargo-clippy", allow(print_literal))]
-    fn print<S>(&self, pref: S)
+    fn write_to<W, S>(&self, w: &mut W, pref: S) -> IoRes<()>
     where
+        W: Write,
         S: Into<String>,
     {
         let pref = pref.into();
@@ -205,24 +238,27 @@ impl CanPrint for Stats {
         for (stat, count) in stats {
             if *count > 0 {
                 let stat_len = ::std::cmp::min(STAT_LEN, stat.len());
-                println!(
+                writeln!(
+                    w,
                     "; {4}  {0: >1$}{2}: {3: >5}",
                     "",
                     STAT_LEN - stat_len,
                     conf.emph(stat),
                     count,
                     pref
-                )
+                )?
             }
         }
+        Ok(())
     }
 }
 
 /// Maps scopes to
 ///
-/// - a (start) instant option: `Some` if the scope is currently active, and
-/// - a duration representing the total runtime of this scope.
-pub type InstantMap = BTreeMap<Vec<&'static str>, (Option<Instant>, Duration)>;
+/// - a (start) instant option: `Some` if the scope is currently active,
+/// - a duration representing the total runtime of this scope, and
+/// - the number of times the scope was measured.
+pub type InstantMap = BTreeMap<Vec<&'static str>, (Option<Instant>, Duration, usize)>;
 
 // The following import is not used in bench mode.
 #[allow(unused_imports)]
@@ -285,12 +321,14 @@ impl Profiler {
         for sub in subs {
             self.subs.get_mut().push(sub)
         }
-        for (scope, (_, duration)) in map {
-            self.map
+        for (scope, (_, duration, count)) in map {
+            let entry = self
+                .map
                 .get_mut()
                 .entry(scope)
-                .or_insert_with(|| (None, Duration::new(0, 0)))
-                .1 += duration
+                .or_insert_with(|| (None, Duration::new(0, 0), 0));
+            entry.1 += duration;
+            entry.2 += count
         }
         for (scope, val) in stats {
             *self.stats.get_mut().entry(scope).or_insert_with(|| 0) += val
@@ -309,12 +347,14 @@ impl Profiler {
         for sub in subs {
             self.subs.get_mut().push(sub)
         }
-        for (scope, (_, duration)) in map {
-            self.map
+        for (scope, (_, duration, count)) in map {
+            let entry = self
+                .map
                 .get_mut()
                 .entry(scope)
-                .or_insert_with(|| (None, Duration::new(0, 0)))
-                .1 += duration
+                .or_insert_with(|| (None, Duration::new(0, 0), 0));
+            entry.1 += duration;
+            entry.2 += count
         }
         for (scope, val) in stats {
             *self.stats.get_mut().entry(scope).or_insert_with(|| 0) = val
@@ -344,7 +384,7 @@ impl Profiler {
         let mut map = self.map.borrow_mut();
         let time = map
             .entry(scope)
-            .or_insert_with(|| (None, Duration::from_secs(0)));
+            .or_insert_with(|| (None, Duration::from_secs(0), 0));
         time.0 = Some(Instant::now())
     }
 
@@ -358,11 +398,12 @@ impl Profiler {
             panic!("Profile: can't use scope `total`")
         }
         let mut map = self.map.borrow_mut();
-        if let Some(&mut (ref mut tick, ref mut sum)) = map.get_mut(&scope) {
+        if let Some(&mut (ref mut tick, ref mut sum, ref mut count)) = map.get_mut(&scope) {
             let mut instant = None;
             ::std::mem::swap(&mut instant, tick);
             if let Some(instant) = instant {
                 *sum += Instant::now().duration_since(instant);
+                *count += 1;
                 *tick = None
             }
         } else {
@@ -377,7 +418,7 @@ impl Profiler {
     #[cfg(not(feature = "bench"))]
     fn extract(self) -> (ProfileTree, Stats, Vec<(String, Profiler)>) {
         let mut tree = ProfileTree::top(Instant::now().duration_since(self.start));
-        for (scope, &(ref should_be_none, ref time)) in self.map.borrow().iter() {
+        for (scope, &(ref should_be_none, ref time, _)) in self.map.borrow().iter() {
             if should_be_none.is_some() {
                 warn!(
                     "Profile::extract_tree: \
@@ -390,6 +431,102 @@ impl Profiler {
         (tree, self.stats.into_inner(), self.subs.into_inner())
     }
 
+    /// Serializes the durations of the profiler as a JSON object.
+    ///
+    /// Maps each scope, written `category.subcategory`, to an object with the total time spent in
+    /// this scope in milliseconds (`wall_ms`) and the number of times it was measured (`count`).
+    /// The scopes of the sub-profilers and of the other profilers, typically the ones of the
+    /// learners, are prefixed with their name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let profiler = Profiler::new();
+    /// profiler.tick(vec!["preproc", "simplify"]);
+    /// profiler.mark(vec!["preproc", "simplify"]);
+    /// let json = profiler.to_json();
+    /// assert! { json.starts_with("{") && json.ends_with("}") }
+    /// assert! { json.contains(r#""preproc.simplify": { "wall_ms": "#) }
+    /// assert! { json.contains(r#""count": 1 }"#) }
+    /// assert_eq! { Profiler::new().to_json(), "{}" }
+    ///
+    /// let (sub, other) = (Profiler::new(), Profiler::new());
+    /// sub.tick(vec!["check"]);
+    /// sub.mark(vec!["check"]);
+    /// other.tick(vec!["learning"]);
+    /// other.mark(vec!["learning"]);
+    /// profiler.add_sub("teacher", sub);
+    /// profiler.add_other("ice", other);
+    /// let json = profiler.to_json();
+    /// assert! { json.contains(r#""teacher.check": { "wall_ms": "#) }
+    /// assert! { json.contains(r#""ice.learning": { "wall_ms": "#) }
+    /// ```
+    #[cfg(not(feature = "bench"))]
+    pub fn to_json(&self) -> String {
+        self.to_json_with(vec![])
+    }
+    #[cfg(feature = "bench")]
+    pub fn to_json(&self) -> String {
+        "{}".into()
+    }
+
+    /// Same as [`to_json`][json], with some additional entries at the end of the object.
+    ///
+    /// The entries must be of the form `"key": value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let json = Profiler::new().to_json_with(vec![r#""answer": 42"#.into()]);
+    /// assert_eq! { json, "{\n  \"answer\": 42\n}" }
+    /// ```
+    ///
+    /// [json]: #method.to_json (to_json function)
+    #[cfg(not(feature = "bench"))]
+    pub fn to_json_with(&self, extra: Vec<String>) -> String {
+        let mut entries = vec![];
+        self.json_entries("", &mut entries);
+        entries.extend(extra);
+        let mut json = "{".to_string();
+        for (index, entry) in entries.iter().enumerate() {
+            if index > 0 {
+                json.push(',')
+            }
+            json.push_str("\n  ");
+            json.push_str(entry)
+        }
+        if !entries.is_empty() {
+            json.push('\n')
+        }
+        json.push('}');
+        json
+    }
+    #[cfg(feature = "bench")]
+    pub fn to_json_with(&self, _: Vec<String>) -> String {
+        "{}".into()
+    }
+
+    /// Collects the JSON entries of a profiler, its sub-profilers and its other profilers.
+    #[cfg(not(feature = "bench"))]
+    fn json_entries(&self, pref: &str, entries: &mut Vec<String>) {
+        for (scope, &(_, ref duration, count)) in self.map.borrow().iter() {
+            let key = format!("{}{}", pref, scope.join("."));
+            let wall_ms = duration.as_secs() as f64 * 1000.0
+                + f64::from(duration.subsec_nanos()) / 1_000_000.0;
+            entries.push(format!(
+                "\"{}\": {{ \"wall_ms\": {}, \"count\": {} }}",
+                json_escape(&key),
+                wall_ms,
+                count
+            ))
+        }
+        for (name, sub) in self.subs.borrow().iter().chain(self.others.borrow().iter()) {
+            sub.json_entries(&format!("{}{}.", pref, name), entries)
+        }
+    }
+
     /// Adds a sub-profiler.
     #[cfg(not(feature = "bench"))]
     pub fn add_sub<S: Into<String>>(&self, name: S, sub: Self) {
@@ -422,37 +559,75 @@ impl Profiler {
         res
     }
 
-    /// Consumes and prints a profiler.
+    /// Consumes and prints a profiler on stdout.
+    ///
+    /// See [`write_to`][write].
+    ///
+    /// [write]: #method.write_to (write_to function)
+    #[cfg(not(feature = "bench"))]
+    pub fn print<S1, S2>(self, name: S1, pref: S2, set_sum: &[&'static str])
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.write_to(&mut stdout(), name, pref, set_sum)
+            .expect("failed to write to stdout")
+    }
+
+    /// Consumes and writes a profiler.
     ///
     /// - `set_sum` is a slice of scopes which have no duration and will be set
     ///   to the sum of their branches (without triggering a warning)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let profiler = Profiler::new();
+    /// profiler.tick(vec!["preproc"]);
+    /// profiler.mark(vec!["preproc"]);
+    /// let mut buf: Vec<u8> = vec![];
+    /// profiler.write_to(&mut buf, "top", "", &[]).unwrap();
+    /// let out = String::from_utf8(buf).unwrap();
+    /// assert! { out.lines().count() >= 4 }
+    /// assert! { out.lines().all(|line| line.starts_with(";")) }
+    /// assert! { out.contains("preproc") }
+    /// ```
     #[cfg(not(feature = "bench"))]
-    pub fn print<S1, S2>(self, name: S1, pref: S2, set_sum: &[&'static str])
+    pub fn write_to<W, S1, S2>(
+        self,
+        w: &mut W,
+        name: S1,
+        pref: S2,
+        set_sum: &[&'static str],
+    ) -> IoRes<()>
     where
+        W: Write,
         S1: Into<String>,
         S2: Into<String>,
     {
         let name = name.into();
         let pref = pref.into();
 
-        println!("; {}{} {}", pref, conf.emph(&name), conf.emph("{"));
+        writeln!(w, "; {}{} {}", pref, conf.emph(&name), conf.emph("{"))?;
         let sub_pref = format!("{}  ", pref);
 
         let (tree, stats, subs) = self.extract();
-        tree.print(sub_pref.clone(), set_sum);
+        tree.write_to(w, sub_pref.clone(), set_sum)?;
         if stats.has_non_zero() {
-            println!("; {}{}:", sub_pref, conf.happy("metrics"));
-            stats.print(format!("{}{} ", sub_pref, conf.happy("|")))
+            writeln!(w, "; {}{}:", sub_pref, conf.happy("metrics"))?;
+            stats.write_to(w, format!("{}{} ", sub_pref, conf.happy("|")))?
         }
 
-        scoped! {
-          for (sub_name, sub) in subs {
-            println!("; ") ;
-            sub.print(
-              format!("{}/{}", name, sub_name), sub_pref.clone(), set_sum
-            ) ;
-          }
+        for (sub_name, sub) in subs {
+            writeln!(w, "; ")?;
+            sub.write_to(
+                w,
+                format!("{}/{}", name, sub_name),
+                sub_pref.clone(),
+                set_sum,
+            )?
         }
-        println!("; {}{}", pref, conf.emph("}"))
+        writeln!(w, "; {}{}", pref, conf.emph("}"))
     }
 }
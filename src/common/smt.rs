@@ -17,25 +17,32 @@ use crate::{
 
 /// Initial setup for a solver.
 ///
-/// - declares all the datatypes
+/// - declares all the datatypes, and defines their size function
 /// - defines all the functions
 /// - asserts all the side-clauses if `preproc` is false
 pub fn init<P, I>(solver: &mut Solver<P>, instance: I) -> Res<()>
 where
     I: AsRef<Instance>,
 {
+    let instance = instance.as_ref();
     dtyp::write_all(solver, "")?;
+    if conf.solver.is_z3() {
+        dtyp::write_size_funs(solver, "", &instance.used_dtyps())?
+    }
     fun::write_all(solver, "", true)?;
-    instance.as_ref().assert_side_clauses(solver)
+    instance.assert_side_clauses(solver)
 }
 
 /// Initial setup for a preprocessing solver.
 ///
-/// - declares all the datatypes
+/// - declares all the datatypes, and defines their size function
 /// - defines all the functions
 /// - asserts all the side-clauses if `preproc` is false
-pub fn preproc_init<P>(solver: &mut Solver<P>) -> Res<()> {
+pub fn preproc_init<P>(solver: &mut Solver<P>, instance: &Instance) -> Res<()> {
     dtyp::write_all(solver, "")?;
+    if conf.solver.is_z3() {
+        dtyp::write_size_funs(solver, "", &instance.used_dtyps())?
+    }
     fun::write_all(solver, "", true)?;
     Ok(())
 }
@@ -56,9 +63,9 @@ where
 ///
 /// Use this and not `solver.reset()`. This declares all the
 /// datatypes/functions used in the instance.
-pub fn preproc_reset<P>(solver: &mut Solver<P>) -> Res<()> {
+pub fn preproc_reset<P>(solver: &mut Solver<P>, instance: &Instance) -> Res<()> {
     solver.reset()?;
-    preproc_init(solver)
+    preproc_init(solver, instance)
 }
 
 /// Performs a check-sat.
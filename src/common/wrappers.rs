@@ -193,6 +193,8 @@ wrap_usize! {
     range: CstrRange
     #[doc = "Constraint set."]
     set: CstrSet
+    #[doc = "Hash map from constraints to something."]
+    hash map: CstrHMap
     #[doc = "Constraint total map."]
     map: CstrMap with iter: CstrMapIter
 }
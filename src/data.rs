@@ -15,11 +15,54 @@ use crate::{
     learning::ice::data::CData,
 };
 
+/// Iterates over the samples of a constraint, lhs first.
+///
+/// Shared by [`Constraint::samples`] and the consistency checks on the sample-to-constraint map
+/// so that they agree on what "appearing in a constraint" means.
+///
+/// [`Constraint::samples`]: constraint/struct.Constraint.html#method.samples
+/// (samples function of Constraint)
+macro_rules! constraint_map {
+    ($cstr:expr => |$pred:ident, $sample:ident| $body:expr) => {
+        if let Some(lhs) = $cstr.lhs() {
+            for (pred, samples) in lhs {
+                let $pred = *pred;
+                for $sample in samples {
+                    $body
+                }
+            }
+        }
+        if let Some(&Sample {
+            pred: $pred,
+            args: ref $sample,
+        }) = $cstr.rhs()
+        {
+            $body
+        }
+    };
+}
+
 pub mod constraint;
 mod info;
 pub mod sample;
+#[cfg(test)]
+mod test;
 
 pub use self::constraint::Constraint;
+
+/// Source of the revisions of the projected data of the predicates.
+///
+/// Shared by all the [`Data`] so that revisions from different copies of the data never collide.
+///
+/// [`Data`]: struct.Data.html (Data struct)
+static REVISION: ::std::sync::atomic::AtomicUsize = ::std::sync::atomic::AtomicUsize::new(0);
+
+/// A revision no [`Data`] has used yet.
+///
+/// [`Data`]: struct.Data.html (Data struct)
+fn fresh_revision() -> usize {
+    REVISION.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed) + 1
+}
 use self::info::CstrInfo;
 pub use self::sample::Sample;
 
@@ -290,6 +333,7 @@ impl LrnData {
         scoped! {
             let data = &mut self.data;
             let map = & mut data.map ;
+            let revisions = & mut data.revisions ;
             let mut constraints = CstrSet::new() ;
             for (_, cs) in map[pred].drain() {
                 for c in cs {
@@ -297,9 +341,10 @@ impl LrnData {
                 }
             }
             for constraint in constraints {
+                debug_assert! { data.constraints[constraint].pred_set().contains(& pred) }
                 let tautology = data.constraints[constraint].force(
                     pred, pos, |pred, args| Data::tauto_fun(
-                        map, constraint, pred, & args
+                        map, revisions, constraint, pred, & args
                     )
                 ) ? ;
 
@@ -315,6 +360,7 @@ impl LrnData {
                                 let was_there = set.remove(& constraint) ;
                                 debug_assert! { was_there }
                             }
+                            revisions[pred] = fresh_revision() ;
                             data.cstr_info.forget(constraint) ;
                             // Stage the consequence of the triviality.
                             data.staged.add(pred, args, pos) ;
@@ -339,6 +385,7 @@ impl LrnData {
 
         self.data.pos[pred].clear();
         self.data.neg[pred].clear();
+        self.data.touch(pred);
 
         for constraint in modded_constraints.drain() {
             if !self.data.constraints[constraint].is_tautology()
@@ -565,6 +612,233 @@ impl LrnData {
     }
 }
 
+/// Number of positive and negative samples of each learned predicate.
+///
+/// Accumulated over the sub-systems solved by the teacher, used to report on the model with
+/// `--model_stats`. A predicate that does not appear was not learned, *i.e.* it was forced by
+/// pre-processing.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate hoice;
+/// use hoice::{ common::*, data::{Data, SampleCounts} };
+/// fn main() {
+///     let instance = ::hoice::parse::mc_91();
+///     let pred: PrdIdx = 0.into();
+///     let mut data = Data::new(Arc::new(instance));
+///     data.add_data(
+///         0.into(), vec![], Some((pred, r_var_vals!((int 3) (int 0)))),
+///     ).expect("while adding constraint");
+///     data.propagate().expect("during propagation");
+///
+///     let mut counts = SampleCounts::new();
+///     assert_eq! { counts.get(pred), None }
+///     counts.record(&data);
+///     assert_eq! { counts.get(pred), Some((1, 0)) }
+///     counts.record(&data);
+///     assert_eq! { counts.get(pred), Some((2, 0)) }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SampleCounts {
+    /// Positive and negative sample counts.
+    counts: PrdHMap<(usize, usize)>,
+}
+
+impl SampleCounts {
+    /// Constructor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the positive and negative samples of the predicates some data is about.
+    ///
+    /// Ignores the predicates defined in the instance of the data.
+    pub fn record(&mut self, data: &Data) {
+        for pred in data.instance.pred_indices() {
+            if data.instance[pred].is_defined() {
+                continue;
+            }
+            let counts = self.counts.entry(pred).or_insert((0, 0));
+            counts.0 += data.pos_count_for(pred);
+            counts.1 += data.neg_count_for(pred)
+        }
+    }
+
+    /// Positive and negative sample counts of a predicate, `None` if it was not learned.
+    pub fn get(&self, pred: PrdIdx) -> Option<(usize, usize)> {
+        self.counts.get(&pred).cloned()
+    }
+}
+
+/// Learning data kept from one `check-sat` to the next.
+///
+/// Used when reading from stdin, where a `check-sat` can be followed by more clauses and another
+/// `check-sat`. Samples and constraints only make sense for the preprocessed instance they were
+/// learned on: signatures are reduced and predicates are defined differently depending on the
+/// clauses. So the map from the current signature to the original one is stored for each
+/// predicate, and the samples and constraints are ignored if preprocessing did not yield the
+/// same predicates with the same signatures (see [`same_preproc`]). Qualifiers are only terms
+/// over the signature of their predicate, they are kept as long as this signature is the same.
+///
+/// [`same_preproc`]: #method.same_preproc (same_preproc function)
+#[derive(Clone, Default)]
+pub struct WarmData {
+    /// Number of predicates in the (unpreprocessed) instance at the last check.
+    pred_count: usize,
+    /// Number of clauses in the (unpreprocessed) instance at the last check.
+    clause_count: usize,
+    /// Map from the current signature to the original one, for each predicate.
+    ///
+    /// `None` for the predicates defined by preprocessing.
+    sig_maps: PrdHMap<Option<VarMap<VarIdx>>>,
+    /// Positive samples.
+    pos: PrdHMap<VarValsSet>,
+    /// Negative samples.
+    neg: PrdHMap<VarValsSet>,
+    /// Constraints.
+    constraints: Vec<Constraint>,
+    /// Qualifiers, extracted from the last model.
+    quals: PrdHMap<TermSet>,
+}
+
+impl WarmData {
+    /// Constructor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// True if the qualifiers for a predicate can be used in some instance.
+    ///
+    /// Meaning the predicate is not defined and preprocessing reduced its signature the same way
+    /// it did when the qualifiers were recorded.
+    pub fn is_compatible(&self, instance: &Instance, pred: PrdIdx) -> bool {
+        !instance[pred].is_defined()
+            && match self.sig_maps.get(&pred) {
+                Some(Some(map)) => map == instance[pred].original_sig_map(),
+                _ => false,
+            }
+    }
+
+    /// True if the samples and constraints can be used in some instance.
+    ///
+    /// Meaning preprocessing defined the same predicates as when the data was recorded, and
+    /// reduced the signatures of the others the same way. A sample or a constraint can depend on
+    /// the definition of a predicate it does not mention, so this is checked on all the
+    /// predicates recorded.
+    pub fn same_preproc(&self, instance: &Instance) -> bool {
+        !self.sig_maps.is_empty()
+            && self.sig_maps.iter().all(|(pred, map)| {
+                let info = &instance[*pred];
+                match map {
+                    None => info.is_defined(),
+                    Some(map) => !info.is_defined() && map == info.original_sig_map(),
+                }
+            })
+    }
+
+    /// Qualifiers for a predicate.
+    pub fn quals_of(&self, pred: PrdIdx) -> Option<&TermSet> {
+        self.quals.get(&pred)
+    }
+
+    /// Drops the data invalidated by the clauses added since the last check.
+    ///
+    /// Takes the **unpreprocessed** instance. This is conservative: everything mentioning a
+    /// predicate that is new or appears in a new clause is dropped. The signature maps are kept
+    /// so that [`same_preproc`] still checks these predicates.
+    ///
+    /// [`same_preproc`]: #method.same_preproc (same_preproc function)
+    pub fn update(&mut self, instance: &Instance) {
+        let mut preds: PrdSet = instance
+            .pred_indices()
+            .filter(|pred| **pred >= self.pred_count)
+            .collect();
+        for clause in instance.clause_indices() {
+            if *clause >= self.clause_count {
+                preds.extend(instance[clause].lhs_preds().keys().cloned());
+                if let Some((pred, _)) = instance[clause].rhs() {
+                    preds.insert(pred);
+                }
+            }
+        }
+
+        self.pred_count = instance.preds().len();
+        self.clause_count = instance.clauses().len();
+
+        for pred in &preds {
+            self.pos.remove(pred);
+            self.neg.remove(pred);
+            self.quals.remove(pred);
+        }
+        self.constraints
+            .retain(|cstr| cstr.pred_set().is_disjoint(&preds))
+    }
+
+    /// Records the data of a check, and the model it yielded.
+    ///
+    /// Previous data is dropped: the data of the check was seeded with the part of it that was
+    /// still relevant. Does nothing if the instance is a split, since its data might depend on
+    /// the models of the other splits.
+    pub fn record(&mut self, data: &Data, model: &Candidates) {
+        let instance = &data.instance;
+        if instance.split().is_some() {
+            return;
+        }
+
+        self.sig_maps.clear();
+        self.pos.clear();
+        self.neg.clear();
+        self.constraints.clear();
+        self.quals.clear();
+
+        for pred in instance.pred_indices() {
+            if instance[pred].is_defined() {
+                self.sig_maps.insert(pred, None);
+                continue;
+            }
+            self.sig_maps
+                .insert(pred, Some(instance[pred].original_sig_map().clone()));
+            if !data.pos[pred].is_empty() {
+                self.pos.insert(pred, data.pos[pred].clone());
+            }
+            if !data.neg[pred].is_empty() {
+                self.neg.insert(pred, data.neg[pred].clone());
+            }
+        }
+
+        for cstr in data.constraints.iter() {
+            if !cstr.is_tautology() {
+                self.constraints.push(cstr.clone())
+            }
+        }
+
+        for (pred, cand) in model.index_iter() {
+            if let Some(cand) = cand.as_ref() {
+                let quals = self.quals.entry(pred).or_insert_with(TermSet::new);
+                Self::atoms_of(cand, quals)
+            }
+        }
+    }
+
+    /// Collects the atoms of a boolean term, ignoring its boolean structure.
+    fn atoms_of(term: &Term, atoms: &mut TermSet) {
+        if let Some(kids) = term.conj_inspect().or_else(|| term.disj_inspect()) {
+            for kid in kids {
+                Self::atoms_of(kid, atoms)
+            }
+        } else if let Some((Op::Not, kids)) = term.app_inspect() {
+            for kid in kids {
+                Self::atoms_of(kid, atoms)
+            }
+        } else if term.bool().is_none() {
+            atoms.insert(term.clone());
+        }
+    }
+}
+
 /// Structure manipulating unprojected learning data.
 pub struct Data {
     /// Instance, only used for printing.
@@ -592,8 +866,23 @@ pub struct Data {
     _profiler: Profiler,
     /// Entry point tracker.
     entry_points: Option<crate::unsat_core::entry_points::EntryPoints>,
+    /// Constraints waiting for the end of the current batch, `None` if not in batch mode.
+    batch: Option<Vec<PendingCstr>>,
+    /// Revision of the projected data of each predicate, see [`revision`][rev].
+    ///
+    /// [rev]: #method.revision (revision function)
+    revisions: PrdMap<usize>,
+    /// Current learning round, see [`new_round`][round].
+    ///
+    /// [round]: #method.new_round (new_round function)
+    round: usize,
+    /// Round at which each constraint was created.
+    cstr_rounds: CstrMap<usize>,
 }
 
+/// A constraint added in batch mode, not pruned nor registered yet.
+type PendingCstr = (ClsIdx, Vec<(PrdIdx, RVarVals)>, Option<(PrdIdx, RVarVals)>);
+
 impl Clone for Data {
     fn clone(&self) -> Self {
         Data {
@@ -611,6 +900,10 @@ impl Clone for Data {
             // graph: None,
             _profiler: Profiler::new(),
             entry_points: None,
+            batch: self.batch.clone(),
+            revisions: self.revisions.clone(),
+            round: self.round,
+            cstr_rounds: self.cstr_rounds.clone(),
         }
     }
 }
@@ -640,16 +933,17 @@ impl Data {
             PrdMap::with_capacity(pred_count),
         );
 
+        let mut revisions = PrdMap::with_capacity(pred_count);
+
         for _ in instance.preds() {
+            revisions.push(fresh_revision());
             map.push(VarValsMap::with_capacity(103));
             pos.push(VarValsSet::with_capacity(103));
             neg.push(VarValsSet::with_capacity(103));
             pos_single.push(VarValsSet::with_capacity(13));
             neg_single.push(VarValsSet::with_capacity(13));
         }
-        // let track_samples = instance.track_samples() ;
-
-        let entry_points = if instance.proofs() {
+        let entry_points = if instance.track_samples() {
             Some(crate::unsat_core::entry_points::EntryPoints::new())
         } else {
             None
@@ -668,6 +962,10 @@ impl Data {
             neg_single,
             _profiler: Profiler::new(),
             entry_points,
+            batch: None,
+            revisions,
+            round: 0,
+            cstr_rounds: CstrMap::with_capacity(103),
         }
     }
 
@@ -681,6 +979,293 @@ impl Data {
         &self.map
     }
 
+    /// Iterator over the positive samples of a predicate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data };
+    /// fn main() {
+    ///     let instance = ::hoice::parse::mc_91();
+    ///     let pred: PrdIdx = 0.into();
+    ///     let mut data = Data::new(Arc::new(instance));
+    ///     data.add_data(
+    ///         0.into(), vec![], Some((pred, r_var_vals!((int 7) (int 0))))
+    ///     ).expect("while adding positive data");
+    ///     data.add_data(
+    ///         0.into(), vec![ (pred, r_var_vals!((int 3) (int 1))) ], None
+    ///     ).expect("while adding negative data");
+    ///     data.propagate().expect("during propagation");
+    ///
+    ///     assert_eq! { data.pos_count_for(pred), 1 }
+    ///     assert_eq! { data.neg_count_for(pred), 1 }
+    ///     let pos = var_to::vals::new(r_var_vals!((int 7) (int 0)));
+    ///     assert_eq! { data.pos_for(pred).collect::<Vec<_>>(), vec![&pos] }
+    ///     let neg = var_to::vals::new(r_var_vals!((int 3) (int 1)));
+    ///     assert_eq! { data.neg_for(pred).collect::<Vec<_>>(), vec![&neg] }
+    /// }
+    /// ```
+    #[inline]
+    pub fn pos_for(&self, pred: PrdIdx) -> impl Iterator<Item = &VarVals> {
+        self.pos[pred].iter()
+    }
+    /// Iterator over the negative samples of a predicate.
+    ///
+    /// See [`pos_for`](#method.pos_for) for examples.
+    #[inline]
+    pub fn neg_for(&self, pred: PrdIdx) -> impl Iterator<Item = &VarVals> {
+        self.neg[pred].iter()
+    }
+    /// Number of positive samples of a predicate.
+    #[inline]
+    pub fn pos_count_for(&self, pred: PrdIdx) -> usize {
+        self.pos[pred].len()
+    }
+    /// Number of negative samples of a predicate.
+    #[inline]
+    pub fn neg_count_for(&self, pred: PrdIdx) -> usize {
+        self.neg[pred].len()
+    }
+
+    /// Revision of the projected data of a predicate.
+    ///
+    /// Changes whenever the positive, negative or unclassified samples of `pred` (see
+    /// [`data_of`][data of]) might have changed. Two data with the same revision for a predicate
+    /// have the same projected data for this predicate, even if one is a copy of the other.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data };
+    /// fn main() {
+    ///     let mut instance = ::hoice::parse::mc_91();
+    ///     let p_0: PrdIdx = 0.into();
+    ///     let p_1: PrdIdx = 1.into();
+    ///     instance.push_pred("dummy", vec![typ::int()].into());
+    ///     let mut data = Data::new(Arc::new(instance));
+    ///     let (rev_0, rev_1) = (data.revision(p_0), data.revision(p_1));
+    ///
+    ///     data.add_data(
+    ///         0.into(), vec![], Some((p_1, r_var_vals!((int 7))))
+    ///     ).expect("while adding positive data");
+    ///     data.propagate().expect("during propagation");
+    ///     assert_eq! { data.revision(p_0), rev_0 }
+    ///     assert_ne! { data.revision(p_1), rev_1 }
+    ///
+    ///     let rev_1 = data.revision(p_1);
+    ///     let mut lrn_data = data.to_lrn_data();
+    ///     assert_eq! { lrn_data.revision(p_1), rev_1 }
+    ///     lrn_data.force_pred(p_0, true).expect("during force true");
+    ///     assert_ne! { lrn_data.revision(p_0), rev_0 }
+    ///     assert_eq! { lrn_data.revision(p_1), rev_1 }
+    /// }
+    /// ```
+    ///
+    /// [data of]: struct.LrnData.html#method.data_of (data_of function of LrnData)
+    pub fn revision(&self, pred: PrdIdx) -> usize {
+        self.revisions[pred]
+    }
+
+    /// Starts a new learning round.
+    ///
+    /// Constraints remember the round they were created at, see [`evict_constraints`][evict].
+    ///
+    /// [evict]: #method.evict_constraints (evict_constraints function)
+    pub fn new_round(&mut self) {
+        self.round += 1
+    }
+
+    /// Current learning round.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Rough estimation of the memory footprint of the data.
+    ///
+    /// Number of positive and negative samples, plus the number of samples appearing in
+    /// constraints, plus the number of constraints.
+    pub fn footprint(&self) -> usize {
+        let mut count = self.constraints.len();
+        for pred in self.instance.pred_indices() {
+            count += self.pos_count_for(pred) + self.neg_count_for(pred) + self.map[pred].len()
+        }
+        count
+    }
+
+    /// Drops the constraints older than `age` rounds, the samples of which are all unclassified.
+    ///
+    /// Used when the data is over the memory budget. Constraints are only hints for the learner,
+    /// forgetting some of them is safe as long as the positive and negative samples are kept.
+    /// The teacher will produce them again if necessary.
+    ///
+    /// Does nothing if the data tracks sample dependencies, *i.e.* when producing unsat cores or
+    /// proofs.
+    ///
+    /// Returns the number of constraints dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data };
+    /// fn main() {
+    ///     let instance = ::hoice::parse::mc_91();
+    ///     let pred: PrdIdx = 0.into();
+    ///     let mut data = Data::new(Arc::new(instance));
+    ///     data.add_data(
+    ///         0.into(),
+    ///         vec![ (pred, r_var_vals!((int 7) (int 0))) ],
+    ///         Some((pred, r_var_vals!((int 3) (int 0)))),
+    ///     ).expect("while adding constraint");
+    ///     data.propagate().expect("during propagation");
+    ///     assert_eq! { data.constraints.len(), 1 }
+    ///
+    ///     assert_eq! { data.evict_constraints(2).unwrap(), 0 }
+    ///     data.new_round();
+    ///     data.new_round();
+    ///     assert_eq! { data.evict_constraints(2).unwrap(), 0 }
+    ///     data.new_round();
+    ///     assert_eq! { data.evict_constraints(2).unwrap(), 1 }
+    ///     assert_eq! { data.constraints.len(), 0 }
+    /// }
+    /// ```
+    pub fn evict_constraints(&mut self, age: usize) -> Res<usize> {
+        if self.entry_points.is_some() {
+            return Ok(0);
+        }
+
+        let mut to_evict = vec![];
+        'all_cstrs: for (idx, cstr) in self.constraints.index_iter() {
+            if cstr.is_tautology() || self.round - self.cstr_rounds[idx] <= age {
+                continue 'all_cstrs;
+            }
+            for (pred, args) in cstr.samples() {
+                if args.set_subsumed(&self.pos[pred]) || args.set_subsumed(&self.neg[pred]) {
+                    continue 'all_cstrs;
+                }
+            }
+            to_evict.push(idx)
+        }
+
+        let count = to_evict.len();
+        for idx in to_evict {
+            self.tautologize(idx)?
+        }
+        self.shrink_constraints();
+        profile! { self "constraints evicted" => add count }
+
+        Ok(count)
+    }
+
+    /// Drops the samples of [`self.map`][map] that no live constraint mentions anymore.
+    ///
+    /// Removes the references to tautologies from the constraint sets, and then the samples with
+    /// an empty constraint set. Complements [`shrink_constraints`][shrink], which only runs at the
+    /// end of propagation. Positive and negative samples are not stored in the map, they are left
+    /// untouched. The [revision] of the predicates that lost samples is updated.
+    ///
+    /// Returns the number of samples removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data };
+    /// fn main() {
+    ///     let instance = ::hoice::parse::mc_91();
+    ///     let pred: PrdIdx = 0.into();
+    ///     let mut data = Data::new(Arc::new(instance));
+    ///     data.add_data(
+    ///         0.into(),
+    ///         vec![ (pred, r_var_vals!((int 7) (int 0))) ],
+    ///         Some((pred, r_var_vals!((int 3) (int 0)))),
+    ///     ).expect("while adding constraint");
+    ///     data.propagate().expect("during propagation");
+    ///     assert_eq! { data.prune_unreferenced_samples(), 0 }
+    ///     assert! { data.constraints_of(pred, &var_vals!((int 7) (int 0))).is_some() }
+    ///
+    ///     data.new_round();
+    ///     assert_eq! { data.evict_constraints(0).unwrap(), 1 }
+    ///     assert_eq! { data.prune_unreferenced_samples(), 0 }
+    ///     assert! { data.constraints_of(pred, &var_vals!((int 7) (int 0))).is_none() }
+    ///     assert! { data.constraints_of(pred, &var_vals!((int 3) (int 0))).is_none() }
+    /// }
+    /// ```
+    ///
+    /// [map]: #structfield.map (map field)
+    /// [shrink]: #method.shrink_constraints (shrink_constraints function)
+    /// [revision]: #method.revision (revision function)
+    pub fn prune_unreferenced_samples(&mut self) -> usize {
+        let (constraints, revisions) = (&self.constraints, &mut self.revisions);
+        let mut count = 0;
+        for (pred, map) in self.map.index_iter_mut() {
+            let len = map.len();
+            map.retain(|_, set| {
+                set.retain(|idx| *idx < constraints.len() && !constraints[*idx].is_tautology());
+                !set.is_empty()
+            });
+            if map.len() != len {
+                // Same as `touch`, which would borrow `self` entirely.
+                revisions[pred] = fresh_revision();
+                count += len - map.len()
+            }
+        }
+        profile! { self "samples pruned" => add count }
+        count
+    }
+
+    /// Signals that the projected data of a predicate might have changed.
+    fn touch(&mut self, pred: PrdIdx) {
+        self.revisions[pred] = fresh_revision()
+    }
+
+    /// Constraints a sample appears in, lhs or rhs.
+    ///
+    /// This is the reverse of [`Constraint::samples`]. Returns `None` if the sample does not
+    /// appear in any constraint.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data };
+    /// fn main() {
+    ///     let instance = ::hoice::parse::mc_91();
+    ///     let p_0: PrdIdx = 0.into();
+    ///     let mut data = Data::new(Arc::new(instance));
+    ///     data.add_data(
+    ///         1.into(), vec![
+    ///             (p_0, r_var_vals!((int 1) (int 101))),
+    ///             (p_0, r_var_vals!((int 2) (int 102))),
+    ///         ], Some((p_0, r_var_vals!((int 7) (int 3))))
+    ///     ).expect("while adding constraint");
+    ///
+    ///     let cstr: CstrIdx = 0.into();
+    ///     let samples = data.constraints[cstr].samples();
+    ///     assert_eq! { samples.len(), 3 }
+    ///     for (pred, sample) in &samples {
+    ///         let cstrs = data.constraints_of(*pred, sample).expect("unregistered sample");
+    ///         assert! { cstrs.contains(&cstr) }
+    ///     }
+    ///
+    ///     let unknown = var_vals!((int 0) (int 0));
+    ///     assert! { data.constraints_of(p_0, &unknown).is_none() }
+    /// }
+    /// ```
+    ///
+    /// [`Constraint::samples`]: constraint/struct.Constraint.html#method.samples
+    /// (samples function of Constraint)
+    pub fn constraints_of(&self, pred: PrdIdx, sample: &VarVals) -> Option<&CstrSet> {
+        self.map[pred].get(sample).filter(|set| !set.is_empty())
+    }
+
     /// Generates data for the assistant.
     ///
     /// Takes all the constraints modified since the last call to this function, and generates
@@ -743,6 +1328,43 @@ impl Data {
         Ok(data)
     }
 
+    /// Adds the data from previous checks.
+    ///
+    /// Does nothing if preprocessing yielded a [different instance] from the one the data was
+    /// recorded on, if the instance is a split, or if producing proofs since this data has no
+    /// origin.
+    ///
+    /// Returns `true` if something was added.
+    ///
+    /// [different instance]: struct.WarmData.html#method.same_preproc (same_preproc function)
+    pub fn add_warm_data(&mut self, warm: &WarmData) -> Res<bool> {
+        if self.instance.split().is_some()
+            || self.entry_points.is_some()
+            || !warm.same_preproc(&self.instance)
+        {
+            return Ok(false);
+        }
+        let mut nu_stuff = false;
+
+        for cstr in &warm.constraints {
+            nu_stuff = self.raw_add_cstr(cstr.clone())? || nu_stuff
+        }
+
+        for (pred, samples) in &warm.pos {
+            for sample in samples {
+                nu_stuff = self.staged.add_pos(*pred, sample.clone()) || nu_stuff
+            }
+        }
+        for (pred, samples) in &warm.neg {
+            for sample in samples {
+                nu_stuff = self.staged.add_neg(*pred, sample.clone()) || nu_stuff
+            }
+        }
+
+        self.propagate()?;
+        Ok(nu_stuff)
+    }
+
     /// Generates learning data for learners.
     pub fn to_lrn_data(&self) -> LrnData {
         let data = Data {
@@ -760,6 +1382,10 @@ impl Data {
             // graph: None,
             _profiler: Profiler::new(),
             entry_points: None,
+            batch: None,
+            revisions: self.revisions.clone(),
+            round: self.round,
+            cstr_rounds: self.cstr_rounds.clone(),
         };
         LrnData { data }
     }
@@ -867,9 +1493,18 @@ impl Data {
     ///
     /// Remove all constraints that this constraint makes useless, including the
     /// one(s) it is equal to.
+    ///
+    /// Two constraints are only comparable if the lhs of one of them is a subset of the lhs of the
+    /// other. So the candidates are the constraints with the same rhs that share lhs samples with
+    /// `index`, retrieved through the sample to constraint map. A candidate is only compared to
+    /// `index` if the number of samples they share is the lhs length of one of them.
+    ///
+    /// Candidates are compared by increasing lhs length, so that the most general constraints
+    /// are found first.
     fn cstr_useful(&mut self, index: CstrIdx) -> Res<bool> {
         profile! { self tick "constraint subsumption" }
-        let mut to_check = CstrSet::new();
+        // Candidates and the length of their lhs.
+        let mut to_check = Vec::new();
         scoped! {
           let constraint = & self.constraints[index] ;
           let similar = if let Some(
@@ -883,17 +1518,38 @@ impl Data {
           } else {
             self.cstr_info.neg()
           } ;
-          for idx in similar {
-            if * idx != index {
-              let is_new = to_check.insert(* idx) ;
-              debug_assert! { is_new }
+
+          // Number of lhs samples shared with `index`, for each candidate.
+          let mut shared: CstrHMap<usize> = CstrHMap::new() ;
+          if let Some(lhs) = constraint.lhs() {
+            for (pred, argss) in lhs {
+              for args in argss {
+                if let Some(cstrs) = self.constraints_of(* pred, args) {
+                  for idx in cstrs {
+                    if * idx != index && similar.contains(idx) {
+                      * shared.entry(* idx).or_insert(0) += 1
+                    }
+                  }
+                }
+              }
+            }
+          }
+
+          let len = constraint.lhs_len() ;
+          for (idx, count) in shared {
+            let other_len = self.constraints[idx].lhs_len() ;
+            if count == len || count == other_len {
+              to_check.push((other_len, idx))
             }
           }
         }
 
+        // Most general constraints (smallest lhs) first.
+        to_check.sort_unstable();
+
         let mut useful = true;
 
-        for similar in to_check.drain() {
+        for (_, similar) in to_check {
             use std::cmp::Ordering::*;
             match self.constraints[index]
                 .compare(&self.constraints[similar])
@@ -1041,9 +1697,7 @@ impl Data {
     /// [cstrs]: #structfield.constraints (constraints field)
     /// [prop]: #method.propagate (propagate function)
     fn shrink_constraints(&mut self) {
-        for map in self.map.iter_mut() {
-            map.retain(|_, set| !set.is_empty())
-        }
+        self.prune_unreferenced_samples();
         loop {
             scoped! {
               if let Some(last) = self.constraints.last() {
@@ -1055,13 +1709,79 @@ impl Data {
               }
             }
             let last = self.constraints.pop();
-            debug_assert_eq!(last.map(|c| c.is_tautology()), Some(true))
+            debug_assert_eq!(last.map(|c| c.is_tautology()), Some(true));
+            let _ = self.cstr_rounds.pop();
         }
     }
 
+    /// Removes positive/negative samples subsumed by a more general sample of the same set.
+    ///
+    /// Dual of [`cstr_useful`][useful] for samples: a sample covered by a more general (partial)
+    /// sample of the same polarity carries no information. Called by [`propagate`][prop] after
+    /// tautologizing constraints, so classified samples do not appear in any constraint anymore.
+    ///
+    /// Samples are **not** dropped because of constraints. A constraint only mentions unclassified
+    /// samples, and the lhs of a constraint, satisfied or not, does not force any of its
+    /// applications to hold: a positive sample subsumed by an lhs sample is still needed, and so
+    /// is a negative one.
+    ///
+    /// Returns the number of samples removed.
+    ///
+    /// [useful]: #method.cstr_useful (cstr_useful function)
+    /// [prop]: #method.propagate (propagate function)
+    pub fn shrink_pos_neg(&mut self) -> usize {
+        if !conf.teacher.partial {
+            return 0;
+        }
+        let mut count = 0;
+
+        for pred in self.instance.pred_indices() {
+            for pos in &[true, false] {
+                let (set, single) = if *pos {
+                    (&mut self.pos[pred], &mut self.pos_single[pred])
+                } else {
+                    (&mut self.neg[pred], &mut self.neg_single[pred])
+                };
+                let map = &self.map[pred];
+
+                let general: Vec<VarVals> = set
+                    .iter()
+                    .filter(|sample| sample.is_partial())
+                    .cloned()
+                    .collect();
+                if general.is_empty() {
+                    continue;
+                }
+
+                let len = set.len();
+                set.retain(|sample| {
+                    let subsumed = general
+                        .iter()
+                        .any(|other| other != sample && other.subsumes(sample));
+                    debug_assert! {
+                        !subsumed || map.get(sample).map_or(true, |cstrs| cstrs.is_empty()),
+                        "removing sample {} which still appears in some constraint(s)", sample
+                    }
+                    !subsumed
+                });
+                let removed = len - set.len();
+                single.retain(|sample| set.contains(sample));
+                if removed > 0 {
+                    count += removed;
+                    self.revisions[pred] = fresh_revision()
+                }
+            }
+        }
+
+        count
+    }
+
     /// Function used when tautologizing a constraint, to forget the samples.
+    ///
+    /// Updates the revision of `pred` if `args` is not unclassified anymore.
     fn tauto_fun(
         map: &mut PrdMap<VarValsMap<CstrSet>>,
+        revisions: &mut PrdMap<usize>,
         constraint: CstrIdx,
         pred: PrdIdx,
         args: &VarVals,
@@ -1081,6 +1801,7 @@ impl Data {
         if remove {
             let prev = map[pred].remove(&args);
             debug_assert! { prev.is_some() }
+            revisions[pred] = fresh_revision()
         }
         Ok(())
     }
@@ -1090,8 +1811,9 @@ impl Data {
     fn tautologize(&mut self, constraint: CstrIdx) -> Res<()> {
         scoped! {
           let map = & mut self.map ;
+          let revisions = & mut self.revisions ;
           self.constraints[constraint].tautologize(
-            |pred, args| Self::tauto_fun(map, constraint, pred, & args)
+            |pred, args| Self::tauto_fun(map, revisions, constraint, pred, & args)
           ).chain_err(
             || "in tautologize"
           ) ? ;
@@ -1120,6 +1842,56 @@ impl Data {
         res
     }
 
+    /// Panics if a positive sample and a negative sample of the same predicate are related.
+    ///
+    /// Checks every positive sample against every negative sample with [`subsumes`]. Debug
+    /// builds only, meant to catch contradictory samples at their injection point when debugging
+    /// a teacher.
+    ///
+    /// Note that contradictory samples are **not** a bug in general, they are how the learning
+    /// data becomes unsat (see [`check_unsat`]). Hence this function is not called by the data
+    /// itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data };
+    /// fn main() {
+    ///     let instance = ::hoice::parse::mc_91();
+    ///     let p_0: PrdIdx = 0.into();
+    ///     let mut data = Data::new(Arc::new(instance));
+    ///     data.add_data(
+    ///         0.into(), vec![], Some((p_0, r_var_vals!((int 3) (int 0))))
+    ///     ).expect("while adding positive data");
+    ///     data.add_data(
+    ///         0.into(), vec![(p_0, r_var_vals!((int 3) (int 1)))], None
+    ///     ).expect("while adding negative data");
+    ///     data.propagate().expect("during propagation");
+    ///     data.assert_no_contradictions();
+    /// }
+    /// ```
+    ///
+    /// [`subsumes`]: ../var_to/vals/trait.SubsumeExt.html#method.subsumes
+    /// (subsumes function of SubsumeExt)
+    /// [`check_unsat`]: #method.check_unsat (check_unsat function)
+    #[cfg(debug_assertions)]
+    pub fn assert_no_contradictions(&self) {
+        for (pred, samples) in self.pos.index_iter() {
+            for pos in samples {
+                for neg in self.neg_for(pred) {
+                    if pos.subsumes(neg) || neg.subsumes(pos) {
+                        panic!(
+                            "contradictory samples for predicate {}:\n  pos {}\n  neg {}",
+                            self.instance[pred], pos, neg
+                        )
+                    }
+                }
+            }
+        }
+    }
+
     /// Checks whether the data is contradictory.
     ///
     /// Mutable because data needs to be propagated.
@@ -1186,7 +1958,7 @@ impl Data {
         log_verb! { "learning data on unsat:\n{}", self.string_do(& (), |s| s.to_string()).unwrap() }
         for (pred, samples) in self.pos.index_iter() {
             for sample in samples {
-                for neg in &self.neg[pred] {
+                for neg in self.neg_for(pred) {
                     if sample.is_complementary(neg) {
                         let entry_points = if let Some(entry_points) = &self.entry_points {
                             log! { @5
@@ -1308,6 +2080,8 @@ impl Data {
                 continue 'propagate;
             }
 
+            self.touch(pred);
+
             if pos {
                 pos_cnt += argss.len()
             } else {
@@ -1333,10 +2107,11 @@ impl Data {
 
                         let tautology = {
                             let map = &mut self.map;
+                            let revisions = &mut self.revisions;
                             let constraint = &mut constraint!();
                             constraint
                                 .force_sample(pred, &args, pos, |pred, args| {
-                                    Self::tauto_fun(map, constraint_idx, pred, &args)
+                                    Self::tauto_fun(map, revisions, constraint_idx, pred, &args)
                                 })
                                 .chain_err(|| "in propagate")?
                         };
@@ -1356,6 +2131,7 @@ impl Data {
                                         let was_there = set.remove(&constraint_idx);
                                         debug_assert! { was_there }
                                     }
+                                    self.touch(pred);
                                     self.cstr_info.forget(constraint_idx);
                                     // Stage the consequence of the triviality.
                                     self.staged.add(pred, args, pos);
@@ -1391,6 +2167,8 @@ impl Data {
         }
 
         profile! { self tick "propagate", "check shrink" }
+        let _shrunk = self.shrink_pos_neg();
+        profile! { self "redundant samples" => add _shrunk }
         self.check("after propagate")?;
 
         self.shrink_constraints();
@@ -1418,6 +2196,23 @@ impl Data {
     /// - performs the usefulness check
     fn raw_add_cstr(&mut self, constraint: Constraint) -> Res<bool> {
         self.shrink_constraints();
+        let cstr_index = self.link_cstr(constraint)?;
+
+        if !self
+            .cstr_useful(cstr_index)
+            .chain_err(|| "in raw_add_cstr")?
+        {
+            self.tautologize(cstr_index)?;
+            Ok(false)
+        } else {
+            Ok(true)
+        }
+    }
+
+    /// Pushes a constraint and creates its links, no usefulness check.
+    ///
+    /// Returns the index of the new constraint.
+    fn link_cstr(&mut self, constraint: Constraint) -> Res<CstrIdx> {
         let cstr_index = self.constraints.next_index();
 
         // Create links.
@@ -1430,6 +2225,7 @@ impl Data {
                         .insert(cstr_index);
                     debug_assert! { is_new }
                 }
+                self.touch(*pred)
             }
         }
         if let Some(&Sample { pred, ref args }) = constraint.rhs() {
@@ -1438,21 +2234,15 @@ impl Data {
                 .or_insert_with(|| CstrSet::with_capacity(17))
                 .insert(cstr_index);
             debug_assert! { is_new }
+            self.touch(pred)
         }
 
         self.cstr_info.register_modded(cstr_index, &constraint)?;
 
         self.constraints.push(constraint);
+        self.cstr_rounds.push(self.round);
 
-        if !self
-            .cstr_useful(cstr_index)
-            .chain_err(|| "in raw_add_cstr")?
-        {
-            self.tautologize(cstr_index)?;
-            Ok(false)
-        } else {
-            Ok(true)
-        }
+        Ok(cstr_index)
     }
 
     /// Adds a sample or a constraint.
@@ -1674,12 +2464,20 @@ impl Data {
     /// Partial samples ARE NOT ALLOWED in constraints.
     ///
     /// - propagates staged samples beforehand
+    /// - in [batch mode][batch], only stores the constraint and returns `false`
+    ///
+    /// [batch]: #method.begin_batch (begin_batch function)
     fn add_cstr(
         &mut self,
         clause: ClsIdx,
         lhs: Vec<(PrdIdx, RVarVals)>,
         rhs: Option<(PrdIdx, RVarVals)>,
     ) -> Res<bool> {
+        if let Some(batch) = self.batch.as_mut() {
+            batch.push((clause, lhs, rhs));
+            return Ok(false);
+        }
+
         profile!(
             self wrap { self.propagate() } "add cstr", "pre-propagate"
         )?;
@@ -1739,6 +2537,135 @@ impl Data {
         }
     }
 
+    /// Enters batch mode.
+    ///
+    /// Until the next call to [`commit_batch`][commit], [`add_data`][add] only stages samples and
+    /// stores constraints without propagating. Pruning constraints against the positive and
+    /// negative samples, and checking their usefulness, is delayed until commit. Does nothing if
+    /// already in batch mode.
+    ///
+    /// [commit]: #method.commit_batch (commit_batch function)
+    /// [add]: #method.add_data (add_data function)
+    pub fn begin_batch(&mut self) {
+        if self.batch.is_none() {
+            self.batch = Some(vec![])
+        }
+    }
+
+    /// True if in batch mode.
+    pub fn in_batch(&self) -> bool {
+        self.batch.is_some()
+    }
+
+    /// Leaves batch mode and registers everything added since [`begin_batch`][begin].
+    ///
+    /// Prunes and registers the pending constraints, checks their usefulness, and propagates
+    /// once. Returns `true` if something new was added. Does nothing if not in batch mode, except
+    /// propagating.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data };
+    /// fn main() {
+    ///     let instance = ::hoice::parse::mc_91();
+    ///     let p_0: PrdIdx = 0.into();
+    ///     let mut data = Data::new(Arc::new(instance));
+    ///     data.begin_batch();
+    ///     data.add_data(
+    ///         0.into(), vec![], Some((p_0, r_var_vals!((int 3) (int 0))))
+    ///     ).expect("while adding positive data");
+    ///     data.add_data(
+    ///         1.into(), vec![
+    ///             (p_0, r_var_vals!((int 1) (int 101))),
+    ///             (p_0, r_var_vals!((int 2) (int 102))),
+    ///         ], Some((p_0, r_var_vals!((int 7) (int 3))))
+    ///     ).expect("while adding constraint");
+    ///     data.add_data(
+    ///         1.into(), vec![
+    ///             (p_0, r_var_vals!((int 3) (int 0))),
+    ///         ], Some((p_0, r_var_vals!((int 5) (int 1))))
+    ///     ).expect("while adding constraint");
+    ///     // Nothing is registered before commit.
+    ///     assert_eq! { data.metrics(), (0, 0, 0) }
+    ///
+    ///     let changed = data.commit_batch().expect("during commit");
+    ///     assert! { changed }
+    ///     assert! { !data.in_batch() }
+    ///     // The lhs of the second constraint is positive, so is its rhs.
+    ///     assert_eq! { data.pos_neg_count(), (2, 0) }
+    /// }
+    /// ```
+    ///
+    /// [begin]: #method.begin_batch (begin_batch function)
+    pub fn commit_batch(&mut self) -> Res<bool> {
+        let batch = if let Some(batch) = self.batch.take() {
+            batch
+        } else {
+            let (pos, neg) = self.propagate()?;
+            return Ok(pos > 0 || neg > 0);
+        };
+
+        profile! { self tick "commit batch" }
+
+        let mut nu_stuff = false;
+        let mut nu_cstrs = Vec::with_capacity(batch.len());
+
+        // Constraints are not removed until the next propagation, indices are stable.
+        self.shrink_constraints();
+
+        for (clause, lhs, rhs) in batch {
+            let (nu_lhs, nu_rhs) = if let Some(res) = self.prune_cstr(clause, lhs, rhs)? {
+                res
+            } else {
+                continue;
+            };
+
+            let constraint = Constraint::new(nu_lhs, nu_rhs);
+            constraint.check().chain_err(|| {
+                format!(
+                    "while checking {}",
+                    constraint.to_string_info(self.instance.preds()).unwrap()
+                )
+            })?;
+
+            match constraint.try_trivial() {
+                Either::Left((Sample { pred, args }, pos)) => {
+                    let is_new = self.staged.add(pred, args, pos);
+                    nu_stuff = nu_stuff || is_new
+                }
+                Either::Right(false) => {
+                    let index = self.link_cstr(constraint)?;
+                    nu_cstrs.push(index)
+                }
+                Either::Right(true) => {
+                    unsat!("by `true => false` in constraint (data, commit_batch)")
+                }
+            }
+        }
+
+        // Single usefulness sweep over the new constraints.
+        for index in nu_cstrs {
+            if self.constraints[index].is_tautology() {
+                continue;
+            }
+            if self.cstr_useful(index).chain_err(|| "in commit_batch")? {
+                nu_stuff = true
+            } else {
+                self.tautologize(index)?
+            }
+        }
+
+        profile! { self mark "commit batch" }
+
+        // Single propagation, also re-checks the new constraints against the new samples.
+        let (pos, neg) = self.propagate()?;
+
+        Ok(nu_stuff || pos > 0 || neg > 0)
+    }
+
     /// Checks the state of the data. Does nothing in release.
     ///
     /// Checks:
@@ -1774,26 +2701,6 @@ impl Data {
         self.check_constraint_data()?;
         self.check_redundant()?;
 
-        macro_rules! constraint_map {
-            ($cstr:expr => |$pred:ident, $sample:ident| $body:expr) => {
-                if let Some(lhs) = $cstr.lhs() {
-                    for (pred, samples) in lhs {
-                        let $pred = *pred;
-                        for $sample in samples {
-                            $body
-                        }
-                    }
-                }
-                if let Some(&Sample {
-                    pred: $pred,
-                    args: ref $sample,
-                }) = $cstr.rhs()
-                {
-                    $body
-                }
-            };
-        }
-
         // Constraints are consistent with map.
         for (idx, constraint) in self.constraints.index_iter() {
             constraint_map! {
@@ -1928,8 +2835,8 @@ impl Data {
     #[cfg(debug_assertions)]
     fn check_constraint_data(&self) -> Res<()> {
         for pred in self.instance.pred_indices() {
-            let pos = self.pos[pred].iter().map(|p| (p, "positive"));
-            let neg = self.neg[pred].iter().map(|n| (n, "negative"));
+            let pos = self.pos_for(pred).map(|p| (p, "positive"));
+            let neg = self.neg_for(pred).map(|n| (n, "negative"));
             for (sample, polarity) in pos.chain(neg) {
                 for (s, set) in &self.map[pred] {
                     if sample.subsumes(s) {
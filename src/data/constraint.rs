@@ -17,13 +17,20 @@ use super::Sample;
 ///   directly for sample removal. Use [`lhs_rm`][lhs rm].)
 /// - `lhs` cannot contain a sample that subsumes `rhs`.
 ///
+/// The lhs is shared between the clones of a constraint, and only copied when one of them modifies
+/// it. Cloning a constraint is thus cheap.
+///
 /// [lhs rm]: #method.lhs_rm (lhs_rm function)
 #[derive(Clone, Debug)]
 pub struct Constraint {
     /// Left-hand side.
-    lhs: Option<PrdHMap<VarValsSet>>,
+    lhs: Option<Arc<PrdHMap<VarValsSet>>>,
     /// Right-hand side.
     rhs: Option<Sample>,
+    /// Predicates appearing in the constraint, see [`pred_set`][preds].
+    ///
+    /// [preds]: #method.pred_set (pred_set function)
+    preds: PrdSet,
 }
 
 impl Constraint {
@@ -33,10 +40,50 @@ impl Constraint {
     ///
     /// - `lhs.is_empty` and `rhs.is_empty()`
     pub fn new(lhs: PrdHMap<VarValsSet>, rhs: Option<Sample>) -> Constraint {
-        Constraint {
-            lhs: Some(lhs),
+        let mut res = Constraint {
+            lhs: Some(Arc::new(lhs)),
             rhs,
+            preds: PrdSet::new(),
+        };
+        res.update_preds();
+        res
+    }
+
+    /// Creates a constraint with the same lhs as this one, and a different rhs.
+    ///
+    /// The lhs is shared with `self`, not copied. Does not check the invariants, in particular
+    /// `rhs` should not be subsumed by a sample of the lhs.
+    ///
+    /// Error if `self` is a tautology.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, data::{ Constraint, Sample }, var_to::vals };
+    /// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+    /// let mut samples = vals::VarValsSet::new();
+    /// samples.insert(vals::new(vec![ val::int(7) ]));
+    /// let mut lhs = PrdHMap::new();
+    /// lhs.insert(p, samples);
+    /// let cstr = Constraint::new(lhs, Some(Sample::new(q, vals::new(vec![ val::int(3) ]))));
+    ///
+    /// let nu_rhs = Sample::new(q, vals::new(vec![ val::int(42) ]));
+    /// let nu_cstr = cstr.substitute_rhs(Some(nu_rhs.clone())).unwrap();
+    /// assert_eq! { nu_cstr.lhs(), cstr.lhs() }
+    /// assert_eq! { nu_cstr.rhs(), Some(&nu_rhs) }
+    /// let nu_cstr = cstr.substitute_rhs(None).unwrap();
+    /// assert_eq! { nu_cstr.lhs(), cstr.lhs() }
+    /// assert_eq! { nu_cstr.rhs(), None }
+    /// ```
+    pub fn substitute_rhs(&self, rhs: Option<Sample>) -> Res<Constraint> {
+        if self.is_tautology() {
+            bail!("cannot substitute the rhs of a tautology")
         }
+        let mut res = Constraint {
+            lhs: self.lhs.clone(),
+            rhs,
+            preds: PrdSet::new(),
+        };
+        res.update_preds();
+        Ok(res)
     }
 
     /// Checks itself.
@@ -78,6 +125,9 @@ impl Constraint {
                 }
             }
         }
+        if self.compute_preds() != self.preds {
+            bail!("cached predicate set is inconsistent")
+        }
 
         Ok(())
     }
@@ -95,13 +145,68 @@ impl Constraint {
 
     /// Lhs accessor.
     pub fn lhs(&self) -> Option<&PrdHMap<VarValsSet>> {
-        self.lhs.as_ref()
+        self.lhs.as_ref().map(|lhs| &**lhs)
     }
     /// Rhs accessor.
     pub fn rhs(&self) -> Option<&Sample> {
         self.rhs.as_ref()
     }
 
+    /// Predicates appearing in the constraint, lhs or rhs.
+    ///
+    /// Cached, empty for tautologies.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, data::{ Constraint, Sample }, var_to::vals };
+    /// let (p, q, r): (PrdIdx, PrdIdx, PrdIdx) = (0.into(), 1.into(), 2.into());
+    /// let mut samples = vals::VarValsSet::new();
+    /// samples.insert(vals::new(vec![ val::int(7) ]));
+    /// let mut lhs = PrdHMap::new();
+    /// lhs.insert(p, samples.clone());
+    /// lhs.insert(q, samples);
+    /// let mut cstr = Constraint::new(lhs, Some(Sample::new(r, vals::new(vec![ val::int(3) ]))));
+    /// assert_eq! { cstr.pred_set(), &vec![p, q, r].into_iter().collect::<PrdSet>() }
+    ///
+    /// cstr.force(r, false, |_, _| Ok(())).unwrap();
+    /// assert_eq! { cstr.pred_set(), &vec![p, q].into_iter().collect::<PrdSet>() }
+    ///
+    /// cstr.tautologize(|_, _| Ok(())).unwrap();
+    /// assert! { cstr.pred_set().is_empty() }
+    /// ```
+    pub fn pred_set(&self) -> &PrdSet {
+        &self.preds
+    }
+
+    /// Computes the predicates appearing in the constraint.
+    fn compute_preds(&self) -> PrdSet {
+        let mut preds = PrdSet::new();
+        if let Some(lhs) = self.lhs() {
+            preds.extend(lhs.keys().cloned())
+        }
+        if let Some(&Sample { pred, .. }) = self.rhs() {
+            preds.insert(pred);
+        }
+        preds
+    }
+
+    /// Updates the cached predicates appearing in the constraint.
+    fn update_preds(&mut self) {
+        self.preds = self.compute_preds()
+    }
+
+    /// Samples appearing in the constraint, lhs first.
+    ///
+    /// Empty for tautologies.
+    pub fn samples(&self) -> Vec<(PrdIdx, VarVals)> {
+        let mut res = Vec::with_capacity(self.lhs_len() + 1);
+        constraint_map! {
+            self => |pred, sample| res.push((pred, sample.clone()))
+        }
+        res
+    }
+
     /// Removes samples subsumed by a sample from the lhs.
     ///
     /// Returns the number of sample removed.
@@ -112,6 +217,10 @@ impl Constraint {
         self.lhs
             .as_mut()
             .map(|lhs| {
+                if !lhs.contains_key(&pred) {
+                    return 0;
+                }
+                let lhs = Arc::make_mut(lhs);
                 let (pred_rm, rmed) = if let Some(argss) = lhs.get_mut(&pred) {
                     let mut rmed = if argss.remove(args) { 1 } else { 0 };
                     if conf.teacher.partial && args.is_partial() {
@@ -144,14 +253,15 @@ impl Constraint {
             f(pred, args)?
         }
 
-        if let Some(lhs) = self.lhs.as_mut() {
-            for (pred, argss) in lhs.drain() {
+        self.preds.clear();
+
+        if let Some(lhs) = self.lhs.take() {
+            for (pred, argss) in lhs.iter() {
                 for args in argss {
-                    f(pred, args)?
+                    f(*pred, args.clone())?
                 }
             }
         }
-        self.lhs = None;
         Ok(())
     }
 
@@ -165,20 +275,92 @@ impl Constraint {
         }
     }
 
+    /// True if `self` subsumes `other`.
+    ///
+    /// That is, if
+    ///
+    /// - every lhs sample of `self` subsumes some lhs sample of `other` for the same predicate,
+    ///   and
+    /// - the rhs of `self` subsumes the rhs of `other`, or both are negative.
+    ///
+    /// A partial rhs is a stronger conclusion than a complete one: `self` then concludes at least
+    /// as much as `other` from less.
+    ///
+    /// On complete samples, subsumption is equality and this boils down to `self.lhs` being a
+    /// subset of `other.lhs` and the rhs being equal.
+    ///
+    /// False if `self` or `other` is a tautology.
+    fn subsumes(&self, other: &Constraint) -> bool {
+        let rhs_ok = match (self.rhs.as_ref(), other.rhs.as_ref()) {
+            (None, None) => true,
+            (Some(rhs), Some(other_rhs)) => {
+                rhs.pred == other_rhs.pred && rhs.args.subsumes(&other_rhs.args)
+            }
+            (Some(_), None) | (None, Some(_)) => false,
+        };
+        if !rhs_ok {
+            return false;
+        }
+
+        match (self.lhs(), other.lhs()) {
+            (Some(lhs), Some(other_lhs)) => lhs.iter().all(|(pred, argss)| {
+                if let Some(other_argss) = other_lhs.get(pred) {
+                    argss.iter().all(|args| {
+                        other_argss.contains(args)
+                            || (args.is_partial()
+                                && other_argss.iter().any(|other| args.subsumes(other)))
+                    })
+                } else {
+                    false
+                }
+            }),
+            (None, _) | (_, None) => false,
+        }
+    }
+
     /// Constraint comparison.
     ///
-    /// Returns `Greater` if both
+    /// Relies on constraint subsumption: `c` subsumes `c'` if every lhs sample of `c` subsumes
+    /// some lhs sample of `c'` (for the same predicate), and the rhs of `c` subsumes the rhs of
+    /// `c'` (or both are negative). Returns
     ///
-    /// - `None` if one of the constraints is a tautology
-    /// - `Greater` if `self.rhs == other.rhs` and `self.lhs` is a subset of
-    ///   `other.rhs`
-    /// - `Less` in the dual case from above
-    /// - `None` otherwise
+    /// - `Equal` if `self` and `other` subsume each other,
+    /// - `Greater` if `self` subsumes `other` but not the other way around,
+    /// - `Less` in the dual case from above,
+    /// - `None` otherwise.
     ///
-    /// So `c >= c'` means `c` has a lhs strictly more generic than `c'`, so `c'`
-    /// is redundant.
+    /// So `c >= c'` means `c` has a lhs and a rhs more generic than `c'`, so `c'` is redundant.
     ///
     /// Error if `self` or `other` is a tautology.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, data::{ Constraint, Sample }, var_to::vals };
+    /// use std::cmp::Ordering::*;
+    /// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+    /// let cstr = |lhs: Vec<Vec<Val>>, rhs: Vec<Val>| {
+    ///     let mut samples = vals::VarValsSet::new();
+    ///     for args in lhs {
+    ///         samples.insert(vals::new(args));
+    ///     }
+    ///     let mut lhs = PrdHMap::new();
+    ///     lhs.insert(p, samples);
+    ///     Constraint::new(lhs, Some(Sample::new(q, vals::new(rhs))))
+    /// };
+    ///
+    /// let c_1 = cstr(vec![ vec![val::int(1), val::int(2)] ], vec![ val::int(0) ]);
+    /// let c_2 = cstr(
+    ///     vec![ vec![val::int(1), val::int(2)], vec![val::int(3), val::int(4)] ],
+    ///     vec![ val::int(0) ],
+    /// );
+    /// assert_eq! { c_1.compare(&c_2).unwrap(), Some(Greater) }
+    /// assert_eq! { c_2.compare(&c_1).unwrap(), Some(Less) }
+    /// assert_eq! { c_1.compare(&c_1).unwrap(), Some(Equal) }
+    ///
+    /// let c_3 = cstr(vec![ vec![val::int(1), val::int(2)] ], vec![ val::int(7) ]);
+    /// assert_eq! { c_1.compare(&c_3).unwrap(), None }
+    /// ```
     pub fn compare(&self, other: &Constraint) -> Res<Option<::std::cmp::Ordering>> {
         use std::cmp::Ordering;
 
@@ -188,43 +370,13 @@ impl Constraint {
             bail!("other is tautology")
         }
 
-        if self.rhs != other.rhs {
-            Ok(None)
-        } else {
-            let (reversed, c_1, c_2) = match self.lhs_len().cmp(&other.lhs_len()) {
-                Ordering::Less => (false, self, other),
-                Ordering::Equal => {
-                    if self == other {
-                        return Ok(Some(Ordering::Equal));
-                    } else {
-                        return Ok(None);
-                    }
-                }
-                Ordering::Greater => (true, other, self),
-            };
-
-            match (c_1.lhs.as_ref(), c_2.lhs.as_ref()) {
-                (Some(lhs_1), Some(lhs_2)) => {
-                    for (pred, samples_1) in lhs_1 {
-                        if let Some(samples_2) = lhs_2.get(pred) {
-                            if !samples_1.is_subset(samples_2) {
-                                return Ok(None);
-                            }
-                        } else {
-                            return Ok(None);
-                        }
-                    }
-                }
-                // Should be unreachable.
-                (None, _) | (_, None) => unreachable!(),
-            }
-
-            if reversed {
-                Ok(Some(Ordering::Less))
-            } else {
-                Ok(Some(Ordering::Greater))
-            }
-        }
+        let res = match (self.subsumes(other), other.subsumes(self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Greater),
+            (false, true) => Some(Ordering::Less),
+            (false, false) => None,
+        };
+        Ok(res)
     }
 
     /// Sets a sample in the constraint.
@@ -268,6 +420,7 @@ impl Constraint {
             self.tautologize(if_tautology)?;
             return Ok(true);
         }
+        self.update_preds();
 
         if !was_in_rhs && !was_in_lhs {
             bail!("asked to remove sample from a clause where it wasn't")
@@ -298,12 +451,17 @@ impl Constraint {
             }
         }
         if let Some(ref mut lhs) = self.lhs {
-            if lhs.remove(&pred).is_some() && !pos {
-                tautology = true
+            if lhs.contains_key(&pred) {
+                Arc::make_mut(lhs).remove(&pred);
+                if !pos {
+                    tautology = true
+                }
             }
         }
         if tautology {
             self.tautologize(if_tautology)?
+        } else {
+            self.preds.remove(&pred);
         }
         Ok(tautology)
     }
@@ -325,6 +483,7 @@ impl Constraint {
             ::std::mem::swap(&mut rhs, &mut self.rhs);
             let mut lhs = None;
             ::std::mem::swap(&mut lhs, &mut self.lhs);
+            self.preds.clear();
 
             if let Some(s) = rhs {
                 Either::Left((s, true))
@@ -350,11 +509,13 @@ impl Constraint {
 
             let mut old_lhs = None;
             ::std::mem::swap(&mut self.lhs, &mut old_lhs);
+            self.preds.clear();
 
             // Only reachable if there's one pred app in lhs.
-            let (pred, argss) = old_lhs.unwrap().into_iter().next().unwrap();
-            let args = argss.into_iter().next().unwrap();
-            Either::Left((Sample { pred, args }, false))
+            let old_lhs = old_lhs.unwrap();
+            let (pred, argss) = old_lhs.iter().next().unwrap();
+            let args = argss.iter().next().unwrap().clone();
+            Either::Left((Sample { pred: *pred, args }, false))
         } else {
             Either::Right(false)
         }
@@ -371,7 +532,7 @@ impl<'a> PebcakFmt<'a> for Constraint {
             if lhs.is_empty() {
                 write!(w, "true ")?
             }
-            for (pred, samples) in lhs {
+            for (pred, samples) in lhs.iter() {
                 for sample in samples {
                     write!(w, "({} {}) ", map[*pred], sample)?
                 }
@@ -394,7 +555,7 @@ mylib::impl_fmt! {
             if lhs.is_empty() {
                 write!(fmt, "true ") ?
             }
-            for (pred, samples) in lhs {
+            for (pred, samples) in lhs.iter() {
                 for sample in samples {
                     write!(fmt, "(p_{} {}) ", pred, sample) ?
                 }
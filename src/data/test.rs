@@ -0,0 +1,164 @@
+//! Tests for the data structures.
+
+use std::cmp::Ordering::*;
+
+use crate::{
+    common::*,
+    data::{Constraint, Sample},
+    var_to::vals::{self, VarValsSet},
+};
+
+fn p() -> PrdIdx {
+    0.into()
+}
+fn q() -> PrdIdx {
+    1.into()
+}
+
+/// Integer value, `None` for a *don't care* value.
+fn v(val: Option<i64>) -> Val {
+    if let Some(val) = val {
+        val::int(val)
+    } else {
+        val::none(typ::int())
+    }
+}
+
+/// Builds a constraint from the lhs samples for `p` and the rhs sample for `q`.
+fn cstr(lhs: Vec<Vec<Option<i64>>>, rhs: Option<Vec<Option<i64>>>) -> Constraint {
+    let mut samples = VarValsSet::new();
+    for args in lhs {
+        let args: Vec<_> = args.into_iter().map(v).collect();
+        samples.insert(vals::new(args));
+    }
+    let mut lhs = PrdHMap::new();
+    lhs.insert(p(), samples);
+    let rhs = rhs.map(|args| {
+        let args: Vec<_> = args.into_iter().map(v).collect();
+        Sample::new(q(), vals::new(args))
+    });
+    Constraint::new(lhs, rhs)
+}
+
+#[test]
+fn cstr_compare_complete() {
+    let c_1 = cstr(vec![vec![Some(1), Some(2)]], None);
+    let c_2 = cstr(vec![vec![Some(1), Some(2)], vec![Some(3), Some(4)]], None);
+    assert_eq! { c_1.compare(&c_2).unwrap(), Some(Greater) }
+    assert_eq! { c_2.compare(&c_1).unwrap(), Some(Less) }
+    assert_eq! { c_2.compare(&c_2).unwrap(), Some(Equal) }
+
+    let c_3 = cstr(vec![vec![Some(3), Some(4)], vec![Some(5), Some(6)]], None);
+    assert_eq! { c_2.compare(&c_3).unwrap(), None }
+    assert_eq! { c_3.compare(&c_2).unwrap(), None }
+}
+
+#[test]
+fn cstr_compare_partial_lhs() {
+    let partial = cstr(vec![vec![Some(1), None]], Some(vec![Some(0)]));
+    let complete = cstr(vec![vec![Some(1), Some(2)]], Some(vec![Some(0)]));
+    assert_eq! { partial.compare(&complete).unwrap(), Some(Greater) }
+    assert_eq! { complete.compare(&partial).unwrap(), Some(Less) }
+
+    // Partial samples that do not subsume each other.
+    let other_partial = cstr(vec![vec![None, Some(2)]], Some(vec![Some(0)]));
+    assert_eq! { partial.compare(&other_partial).unwrap(), None }
+    assert_eq! { other_partial.compare(&partial).unwrap(), None }
+
+    // Not the same value.
+    let complete = cstr(vec![vec![Some(7), Some(2)]], Some(vec![Some(0)]));
+    assert_eq! { partial.compare(&complete).unwrap(), None }
+    assert_eq! { complete.compare(&partial).unwrap(), None }
+}
+
+#[test]
+fn cstr_compare_partial_lhs_shared() {
+    // Both lhs samples of `partial` subsume the only lhs sample of `complete`.
+    let partial = cstr(vec![vec![Some(1), None], vec![None, Some(2)]], None);
+    let complete = cstr(vec![vec![Some(1), Some(2)]], None);
+    assert_eq! { partial.compare(&complete).unwrap(), Some(Greater) }
+    assert_eq! { complete.compare(&partial).unwrap(), Some(Less) }
+
+    // One of the lhs samples of `partial` does not subsume anything.
+    let partial = cstr(vec![vec![Some(1), None], vec![None, Some(3)]], None);
+    assert_eq! { partial.compare(&complete).unwrap(), None }
+    assert_eq! { complete.compare(&partial).unwrap(), None }
+}
+
+#[test]
+fn cstr_compare_partial_rhs() {
+    let complete = cstr(vec![vec![Some(1), Some(2)]], Some(vec![Some(0)]));
+    let partial = cstr(vec![vec![Some(1), Some(2)]], Some(vec![None]));
+    // The rhs of `partial` subsumes the rhs of `complete`.
+    assert_eq! { partial.compare(&complete).unwrap(), Some(Greater) }
+    assert_eq! { complete.compare(&partial).unwrap(), Some(Less) }
+
+    // More generic lhs, but less generic rhs.
+    let lhs_partial = cstr(vec![vec![Some(1), None]], Some(vec![Some(0)]));
+    assert_eq! { lhs_partial.compare(&partial).unwrap(), None }
+    assert_eq! { partial.compare(&lhs_partial).unwrap(), None }
+
+    let both_partial = cstr(vec![vec![Some(1), None]], Some(vec![None]));
+    assert_eq! { both_partial.compare(&partial).unwrap(), Some(Greater) }
+    assert_eq! { partial.compare(&both_partial).unwrap(), Some(Less) }
+    assert_eq! { both_partial.compare(&complete).unwrap(), Some(Greater) }
+}
+
+#[test]
+fn cstr_useful_partial_rhs() {
+    use crate::{data::Data, var_to::vals::RVarVals};
+    let sample = |vals: Vec<Option<i64>>| {
+        let vals: Vec<_> = vals.into_iter().map(v).collect();
+        let vals: RVarVals = vals.into();
+        vals
+    };
+
+    let mut data = Data::new(Arc::new(crate::parse::mc_91()));
+    let p_0: PrdIdx = 0.into();
+    data.add_data(
+        1.into(),
+        vec![(p_0, sample(vec![Some(1), Some(101)]))],
+        Some((p_0, sample(vec![Some(7), Some(3)]))),
+    )
+    .unwrap();
+    // Same lhs, partial rhs: the first constraint is redundant.
+    data.add_data(
+        1.into(),
+        vec![(p_0, sample(vec![Some(1), Some(101)]))],
+        Some((p_0, sample(vec![Some(7), None]))),
+    )
+    .unwrap();
+
+    let live: Vec<_> = data
+        .constraints
+        .iter()
+        .filter(|cstr| !cstr.is_tautology())
+        .collect();
+    assert_eq! { live.len(), 1 }
+    assert! { live[0].rhs().unwrap().args.is_partial() }
+}
+
+#[test]
+fn cstr_compare_rhs_mismatch() {
+    let neg = cstr(vec![vec![Some(1), None]], None);
+    let pos = cstr(vec![vec![Some(1), Some(2)]], Some(vec![Some(0)]));
+    assert_eq! { neg.compare(&pos).unwrap(), None }
+    assert_eq! { pos.compare(&neg).unwrap(), None }
+
+    let mut other_pred = PrdHMap::new();
+    let mut samples = VarValsSet::new();
+    samples.insert(vals::new(vec![v(Some(1)), v(Some(2))]));
+    other_pred.insert(q(), samples);
+    let other_pred = Constraint::new(other_pred, None);
+    assert_eq! { neg.compare(&other_pred).unwrap(), None }
+    assert_eq! { other_pred.compare(&neg).unwrap(), None }
+}
+
+#[test]
+fn cstr_compare_tautology() {
+    let cstr_1 = cstr(vec![vec![Some(1), None]], None);
+    let mut cstr_2 = cstr_1.clone();
+    cstr_2.tautologize(|_, _| Ok(())).unwrap();
+    assert! { cstr_1.compare(&cstr_2).is_err() }
+    assert! { cstr_2.compare(&cstr_1).is_err() }
+}
@@ -565,6 +565,52 @@ pub fn check_reserved(_name: &str) -> Res<()> {
     Ok(())
 }
 
+/// Creates mutually recursive datatypes, allowing references to undeclared datatypes.
+///
+/// Same as [`new_recs`], except that the datatypes are not [checked]. They can mention datatypes
+/// that are declared later, [`RDTyp::undeclared`] lists the ones that are not declared yet.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::{ dtyp, dtyp::{ RDTyp, PartialTyp }, parse::Pos };
+/// let dummy_pos = Pos::default();
+/// let (fst_name, snd_name) = ("DTypTestFst", "DTypTestSnd");
+/// let mk = |name: &str, other: &str| {
+///     let mut dtyp = RDTyp::new(name);
+///     dtyp.add_constructor(format!("{}_nil", name), vec![]).unwrap();
+///     dtyp.add_constructor(format!("{}_cons", name), vec![(
+///         format!("{}_next", name), PartialTyp::DTyp(other.into(), dummy_pos, vec![].into())
+///     )]).unwrap();
+///     dtyp
+/// };
+///
+/// let fst = dtyp::new_recs_unchecked(vec![ mk(fst_name, snd_name) ]).unwrap().pop().unwrap();
+/// assert! { fst.check().is_err() }
+/// assert_eq! { fst.undeclared(), vec![ (dummy_pos, snd_name.to_string()) ] }
+///
+/// let snd = dtyp::new_recs_unchecked(vec![ mk(snd_name, fst_name) ]).unwrap().pop().unwrap();
+/// assert! { snd.undeclared().is_empty() }
+/// fst.check().unwrap();
+/// snd.check().unwrap();
+/// ```
+///
+/// [`new_recs`]: fn.new_recs.html (datatypes construction function)
+/// [checked]: struct.RDTyp.html#method.check (check function of RDTyp)
+/// [`RDTyp::undeclared`]: struct.RDTyp.html#method.undeclared
+/// (undeclared function of RDTyp)
+pub fn new_recs_unchecked<RDTyps>(dtyp: RDTyps) -> Result<Vec<DTyp>, (usize, Error)>
+where
+    RDTyps: IntoIterator<Item = RDTyp>,
+{
+    let mut res = vec![];
+    for (index, dtyp) in dtyp.into_iter().enumerate() {
+        check_reserved(&dtyp.name).map_err(|e| (index, e))?;
+        res.push(new_raw(dtyp).map_err(|e| (index, e))?)
+    }
+    Ok(res)
+}
+
 /// Creates a datatype.
 ///
 /// Will fail if either
@@ -621,11 +667,7 @@ where
     F: Fn(Pos, String) -> E,
     RDTyps: IntoIterator<Item = RDTyp>,
 {
-    let mut res = vec![];
-    for (index, dtyp) in dtyp.into_iter().enumerate() {
-        check_reserved(&dtyp.name).map_err(|e| (index, e))?;
-        res.push(new_raw(dtyp).map_err(|e| (index, e))?)
-    }
+    let res = new_recs_unchecked(dtyp)?;
     for (index, dtyp) in res.iter().enumerate() {
         if let Err((pos, blah)) = dtyp.check() {
             return Err((index, err(pos, blah).into()));
@@ -860,6 +902,79 @@ pub fn get(dtyp: &str) -> Res<DTyp> {
     }
 }
 
+/// Forgets a datatype.
+///
+/// Used when resetting, so that the datatype can be declared again. Also forgets its constructors
+/// and selectors. Returns the datatype forgotten, if any.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// use hoice::dtyp::{ RDTyp, PartialTyp };
+/// let mk_dtyp = || {
+///     let mut dtyp = RDTyp::new("DTypForgetTest");
+///     dtyp.add_constructor("forget_test_leaf", vec![])
+///         .expect("while adding `forget_test_leaf` constructor");
+///     dtyp.add_constructor(
+///         "forget_test_node", vec![ ("forget_test_val".into(), PartialTyp::Typ(typ::int())) ]
+///     ).expect("while adding `forget_test_node` constructor");
+///     dtyp
+/// };
+/// dtyp::new(mk_dtyp(), |_, blah| blah).expect("while creating datatype");
+/// assert! { dtyp::of_constructor("forget_test_node").is_some() }
+/// assert! { dtyp::is_selector("forget_test_val") }
+///
+/// assert! { dtyp::forget("DTypForgetTest").unwrap().is_some() }
+/// assert! { dtyp::get("DTypForgetTest").is_err() }
+/// assert! { dtyp::of_constructor("forget_test_node").is_none() }
+/// assert! { ! dtyp::is_selector("forget_test_val") }
+/// assert! { dtyp::forget("DTypForgetTest").unwrap().is_none() }
+///
+/// // Declaring it again is fine.
+/// dtyp::new(mk_dtyp(), |_, blah| blah).expect("while re-creating datatype");
+/// ```
+pub fn forget(name: &str) -> Res<Option<DTyp>> {
+    let dtyp = if let Ok(mut f) = factory.write() {
+        if let Some(dtyp) = f.remove(name) {
+            dtyp
+        } else {
+            return Ok(None);
+        }
+    } else {
+        bail!("failed to access datatype factory")
+    };
+
+    if let Ok(mut map) = constructor_map.write() {
+        for constructor in dtyp.news.keys() {
+            map.remove(constructor);
+        }
+    } else {
+        bail!("failed to retrieve datatype constructor map")
+    }
+
+    // Selectors of the remaining datatypes.
+    let mut selectors = BTreeSet::new();
+    if let Ok(f) = factory.read() {
+        for other in f.values() {
+            for args in other.news.values() {
+                for (selector, _) in args {
+                    selectors.insert(selector.clone());
+                }
+            }
+        }
+    } else {
+        bail!("failed to access datatype factory")
+    }
+    if let Ok(mut set) = selector_set.write() {
+        *set = selectors
+    } else {
+        bail!("failed to retrieve datatype selector set")
+    }
+
+    Ok(Some(dtyp))
+}
+
 /// All the datatypes.
 pub fn get_all() -> impl ::std::ops::Deref<Target = BTreeMap<String, DTyp>> {
     factory.read().expect("failed to access datatype factory")
@@ -877,7 +992,83 @@ pub fn write_constructor_map<W: Write>(w: &mut W, pref: &str) -> ::std::io::Resu
     Ok(())
 }
 
+/// Groups datatypes by mutual recursion.
+///
+/// Yields the strongly connected components of the graph of the [dependencies] between the
+/// datatypes. A group only depends on itself and on the groups appearing before it. Datatypes
+/// inside a group are sorted by name.
+///
+/// [dependencies]: struct.RDTyp.html#method.dtyp_deps (dtyp_deps function of RDTyp)
+fn rec_groups(decs: &BTreeMap<String, DTyp>) -> Vec<Vec<DTyp>> {
+    /// Tarjan's algorithm context.
+    struct Cxt<'a> {
+        decs: &'a BTreeMap<String, DTyp>,
+        index: BTreeMap<String, usize>,
+        low: BTreeMap<String, usize>,
+        stack: Vec<String>,
+        groups: Vec<Vec<DTyp>>,
+    }
+
+    fn visit(cxt: &mut Cxt, name: &str) {
+        let index = cxt.index.len();
+        cxt.index.insert(name.into(), index);
+        cxt.low.insert(name.into(), index);
+        cxt.stack.push(name.into());
+
+        let deps = cxt.decs[name].dtyp_deps();
+        for dep in deps {
+            if !cxt.decs.contains_key(&dep) {
+                continue;
+            }
+            let dep_low = if !cxt.index.contains_key(&dep) {
+                visit(cxt, &dep);
+                cxt.low[&dep]
+            } else if cxt.stack.contains(&dep) {
+                cxt.index[&dep]
+            } else {
+                continue;
+            };
+            let low = cxt
+                .low
+                .get_mut(name)
+                .expect("unknown datatype in rec_groups");
+            if dep_low < *low {
+                *low = dep_low
+            }
+        }
+
+        if cxt.low[name] == cxt.index[name] {
+            let mut group = vec![];
+            while let Some(other) = cxt.stack.pop() {
+                let done = other == name;
+                group.push(cxt.decs[&other].clone());
+                if done {
+                    break;
+                }
+            }
+            group.sort_by(|d_1, d_2| d_1.name.cmp(&d_2.name));
+            cxt.groups.push(group)
+        }
+    }
+
+    let mut cxt = Cxt {
+        decs,
+        index: BTreeMap::new(),
+        low: BTreeMap::new(),
+        stack: vec![],
+        groups: vec![],
+    };
+    for name in decs.keys() {
+        if !cxt.index.contains_key(name) {
+            visit(&mut cxt, name)
+        }
+    }
+    cxt.groups
+}
+
 /// Writes all the datatypes, SMT-LIB style.
+///
+/// Mutually recursive datatypes are declared together, even if they were declared separately.
 pub fn write_all<W: Write>(w: &mut W, pref: &str) -> ::std::io::Result<()> {
     let decs = get_all();
 
@@ -885,31 +1076,14 @@ pub fn write_all<W: Write>(w: &mut W, pref: &str) -> ::std::io::Result<()> {
         return Ok(());
     }
 
-    let mut known = HashSet::new();
     let dtyp_pref = &format!("{}  ", pref);
 
-    for (name, dtyp) in decs.iter() {
-        if reserved_dtyps.contains(name.as_str()) {
-            continue;
-        }
-        let is_new = known.insert(name);
-        if !is_new {
+    for mut all in rec_groups(&decs) {
+        all.retain(|dtyp| !reserved_dtyps.contains(dtyp.name.as_str()));
+        if all.is_empty() {
             continue;
         }
 
-        let mut all = vec![dtyp.clone()];
-
-        for dtyp in &dtyp.deps {
-            let is_new = known.insert(dtyp);
-            assert! { is_new }
-
-            if let Ok(dtyp) = get(dtyp) {
-                all.push(dtyp)
-            } else {
-                panic!("inconsistent datatype factory/maps state")
-            }
-        }
-
         writeln!(w, "{}({} (", pref, keywords::cmd::dec_dtyps)?;
         write!(w, "{} ", pref)?;
 
@@ -923,11 +1097,6 @@ pub fn write_all<W: Write>(w: &mut W, pref: &str) -> ::std::io::Result<()> {
             dtyp.write_dec(w, dtyp_pref)?
         }
 
-        for dtyp in &dtyp.deps {
-            let is_new = known.insert(dtyp);
-            assert!(is_new)
-        }
-
         writeln!(w, "{}) )", pref)?
     }
 
@@ -936,6 +1105,114 @@ pub fn write_all<W: Write>(w: &mut W, pref: &str) -> ::std::io::Result<()> {
     Ok(())
 }
 
+/// Writes the definition of the `dtyp-size` function for some datatypes.
+///
+/// Solvers do not know about this operator, so it is defined as a function overloaded on the
+/// datatypes without type parameters, which is a z3 extension. Only the datatypes in `used` and
+/// the ones their constructors mention are considered, see [`Instance::used_dtyps`]. Datatypes
+/// with type parameters are ignored. Must be called after [`write_all`].
+///
+/// [`write_all`]: fn.write_all.html (write_all function)
+/// [`Instance::used_dtyps`]: ../instance/struct.Instance.html#method.used_dtyps
+/// (used_dtyps function of Instance)
+pub fn write_size_funs<W: Write>(
+    w: &mut W,
+    pref: &str,
+    used: &BTreeSet<String>,
+) -> ::std::io::Result<()> {
+    /// Name of the datatype of a constructor argument, if it has no type parameters.
+    fn mono_dtyp(typ: &PartialTyp) -> Option<&str> {
+        match typ {
+            PartialTyp::DTyp(name, _, prms) if prms.is_empty() => Some(name.as_str()),
+            PartialTyp::Typ(typ) => match typ.dtyp_inspect() {
+                Some((dtyp, prms)) if prms.is_empty() => Some(dtyp.name.as_str()),
+                _ => None,
+            },
+            PartialTyp::DTyp(_, _, _) | PartialTyp::Array(_, _) | PartialTyp::Param(_) => None,
+        }
+    }
+
+    let decs = get_all();
+
+    // Close the used datatypes under constructor arguments.
+    let mut reachable: BTreeSet<&str> = BTreeSet::new();
+    let mut todo: Vec<&str> = used.iter().map(|name| name.as_str()).collect();
+    while let Some(name) = todo.pop() {
+        if !reachable.insert(name) {
+            continue;
+        }
+        if let Some(dtyp) = decs.get(name) {
+            for args in dtyp.news.values() {
+                for (_, typ) in args {
+                    if let Some(name) = mono_dtyp(typ) {
+                        todo.push(name)
+                    }
+                }
+            }
+        }
+    }
+
+    let dtyps: Vec<_> = decs
+        .iter()
+        .filter(|(name, dtyp)| {
+            reachable.contains(name.as_str())
+                && !reserved_dtyps.contains(name.as_str())
+                && dtyp.prms.is_empty()
+        })
+        .map(|(_, dtyp)| dtyp)
+        .collect();
+    if dtyps.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(w, "{}({} (", pref, keywords::cmd::def_funs_rec)?;
+    for dtyp in &dtyps {
+        writeln!(
+            w,
+            "{}  ({} ((x {})) Int)",
+            pref,
+            keywords::op::dtyp_size_,
+            dtyp.name
+        )?
+    }
+    writeln!(w, "{}) (", pref)?;
+    for dtyp in &dtyps {
+        write!(w, "{}  ", pref)?;
+        let mut news = dtyp.news.iter().peekable();
+        let mut closing = 0;
+        while let Some((constructor, args)) = news.next() {
+            let mut size = "1".to_string();
+            for (selector, typ) in args {
+                if mono_dtyp(typ).is_some() {
+                    size = format!("{} ({} ({} x))", size, keywords::op::dtyp_size_, selector)
+                }
+            }
+            if size != "1" {
+                size = format!("(+ {})", size)
+            }
+
+            if news.peek().is_some() {
+                write!(
+                    w,
+                    "({} ({}-{} x) {} ",
+                    keywords::op::ite_,
+                    keywords::op::is_,
+                    constructor,
+                    size
+                )?;
+                closing += 1
+            } else {
+                write!(w, "{}", size)?
+            }
+        }
+        for _ in 0..closing {
+            write!(w, ")")?
+        }
+        writeln!(w)?
+    }
+    writeln!(w, "{}) )", pref)
+}
+
 /// Types a constructor application.
 ///
 /// Returns `None` if the constructor is unknown.
@@ -1248,6 +1525,79 @@ impl RDTyp {
         self.deps.push(dep.into())
     }
 
+    /// Datatypes mentioned by this datatype, and its explicit dependencies.
+    ///
+    /// Does not contain the datatype itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let list = dtyp::get("List").unwrap();
+    /// assert! { list.dtyp_deps().is_empty() }
+    /// ```
+    pub fn dtyp_deps(&self) -> BTreeSet<String> {
+        let mut res: BTreeSet<String> = self.deps.iter().cloned().collect();
+        for cargs in self.news.values() {
+            for (_, ptyp) in cargs {
+                let mut stack = vec![ptyp];
+                while let Some(ptyp) = stack.pop() {
+                    match ptyp {
+                        PartialTyp::Array(src, tgt) => {
+                            stack.push(&**src);
+                            stack.push(&**tgt)
+                        }
+                        PartialTyp::DTyp(name, _, prms) => {
+                            res.insert(name.clone());
+                            stack.extend(prms.iter())
+                        }
+                        PartialTyp::Typ(typ) => {
+                            if let Some((dtyp, _)) = typ.dtyp_inspect() {
+                                res.insert(dtyp.name.clone());
+                            }
+                        }
+                        PartialTyp::Param(_) => (),
+                    }
+                }
+            }
+        }
+        res.remove(&self.name);
+        res
+    }
+
+    /// Datatypes mentioned by this datatype that are not declared, with the position of the
+    /// reference.
+    ///
+    /// Datatypes created with [`new_recs_unchecked`] can mention datatypes that are declared
+    /// later.
+    ///
+    /// [`new_recs_unchecked`]: fn.new_recs_unchecked.html
+    /// (new_recs_unchecked function)
+    pub fn undeclared(&self) -> Vec<(Pos, String)> {
+        let mut res = vec![];
+        for cargs in self.news.values() {
+            for (_, ptyp) in cargs {
+                let mut stack = vec![ptyp];
+                while let Some(ptyp) = stack.pop() {
+                    match ptyp {
+                        PartialTyp::Array(src, tgt) => {
+                            stack.push(&**src);
+                            stack.push(&**tgt)
+                        }
+                        PartialTyp::DTyp(name, pos, prms) => {
+                            if get(name).is_err() {
+                                res.push((*pos, name.clone()))
+                            }
+                            stack.extend(prms.iter())
+                        }
+                        PartialTyp::Typ(_) | PartialTyp::Param(_) => (),
+                    }
+                }
+            }
+        }
+        res
+    }
+
     /// Checks a datatype is legal.
     ///
     /// This checks that all partial types are legal, meaning that all datatypes referenced *must
@@ -6,12 +6,15 @@
 //! - [`ErrorKind::Unsat`]
 //! - [`ErrorKind::Timeout`]
 //! - [`ErrorKind::Unknown`] (when hoice gave up on solving the clauses)
+//! - [`ErrorKind::PartialModel`] (when hoice reached its iteration limit)
 //!
 //! As such, one should **not** use the usual `chain_err` function but [`chain`] instead.
 //!
 //! [`ErrorKind::Unsat`]: enum.ErrorKind.html#variant.Unsat (Unsat variant of ErrorKind)
 //! [`ErrorKind::Timeout`]: enum.ErrorKind.html#variant.Timeout (Timeout variant of ErrorKind)
 //! [`ErrorKind::Unknown`]: enum.ErrorKind.html#variant.Unknown (Unknown variant of ErrorKind)
+//! [`ErrorKind::PartialModel`]: enum.ErrorKind.html#variant.PartialModel
+//! (PartialModel variant of ErrorKind)
 //! [`chain`]: struct.Error.html#method.chain (chain function over Error)
 
 use crate::common::*;
@@ -152,6 +155,11 @@ error_chain::error_chain! {
             description(consts::err::unknown_desc)
             display("unknown")
         }
+        #[doc = "Not really an error, unknown early return with a partial, non-validated model."]
+        PartialModel(model: ConjCandidates) {
+            description(consts::err::unknown_desc)
+            display("unknown")
+        }
         #[doc = "Not really an error, unsat early return."]
         Unsat {
             description(consts::err::unsat_desc)
@@ -212,6 +220,19 @@ impl Error {
         }
     }
 
+    /// Returns the partial model attached to an unknown result if any.
+    ///
+    /// This model is **not** validated, see [`ErrorKind::PartialModel`][partial].
+    ///
+    /// [partial]: enum.ErrorKind.html#variant.PartialModel
+    /// (ErrorKind's PartialModel variant)
+    pub fn partial_model(&self) -> Option<&ConjCandidates> {
+        match self.kind() {
+            ErrorKind::PartialModel(model) => Some(model),
+            _ => None,
+        }
+    }
+
     /// True if the kind of the error is a timeout.
     ///
     /// [timeout]: enum.ErrorKind.html#variant.Timeout
@@ -228,6 +249,18 @@ impl Error {
         false
     }
 
+    /// Position of a [`ErrorKind::Z3SpawnError`][spawn] in the error chain, if any.
+    ///
+    /// The error kind is usually not at top-level, since failing to spawn the solver is chained
+    /// with the context it happened in.
+    ///
+    /// [spawn]: enum.ErrorKind.html#variant.Z3SpawnError
+    /// (ErrorKind's Z3SpawnError variant)
+    pub fn z3_spawn_index(&self) -> Option<usize> {
+        let desc = ErrorKind::Z3SpawnError.to_string();
+        self.iter().position(|err| err.to_string() == desc)
+    }
+
     /// True if the kind of the error is [`ErrorKind::Exit`][exit].
     ///
     /// [exit]: enum.ErrorKind.html#variant.Exit (ErrorKind's Exit variant)
@@ -243,15 +276,15 @@ impl Error {
 
 /// Prints an error.
 pub fn print_err(errs: &Error) {
-    println!("({} \"", conf.bad("error"));
+    println_side!("({} \"", conf.bad("error"));
     let mut list = vec![];
     for err in errs.iter() {
         list.push(err)
     }
     for err in list.into_iter().rev() {
         for line in format!("{}", err).lines() {
-            println!("  {}", line)
+            println_side!("  {}", line)
         }
     }
-    println!("\")")
+    println_side!("\")")
 }
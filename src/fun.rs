@@ -503,6 +503,59 @@ pub fn get(name: &str) -> Option<Fun> {
     f.get(name).cloned()
 }
 
+/// Forgets a function signature.
+///
+/// Signatures are removed when the function is defined, see [`retrieve_sig`]. Used when
+/// resetting, to forget the signatures of functions whose definition failed to parse. Returns
+/// the signature forgotten, if any.
+///
+/// [`retrieve_sig`]: fn.retrieve_sig.html (retrieve_sig function)
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::{ common::*, fun, info::VarInfo };
+/// let sig: VarInfos = vec![ VarInfo::new("v_0", typ::int(), 0.into()) ].into();
+/// let fun_name = "fun_forget_sig_test";
+/// let sig = fun::FunSig::new(fun_name, sig, typ::int());
+/// fun::register_sig(sig.clone()).expect("during first signature registration");
+/// assert! { fun::register_sig(sig.clone()).is_err() }
+/// assert! { fun::forget_sig(fun_name).is_some() }
+/// assert! { fun::forget_sig(fun_name).is_none() }
+/// fun::register_sig(sig).expect("registration after forgetting the signature");
+/// ```
+pub fn forget_sig(name: &str) -> Option<FunSig> {
+    fun_sigs
+        .write()
+        .expect("function signatures are corrupted...")
+        .remove(name)
+}
+
+/// Forgets a function definition.
+///
+/// Used when resetting, so that the function can be defined again. Returns the definition
+/// forgotten, if any. Terms mentioning the function are not affected but cannot be evaluated
+/// anymore.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::{ common::*, fun, info::VarInfo };
+/// let sig: VarInfos = vec![ VarInfo::new("v_0", typ::int(), 0.into()) ].into();
+/// let fun_name = "fun_forget_test_identity";
+/// let sig = fun::FunSig::new(fun_name, sig, typ::int());
+/// let def = sig.into_fun( term::int_var(0) );
+/// fun::new(def.clone()).expect("during first function registration");
+/// assert! { fun::forget(fun_name).is_some() }
+/// assert! { fun::get(fun_name).is_none() }
+/// assert! { fun::forget(fun_name).is_none() }
+/// fun::new(def).expect("registration after forgetting the function");
+/// ```
+pub fn forget(name: &str) -> Option<Fun> {
+    let f = factory!(write);
+    f.remove(name)
+}
+
 /// A function signature, used when creating (mutually) recursive function(s).
 ///
 /// For details, see [module-level documentation].
@@ -52,6 +52,13 @@ use crate::instance::Instance;
 
 /// Parses command-line arguments and works.
 pub fn work() -> Res<()> {
+    // Make sure the solver can be spawned before doing anything, parse-only mode never spawns it.
+    if !conf.parse_only {
+        conf.solver.probe()?
+    }
+    // Profiling output, see `--profile_to`.
+    open_profile_out()?;
+
     // Reading from file?
     if let Some(file_path) = conf.in_file() {
         use std::fs::OpenOptions;
@@ -90,6 +97,16 @@ pub fn work() -> Res<()> {
 ///   complete. Only used in tests.
 ///
 /// - `stop_on_err`: forces to stop at the first error. Only used in tests.
+///
+/// When not reading from a file, the script can have several `check-sat`s with new clauses in
+/// between. In this case each check works on a copy of the instance parsed so far, and the
+/// learning data and model of each check are used to warm-start the next one. See [`WarmData`].
+///
+/// A `check-sat-assuming` deactivates the clauses whose name appears negated in its literals, for
+/// this check only. It also works on a copy of the instance, so that several of them can follow
+/// each other without parsing the clauses again.
+///
+/// [`WarmData`]: data/struct.WarmData.html (WarmData struct)
 pub fn read_and_work<R: ::std::io::Read>(
     reader: R,
     file_input: bool,
@@ -109,6 +126,14 @@ pub fn read_and_work<R: ::std::io::Read>(
     let mut line_off = 0;
     // Instance.
     let mut instance = Instance::new();
+    // Unpreprocessed instance, only used when not reading from a file.
+    let mut parsed: Option<Instance> = None;
+    // Learning data from previous checks, only used when not reading from a file.
+    let mut warm = if file_input {
+        None
+    } else {
+        Some(data::WarmData::new())
+    };
     // Current model.
     let mut model = None;
     // Any error encountered?
@@ -118,10 +143,16 @@ pub fn read_and_work<R: ::std::io::Read>(
     //
     // - `None`             if not unsat
     let mut unsat = None;
+    // True if the last check deactivated some clauses, in which case its result does not carry
+    // over to the next check.
+    let mut assuming = false;
 
     // Original instance.
     let mut original_instance = None;
 
+    // JSON report on the last model, see `--model_stats`.
+    let mut model_stats_json = None;
+
     'parse_work: loop {
         use crate::parse::Parsed;
 
@@ -136,7 +167,7 @@ pub fn read_and_work<R: ::std::io::Read>(
         }
         let parse_res = parser_cxt
             .parser(&buf, line_off, &profiler)
-            .parse(&mut instance);
+            .parse(parsed.as_mut().unwrap_or(&mut instance));
 
         line_off += lines_parsed;
 
@@ -157,8 +188,18 @@ pub fn read_and_work<R: ::std::io::Read>(
 
         match parse_res {
             // Check-sat on unsat instance?
-            Parsed::CheckSat if unsat.is_some() => {
+            Parsed::CheckSat if unsat.is_some() && !assuming => {
                 println!("unsat");
+                check_status(&instance, Some(false))?;
+
+                if stop_on_check {
+                    return Ok((model, instance));
+                }
+            }
+
+            // Check-sat in parse-only mode, print the instance back.
+            Parsed::CheckSat | Parsed::CheckSatAssuming if conf.parse_only => {
+                instance.reprint(&mut stdout())?;
 
                 if stop_on_check {
                     return Ok((model, instance));
@@ -166,7 +207,70 @@ pub fn read_and_work<R: ::std::io::Read>(
             }
 
             // Check-sat, start class.
-            Parsed::CheckSat => {
+            Parsed::CheckSat | Parsed::CheckSatAssuming => {
+                let deactivated = parsed
+                    .as_mut()
+                    .unwrap_or(&mut instance)
+                    .take_deactivated_clauses();
+                // Results obtained under assumptions do not carry over.
+                unsat = None;
+                // Sample counts of the learned predicates, for `--model_stats`.
+                let mut sample_counts = data::SampleCounts::new();
+
+                // More clauses might come after this check, work on a copy of the instance.
+                if let Some(warm) = warm.as_mut() {
+                    if !deactivated.is_empty() {
+                        // Data learned with more clauses is not sound when some are deactivated.
+                        *warm = data::WarmData::new()
+                    }
+                    let parsed = parsed.get_or_insert_with(|| instance.clone());
+                    warm.update(parsed);
+                    instance = parsed.clone();
+                    instance.set_warm(warm.clone())
+                } else if parse_res == Parsed::CheckSatAssuming
+                    || parsed.is_some()
+                    || instance.has_old_clause_names()
+                {
+                    // Later `check-sat-assuming`s might activate clauses this check deactivates,
+                    // or deactivate clauses it uses.
+                    let parsed = parsed.get_or_insert_with(|| instance.clone());
+                    instance = parsed.clone()
+                }
+
+                instance
+                    .deactivate_clauses(&deactivated)
+                    .chain_err(|| "while deactivating clauses")?;
+                assuming = !deactivated.is_empty();
+
+                // Instance too large to attempt.
+                if let Some(max) = conf.instance.max_clauses {
+                    let count = instance.clauses().len();
+                    if count > max {
+                        println!("unknown");
+                        println_side!(
+                            "; instance too large, not attempted: {} clauses (`--max_clauses {}`)",
+                            count,
+                            max
+                        );
+                        model = None;
+                        if stop_on_check {
+                            return Ok((model, instance));
+                        }
+                        continue;
+                    }
+                }
+
+                // Clause counts before pre-processing, for `--model_stats`.
+                let clause_counts: Option<PrdMap<(usize, usize)>> = if conf.model_stats {
+                    let counts = instance.pred_indices().map(|pred| {
+                        let (lhs, rhs) = instance.clauses_of(pred);
+                        (lhs.len(), rhs.len())
+                    });
+                    Some(counts.collect())
+                } else {
+                    None
+                };
+
                 if instance.proofs() {
                     let mut old = instance.clone();
                     old.finalize()
@@ -184,7 +288,8 @@ pub fn read_and_work<R: ::std::io::Read>(
                     Ok(()) => (),
                     Err(e) => {
                         if e.is_timeout() {
-                            println!("timeout");
+                            println!("{}", timeout_status());
+                            write_profile_json(&profiler, &model_stats_json)?;
                             print_stats("top", profiler);
                             ::std::process::exit(0)
                         } else if e.is_unknown() {
@@ -199,6 +304,15 @@ pub fn read_and_work<R: ::std::io::Read>(
                 }
                 print_stats("top preproc", preproc_profiler);
 
+                // Dry-run mode, print some statistics and stop.
+                if conf.dry_run {
+                    if unsat.is_some() {
+                        println_side!("unsat by pre-processing")
+                    }
+                    println_side!("{}", instance.stats());
+                    break 'parse_work;
+                }
+
                 model = if instance.simplify_clauses() {
                     if let Some(maybe_model) = instance.is_trivial_conj()? {
                         // Pre-processing already decided satisfiability.
@@ -217,7 +331,16 @@ pub fn read_and_work<R: ::std::io::Read>(
                         maybe_model.into_option()
                     } else {
                         let arc_instance = Arc::new(instance);
-                        let solve_res = split::work(&arc_instance, &profiler);
+                        let solve_res = split::work(
+                            &arc_instance,
+                            warm.as_mut(),
+                            if conf.model_stats {
+                                Some(&mut sample_counts)
+                            } else {
+                                None
+                            },
+                            &profiler,
+                        );
 
                         instance = unwrap_arc(arc_instance)
                             .chain_err(|| "while trying to recover instance")?;
@@ -246,10 +369,28 @@ pub fn read_and_work<R: ::std::io::Read>(
                                 None
                             }
                             Err(ref e) if e.is_timeout() => {
-                                println!("timeout");
+                                println!("{}", timeout_status());
+                                write_profile_json(&profiler, &model_stats_json)?;
                                 print_stats("top", profiler);
                                 ::std::process::exit(0)
                             }
+                            Err(ref e) if e.partial_model().is_some() => {
+                                println!("unknown");
+                                let partial = e
+                                    .partial_model()
+                                    .cloned()
+                                    .unwrap_or_else(ConjCandidates::new);
+                                let partial = instance.extend_model(partial)?;
+                                println_side!(
+                                    "; partial model, iteration limit reached: NOT validated"
+                                );
+                                if conf.chc_comp {
+                                    instance.write_model(&partial, &mut ::std::io::stderr())?
+                                } else {
+                                    instance.write_model(&partial, &mut stdout())?
+                                }
+                                None
+                            }
                             Err(ref e) if e.is_unknown() => {
                                 println!("unknown");
                                 None
@@ -263,15 +404,32 @@ pub fn read_and_work<R: ::std::io::Read>(
                     None
                 };
 
+                let sat = if model.is_some() {
+                    Some(true)
+                } else if unsat.is_some() {
+                    Some(false)
+                } else {
+                    None
+                };
+                check_status(&instance, sat)?;
+
+                if let (Some(model), Some(clause_counts)) = (model.as_ref(), clause_counts.as_ref())
+                {
+                    let stats = model_stats(&instance, model, &sample_counts, clause_counts);
+                    print_model_stats(&instance, &stats);
+                    model_stats_json = Some(model_stats_to_json(&instance, &stats))
+                }
+
                 if stop_on_check {
                     return Ok((model, instance));
                 }
             }
 
             Parsed::GetUnsatCore | Parsed::GetModel if !conf.infer => (),
+            Parsed::GetUnsatCore | Parsed::GetModel | Parsed::GetProof if conf.parse_only => (),
 
             // Print unsat core if available.
-            Parsed::GetUnsatCore => println!("unsupported"),
+            Parsed::GetUnsatCore => println_side!("unsupported"),
 
             // Print unsat core if available.
             Parsed::GetProof => {
@@ -307,15 +465,34 @@ pub fn read_and_work<R: ::std::io::Read>(
             }
 
             Parsed::Items => {
-                if instance.print_success() {
-                    println!("success")
+                if parsed.as_ref().unwrap_or(&instance).print_success() {
+                    println_side!("success")
                 }
             }
 
-            Parsed::Reset => {
-                parser_cxt.reset();
-                instance = Instance::new();
-                model = None
+            Parsed::Reset | Parsed::ResetAssertions => {
+                if parsed.as_ref().unwrap_or(&instance).print_success() {
+                    println_side!("success")
+                }
+                if parse_res == Parsed::Reset {
+                    parser_cxt.reset()?;
+                    instance = Instance::new()
+                } else {
+                    // Declarations are kept, start from the instance as it was parsed if any.
+                    if let Some(parsed) = parsed.take() {
+                        instance = parsed
+                    }
+                    instance.reset_assertions()
+                }
+                parsed = None;
+                if warm.is_some() {
+                    warm = Some(data::WarmData::new())
+                }
+                model = None;
+                model_stats_json = None;
+                unsat = None;
+                assuming = false;
+                original_instance = None
             }
 
             Parsed::Eof => {
@@ -326,15 +503,53 @@ pub fn read_and_work<R: ::std::io::Read>(
                 }
             }
 
-            Parsed::Exit => break 'parse_work,
+            Parsed::Exit => {
+                if parsed.as_ref().unwrap_or(&instance).print_success() {
+                    println_side!("success")
+                }
+                break 'parse_work;
+            }
         }
     }
 
+    write_profile_json(&profiler, &model_stats_json)?;
+
     print_stats("top", profiler);
+    if conf.show_stats {
+        println_side!("; {} {}", conf.emph("term factory"), conf.emph("{"));
+        for line in term::hcons_stats().to_string().lines() {
+            println_side!(";   {}", line)
+        }
+        println_side!("; {}", conf.emph("}"))
+    }
 
     Ok((model, instance))
 }
 
+/// Writes the profiling information to the file given by `--profile_json`, if any.
+///
+/// Called before exiting, including on timeouts.
+fn write_profile_json(profiler: &Profiler, model_stats_json: &Option<String>) -> Res<()> {
+    if let Some(path) = conf.profile_json.as_ref() {
+        use std::io::Write;
+        let mut file = ::std::fs::File::create(path)
+            .chain_err(|| format!("while creating profiling file `{}`", path))?;
+        let json = profiler.to_json_with(model_stats_json.iter().cloned().collect());
+        file.write_all(json.as_bytes())
+            .chain_err(|| format!("while writing profiling information to `{}`", path))?
+    }
+    Ok(())
+}
+
+/// Status printed on timeouts, CHC-COMP only allows `unknown`.
+fn timeout_status() -> &'static str {
+    if conf.chc_comp {
+        "unknown"
+    } else {
+        "timeout"
+    }
+}
+
 /// Waits until an `Arc` is unwrap-able.
 fn unwrap_arc<T>(arc: Arc<T>) -> Res<T> {
     while Arc::strong_count(&arc) != 1 {}
@@ -344,3 +559,115 @@ fn unwrap_arc<T>(arc: Arc<T>) -> Res<T> {
         bail!("unable to unwrap `Arc`")
     }
 }
+
+/// Fails if a result contradicts the status declared with `(set-info :status ...)`.
+///
+/// Only active in `--check_status` mode. `sat` is `None` if the result is unknown, which never
+/// contradicts anything.
+fn check_status(instance: &Instance, sat: Option<bool>) -> Res<()> {
+    if !conf.check_status {
+        return Ok(());
+    }
+    if let (Some(expected), Some(sat)) = (instance.expected_status(), sat) {
+        if expected != sat {
+            let desc = |sat| if sat { "sat" } else { "unsat" };
+            bail!(
+                "result `{}` contradicts the status `{}` declared by the input",
+                desc(sat),
+                desc(expected)
+            )
+        }
+    }
+    Ok(())
+}
+
+/// Statistics about a predicate of a model, see `--model_stats`.
+struct PredStats {
+    /// The predicate.
+    pred: PrdIdx,
+    /// Number of positive and negative samples, `None` if the predicate was not learned.
+    samples: Option<(usize, usize)>,
+    /// Number of clauses mentioning the predicate in their lhs and rhs.
+    clauses: (usize, usize),
+    /// Size of the definition of the predicate in the model.
+    def_size: usize,
+}
+
+impl PredStats {
+    /// Origin of the definition of the predicate.
+    fn origin(&self) -> &'static str {
+        if self.samples.is_some() {
+            "learned"
+        } else {
+            "forced"
+        }
+    }
+}
+
+/// Aggregates the statistics about the predicates of a model, see `--model_stats`.
+///
+/// Clause counts are the ones of the instance before pre-processing, pre-processing removes the
+/// clauses of the predicates it defines.
+fn model_stats(
+    instance: &Instance,
+    model: ConjModelRef,
+    counts: &data::SampleCounts,
+    clause_counts: &PrdMap<(usize, usize)>,
+) -> Vec<PredStats> {
+    let mut def_sizes = PrdHMap::new();
+    for defs in model {
+        for (pred, conj) in defs {
+            *def_sizes.entry(*pred).or_insert(0) += conj.iter().map(TTerms::size).sum::<usize>()
+        }
+    }
+    instance
+        .pred_indices()
+        .map(|pred| PredStats {
+            pred,
+            samples: counts.get(pred),
+            clauses: clause_counts[pred],
+            def_size: def_sizes.get(&pred).cloned().unwrap_or(0),
+        })
+        .collect()
+}
+
+/// Prints the statistics about the predicates of a model.
+fn print_model_stats(instance: &Instance, stats: &[PredStats]) {
+    println_side!("; {} {}", conf.emph("model stats"), conf.emph("{"));
+    for stat in stats {
+        let (pos, neg) = stat.samples.unwrap_or((0, 0));
+        println_side!(
+            ";   {}: {}, pos {}, neg {}, lhs {}, rhs {}, size {}",
+            instance[stat.pred],
+            stat.origin(),
+            pos,
+            neg,
+            stat.clauses.0,
+            stat.clauses.1,
+            stat.def_size
+        )
+    }
+    println_side!("; {}", conf.emph("}"))
+}
+
+/// JSON entry for the statistics about the predicates of a model.
+fn model_stats_to_json(instance: &Instance, stats: &[PredStats]) -> String {
+    let preds: Vec<_> = stats
+        .iter()
+        .map(|stat| {
+            let (pos, neg) = stat.samples.unwrap_or((0, 0));
+            format!(
+                "\"{}\": {{ \"origin\": \"{}\", \"pos\": {}, \"neg\": {}, \
+                 \"lhs\": {}, \"rhs\": {}, \"size\": {} }}",
+                profiling::json_escape(&instance[stat.pred].name),
+                stat.origin(),
+                pos,
+                neg,
+                stat.clauses.0,
+                stat.clauses.1,
+                stat.def_size
+            )
+        })
+        .collect();
+    format!("\"model_stats\": {{ {} }}", preds.join(", "))
+}
@@ -36,6 +36,45 @@ impl VarInfo {
     pub fn as_bytes(&self) -> &[u8] {
         self.name.as_bytes()
     }
+
+    /// Name of the variable as a legal SMT-LIB symbol.
+    ///
+    /// Quotes the name with `|...|` if it is not a legal unquoted symbol and is not already
+    /// quoted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::{ common::*, info::VarInfo };
+    /// let smt_name = |name: &str| VarInfo::new(name, typ::int(), 0.into()).smt_name();
+    /// assert_eq! { smt_name("n"), "n" }
+    /// assert_eq! { smt_name("n'@1"), "|n'@1|" }
+    /// assert_eq! { smt_name("x.next"), "x.next" }
+    /// assert_eq! { smt_name("a b"), "|a b|" }
+    /// assert_eq! { smt_name("|a b|"), "|a b|" }
+    /// assert_eq! { smt_name("7x"), "|7x|" }
+    /// ```
+    pub fn smt_name(&self) -> String {
+        let name = &self.name;
+        let quoted = name.len() > 1 && name.starts_with('|') && name.ends_with('|');
+        let legal_unquoted = !name.is_empty()
+            && !name.starts_with(|c: char| c.is_numeric())
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c));
+        if quoted || legal_unquoted {
+            name.clone()
+        } else {
+            format!("|{}|", name.replace('|', ""))
+        }
+    }
+
+    /// Writes the name of the variable as a legal SMT-LIB symbol, see [`smt_name`].
+    ///
+    /// [`smt_name`]: #method.smt_name (smt_name function)
+    pub fn write_name<W: Write>(&self, w: &mut W) -> IoRes<()> {
+        write!(w, "{}", self.smt_name())
+    }
 }
 impl Sym2Smt<()> for VarInfo {
     fn sym_to_smt2<Writer>(&self, w: &mut Writer, _: ()) -> SmtRes<()>
@@ -94,6 +133,8 @@ pub struct Pred {
     /// Companion functions. Function that were created specifically for this predicate, and must
     /// be given to the user before giving the definition for this predicate.
     funs: Vec<Fun>,
+    /// Hints from the user, over the **original** signature.
+    hints: Vec<Term>,
 }
 
 impl Pred {
@@ -112,6 +153,7 @@ impl Pred {
             def: None,
             strength: None,
             funs: vec![],
+            hints: vec![],
         }
     }
 
@@ -129,6 +171,20 @@ impl Pred {
         &self.sig
     }
 
+    /// Arity of the predicate, *i.e.* the length of its current signature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, info::Pred };
+    /// let sig: VarMap<_> = vec![ typ::int(), typ::bool() ].into();
+    /// let pred = Pred::new("pred", 0.into(), sig.clone());
+    /// assert_eq! { pred.arity(), 2 }
+    /// ```
+    pub fn arity(&self) -> usize {
+        self.sig.len()
+    }
+
     /// The original signature of the predicate, as it was declared.
     ///
     /// # Examples
@@ -448,6 +504,43 @@ impl Pred {
         self.funs.push(fun)
     }
 
+    /// Adds a hint, a term over the **original** signature the predicate is likely to imply.
+    pub fn add_hint(&mut self, hint: Term) {
+        self.hints.push(hint)
+    }
+
+    /// Hints over the **current** signature.
+    ///
+    /// Hints mentioning variables removed from the signature are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, info::Pred };
+    /// let sig: VarMap<_> = vec![ typ::int(), typ::int(), typ::int() ].into();
+    /// let mut pred = Pred::new("pred", 0.into(), sig.clone());
+    /// pred.add_hint( term::ge(term::int_var(0), term::int_var(2)) );
+    /// pred.add_hint( term::ge(term::int_var(1), term::int(0)) );
+    /// // Drop `v_1`.
+    /// let nu_sig: VarMap<_> = vec![ typ::int(), typ::int() ].into();
+    /// let map: VarMap<VarIdx> = vec![ 0.into(), 2.into() ].into();
+    /// pred.set_sig(nu_sig, map);
+    /// assert_eq! { pred.hints(), vec![ term::ge(term::int_var(0), term::int_var(1)) ] }
+    /// ```
+    pub fn hints(&self) -> Vec<Term> {
+        if self.hints.is_empty() {
+            return vec![];
+        }
+        let mut map = VarHMap::new();
+        for (var, old_var) in self.original_sig_map.index_iter() {
+            map.insert(*old_var, term::var(var, self.sig[var].clone()));
+        }
+        self.hints
+            .iter()
+            .filter_map(|hint| hint.subst_total(&map).map(|(hint, _)| hint))
+            .collect()
+    }
+
     /// Finalizes the predicate information.
     ///
     /// After finalization, calls to [`original_sig_term_map`] will always succeed.
@@ -1,10 +1,22 @@
 //! Actual instance structure.
 
-use crate::{common::*, data::Data, info::*, var_to::terms::VarTermsSet};
+use crate::{
+    common::{profiling::json_escape, *},
+    data::Data,
+    info::*,
+    var_to::terms::VarTermsSet,
+};
 
 mod clause;
 mod pre_instance;
 
+/// Maximal number of constants in the constant pool of a predicate, see
+/// [`Instance::constants_of`].
+///
+/// [`Instance::constants_of`]: struct.Instance.html#method.constants_of
+/// (constants_of function of Instance)
+pub const CST_POOL_CAPA: usize = 30;
+
 pub use self::clause::Clause;
 pub use self::pre_instance::PreInstance;
 
@@ -33,6 +45,7 @@ pub use self::pre_instance::PreInstance;
 /// - [`neg_clauses`]
 /// - [`non_strict_neg_clauses`]
 /// - [`imp_clauses`]
+/// - [`ground_clauses`]
 /// - [`model_of`]
 /// - [`extend_model`]
 /// - [`sorted_forced_terms`]
@@ -51,6 +64,8 @@ pub use self::pre_instance::PreInstance;
 /// (non_strict_neg_clauses function)
 /// [`imp_clauses`]: struct.Instance.html#method.imp_clauses
 /// (imp_clauses function)
+/// [`ground_clauses`]: struct.Instance.html#method.ground_clauses
+/// (ground_clauses function)
 /// [`model_of`]: struct.Instance.html#method.model_of
 /// (model_of function)
 /// [`extend_model`]: struct.Instance.html#method.extend_model
@@ -85,6 +100,16 @@ pub struct Instance {
     /// Maps predicates to the clauses where they appear in the lhs and rhs
     /// respectively.
     pred_to_clauses: PrdMap<(ClsSet, ClsSet)>,
+    /// Maps operators to the clauses they (may) appear in.
+    ///
+    /// Over-approximation: a clause mentioning an operator is always in the set of this operator,
+    /// unless it is in `op_dirty`. Maintained by `push_clause_unchecked` and `forget_clause`, see
+    /// [`clauses_mentioning`] for the invalidation points.
+    ///
+    /// [`clauses_mentioning`]: #method.clauses_mentioning (clauses_mentioning function)
+    op_to_clauses: HashMap<Op, ClsSet>,
+    /// Clauses that may have changed since they were indexed in `op_to_clauses`.
+    op_dirty: ClsSet,
     /// Unsat flag.
     is_unsat: bool,
     /// Set of positive clauses.
@@ -111,6 +136,10 @@ pub struct Instance {
     ///
     /// Only available after finalize.
     imp_clauses: ClsSet,
+    /// Set of ground clauses, *i.e.* clauses with no variables.
+    ///
+    /// Only available after finalize.
+    ground_clauses: ClsSet,
     /// True if finalized already ran.
     is_finalized: bool,
     /// If this instance is the result of a split, contains the index of the
@@ -125,6 +154,13 @@ pub struct Instance {
 
     /// Maps **original** clause indexes to their optional name.
     old_names: ClsHMap<String>,
+    /// **Original** clauses deactivated by the last `check-sat-assuming`.
+    ///
+    /// Taken by the check right after parsing, see [`take_deactivated_clauses`].
+    ///
+    /// [`take_deactivated_clauses`]: #method.take_deactivated_clauses
+    /// (take_deactivated_clauses function)
+    deactivated: ClsSet,
 
     /// Print success.
     ///
@@ -144,13 +180,33 @@ pub struct Instance {
     no_inlining: bool,
     /// Disable inlining for specified predicate variables.
     ///
-    /// Can only be set by `(set-option :no-inlining-preds "P1 P2 ... Pn ")`.
-    /// FIXME The trailing space after `Pn` is required due to a bug(?) of parser now.
+    /// Can only be set by `(set-option :no-inlining-preds "P1 P2 ... Pn")`.
     no_inlining_preds: HashSet<String>,
+    /// Disable qualifier synthesis for specified predicate variables.
+    ///
+    /// Can only be set by `(set-option :no-synth-preds "P1 P2 ... Pn")`.
+    no_synth_preds: HashSet<String>,
     /// Clause simplification flag.
     ///
     /// Can only be set by `(set-option :simplify-clause <bool>)`.
     simplify_clauses: bool,
+    /// Expected status of the instance, `Some(true)` for sat and `Some(false)` for unsat.
+    ///
+    /// Can only be set by `(set-info :status <sat|unsat>)`.
+    expected_status: Option<bool>,
+
+    /// Learning data from previous checks, if any.
+    warm: Option<Arc<crate::data::WarmData>>,
+
+    /// Reduction info of the pre-processors that did something, by name.
+    preproc_info: BTreeMap<&'static str, RedInfo>,
+
+    /// Arithmetic constants of the clauses of each predicate, see [`constants_of`].
+    ///
+    /// Populated by the `finalize` function.
+    ///
+    /// [`constants_of`]: #method.constants_of (constants_of function)
+    pred_csts: PrdMap<Vec<Val>>,
 }
 
 impl Default for Instance {
@@ -173,25 +229,110 @@ impl Instance {
             clauses: ClsMap::with_capacity(clause_capa),
             // clusters: CtrMap::with_capacity( clause_capa / 3 ),
             pred_to_clauses: PrdMap::with_capacity(pred_capa),
+            op_to_clauses: HashMap::new(),
+            op_dirty: ClsSet::new(),
             is_unsat: false,
             pos_clauses: ClsSet::new(),
             strict_neg_clauses: ClsSet::new(),
             non_strict_neg_clauses: ClsSet::new(),
             neg_clauses: ClsSet::new(),
             imp_clauses: ClsSet::new(),
+            ground_clauses: ClsSet::new(),
             is_finalized: false,
             split: None,
             define_funs: BTreeMap::new(),
             old_names: ClsHMap::with_capacity(clause_capa),
+            deactivated: ClsSet::new(),
             print_success: false,
             unsat_cores: false,
             proofs: false,
             no_inlining: false,
             no_inlining_preds: HashSet::with_capacity(0),
+            no_synth_preds: HashSet::with_capacity(0),
             simplify_clauses: true,
+            expected_status: None,
+            warm: None,
+            preproc_info: BTreeMap::new(),
+            pred_csts: PrdMap::new(),
         }
     }
 
+    /// Parses some SMT-LIB 2 items from a `BufRead`er, one item at a time.
+    ///
+    /// Only one item is ever stored in memory. See [`ParseStream`] for details.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, parse::Parsed };
+    /// let input = "\
+    /// (set-logic HORN)
+    /// ; Some comment.
+    /// (declare-fun pred (Int Int) Bool)
+    /// (assert
+    ///   (forall ((n Int)) (pred n n))
+    /// )
+    /// (check-sat)
+    /// ";
+    /// let mut instance = Instance::new();
+    /// let res: Vec<Parsed> = instance
+    ///     .parse_stream(input.as_bytes())
+    ///     .collect::<Res<_>>()
+    ///     .expect("while parsing stream");
+    /// assert_eq! {
+    ///     res, vec![Parsed::Items, Parsed::Items, Parsed::Items, Parsed::CheckSat]
+    /// }
+    /// assert_eq! { instance.preds().len(), 1 }
+    /// assert_eq! { instance.clauses().len(), 1 }
+    /// ```
+    ///
+    /// [`ParseStream`]: ../parse/struct.ParseStream.html (ParseStream struct)
+    pub fn parse_stream<R: ::std::io::BufRead>(
+        &mut self,
+        reader: R,
+    ) -> crate::parse::ParseStream<R> {
+        crate::parse::ParseStream::new(reader, self)
+    }
+
+    /// Drops all assertions, keeps the declarations and the options.
+    ///
+    /// Semantics of `(reset-assertions)`. The predicates are restored to their original signature
+    /// and lose their definition, if any, so that this also works on a pre-processed instance.
+    /// Define-funs are kept. Everything else is dropped, including clauses, side-clauses, hints
+    /// and learning data from previous checks.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, parse::Parsed };
+    /// let input = "\
+    /// (declare-fun pred (Int Int) Bool)
+    /// (assert (forall ((n Int)) (pred n 0)))
+    /// (reset-assertions)
+    /// (assert (forall ((n Int)) (=> (pred n 0) false)))
+    /// ";
+    /// let mut instance = Instance::new();
+    /// let res: Vec<Parsed> = instance
+    ///     .parse_stream(input.as_bytes())
+    ///     .collect::<Res<_>>()
+    ///     .expect("while parsing stream");
+    /// assert_eq! { res[2], Parsed::ResetAssertions }
+    /// assert_eq! { instance.preds().len(), 1 }
+    /// assert_eq! { instance.clauses().len(), 1 }
+    /// assert! { instance[ClsIdx::from(0)].rhs().is_none() }
+    /// ```
+    pub fn reset_assertions(&mut self) {
+        let mut nu = Instance::new();
+        for pred in self.preds.iter() {
+            nu.push_pred(pred.name.clone(), pred.original_sig().clone());
+        }
+        nu.define_funs = ::std::mem::replace(&mut self.define_funs, BTreeMap::new());
+        nu.print_success = self.print_success;
+        nu.unsat_cores = self.unsat_cores;
+        nu.proofs = self.proofs;
+        nu.no_inlining = self.no_inlining;
+        nu.no_inlining_preds = ::std::mem::replace(&mut self.no_inlining_preds, HashSet::new());
+        nu.no_synth_preds = ::std::mem::replace(&mut self.no_synth_preds, HashSet::new());
+        nu.simplify_clauses = self.simplify_clauses;
+        *self = nu
+    }
+
     /// Clones itself.
     ///
     /// This is only used when splitting. `clause` will be remembered as the
@@ -211,25 +352,121 @@ impl Instance {
             side_clauses: self.side_clauses.clone(),
             clauses: self.clauses.clone(),
             pred_to_clauses: self.pred_to_clauses.clone(),
+            op_to_clauses: self.op_to_clauses.clone(),
+            op_dirty: self.op_dirty.clone(),
             is_unsat: false,
             pos_clauses: ClsSet::new(),
             strict_neg_clauses: ClsSet::new(),
             non_strict_neg_clauses: ClsSet::new(),
             neg_clauses: ClsSet::new(),
             imp_clauses: ClsSet::new(),
+            ground_clauses: ClsSet::new(),
             is_finalized: false,
             split: Some(clause),
             define_funs: self.define_funs.clone(),
             old_names: self.old_names.clone(),
+            deactivated: ClsSet::new(),
             print_success: false,
             unsat_cores: false,
             proofs: false,
             no_inlining: self.no_inlining,
             no_inlining_preds: self.no_inlining_preds.clone(),
+            no_synth_preds: self.no_synth_preds.clone(),
             simplify_clauses: self.simplify_clauses,
+            expected_status: self.expected_status,
+            warm: self.warm.clone(),
+            preproc_info: self.preproc_info.clone(),
+            pred_csts: PrdMap::new(),
         }
     }
 
+    /// Clones the part of the instance that only mentions some predicates.
+    ///
+    /// The result contains the predicates of `preds_to_keep` that are not defined, and the
+    /// clauses that only mention these predicates. Predicates are re-indexed, and the terms of the
+    /// clauses are cloned. Side clauses and function definitions are kept.
+    ///
+    /// Useful to isolate the clauses of a few predicates from a big instance, for instance to
+    /// dump them with [`reprint`].
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// let instance = hoice::parse::instance("
+    ///   (declare-fun P (Int) Bool)
+    ///   (declare-fun Q (Int) Bool)
+    ///   (assert (forall ((n Int)) (=> (= n 0) (P n))))
+    ///   (assert (forall ((n Int)) (=> (P n) (Q n))))
+    ///   (assert (forall ((n Int)) (=> (and (Q n) (< n 0)) false)))
+    ///   (assert (forall ((n Int)) (=> (and (P n) (< n 0)) false)))
+    /// ");
+    /// let p: PrdIdx = 0.into();
+    /// let keep: PrdSet = vec![p].into_iter().collect();
+    /// let reduced = instance.clone_reduced(&keep).unwrap();
+    /// assert_eq! { reduced.preds().len(), 1 }
+    /// assert_eq! { reduced.clauses().len(), 2 }
+    ///
+    /// let mut buff: Vec<u8> = vec![];
+    /// reduced.reprint(&mut buff).unwrap();
+    /// let reparsed = hoice::parse::instance(&String::from_utf8(buff).unwrap());
+    /// assert_eq! { reparsed.preds().len(), 1 }
+    /// assert_eq! { reparsed.clauses().len(), 2 }
+    /// ```
+    ///
+    /// [`reprint`]: #method.reprint (reprint function)
+    pub fn clone_reduced(&self, preds_to_keep: &PrdSet) -> Res<Self> {
+        let mut instance = Instance::new();
+        instance.side_clauses = self.side_clauses.clone();
+        instance.define_funs = self.define_funs.clone();
+
+        let mut pred_map = PrdHMap::new();
+        for pred in self.preds.iter() {
+            if preds_to_keep.contains(&pred.idx) && !pred.is_defined() {
+                let nu_pred = instance.push_pred(pred.name.clone(), pred.sig.clone());
+                pred_map.insert(pred.idx, nu_pred);
+            }
+        }
+
+        let clone_args = |args: &VarTerms| -> VarTerms {
+            let args: Vec<Term> = args.iter().map(|arg| arg.to_hcons()).collect();
+            var_to::terms::new(args.into())
+        };
+
+        'clauses: for clause in self.clauses.iter() {
+            let mut lhs = Vec::with_capacity(clause.lhs_len());
+            for (pred, argss) in clause.lhs_preds() {
+                let pred = if let Some(pred) = pred_map.get(pred) {
+                    *pred
+                } else {
+                    continue 'clauses;
+                };
+                for args in argss {
+                    lhs.push(TTerm::P {
+                        pred,
+                        args: clone_args(args),
+                    })
+                }
+            }
+
+            let rhs = if let Some((pred, args)) = clause.rhs() {
+                if let Some(pred) = pred_map.get(&pred) {
+                    Some((*pred, clone_args(args)))
+                } else {
+                    continue 'clauses;
+                }
+            } else {
+                None
+            };
+
+            for term in clause.lhs_terms() {
+                lhs.push(TTerm::T(term.to_hcons()))
+            }
+
+            let _ = instance.push_new_clause(clause.vars().clone(), lhs, rhs, "clone_reduced")?;
+        }
+
+        Ok(instance)
+    }
+
     /// Set of positive clauses.
     ///
     /// Only available after finalize.
@@ -260,6 +497,12 @@ impl Instance {
     pub fn imp_clauses(&self) -> &ClsSet {
         &self.imp_clauses
     }
+    /// Set of ground clauses, *i.e.* clauses with no variables.
+    ///
+    /// The teacher checks these clauses by evaluation. Only available after finalize.
+    pub fn ground_clauses(&self) -> &ClsSet {
+        &self.ground_clauses
+    }
 
     /// Number of active (not forced) predicates.
     pub fn active_pred_count(&self) -> usize {
@@ -344,8 +587,7 @@ impl Instance {
             }
         }
 
-        for pred in &self.sorted_pred_terms {
-            let pred = *pred;
+        for pred in self.model_forced_preds() {
             if let Some(tterms) = self[pred].def() {
                 model.push((pred, tterms.subst(self[pred].original_sig_term_map()?)))
             } else {
@@ -356,6 +598,159 @@ impl Instance {
         Ok(model)
     }
 
+    /// Simplifies some candidates before they are turned into a model.
+    ///
+    /// Candidates produced by the learner are disjunctions of conjunctions of qualifiers, one
+    /// conjunction per branch of the decision tree. This function
+    ///
+    /// - drops the qualifiers of a branch that are implied by the rest of the branch, using one
+    ///   SMT check per qualifier,
+    /// - merges branches of the form `P /\ q` and `P /\ (not q)` into `P`,
+    /// - drops branches that are subsumed by another branch.
+    pub fn simplify_candidates(&self, candidates: &mut Candidates) -> Res<()> {
+        let mut solver = conf
+            .solver
+            .preproc_spawn("model_simplification", (), self)?;
+
+        for (pred, candidate) in candidates.index_iter_mut() {
+            if let Some(term) = candidate.as_mut() {
+                let nu_term = self
+                    .simplify_candidate(&mut solver, pred, term)
+                    .chain_err(|| {
+                        format!(
+                            "while simplifying candidate for {}",
+                            conf.emph(&self[pred].name)
+                        )
+                    })?;
+                *term = nu_term
+            }
+        }
+
+        solver
+            .kill()
+            .chain_err(|| "while killing model simplification solver")?;
+
+        Ok(())
+    }
+
+    /// Simplifies a candidate for a predicate, see `simplify_candidates`.
+    fn simplify_candidate(&self, solver: &mut Solver<()>, pred: PrdIdx, term: &Term) -> Res<Term> {
+        if term.bool().is_some() {
+            return Ok(term.clone());
+        }
+
+        let branch_of = |term: &Term| -> Vec<Term> {
+            if let Some(kids) = term.conj_inspect() {
+                kids.clone()
+            } else {
+                vec![term.clone()]
+            }
+        };
+        let branches: Vec<Vec<Term>> = if let Some(kids) = term.disj_inspect() {
+            kids.iter().map(branch_of).collect()
+        } else {
+            vec![branch_of(term)]
+        };
+
+        solver.push(1)?;
+        for (var, typ) in self[pred].sig.index_iter() {
+            solver.declare_const(&var, typ.get())?
+        }
+
+        // Drop qualifiers implied by the rest of their branch.
+        let mut pruned = Vec::with_capacity(branches.len());
+        for mut branch in branches {
+            let mut idx = 0;
+            while idx < branch.len() {
+                let mut query: Vec<Term> = Vec::with_capacity(branch.len());
+                for (other_idx, other) in branch.iter().enumerate() {
+                    if other_idx != idx {
+                        query.push(other.clone())
+                    }
+                }
+                query.push(term::not(branch[idx].clone()));
+                let query = term::and(query);
+
+                let implied = match query.bool() {
+                    Some(b) => !b,
+                    None => {
+                        let actlit = solver.get_actlit()?;
+                        solver.assert_act(&actlit, &smt::SmtTerm::new(&query))?;
+                        let sat = solver.check_sat_act_or_unk(Some(&actlit))?;
+                        solver.de_actlit(actlit)?;
+                        sat == Some(false)
+                    }
+                };
+
+                if implied {
+                    branch.remove(idx);
+                } else {
+                    idx += 1
+                }
+
+                conf.check_timeout()?
+            }
+
+            let mut set = TermSet::new();
+            for qual in branch {
+                set.insert(qual);
+            }
+            pruned.push(set)
+        }
+
+        solver.pop(1)?;
+
+        // Merge and drop branches until fixed point.
+        let mut changed = true;
+        'merge: while changed {
+            changed = false;
+            for i in 0..pruned.len() {
+                for j in 0..pruned.len() {
+                    if i == j {
+                        continue;
+                    }
+
+                    // Branch `j` is stronger than branch `i`, drop it.
+                    if pruned[i].iter().all(|qual| pruned[j].contains(qual)) {
+                        pruned.swap_remove(j);
+                        changed = true;
+                        continue 'merge;
+                    }
+
+                    if pruned[i].len() != pruned[j].len() {
+                        continue;
+                    }
+
+                    let mut lft = pruned[i].iter().filter(|qual| !pruned[j].contains(*qual));
+                    let mut rgt = pruned[j].iter().filter(|qual| !pruned[i].contains(*qual));
+                    let merge_on = match (lft.next(), lft.next(), rgt.next()) {
+                        (Some(lft), None, Some(rgt))
+                            if &term::not(lft.clone()) == rgt || &term::not(rgt.clone()) == lft =>
+                        {
+                            Some(lft.clone())
+                        }
+                        _ => None,
+                    };
+
+                    if let Some(qual) = merge_on {
+                        let was_there = pruned[i].remove(&qual);
+                        debug_assert! { was_there }
+                        pruned.swap_remove(j);
+                        changed = true;
+                        continue 'merge;
+                    }
+                }
+            }
+        }
+
+        Ok(term::or(
+            pruned
+                .into_iter()
+                .map(|branch| term::and(branch.iter().cloned().collect()))
+                .collect(),
+        ))
+    }
+
     /// Returns the model corresponding to the input predicates and the forced
     /// predicates.
     ///
@@ -397,8 +792,7 @@ impl Instance {
             model.push(tmp.into_iter().map(|(pred, _, dnf)| (pred, dnf)).collect())
         }
 
-        for pred in &self.sorted_pred_terms {
-            let pred = *pred;
+        for pred in self.model_forced_preds() {
             if let Some(tterms) = self[pred].def() {
                 let tterms = tterms.subst(self[pred].original_sig_term_map()?);
                 model.push(vec![(pred, vec![tterms])])
@@ -496,6 +890,11 @@ impl Instance {
         }
         self.is_finalized = true;
 
+        self.pred_csts = self
+            .pred_indices()
+            .map(|pred| self.collect_constants(pred))
+            .collect::<Res<_>>()?;
+
         self.sorted_pred_terms.clear();
         self.preds.shrink_to_fit();
         self.clauses.shrink_to_fit();
@@ -526,6 +925,11 @@ impl Instance {
                 let is_new = self.imp_clauses.insert(idx);
                 debug_assert! { is_new }
             }
+
+            if clause.is_ground() {
+                let is_new = self.ground_clauses.insert(idx);
+                debug_assert! { is_new }
+            }
         }
 
         // Populate `tmp`.
@@ -579,6 +983,34 @@ impl Instance {
         &self.sorted_pred_terms
     }
 
+    /// All the forced predicates, in an order suitable for a model.
+    ///
+    /// Same as [`sorted_forced_terms`][sorted], plus the predicates forced after the instance was
+    /// finalized, which `sorted_forced_terms` does not know about. The ones forced to `true` or
+    /// `false` come first since they do not depend on anything, the others come last.
+    ///
+    /// [sorted]: #method.sorted_forced_terms (sorted_forced_terms function)
+    fn model_forced_preds(&self) -> Vec<PrdIdx> {
+        let sorted: PrdSet = self.sorted_pred_terms.iter().cloned().collect();
+        let mut res = Vec::with_capacity(self.preds.len());
+        let mut others = vec![];
+        for pred in self.pred_indices() {
+            if sorted.contains(&pred) {
+                continue;
+            }
+            if let Some(tterms) = self[pred].def() {
+                if tterms.bool().is_some() {
+                    res.push(pred)
+                } else {
+                    others.push(pred)
+                }
+            }
+        }
+        res.extend(self.sorted_pred_terms.iter().cloned());
+        res.extend(others);
+        res
+    }
+
     /// Returns the clauses in which the predicate appears in the lhs and rhs
     /// respectively.
     #[inline]
@@ -599,11 +1031,13 @@ impl Instance {
     /// Adds a predicate application to a clause's lhs.
     pub fn clause_add_lhs_pred(&mut self, clause: ClsIdx, pred: PrdIdx, args: VarMap<Term>) {
         self.pred_to_clauses[pred].0.insert(clause);
+        self.op_dirty.insert(clause);
         self.clauses[clause].insert_pred_app(pred, args.into());
     }
 
     /// Adds a term to a clause's lhs.
     pub fn clause_add_lhs_term(&mut self, clause: ClsIdx, term: Term) {
+        self.op_dirty.insert(clause);
         self.clauses[clause].insert_term(term);
     }
 
@@ -615,6 +1049,7 @@ impl Instance {
         args: VarMap<Term>,
     ) -> Res<()> {
         self.pred_to_clauses[pred].1.insert(clause);
+        self.op_dirty.insert(clause);
         self.clauses[clause].set_rhs(pred, args.into())
     }
 
@@ -626,6 +1061,7 @@ impl Instance {
         clause_idx: ClsIdx,
         tterms: I,
     ) {
+        self.op_dirty.insert(clause_idx);
         let clause = &mut self.clauses[clause_idx];
         for tterm in tterms {
             match tterm {
@@ -645,6 +1081,7 @@ impl Instance {
     /// Updates `pred_to_clauses` for the term it inserts but **not** the one it
     /// removes.
     pub fn clause_rhs_force(&mut self, clause_idx: ClsIdx, tterm: TTerm) -> Res<()> {
+        self.op_dirty.insert(clause_idx);
         let clause = &mut self.clauses[clause_idx];
         match tterm {
             TTerm::P { pred, args } => {
@@ -662,6 +1099,91 @@ impl Instance {
         Ok(())
     }
 
+    /// Constant pool of a predicate: the arithmetic constants of its clauses.
+    ///
+    /// Gathered over the LHS terms and the RHS arguments of all the clauses mentioning the
+    /// predicate, along with their negation and their `± 1` offsets. Deduplicated, and capped to
+    /// [`CST_POOL_CAPA`] constants. Used by qualifier synthesis to relate arguments to constants
+    /// that might never appear in any sample.
+    ///
+    /// Only available after finalize.
+    ///
+    /// [`CST_POOL_CAPA`]: constant.CST_POOL_CAPA.html (CST_POOL_CAPA constant)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let mut instance = hoice::parse::instance("
+    ///     (declare-fun P ( Int ) Bool)
+    ///     (assert (forall ((x Int)) (=> (= x 0) (P x))))
+    ///     (assert (forall ((x Int)) (=> (and (P x) (< x 4096)) (P (+ x 1)))))
+    /// ");
+    /// instance.finalize().unwrap();
+    /// let pool = instance.constants_of(0.into());
+    /// assert! { pool.contains(&val::int(4096)) }
+    /// assert! { pool.contains(&val::int(4095)) }
+    /// assert! { pool.contains(&val::int(-4096)) }
+    /// assert! { pool.contains(&val::int(1)) }
+    /// ```
+    pub fn constants_of(&self, pred: PrdIdx) -> &[Val] {
+        if *pred < self.pred_csts.len() {
+            &self.pred_csts[pred]
+        } else {
+            &[]
+        }
+    }
+
+    /// Collects the constant pool of a predicate, see [`constants_of`].
+    ///
+    /// [`constants_of`]: #method.constants_of (constants_of function)
+    fn collect_constants(&self, pred: PrdIdx) -> Res<Vec<Val>> {
+        let mut pool: Vec<Val> = Vec::new();
+
+        let (lhs, rhs) = &self.pred_to_clauses[pred];
+        let mut clauses: Vec<ClsIdx> = lhs.iter().chain(rhs.iter()).cloned().collect();
+        clauses.sort();
+        clauses.dedup();
+
+        'all_clauses: for clause in clauses {
+            let clause = &self[clause];
+            let rhs_args = clause.rhs().into_iter().flat_map(|(_, args)| args.iter());
+            for term in clause.lhs_terms().iter().chain(rhs_args) {
+                for leaf in term.leaf_iter() {
+                    let val = match leaf {
+                        Either::Right(val) if val.typ().is_arith() && val.is_known() => val,
+                        _ => continue,
+                    };
+                    let one = if val.typ().is_int() {
+                        val::int(1)
+                    } else {
+                        val::real(Rat::from_integer(1.into()))
+                    };
+                    let minus_one = one.minus()?;
+                    let neg = val.minus()?;
+                    let candidates = [
+                        val.clone(),
+                        val.add(&one)?,
+                        val.add(&minus_one)?,
+                        neg.clone(),
+                        neg.add(&one)?,
+                        neg.add(&minus_one)?,
+                    ];
+                    for cst in candidates.iter() {
+                        if !pool.contains(cst) {
+                            if pool.len() >= CST_POOL_CAPA {
+                                break 'all_clauses;
+                            }
+                            pool.push(cst.clone())
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(pool)
+    }
+
     /// Range over the predicate indices.
     pub fn pred_indices(&self) -> PrdRange {
         PrdRange::zero_to(self.preds.len())
@@ -681,6 +1203,45 @@ impl Instance {
         &self.clauses
     }
 
+    /// Names of the datatypes without type parameters used by the instance.
+    ///
+    /// That is, the ones appearing in the signature of a predicate, or as the sort of a variable
+    /// of a clause or side-clause.
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let instance = hoice::parse::instance("
+    ///   (declare-datatypes ( (Nat 0) (Unused 0) ) (
+    ///     ( (Z) (S (pred Nat)) )
+    ///     ( (U) )
+    ///   ) )
+    ///   (declare-fun P (Nat Int) Bool)
+    /// ");
+    /// let used: Vec<_> = instance.used_dtyps().into_iter().collect();
+    /// assert_eq! { used, vec!["Nat".to_string()] }
+    /// ```
+    pub fn used_dtyps(&self) -> BTreeSet<String> {
+        let mut res = BTreeSet::new();
+        let mut add = |typ: &Typ| {
+            if let Some((dtyp, prms)) = typ.dtyp_inspect() {
+                if prms.is_empty() {
+                    res.insert(dtyp.name.clone());
+                }
+            }
+        };
+        for pred in self.preds.iter() {
+            for typ in pred.sig.iter() {
+                add(typ)
+            }
+        }
+        for clause in self.clauses.iter().chain(self.side_clauses.iter()) {
+            for info in clause.vars() {
+                add(&info.typ)
+            }
+        }
+        res
+    }
+
     /// Pushes a new predicate and returns its index.
     pub fn push_pred<S: Into<String>>(&mut self, name: S, sig: Sig) -> PrdIdx {
         let idx = self.preds.next_index();
@@ -693,6 +1254,13 @@ impl Instance {
         idx
     }
 
+    /// Adds a hint for a predicate, see [`Pred::add_hint`].
+    ///
+    /// [`Pred::add_hint`]: ../info/struct.Pred.html#method.add_hint (add_hint function of Pred)
+    pub fn add_hint(&mut self, pred: PrdIdx, hint: Term) {
+        self.preds[pred].add_hint(hint)
+    }
+
     /// Removes and returns the indices of the clauses `pred` appears in the lhs
     /// of from `self.pred_to_clauses`.
     fn unlink_pred_lhs<LHS>(&mut self, pred: PrdIdx, lhs: &mut LHS)
@@ -763,16 +1331,67 @@ impl Instance {
         if let Some((pred, _)) = self.clauses[clause].rhs() {
             self.pred_to_clauses[pred].1.remove(&clause);
         }
+        self.unlink_ops_of(clause);
         // Relink the last clause as its index is going to be `clause`. Except if
         // `clause` is already the last one.
         let last_clause: ClsIdx = (self.clauses.len() - 1).into();
         if clause != last_clause {
-            self.relink_preds_to_clauses(last_clause, clause)?
+            self.relink_preds_to_clauses(last_clause, clause)?;
+            self.relink_ops_to_clauses(last_clause, clause)
         }
         let res = self.clauses.swap_remove(clause);
         Ok(res)
     }
 
+    /// Removes the clauses that are equal to another clause modulo variable renaming.
+    ///
+    /// Clauses that are syntactically equal are already discarded when pushed, this catches
+    /// duplicates that only differ by the name (and thus the index) of their variables. See
+    /// [`Clause::alpha_key`] for details.
+    ///
+    /// Returns the number of clauses removed. **Does not preserve the order of the clauses.**
+    ///
+    /// [`Clause::alpha_key`]: struct.Clause.html#method.alpha_key (alpha_key function)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hoice::parse;
+    /// let mut instance = parse::instance("
+    ///   (declare-fun p ( Int Int ) Bool)
+    ///   (assert
+    ///     (forall ( (x Int) (y Int) )
+    ///       (=> (and (p x y) (> x 0) (< y 7)) false)
+    ///     )
+    ///   )
+    ///   (assert
+    ///     (forall ( (a Int) (b Int) )
+    ///       (=> (and (p b a) (> b 0) (< a 7)) false)
+    ///     )
+    ///   )
+    ///   (assert
+    ///     (forall ( (a Int) (b Int) )
+    ///       (=> (and (p a b) (> b 0) (< a 7)) false)
+    ///     )
+    ///   )
+    /// ");
+    /// assert_eq! { instance.clauses().len(), 3 }
+    /// assert_eq! { instance.remove_duplicate_clauses().unwrap(), 1 }
+    /// assert_eq! { instance.clauses().len(), 2 }
+    /// ```
+    pub fn remove_duplicate_clauses(&mut self) -> Res<usize> {
+        let mut known = HashSet::with_capacity(self.clauses.len());
+        let mut to_rm = vec![];
+        for (idx, clause) in self.clauses.index_iter() {
+            if !known.insert(clause.alpha_key()) {
+                to_rm.push(idx)
+            }
+        }
+        let count = to_rm.len();
+        self.forget_clauses(&mut to_rm)?;
+        Ok(count)
+    }
+
     /// First free clause index.
     pub fn next_clause_index(&self) -> ClsIdx {
         self.clauses.next_index()
@@ -796,6 +1415,65 @@ impl Instance {
         self.old_names.get(&cls)
     }
 
+    /// True if some original clauses have a name.
+    pub fn has_old_clause_names(&self) -> bool {
+        !self.old_names.is_empty()
+    }
+
+    /// The original clause with some name, if any.
+    pub fn old_clause_of_name(&self, name: &str) -> Option<ClsIdx> {
+        self.old_names
+            .iter()
+            .find(|(_, cls_name)| cls_name.as_str() == name)
+            .map(|(idx, _)| *idx)
+    }
+
+    /// Sets the original clauses deactivated by a `check-sat-assuming`.
+    pub fn set_deactivated_clauses(&mut self, clauses: ClsSet) {
+        self.deactivated = clauses
+    }
+
+    /// Takes the original clauses deactivated by the last `check-sat-assuming`.
+    ///
+    /// Leaves the set empty, so that the next check uses all the clauses.
+    pub fn take_deactivated_clauses(&mut self) -> ClsSet {
+        ::std::mem::replace(&mut self.deactivated, ClsSet::new())
+    }
+
+    /// Forgets the clauses stemming from some deactivated original clauses.
+    ///
+    /// Must run before pre-processing, so that the clauses do not take part in the
+    /// check at all, and do not appear in unsat cores and proofs.
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// let mut instance = hoice::parse::instance("
+    ///   (declare-fun P (Int) Bool)
+    ///   (assert (! (forall ((n Int)) (P n)) :named pos))
+    ///   (assert (! (forall ((n Int)) (=> (P n) false)) :named neg))
+    /// ");
+    /// let neg = instance.old_clause_of_name("neg").unwrap();
+    /// assert_eq! { instance.clauses().len(), 2 }
+    /// let deactivated: ClsSet = vec![neg].into_iter().collect();
+    /// instance.deactivate_clauses(&deactivated).unwrap();
+    /// assert_eq! { instance.clauses().len(), 1 }
+    /// let pos = instance.old_clause_of_name("pos").unwrap();
+    /// assert_eq! { instance.clauses().iter().next().unwrap().from(), pos }
+    /// ```
+    pub fn deactivate_clauses(&mut self, deactivated: &ClsSet) -> Res<()> {
+        if deactivated.is_empty() {
+            return Ok(());
+        }
+        let mut to_forget: Vec<_> = self
+            .clauses
+            .index_iter()
+            .filter(|(_, clause)| deactivated.contains(&clause.from()))
+            .map(|(idx, _)| idx)
+            .collect();
+        log! { @verb "deactivating {} clause(s)", to_forget.len() }
+        self.forget_clauses(&mut to_forget)
+    }
+
     /// Sets the name for an original clause.
     pub fn set_old_clause_name(&mut self, cls: ClsIdx, name: String) -> Res<()> {
         let prev = self.old_names.insert(cls, name);
@@ -949,9 +1627,89 @@ impl Instance {
             let is_new = self.pred_to_clauses[pred].1.insert(clause_index);
             debug_assert!(is_new)
         }
+        self.link_ops_of(clause_index);
         true
     }
 
+    /// Clauses an operator may appear in.
+    ///
+    /// The result is a superset of the clauses mentioning `op`: it can contain clauses that no
+    /// longer mention it. Lets preprocessing target the clauses relevant to an operator without
+    /// going through all of them, clause splitting for instance only looks at the clauses
+    /// mentioning a disjunction.
+    ///
+    /// The index is invalidated by
+    ///
+    /// - `forget_clause`, which unlinks the clause and relinks the clause swapped in its place,
+    /// - `push_clause`, which links the new clause,
+    /// - any mutable access to a clause (`instance[clause]`, `clause_add_lhs_term`, ...), which
+    ///   marks it dirty.
+    ///
+    /// Dirty clauses are re-indexed by this function, which is why it needs `&mut self`.
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// let mut instance = hoice::parse::instance("
+    ///   (declare-fun P (Int) Bool)
+    ///   (assert (forall ((n Int)) (=> (= n 0) (P n))))
+    ///   (assert (forall ((n Int)) (=> (P n) (P (+ n 1)))))
+    ///   (assert (forall ((n Int)) (=> (P n) (P (* 2 n)))))
+    /// ");
+    /// let with_add = instance.clauses_mentioning(Op::Add);
+    /// assert_eq! { with_add.len(), 1 }
+    /// let clause = *with_add.iter().next().unwrap();
+    /// let term = term::ge(term::int_var(0), term::int(7));
+    /// let (op, _) = term.app_inspect().unwrap();
+    /// instance.clause_add_lhs_term(clause, term);
+    /// assert! { instance.clauses_mentioning(op).contains(&clause) }
+    /// ```
+    pub fn clauses_mentioning(&mut self, op: Op) -> ClsSet {
+        self.refresh_op_index();
+        self.op_to_clauses
+            .get(&op)
+            .cloned()
+            .unwrap_or_else(ClsSet::new)
+    }
+
+    /// Re-indexes the dirty clauses in `op_to_clauses`.
+    fn refresh_op_index(&mut self) {
+        let dirty: Vec<ClsIdx> = self.op_dirty.drain().collect();
+        for clause in dirty {
+            self.unlink_ops_of(clause);
+            self.link_ops_of(clause)
+        }
+    }
+
+    /// Registers the operators of a clause in `op_to_clauses`.
+    fn link_ops_of(&mut self, clause: ClsIdx) {
+        for op in self.clauses[clause].operators() {
+            self.op_to_clauses
+                .entry(op)
+                .or_insert_with(ClsSet::new)
+                .insert(clause);
+        }
+    }
+
+    /// Removes a clause from `op_to_clauses`.
+    fn unlink_ops_of(&mut self, clause: ClsIdx) {
+        for clauses in self.op_to_clauses.values_mut() {
+            clauses.remove(&clause);
+        }
+        self.op_dirty.remove(&clause);
+    }
+
+    /// Updates `op_to_clauses` so that `from` is now `to`.
+    fn relink_ops_to_clauses(&mut self, from: ClsIdx, to: ClsIdx) {
+        for clauses in self.op_to_clauses.values_mut() {
+            if clauses.remove(&from) {
+                clauses.insert(to);
+            }
+        }
+        if self.op_dirty.remove(&from) {
+            self.op_dirty.insert(to);
+        }
+    }
+
     /// Checks that the instance has no inconsistencies.
     ///
     /// Only active in debug.
@@ -969,6 +1727,9 @@ impl Instance {
         self.check_pred_to_clauses()
             .chain_err(|| format!("while checking `{}`", conf.sad("pred_to_clauses")))
             .chain_err(|| format!("instance consistency check failed: {}", conf.emph(s)))?;
+        self.check_op_to_clauses()
+            .chain_err(|| format!("while checking `{}`", conf.sad("op_to_clauses")))
+            .chain_err(|| format!("instance consistency check failed: {}", conf.emph(s)))?;
         self.check_preds_consistency()?;
 
         for (idx, clause) in self.clauses.index_iter() {
@@ -1023,6 +1784,56 @@ impl Instance {
         Ok(())
     }
 
+    /// Checks the consistency of `op_to_clauses`.
+    ///
+    /// All indices must be legal, and all the operators of a clause that is not dirty must point
+    /// to it.
+    #[cfg(debug_assertions)]
+    fn check_op_to_clauses(&self) -> Res<()> {
+        for (op, clauses) in &self.op_to_clauses {
+            for clause in clauses {
+                if *clause >= self.clauses.len() {
+                    bail!(
+                        "operator {} is linked to unknown clause #{} ({} clauses)",
+                        op,
+                        clause,
+                        self.clauses.len()
+                    )
+                }
+            }
+        }
+        for clause in &self.op_dirty {
+            if *clause >= self.clauses.len() {
+                bail!(
+                    "unknown clause #{} is dirty ({} clauses)",
+                    clause,
+                    self.clauses.len()
+                )
+            }
+        }
+        for (idx, clause) in self.clauses.index_iter() {
+            if self.op_dirty.contains(&idx) {
+                continue;
+            }
+            for op in clause.operators() {
+                if !self
+                    .op_to_clauses
+                    .get(&op)
+                    .map(|clauses| clauses.contains(&idx))
+                    .unwrap_or(false)
+                {
+                    bail!(
+                        "operator {} appears in clause #{} but is not linked to it: {}",
+                        op,
+                        idx,
+                        clause.to_string_info(&self.preds)?
+                    )
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Checks predicate information.
     #[cfg(debug_assertions)]
     fn check_preds_consistency(&self) -> Res<()> {
@@ -1174,7 +1985,7 @@ impl Instance {
         for side_clause in &self.side_clauses {
             side_clause.write(
                 w,
-                |w, var_info| write!(w, "{}", var_info.name),
+                |w, var_info| var_info.write_name(w),
                 |_, _, _, _| panic!("illegal side-clause: found predicate application(s)"),
                 true,
             )?;
@@ -1219,26 +2030,7 @@ impl Instance {
             }
             writeln!(w)?;
 
-            clause.write(
-                w,
-                |w, var_info| write!(w, "{}", var_info.name),
-                |w, p, args, bindings| {
-                    if !args.is_empty() {
-                        write!(w, "(")?
-                    }
-                    w.write_all(self[p].name.as_bytes())?;
-                    for arg in args.iter() {
-                        write!(w, " ")?;
-                        arg.write_with(w, |w, var| write!(w, "{}", clause.vars[var]), bindings)?
-                    }
-                    if !args.is_empty() {
-                        write!(w, ")")
-                    } else {
-                        Ok(())
-                    }
-                },
-                true,
-            )?;
+            self.write_clause_as_smt2(w, clause, true)?;
             writeln!(w)?;
             writeln!(w)?
         }
@@ -1248,6 +2040,286 @@ impl Instance {
         Ok(())
     }
 
+    /// A clause as a standalone SMT-LIB 2 assertion.
+    ///
+    /// The clause is written as `(assert (forall ...))` using the names of its variables, the same
+    /// way [`reprint`] writes clauses. Unlike the output of `to_string_info`, which is meant for
+    /// logging, the result parses back to the same clause given the declarations of the
+    /// predicates it mentions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, parse };
+    /// let decls = "(declare-fun pred (Int Int) Bool)";
+    /// let instance = parse::instance(&format!("{}{}", decls, "
+    ///     (assert (forall ((n Int) (m Int)) (=> (and (pred n m) (>= n 0)) (pred (+ n 1) m))))
+    ///     (assert (forall ((n Int) (m Int)) (=> (and (pred n m) (> n m)) false)))
+    /// "));
+    ///
+    /// for (idx, clause) in instance.clauses().index_iter() {
+    ///     let smt2 = instance.clause_to_smtlib(idx);
+    ///     # println!("{}", smt2);
+    ///     assert! { smt2.starts_with("(assert") }
+    ///     let other = parse::instance(&format!("{}\n{}", decls, smt2));
+    ///     assert_eq! { other.clauses().len(), 1 }
+    ///     let other = &other[ClsIdx::from(0)];
+    ///     assert_eq! { other.lhs_terms(), clause.lhs_terms() }
+    ///     assert_eq! { other.lhs_preds(), clause.lhs_preds() }
+    ///     assert_eq! { other.rhs(), clause.rhs() }
+    /// }
+    /// ```
+    ///
+    /// [`reprint`]: struct.Instance.html#method.reprint (Instance's reprint function)
+    pub fn clause_to_smtlib(&self, clause: ClsIdx) -> String {
+        let mut buff: Vec<u8> = vec![];
+        self.write_clause_as_smt2(&mut buff, &self[clause], false)
+            .unwrap();
+        String::from_utf8_lossy(&buff).into()
+    }
+
+    /// Writes a clause as an SMT-LIB 2 assertion, using the original variable names.
+    fn write_clause_as_smt2<W: Write>(&self, w: &mut W, clause: &Clause, info: bool) -> IoRes<()> {
+        clause.write(
+            w,
+            |w, var_info| var_info.write_name(w),
+            |w, p, args, bindings| {
+                if !args.is_empty() {
+                    write!(w, "(")?
+                }
+                w.write_all(self[p].name.as_bytes())?;
+                for arg in args.iter() {
+                    write!(w, " ")?;
+                    arg.write_with(w, |w, var| clause.vars[var].write_name(w), bindings)?
+                }
+                if !args.is_empty() {
+                    write!(w, ")")
+                } else {
+                    Ok(())
+                }
+            },
+            info,
+        )
+    }
+
+    /// Writes the instance as a normalized SMT-LIB 2 script.
+    ///
+    /// Unlike [`dump_as_smt2`], only writes the actual script: datatypes, functions, predicates as
+    /// `declare-fun`s, and clauses as `(assert (forall ...))`. Parsing the output yields an
+    /// equivalent instance.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, parse };
+    /// let instance = parse::mc_91();
+    /// let mut buf: Vec<u8> = vec![];
+    /// instance.reprint(&mut buf).expect("while reprinting instance");
+    /// let reprinted = String::from_utf8(buf).expect("illegal utf8 in reprinted instance");
+    /// # println!("{}", reprinted);
+    ///
+    /// let other = parse::instance(&reprinted);
+    /// assert_eq! { other.preds().len(), instance.preds().len() }
+    /// assert_eq! { other.clauses().len(), instance.clauses().len() }
+    /// for (idx, clause) in instance.clauses().index_iter() {
+    ///     assert_eq! { other[idx].lhs_terms(), clause.lhs_terms() }
+    ///     assert_eq! { other[idx].lhs_preds(), clause.lhs_preds() }
+    ///     assert_eq! { other[idx].rhs(), clause.rhs() }
+    /// }
+    /// ```
+    ///
+    /// [`dump_as_smt2`]: struct.Instance.html#method.dump_as_smt2
+    /// (Instance's dump_as_smt2 function)
+    pub fn reprint<W: Write>(&self, w: &mut W) -> Res<()> {
+        writeln!(w, "(set-logic HORN)")?;
+        writeln!(w)?;
+
+        dtyp::write_all(w, "")?;
+        fun::write_all(w, "", false)?;
+
+        for side_clause in &self.side_clauses {
+            side_clause.write(
+                w,
+                |w, var_info| var_info.write_name(w),
+                |_, _, _, _| panic!("illegal side-clause: found predicate application(s)"),
+                false,
+            )?;
+            writeln!(w)?
+        }
+
+        for pred in self.preds.iter() {
+            if !pred.is_defined() {
+                write!(w, "({} {} (", keywords::cmd::dec_fun, pred.name)?;
+                for typ in &pred.sig {
+                    write!(w, " {}", typ)?
+                }
+                writeln!(w, " ) Bool)")?
+            }
+        }
+        writeln!(w)?;
+
+        for clause in self.clauses.iter() {
+            self.write_clause_as_smt2(w, clause, false)?;
+            writeln!(w)?
+        }
+
+        writeln!(w, "(check-sat)")?;
+
+        Ok(())
+    }
+
+    /// Writes the instance as JSON.
+    ///
+    /// The output is an object with two fields:
+    ///
+    /// - `preds`: array of predicates, each with its `idx`, its `name`, its signature `sig` as an
+    ///   array of types, and whether it is `defined`;
+    /// - `clauses`: array of clauses, each with its `idx`, its variables `vars` (`idx`, `name`,
+    ///   `typ` and `active`), its `lhs_terms`, its `lhs_preds` and its `rhs`.
+    ///
+    /// Terms are written as SMT-LIB 2 strings using the original variable names. Predicate
+    /// applications are objects with the predicate's `idx`, its `name` and its `args` as an array
+    /// of terms. The `rhs` is `null` if the clause has no predicate application in its rhs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, parse };
+    /// let instance = parse::instance("
+    ///     (declare-fun pred (Int) Bool)
+    ///     (assert (forall ((n Int)) (=> (>= n 0) (pred n))))
+    ///     (assert (forall ((n Int)) (=> (and (pred n) (> n 7)) false)))
+    /// ");
+    /// let mut buf: Vec<u8> = vec![];
+    /// instance.to_json(&mut buf).expect("while writing instance as json");
+    /// let json = String::from_utf8(buf).expect("illegal utf8 in json");
+    /// # println!("{}", json);
+    /// assert! { json.contains(
+    ///     r#"{ "idx": 0, "name": "pred", "sig": [ "Int" ], "defined": false }"#
+    /// ) }
+    /// assert! { json.contains(r#""rhs": { "idx": 0, "name": "pred", "args": [ "n" ] }"#) }
+    /// assert! { json.contains(r#""rhs": null"#) }
+    /// ```
+    pub fn to_json<W: Write>(&self, w: &mut W) -> Res<()> {
+        writeln!(w, "{{")?;
+
+        write!(w, "  \"preds\": [")?;
+        for (pred, info) in self.preds.index_iter() {
+            if *pred > 0 {
+                write!(w, ",")?
+            }
+            write!(
+                w,
+                "\n    {{ \"idx\": {}, \"name\": \"{}\", \"sig\": [",
+                pred,
+                json_escape(&info.name)
+            )?;
+            for (idx, typ) in info.sig.iter().enumerate() {
+                if idx > 0 {
+                    write!(w, ",")?
+                }
+                write!(w, " \"{}\"", json_escape(&typ.to_string()))?
+            }
+            write!(w, " ], \"defined\": {} }}", info.is_defined())?
+        }
+        if !self.preds.is_empty() {
+            write!(w, "\n  ")?
+        }
+        writeln!(w, "],")?;
+
+        write!(w, "  \"clauses\": [")?;
+        for (idx, clause) in self.clauses.index_iter() {
+            if *idx > 0 {
+                write!(w, ",")?
+            }
+            self.write_clause_as_json(w, idx, clause)?
+        }
+        if !self.clauses.is_empty() {
+            write!(w, "\n  ")?
+        }
+        writeln!(w, "]")?;
+
+        writeln!(w, "}}")?;
+        Ok(())
+    }
+
+    /// Writes a clause as a JSON object, see [`to_json`].
+    ///
+    /// [`to_json`]: struct.Instance.html#method.to_json (Instance's to_json function)
+    fn write_clause_as_json<W: Write>(&self, w: &mut W, idx: ClsIdx, clause: &Clause) -> Res<()> {
+        let term_to_json = |term: &Term| -> Res<String> {
+            let mut buf: Vec<u8> = vec![];
+            term.write(&mut buf, |w, var| clause.vars[var].write_name(w))?;
+            let term = String::from_utf8(buf).chain_err(|| "illegal utf8 in term")?;
+            Ok(format!("\"{}\"", json_escape(&term)))
+        };
+        let pred_app_to_json = |pred: PrdIdx, args: &VarTerms| -> Res<String> {
+            let mut res = format!(
+                "{{ \"idx\": {}, \"name\": \"{}\", \"args\": [",
+                pred,
+                json_escape(&self[pred].name)
+            );
+            for (idx, arg) in args.iter().enumerate() {
+                if idx > 0 {
+                    res.push(',')
+                }
+                res.push(' ');
+                res.push_str(&term_to_json(arg)?)
+            }
+            res.push_str(" ] }");
+            Ok(res)
+        };
+
+        write!(w, "\n    {{\n      \"idx\": {},\n      \"vars\": [", idx)?;
+        for var in clause.vars.iter() {
+            if *var.idx > 0 {
+                write!(w, ",")?
+            }
+            write!(
+                w,
+                " {{ \"idx\": {}, \"name\": \"{}\", \"typ\": \"{}\", \"active\": {} }}",
+                var.idx,
+                json_escape(&var.name),
+                json_escape(&var.typ.to_string()),
+                var.active
+            )?
+        }
+        writeln!(w, " ],")?;
+
+        write!(w, "      \"lhs_terms\": [")?;
+        for (idx, term) in clause.lhs_terms().iter().enumerate() {
+            if idx > 0 {
+                write!(w, ",")?
+            }
+            write!(w, " {}", term_to_json(term)?)?
+        }
+        writeln!(w, " ],")?;
+
+        let mut lhs_preds: Vec<_> = clause.lhs_preds().iter().collect();
+        lhs_preds.sort_unstable_by_key(|(pred, _)| **pred);
+        write!(w, "      \"lhs_preds\": [")?;
+        let mut first = true;
+        for (pred, argss) in lhs_preds {
+            for args in argss {
+                if !first {
+                    write!(w, ",")?
+                }
+                first = false;
+                write!(w, " {}", pred_app_to_json(*pred, args)?)?
+            }
+        }
+        writeln!(w, " ],")?;
+
+        if let Some((pred, args)) = clause.rhs() {
+            writeln!(w, "      \"rhs\": {}", pred_app_to_json(pred, args)?)?
+        } else {
+            writeln!(w, "      \"rhs\": null")?
+        }
+        write!(w, "    }}")?;
+
+        Ok(())
+    }
+
     /// Simplifies some predicate definitions.
     ///
     /// Simplifies its internal predicate definitions and the ones in the model.
@@ -1263,11 +2335,11 @@ impl Instance {
             self.finalize()?
         }
 
+        let forced = self.model_forced_preds();
         let mut old_tterms: PrdMap<_> =
             self.preds.iter_mut().map(|pred| pred.unset_def()).collect();
 
-        for pred in &self.sorted_pred_terms {
-            let pred = *pred;
+        for pred in forced {
             let mut curr_def = None;
             ::std::mem::swap(&mut curr_def, &mut old_tterms[pred]);
             if let Some(def) = curr_def {
@@ -1307,6 +2379,57 @@ impl Instance {
         Ok(())
     }
 
+    /// Writes the body of the definition of a predicate in a model.
+    ///
+    /// Writes the prefix first. If `conf.model_lets` is set and the definition does not mention
+    /// predicates or quantifiers, the subterms appearing more than once are factored with
+    /// let-bindings.
+    fn write_def_body<W: Write>(
+        &self,
+        w: &mut W,
+        pref: &str,
+        pred: PrdIdx,
+        conj: &[TTerms],
+    ) -> Res<()> {
+        if conf.model_lets {
+            let mut terms = Vec::with_capacity(conj.len());
+            for tterms in conj {
+                if let Some(term) = tterms.to_term() {
+                    terms.push(term)
+                } else {
+                    break;
+                }
+            }
+
+            if terms.len() == conj.len() {
+                let def = term::and(terms);
+                let mut fresh = self[pred].original_sig().next_index();
+                if let Some(max) = term::max_var(&def) {
+                    if max >= fresh {
+                        fresh = max;
+                        fresh.inc()
+                    }
+                }
+
+                if let Some(bindings) = term::bindings::Builder::new()
+                    .scan_term(&def)
+                    .build_shared(fresh)
+                {
+                    bindings.write_opening(w, VarIdx::write, pref)?;
+                    write!(w, "{}  ", pref)?;
+                    def.write_with(w, VarIdx::write, Some(&bindings))?;
+                    for _ in bindings.bindings() {
+                        write!(w, ")")?
+                    }
+                    return Ok(());
+                }
+            }
+        }
+
+        write!(w, "{}", pref)?;
+        self.write_tterms_conj(w, conj)
+    }
+
     /// Writes some definitions.
     pub fn write_definitions<W: Write>(
         &self,
@@ -1325,8 +2448,8 @@ impl Instance {
                 writeln!(w, "{}({} {}", pref, keywords::cmd::def_fun, self[pred].name)?;
                 write!(w, "{}  ", pref)?;
                 self.write_pred_sig(w, pred)?;
-                write!(w, "\n{}  ", pref)?;
-                self.write_tterms_conj(w, tterms)?;
+                writeln!(w)?;
+                self.write_def_body(w, &format!("{}  ", pref), pred, tterms)?;
                 writeln!(w, "\n{})", pref)?
             } else {
                 write!(w, "{}({} (", pref, keywords::cmd::def_funs_rec)?;
@@ -1335,9 +2458,9 @@ impl Instance {
                     self.write_pred_sig(w, pred)?;
                 }
                 write!(w, "\n{}) (", pref)?;
-                for &(_, ref tterms) in defs {
-                    write!(w, "\n{}  ", pref)?;
-                    self.write_tterms_conj(w, tterms)?;
+                for &(pred, ref tterms) in defs {
+                    writeln!(w)?;
+                    self.write_def_body(w, &format!("{}  ", pref), pred, tterms)?;
                 }
                 writeln!(w, "\n{}) )", pref)?;
             }
@@ -1362,6 +2485,14 @@ impl Instance {
     pub fn print_success(&self) -> bool {
         self.print_success
     }
+    /// Sets the expected status, `true` for sat.
+    pub fn set_expected_status(&mut self, sat: bool) {
+        self.expected_status = Some(sat)
+    }
+    /// Expected status declared by `(set-info :status ...)`, `Some(true)` for sat.
+    pub fn expected_status(&self) -> Option<bool> {
+        self.expected_status
+    }
     /// Sets unsat-cores flag.
     pub fn set_unsat_cores(&mut self, b: bool) {
         self.unsat_cores = b
@@ -1397,6 +2528,34 @@ impl Instance {
         &self.no_inlining_preds
     }
 
+    /// Sets the predicates qualifier synthesis is deactivated for.
+    pub fn set_no_synth_preds(&mut self, preds: HashSet<String>) {
+        self.no_synth_preds = preds
+    }
+    /// Predicates qualifier synthesis is deactivated for, by `set-option` or `--no_synth_preds`.
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// let instance = hoice::parse::instance("
+    /// (declare-fun P (Int) Bool)
+    /// (declare-fun Q (Int) Bool)
+    /// (set-option :no-synth-preds |Q|)
+    /// ");
+    /// let q: PrdIdx = 1.into();
+    /// let expected: PrdSet = vec![q].into_iter().collect();
+    /// assert_eq! { instance.no_synth_preds(), expected }
+    /// ```
+    pub fn no_synth_preds(&self) -> PrdSet {
+        self.preds
+            .iter()
+            .filter(|pred| {
+                self.no_synth_preds.contains(&pred.name)
+                    || conf.ice.no_synth_preds.contains(&pred.name)
+            })
+            .map(|pred| pred.idx)
+            .collect()
+    }
+
     /// Sets the simplify-clauses flag.
     pub fn set_simplify_clauses(&mut self, b: bool) {
         if !b {
@@ -1409,59 +2568,361 @@ impl Instance {
         self.simplify_clauses
     }
 
-    /// True if the teacher needs to maintain a sample graph (unsat
-    /// cores/proofs).
-    pub fn track_samples(&self) -> bool {
-        false // self.unsat_cores() || self.proofs()
+    /// Sets the learning data from previous checks.
+    pub fn set_warm(&mut self, warm: crate::data::WarmData) {
+        self.warm = Some(Arc::new(warm))
+    }
+    /// Learning data from previous checks, if any.
+    pub fn warm(&self) -> Option<&crate::data::WarmData> {
+        self.warm.as_ref().map(|warm| warm.as_ref())
+    }
+
+    /// Registers the reduction info of a pre-processor.
+    pub fn register_preproc_info(&mut self, preproc: &'static str, info: &RedInfo) {
+        *self
+            .preproc_info
+            .entry(preproc)
+            .or_insert_with(RedInfo::new) += info.clone()
+    }
+
+    /// Structural metrics of the instance, see [`InstanceMetrics`].
+    ///
+    /// [`InstanceMetrics`]: struct.InstanceMetrics.html (InstanceMetrics struct)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let instance = ::hoice::parse::mc_91();
+    /// let metrics = instance.metrics();
+    /// assert_eq! { metrics.preds, 1 }
+    /// assert_eq! { metrics.clauses, 3 }
+    /// assert_eq! { metrics.max_arity, 2 }
+    /// assert_eq! { metrics.pred_arity_histogram, vec![0, 0, 1] }
+    /// let p: PrdIdx = 0.into();
+    /// assert_eq! { metrics.clauses_of_pred[p], (2, 2) }
+    /// assert! { metrics.term_size > 0 }
+    /// assert! { metrics.difference_logic }
+    /// ```
+    pub fn metrics(&self) -> InstanceMetrics {
+        let max_arity = self
+            .preds
+            .iter()
+            .map(|pred| pred.arity())
+            .max()
+            .unwrap_or(0);
+        let clauses_of_pred = self
+            .pred_indices()
+            .map(|pred| {
+                let (lhs, rhs) = self.clauses_of(pred);
+                (lhs.len(), rhs.len())
+            })
+            .collect();
+
+        let mut term_size = 0;
+        for clause in self.clauses.iter() {
+            for term in clause.lhs_terms() {
+                term_size += term.size()
+            }
+            for argss in clause.lhs_preds().values() {
+                for args in argss {
+                    term_size += args.iter().fold(0, |acc, arg| acc + arg.size())
+                }
+            }
+            if let Some((_, args)) = clause.rhs() {
+                term_size += args.iter().fold(0, |acc, arg| acc + arg.size())
+            }
+        }
+
+        InstanceMetrics {
+            clauses: self.clauses.len(),
+            preds: self.preds.len(),
+            max_arity,
+            pred_arity_histogram: self.pred_arity_histogram(),
+            clauses_of_pred,
+            term_size,
+            difference_logic: self.is_difference_logic(),
+        }
     }
 
-    /// Converts `"true"` to `true`, `"false"` to `false`, and everything else to
-    /// an error.
-    fn bool_of_str(s: &str) -> Res<bool> {
-        match s {
-            "true" => Ok(true),
-            "false" => Ok(false),
-            _ => bail!("expected boolean `true/false`, got `{}`", s),
+    /// Number of predicates of each arity.
+    ///
+    /// The value at index `n` is the number of predicates of arity `n`. The histogram stops at
+    /// the maximal arity of the predicates, it is empty if there are no predicates.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let instance = ::hoice::parse::instance("
+    ///   (declare-fun p_1 ( Int ) Bool)
+    ///   (declare-fun p_2 ( Int Int Int ) Bool)
+    ///   (declare-fun p_3 ( Int ) Bool)
+    /// ");
+    /// assert_eq! { instance.pred_arity_histogram(), vec![0, 2, 0, 1] }
+    ///
+    /// let instance = ::hoice::parse::instance("");
+    /// assert! { instance.pred_arity_histogram().is_empty() }
+    /// ```
+    pub fn pred_arity_histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![];
+        for pred in self.preds.iter() {
+            let arity = pred.arity();
+            if histogram.len() <= arity {
+                histogram.resize(arity + 1, 0)
+            }
+            histogram[arity] += 1
         }
+        histogram
+    }
+
+    /// True if all the arithmetic atoms of the clauses are difference constraints.
+    ///
+    /// That is, atoms of the form `x - y ⋈ c` or `x ⋈ c` where `⋈` is a comparison operator or
+    /// (dis)equality, once normalized by [`term::simplify_linear`]. Boolean structure and
+    /// boolean variables are fine, anything else (non-linear arithmetic, datatypes, arrays...)
+    /// is not. The arguments of the predicate applications must be of the form `± x + c` or
+    /// `c`, so that difference bounds on the predicates' variables yield difference bounds in
+    /// the clauses.
+    ///
+    /// [`term::simplify_linear`]: ../term/fn.simplify_linear.html (simplify_linear function)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let instance = ::hoice::parse::mc_91();
+    /// assert! { instance.is_difference_logic() }
+    ///
+    /// let instance = ::hoice::parse::instance("
+    ///     (declare-fun P ( Int Int ) Bool)
+    ///     (assert (forall ((x Int) (y Int)) (=> (and (P x y) (<= (- x y) 7)) (P y x))))
+    ///     (assert (forall ((x Int) (y Int)) (=> (and (P x y) (not (= y (- x 2)))) false)))
+    /// ");
+    /// assert! { instance.is_difference_logic() }
+    ///
+    /// let instance = ::hoice::parse::instance("
+    ///     (declare-fun P ( Int Int ) Bool)
+    ///     (assert (forall ((x Int) (y Int)) (=> (and (P x y) (<= (+ x y) 7)) (P y x))))
+    /// ");
+    /// assert! { !instance.is_difference_logic() }
+    ///
+    /// let instance = ::hoice::parse::instance("
+    ///     (declare-fun P ( Int Int ) Bool)
+    ///     (assert (forall ((x Int) (y Int)) (=> (P x y) (P (+ x y) y))))
+    /// ");
+    /// assert! { !instance.is_difference_logic() }
+    /// ```
+    pub fn is_difference_logic(&self) -> bool {
+        self.clauses.iter().all(|clause| {
+            clause.lhs_terms().iter().all(is_difference_formula)
+                && clause
+                    .lhs_preds()
+                    .values()
+                    .all(|argss| argss.iter().all(|args| args.iter().all(is_difference_arg)))
+                && clause
+                    .rhs()
+                    .map(|(_, args)| args.iter().all(is_difference_arg))
+                    .unwrap_or(true)
+        })
+    }
+
+    /// Some statistics about the instance, see [`InstanceStats`].
+    ///
+    /// [`InstanceStats`]: struct.InstanceStats.html (InstanceStats struct)
+    pub fn stats(&self) -> InstanceStats {
+        let defined_preds = self.preds.iter().filter(|pred| pred.is_defined()).count();
+        InstanceStats {
+            preds: self.preds.len(),
+            defined_preds,
+            clauses: self.clauses.len(),
+            pos_clauses: self.pos_clauses.len(),
+            neg_clauses: self.neg_clauses.len(),
+            preproc: self
+                .preproc_info
+                .iter()
+                .map(|(name, info)| (*name, info.clone()))
+                .collect(),
+        }
+    }
+
+    /// True if the teacher needs to maintain a sample graph (unsat
+    /// cores/proofs).
+    ///
+    /// Set by `(set-option :produce-unsat-cores true)` and `(set-option :produce-proofs true)`.
+    pub fn track_samples(&self) -> bool {
+        self.unsat_cores() || self.proofs()
     }
 
     /// Sets an option.
+    ///
+    /// The value is checked against the type of the option, see `known_options`. Unknown options
+    /// are ignored with a warning that lists the known options with a similar name.
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// let mut instance = Instance::new();
+    /// instance.set_option("print-success", "true").unwrap();
+    /// assert! { instance.print_success() }
+    /// instance.set_option("no-inlining-preds", "P Q").unwrap();
+    /// assert_eq! { instance.no_inlining_preds().len(), 2 }
+    ///
+    /// let err = instance.set_option("produce-proofs", "7").unwrap_err();
+    /// assert_eq! { format!("{}", err), "expected boolean `true/false`, got `7`" }
+    /// let err = instance.set_option("random-seed", "seven").unwrap_err();
+    /// assert_eq! { format!("{}", err), "expected an integer, got `seven`" }
+    ///
+    /// // Unknown, ignored.
+    /// instance.set_option("print-sucess", "false").unwrap();
+    /// assert! { instance.print_success() }
+    /// ```
     pub fn set_option(&mut self, flag: &str, val: &str) -> Res<()> {
-        let flag_err = || format!("while handling set-option for {}", flag);
-        match flag {
-            "print-success" => {
-                let print_succ = Self::bool_of_str(&val).chain_err(flag_err)?;
-                self.set_print_success(print_succ)
-            }
-            "produce-unsat-cores" => {
-                let unsat_cores = Self::bool_of_str(&val).chain_err(flag_err)?;
-                self.set_unsat_cores(unsat_cores)
-            }
-            "produce-proofs" => {
-                let proofs = Self::bool_of_str(&val).chain_err(flag_err)?;
-                self.set_proofs(proofs)
-            }
-            "no-inlining" => {
-                let no_inlining = Self::bool_of_str(&val).chain_err(flag_err)?;
-                self.set_no_inlining(no_inlining);
-            }
-            "no-inlining-preds" => {
-                let no_inlining_preds = val.split_whitespace().map(|s| s.to_owned()).collect();
-                self.set_no_inlining_preds(no_inlining_preds);
-            }
-            "simplify-clauses" => {
-                let simplify = Self::bool_of_str(&val).chain_err(flag_err)?;
-                self.set_simplify_clauses(simplify)
-            }
-            _ => warn!(
-                "ignoring (set-option :{} {}): unknown flag {}",
-                flag, val, flag
-            ),
+        let typ = if let Some(typ) = OptionTyp::of(flag) {
+            typ
+        } else {
+            let close = close_options(flag);
+            if close.is_empty() {
+                warn!("ignoring (set-option :{} {}): unknown option", flag, val)
+            } else {
+                warn!(
+                    "ignoring (set-option :{} {}): unknown option", flag, val ;
+                    "did you mean {}?", close.join(", ")
+                )
+            }
+            return Ok(());
+        };
+
+        match (flag, typ.parse(val)?) {
+            ("print-success", OptionVal::Bool(b)) => self.set_print_success(b),
+            ("produce-unsat-cores", OptionVal::Bool(b)) => self.set_unsat_cores(b),
+            ("produce-proofs", OptionVal::Bool(b)) => self.set_proofs(b),
+            ("no-inlining", OptionVal::Bool(b)) => self.set_no_inlining(b),
+            ("no-inlining-preds", OptionVal::Str(preds)) => {
+                let preds = preds.split_whitespace().map(|s| s.to_owned()).collect();
+                self.set_no_inlining_preds(preds)
+            }
+            ("no-synth-preds", OptionVal::Str(preds)) => {
+                let preds = preds.split_whitespace().map(|s| s.to_owned()).collect();
+                self.set_no_synth_preds(preds)
+            }
+            ("simplify-clauses", OptionVal::Bool(b)) => self.set_simplify_clauses(b),
+            // Standard options without effect.
+            (_, val) => log! { @verb "ignoring (set-option :{} {})", flag, val },
         }
         Ok(())
     }
 }
 
+/// Type of the value of an option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OptionTyp {
+    /// Boolean, `true` or `false`.
+    Bool,
+    /// Natural number.
+    Int,
+    /// String, optionally quoted.
+    Str,
+}
+
+/// Options recognized by `set-option`, with the type of their value.
+///
+/// Options not handled by `Instance::set_option` are standard SMT-LIB options that have no
+/// effect.
+const known_options: &[(&str, OptionTyp)] = &[
+    ("print-success", OptionTyp::Bool),
+    ("produce-unsat-cores", OptionTyp::Bool),
+    ("produce-proofs", OptionTyp::Bool),
+    ("no-inlining", OptionTyp::Bool),
+    ("no-inlining-preds", OptionTyp::Str),
+    ("no-synth-preds", OptionTyp::Str),
+    ("simplify-clauses", OptionTyp::Bool),
+    ("produce-models", OptionTyp::Bool),
+    ("produce-assignments", OptionTyp::Bool),
+    ("global-declarations", OptionTyp::Bool),
+    ("interactive-mode", OptionTyp::Bool),
+    ("random-seed", OptionTyp::Int),
+    ("verbosity", OptionTyp::Int),
+    ("reproducible-resource-limit", OptionTyp::Int),
+    ("diagnostic-output-channel", OptionTyp::Str),
+    ("regular-output-channel", OptionTyp::Str),
+];
+
+impl OptionTyp {
+    /// Type of a known option.
+    fn of(option: &str) -> Option<Self> {
+        known_options
+            .iter()
+            .find(|(name, _)| *name == option)
+            .map(|(_, typ)| *typ)
+    }
+
+    /// Parses a value of this type.
+    fn parse(self, val: &str) -> Res<OptionVal> {
+        match self {
+            OptionTyp::Bool => match val {
+                "true" => Ok(OptionVal::Bool(true)),
+                "false" => Ok(OptionVal::Bool(false)),
+                _ => bail!("expected boolean `true/false`, got `{}`", val),
+            },
+            OptionTyp::Int => {
+                if let Ok(int) = val.parse::<usize>() {
+                    Ok(OptionVal::Int(int))
+                } else {
+                    bail!("expected an integer, got `{}`", val)
+                }
+            }
+            OptionTyp::Str => Ok(OptionVal::Str(val.into())),
+        }
+    }
+}
+
+/// Value of an option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OptionVal {
+    /// Boolean value.
+    Bool(bool),
+    /// Natural number.
+    Int(usize),
+    /// String.
+    Str(String),
+}
+mylib::impl_fmt! {
+    OptionVal(self, fmt) {
+        match self {
+            OptionVal::Bool(b) => write!(fmt, "{}", b),
+            OptionVal::Int(i) => write!(fmt, "{}", i),
+            OptionVal::Str(s) => write!(fmt, "\"{}\"", s),
+        }
+    }
+}
+
+/// Known options close to some unknown option, for error reporting.
+///
+/// An option is close if its edit distance to `option` is at most two, or if one contains the
+/// other.
+fn close_options(option: &str) -> Vec<String> {
+    known_options
+        .iter()
+        .filter(|(name, _)| {
+            name.contains(option) || option.contains(name) || edit_distance(name, option) <= 2
+        })
+        .map(|(name, _)| format!("`:{}`", name))
+        .collect()
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(s_1: &str, s_2: &str) -> usize {
+    let s_2: Vec<char> = s_2.chars().collect();
+    let mut prev: Vec<usize> = (0..=s_2.len()).collect();
+    for (i, c_1) in s_1.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, c_2) in s_2.iter().enumerate() {
+            let subst = prev[j] + if c_1 == *c_2 { 0 } else { 1 };
+            current.push(subst.min(prev[j + 1] + 1).min(current[j] + 1))
+        }
+        prev = current
+    }
+    prev[s_2.len()]
+}
+
 /// Lhs part of a cex.
 type CexLhs = Vec<(PrdIdx, VarTermsSet)>;
 /// Lhs part of a cex, reference version.
@@ -1646,24 +3107,207 @@ impl Instance {
     }
 
     /// Turns some teacher counterexamples into learning data.
+    ///
+    /// All the counterexamples are added in a single [batch].
+    ///
+    /// [batch]: ../data/struct.Data.html#method.begin_batch (begin_batch function)
     pub fn cexs_to_data(&self, data: &mut Data, cexs: Cexs) -> Res<bool> {
         let mut changed = false;
 
+        data.begin_batch();
+
         for (clause_idx, cexs) in cexs {
             log! { @5 "adding cexs for #{}", clause_idx }
 
             for cex in cexs {
-                let new_stuff = self.clause_cex_to_data(data, clause_idx, cex)?;
-                changed = changed || new_stuff
+                match self.clause_cex_to_data(data, clause_idx, cex) {
+                    Ok(new_stuff) => changed = changed || new_stuff,
+                    Err(e) => {
+                        // Leave batch mode, the original error takes precedence.
+                        let _ = data.commit_batch();
+                        bail!(e)
+                    }
+                }
             }
         }
 
-        let (pos, neg) = data.propagate()?;
+        let nu_stuff = data.commit_batch()?;
 
-        Ok(changed || pos > 0 || neg > 0)
+        Ok(changed || nu_stuff)
     }
 }
 
+/// Structural metrics of an instance.
+///
+/// Produced by [`Instance::metrics`], cheap to compute.
+///
+/// [`Instance::metrics`]: struct.Instance.html#method.metrics (metrics function of Instance)
+#[derive(Debug, Clone)]
+pub struct InstanceMetrics {
+    /// Number of clauses.
+    pub clauses: usize,
+    /// Number of predicates.
+    pub preds: usize,
+    /// Maximal arity of the predicates.
+    pub max_arity: usize,
+    /// Number of predicates of each arity, see [`Instance::pred_arity_histogram`].
+    ///
+    /// [`Instance::pred_arity_histogram`]: struct.Instance.html#method.pred_arity_histogram
+    /// (pred_arity_histogram function of Instance)
+    pub pred_arity_histogram: Vec<usize>,
+    /// Number of clauses each predicate appears in, in the LHS and RHS respectively.
+    pub clauses_of_pred: PrdMap<(usize, usize)>,
+    /// Total size of the terms of the clauses, see [`RTerm::size`].
+    ///
+    /// [`RTerm::size`]: ../term/enum.RTerm.html#method.size (size function of RTerm)
+    pub term_size: usize,
+    /// True if the instance is in difference logic, see [`Instance::is_difference_logic`].
+    ///
+    /// [`Instance::is_difference_logic`]: struct.Instance.html#method.is_difference_logic
+    /// (is_difference_logic function of Instance)
+    pub difference_logic: bool,
+}
+
+/// True if a boolean term only mentions difference constraints.
+///
+/// See [`Instance::is_difference_logic`].
+///
+/// [`Instance::is_difference_logic`]: struct.Instance.html#method.is_difference_logic
+/// (is_difference_logic function of Instance)
+fn is_difference_formula(term: &Term) -> bool {
+    if term.bool().is_some() {
+        return true;
+    }
+    if term.var_idx().is_some() {
+        return term.typ().is_bool();
+    }
+
+    match term.app_inspect() {
+        Some((Op::And, args))
+        | Some((Op::Or, args))
+        | Some((Op::Not, args))
+        | Some((Op::Impl, args)) => args.iter().all(is_difference_formula),
+
+        Some((Op::Ite, args)) if term.typ().is_bool() => args.iter().all(is_difference_formula),
+
+        Some((Op::Eql, args)) | Some((Op::Distinct, args))
+            if args.iter().all(|arg| arg.typ().is_bool()) =>
+        {
+            args.iter().all(is_difference_formula)
+        }
+
+        Some((Op::Ge, args))
+        | Some((Op::Gt, args))
+        | Some((Op::Le, args))
+        | Some((Op::Lt, args))
+        | Some((Op::Eql, args))
+        | Some((Op::Distinct, args)) => args.iter().enumerate().all(|(idx, lhs)| {
+            args[idx + 1..]
+                .iter()
+                .all(|rhs| is_difference_bound(lhs, rhs))
+        }),
+
+        _ => false,
+    }
+}
+
+/// True if a predicate application argument is a difference formula, or of the form `± x + c` or
+/// `c`, with `c` a constant.
+fn is_difference_arg(arg: &Term) -> bool {
+    let typ = arg.typ();
+    if typ.is_bool() {
+        is_difference_formula(arg)
+    } else if typ.is_arith() {
+        let zero = if typ.is_int() {
+            term::int_zero()
+        } else {
+            term::real_zero()
+        };
+        term::vars(arg).len() <= 1 && is_difference_bound(arg, &zero)
+    } else {
+        false
+    }
+}
+
+/// True if `lhs - rhs` is of the form `x - y + c` or `± x + c`, with `c` a constant.
+fn is_difference_bound(lhs: &Term, rhs: &Term) -> bool {
+    if !lhs.typ().is_arith() {
+        return false;
+    }
+
+    let diff = term::simplify_linear(&term::sub2(lhs.clone(), rhs.clone()));
+    let summands: Vec<&Term> = match diff.app_inspect() {
+        Some((Op::Add, args)) => args.iter().collect(),
+        _ => vec![&diff],
+    };
+
+    // Signs of the variables in the difference, `true` for positive.
+    let mut signs = Vec::with_capacity(2);
+    for summand in summands {
+        if summand.val().is_some() {
+            continue;
+        }
+        let (positive, kid) = if let Some((coef, kid)) = summand.cmul_inspect() {
+            if coef.is_one() {
+                (true, kid)
+            } else if coef.is_minus_one() {
+                (false, kid)
+            } else {
+                return false;
+            }
+        } else {
+            (true, summand)
+        };
+        if kid.var_idx().is_none() || signs.len() == 2 {
+            return false;
+        }
+        signs.push(positive)
+    }
+
+    signs.len() < 2 || signs[0] != signs[1]
+}
+
+/// Statistics about an instance.
+///
+/// Produced by [`Instance::stats`], printed in `--dry_run` mode.
+///
+/// [`Instance::stats`]: struct.Instance.html#method.stats (stats function of Instance)
+#[derive(Debug, Clone)]
+pub struct InstanceStats {
+    /// Number of predicates.
+    pub preds: usize,
+    /// Number of predicates with a definition.
+    pub defined_preds: usize,
+    /// Number of clauses.
+    pub clauses: usize,
+    /// Number of positive clauses, only relevant after finalization.
+    pub pos_clauses: usize,
+    /// Number of negative clauses, only relevant after finalization.
+    pub neg_clauses: usize,
+    /// Reduction info of the pre-processors that did something.
+    pub preproc: Vec<(&'static str, RedInfo)>,
+}
+mylib::impl_fmt! {
+  InstanceStats(self, fmt) {
+    writeln!(
+      fmt, "predicates: {} ({} defined)", self.preds, self.defined_preds
+    ) ? ;
+    writeln!(
+      fmt, "clauses:    {} ({} positive, {} negative)",
+      self.clauses, self.pos_clauses, self.neg_clauses
+    ) ? ;
+    if self.preproc.is_empty() {
+      write!(fmt, "no reduction strategy fired")
+    } else {
+      write!(fmt, "reduction strategies:") ? ;
+      for (name, info) in & self.preproc {
+        write!(fmt, "\n  {:>10}: {}", name, info) ?
+      }
+      Ok(())
+    }
+  }
+}
+
 impl ::std::ops::Index<PrdIdx> for Instance {
     type Output = Pred;
     fn index(&self, index: PrdIdx) -> &Pred {
@@ -1678,6 +3322,7 @@ impl ::std::ops::Index<ClsIdx> for Instance {
 }
 impl ::std::ops::IndexMut<ClsIdx> for Instance {
     fn index_mut(&mut self, index: ClsIdx) -> &mut Clause {
+        self.op_dirty.insert(index);
         &mut self.clauses[index]
     }
 }
@@ -1711,13 +3356,13 @@ impl<'a> PebcakFmt<'a> for Clause {
     fn pebcak_io_fmt<W: Write>(&self, w: &mut W, preds: &'a Preds) -> IoRes<()> {
         self.write(
             w,
-            |w, var_info| write!(w, "{}", var_info.name),
+            |w, var_info| var_info.write_name(w),
             |w, prd, args, bindings| {
                 write!(w, "(")?;
                 w.write_all(preds[prd].name.as_bytes())?;
                 for arg in args.iter() {
                     write!(w, " ")?;
-                    arg.write_with(w, |w, var| write!(w, "{}", self.vars[var]), bindings)?
+                    arg.write_with(w, |w, var| self.vars[var].write_name(w), bindings)?
                 }
                 write!(w, ")")
             },
@@ -1745,7 +3390,7 @@ impl<'a> PebcakFmt<'a> for Instance {
                     write!(w, " {}", typ)?
                 }
                 writeln!(w, " ) Bool\n)")?;
-                if pred.sig.len() != pred.original_sig().len() {
+                if pred.arity() != pred.original_sig().len() {
                     write!(w, "; original signature:\n;")?;
                     for (var, typ) in pred.original_sig().index_iter() {
                         write!(w, " ({} {})", var.default_str(), typ)?
@@ -689,6 +689,88 @@ impl Clause {
             && self.lhs_terms == other.lhs_terms
     }
 
+    /// A normalized representation of the clause, up to variable renaming.
+    ///
+    /// Two clauses with the same key are equal modulo alpha-renaming. Variables are renamed in the
+    /// order they appear in the rhs, the lhs predicate applications and the lhs terms, the last
+    /// two being sorted in a way that does not depend on variable names.
+    ///
+    /// The converse does not hold: alpha-equivalent clauses can have different keys when two
+    /// terms only differ by their variables, since ordering such terms depends on the names.
+    pub fn alpha_key(&self) -> String {
+        use std::fmt::Write as FmtWrite;
+
+        let erased = |term: &Term| {
+            let mut s: Vec<u8> = vec![];
+            term.write(&mut s, |w, _| write!(w, "_"))
+                .expect("writing to a vector cannot fail");
+            String::from_utf8_lossy(&s).into_owned()
+        };
+
+        // Segments of the clause, each with a header and its terms in canonical order.
+        let mut segments: Vec<(String, Vec<&Term>)> = vec![];
+
+        if let Some((pred, args)) = self.rhs() {
+            segments.push((format!("rhs {}", pred), args.iter().collect()))
+        }
+
+        let mut preds: Vec<_> = self.lhs_preds.iter().collect();
+        preds.sort_by_key(|(pred, _)| **pred);
+        for (pred, argss) in preds {
+            let mut argss: Vec<(String, &VarTerms)> = argss
+                .iter()
+                .map(|args| (args.iter().map(&erased).collect(), args))
+                .collect();
+            argss.sort_by(|(k_1, _), (k_2, _)| k_1.cmp(k_2));
+            for (_, args) in argss {
+                segments.push((format!("app {}", pred), args.iter().collect()))
+            }
+        }
+
+        let mut terms: Vec<(String, &Term)> = self
+            .lhs_terms
+            .iter()
+            .map(|term| (erased(term), term))
+            .collect();
+        terms.sort_by(|(k_1, _), (k_2, _)| k_1.cmp(k_2));
+        segments.push((
+            "terms".into(),
+            terms.into_iter().map(|(_, term)| term).collect(),
+        ));
+
+        let mut renaming = VarHMap::new();
+        let mut key = String::new();
+        for (_, terms) in &segments {
+            for term in terms {
+                for leaf in term.leaf_iter() {
+                    if let Either::Left((_, var)) = leaf {
+                        if !renaming.contains_key(&var) {
+                            let nu = renaming.len();
+                            renaming.insert(var, nu);
+                            write!(key, "{} ", self.vars[var].typ)
+                                .expect("writing to a string cannot fail")
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut s: Vec<u8> = vec![];
+        for (header, terms) in &segments {
+            s.extend_from_slice(b"| ");
+            s.extend_from_slice(header.as_bytes());
+            for term in terms {
+                s.push(b' ');
+                term.write(&mut s, |w, var| write!(w, "v_{}", renaming[&var]))
+                    .expect("writing to a vector cannot fail");
+            }
+            s.push(b' ')
+        }
+        key.push_str(&String::from_utf8_lossy(&s));
+
+        key
+    }
+
     /// Cheap unsat check.
     ///
     /// Does not use smt-solving, as this is the responsability of the
@@ -754,11 +836,6 @@ impl Clause {
     /// Checks a clause is well-formed.
     #[cfg(debug_assertions)]
     pub fn check(&self, blah: &'static str) -> Res<()> {
-        let mut vars = VarSet::with_capacity(self.vars.len());
-        for term in &self.lhs_terms {
-            vars.extend(term::vars(term))
-        }
-
         scoped! {
           let mut terms = self.lhs_terms.iter() ;
 
@@ -774,19 +851,7 @@ impl Clause {
           }
         }
 
-        for (_, argss) in &self.lhs_preds {
-            for args in argss {
-                for arg in args.iter() {
-                    vars.extend(term::vars(arg))
-                }
-            }
-        }
-        if let Some((_, ref args)) = self.rhs {
-            for arg in args.iter() {
-                vars.extend(term::vars(arg))
-            }
-        }
-        for var in vars {
+        for var in self.variables() {
             if !self.vars[var].active {
                 bail!(
                     "ill-formed clause: {}, \
@@ -867,6 +932,114 @@ impl Clause {
         &self.vars
     }
 
+    /// Variables appearing in the clause.
+    ///
+    /// Union of the variables of the lhs terms, of the arguments of the lhs predicate
+    /// applications and of the arguments of the rhs. Unlike [`vars`][vars], only contains the
+    /// variables actually used in the clause.
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// let instance = hoice::parse::instance("
+    ///   (declare-fun P (Int) Bool)
+    ///   (assert (forall ((n Int) (m Int) (k Int)) (=> (and (P n) (> n 0)) (P (+ n 1)))))
+    /// ");
+    /// let clause = &instance[ClsIdx::from(0)];
+    /// let expected: VarSet = vec![ 0.into() ].into_iter().collect();
+    /// assert_eq! { clause.variables(), expected }
+    /// ```
+    ///
+    /// [vars]: #method.vars (vars function)
+    pub fn variables(&self) -> VarSet {
+        let mut vars = VarSet::with_capacity(self.vars.len());
+        let mut add = |term: &Term| {
+            term::map_vars(term, |var| {
+                vars.insert(var);
+            })
+        };
+
+        for term in &self.lhs_terms {
+            add(term)
+        }
+        for argss in self.lhs_preds.values() {
+            for args in argss {
+                for arg in args.iter() {
+                    add(arg)
+                }
+            }
+        }
+        if let Some((_, ref args)) = self.rhs {
+            for arg in args.iter() {
+                add(arg)
+            }
+        }
+
+        vars
+    }
+
+    /// True if no variable appears in the clause.
+    ///
+    /// Such clauses can be checked by evaluation, without quantifiers.
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// let instance = hoice::parse::instance("
+    ///   (declare-fun P (Int Int) Bool)
+    ///   (assert (P 3 4))
+    ///   (assert (forall ((n Int) (m Int)) (=> (P n m) (P m n))))
+    /// ");
+    /// assert! { instance[ClsIdx::from(0)].is_ground() }
+    /// assert! { !instance[ClsIdx::from(1)].is_ground() }
+    /// ```
+    pub fn is_ground(&self) -> bool {
+        self.variables().is_empty()
+    }
+
+    /// Operators appearing in the clause.
+    ///
+    /// Collected over the same terms as [`variables`][variables].
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// let instance = hoice::parse::instance("
+    ///   (declare-fun P (Int) Bool)
+    ///   (assert (forall ((n Int)) (=> (and (P n) (> n 0)) (P (+ n 1)))))
+    /// ");
+    /// let ops = instance[ClsIdx::from(0)].operators();
+    /// assert! { ops.contains(&Op::Add) }
+    /// assert! { !ops.contains(&Op::Mul) }
+    /// ```
+    ///
+    /// [variables]: #method.variables (variables function)
+    pub fn operators(&self) -> HashSet<Op> {
+        let mut ops = HashSet::new();
+        let mut add = |term: &Term| {
+            term.iter(|sub| {
+                if let Some((op, _)) = sub.app_inspect() {
+                    ops.insert(op);
+                }
+            })
+        };
+
+        for term in &self.lhs_terms {
+            add(term)
+        }
+        for argss in self.lhs_preds.values() {
+            for args in argss {
+                for arg in args.iter() {
+                    add(arg)
+                }
+            }
+        }
+        if let Some((_, ref args)) = self.rhs {
+            for arg in args.iter() {
+                add(arg)
+            }
+        }
+
+        ops
+    }
+
     /// Returns the source clauses.
     ///
     /// Source clauses are original clauses this clause stems from.
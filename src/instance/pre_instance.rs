@@ -5,7 +5,10 @@ use crate::{
         smt::{ClauseTrivialExt, SmtImpl},
         *,
     },
-    preproc::utils::ExtractionCxt,
+    preproc::{
+        clause_split::MAX_SPLIT,
+        utils::{ExtractionCxt, SkipReason, SkipReasons},
+    },
 };
 
 /// Performs a checksat.
@@ -57,6 +60,9 @@ pub struct PreInstance<'a> {
 
     /// Use actlits in checksats.
     reset_solver: bool,
+
+    /// Reasons why pre-processors did not reduce predicates, see `--explain_preproc`.
+    skip_reasons: SkipReasons,
 }
 impl<'a> PreInstance<'a> {
     /// Constructor.
@@ -85,12 +91,27 @@ impl<'a> PreInstance<'a> {
             vars: VarSet::new(),
             extraction: ExtractionCxt::new(),
             reset_solver,
+            skip_reasons: SkipReasons::new(),
         })
     }
 
+    /// Records the reason why a pre-processor did not reduce a predicate.
+    ///
+    /// Does nothing unless `--explain_preproc` is active.
+    pub fn skip(&mut self, preproc: &'static str, pred: PrdIdx, reason: SkipReason) {
+        if conf.preproc.explain_preproc {
+            self.skip_reasons.record(preproc, pred, reason)
+        }
+    }
+
+    /// Reasons why pre-processors did not reduce predicates so far.
+    pub fn skip_reasons(&self) -> &SkipReasons {
+        &self.skip_reasons
+    }
+
     /// Resets the solver.
     pub fn reset_solver(&mut self) -> Res<()> {
-        smt::preproc_reset(&mut self.solver)
+        smt::preproc_reset(&mut self.solver, &self.instance)
     }
 
     /// Accessor for the solver.
@@ -336,6 +357,8 @@ impl<'a> PreInstance<'a> {
             conf.check_timeout()?
         }
 
+        info += self.normalize_clauses()?;
+
         info += self.force_trivial()?;
 
         if self.reset_solver {
@@ -374,6 +397,20 @@ impl<'a> PreInstance<'a> {
     /// - can change **all** clause indices because of potential swap removes
     /// - does not run `force_trivial`
     fn simplify_clauses(&mut self) -> Res<RedInfo> {
+        self.simplify_clauses_and_split(false)
+    }
+
+    /// Simplifies some clauses and splits their top-level lhs disjunctions, if clause splitting
+    /// is active.
+    ///
+    /// Forcing a predicate to a definition mentioning a disjunction reintroduces disjunctions in
+    /// the lhs of the clauses it appears in. Same as `simplify_clauses` otherwise.
+    fn simplify_and_normalize_clauses(&mut self) -> Res<RedInfo> {
+        self.simplify_clauses_and_split(conf.preproc.active && conf.preproc.clause_split)
+    }
+
+    /// Simplifies some clauses, splits their lhs disjunctions if `split`.
+    fn simplify_clauses_and_split(&mut self, split: bool) -> Res<RedInfo> {
         let mut info = RedInfo::new();
         // We're **popping**, so sort lowest to highest to avoid problems with swap
         // removes.
@@ -395,6 +432,9 @@ impl<'a> PreInstance<'a> {
                 Some(clause)
             };
             info += self.simplify_clause(clause)?;
+            if split && clause < self.instance.clauses.next_index() {
+                info += self.split_clause(clause, MAX_SPLIT)?
+            }
             if !info.non_zero() {
                 if let Some((pred, pos)) = self.force_trivial_from_clause(clause) {
                     let prev = force.insert(pred, pos);
@@ -421,12 +461,51 @@ impl<'a> PreInstance<'a> {
         Ok(info)
     }
 
+    /// Re-simplifies the LHS terms of the clauses `pred` was just forced out of.
+    ///
+    /// Works on `self.clauses_to_simplify`, which the `force_*` functions populate with the
+    /// clauses impacted. Runs propagation on the clauses whose terms changed: equalities are
+    /// substituted away, conjuncts implied by other conjuncts are dropped, and a `false` conjunct
+    /// collapses the LHS. The number of LHS terms that disappear is tracked in
+    /// `terms_simplified`.
+    ///
+    /// Does not remove clauses, so that the indices in `self.clauses_to_simplify` stay valid. The
+    /// terms of the clauses are still marked as changed, so the subsequent call to
+    /// `simplify_clauses` runs the triviality check and removes the clauses that became
+    /// trivially valid.
+    fn simplify_clauses_of(&mut self, pred: PrdIdx) -> Res<RedInfo> {
+        let mut info = RedInfo::new();
+        log! { @4
+            "re-simplifying terms of {} clause(s) after forcing {}",
+            self.clauses_to_simplify.len(), self.instance[pred]
+        }
+
+        for clause in &self.clauses_to_simplify {
+            let clause = *clause;
+            if !self.instance[clause].terms_changed() {
+                continue;
+            }
+            let old_terms = self.instance[clause].lhs_terms().clone();
+            self.instance.op_dirty.insert(clause);
+            self.simplifier
+                .clause_propagate(&mut self.instance.clauses[clause], &self.instance.preds)?;
+            let nu_terms = self.instance[clause].lhs_terms();
+            info.terms_simplified += old_terms
+                .iter()
+                .filter(|term| !nu_terms.contains(*term))
+                .count()
+        }
+
+        Ok(info)
+    }
+
     /// Simplifies the terms of a clause.
     ///
     /// Returns true if the clause should be removed.
     fn simplify_clause_term(&mut self, clause: ClsIdx) -> Res<bool> {
         if self.instance[clause].terms_changed() {
             log! { @3 "propagation..." }
+            self.instance.op_dirty.insert(clause);
             self.simplifier
                 .clause_propagate(&mut self.instance.clauses[clause], &self.instance.preds)?;
             log! { @3 "pruning..." }
@@ -889,6 +968,7 @@ impl<'a> PreInstance<'a> {
             }
         }
 
+        info += self.simplify_clauses_of(pred)?;
         info += self.simplify_clauses()?;
 
         self.check("after force true")?;
@@ -899,6 +979,28 @@ impl<'a> PreInstance<'a> {
     /// Forces some predicates to true.
     ///
     /// Simplifies all clauses impacted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hoice::{ common::*, parse, preproc::PreInstance };
+    /// let mut instance = parse::instance("
+    ///   (declare-fun p_1 ( Int ) Bool)
+    ///   (declare-fun p_2 ( Int ) Bool)
+    ///   (assert
+    ///     (forall ( (n Int) (m Int) )
+    ///       (=> (and (p_1 n) (= m (+ n 1)) (> m 0)) (p_2 m))
+    ///     )
+    ///   )
+    /// ");
+    /// let mut instance = PreInstance::new(& mut instance).unwrap();
+    /// let info = instance.force_true(0.into()).unwrap();
+    /// // The equality is propagated away in the clause `p_1` was removed from.
+    /// assert! { info.terms_simplified > 0 }
+    /// let clause = &instance.clauses()[ClsIdx::from(0)];
+    /// assert_eq! { clause.lhs_terms().len(), 1 }
+    /// assert! { clause.lhs_preds().is_empty() }
+    /// ```
     pub fn force_true(&mut self, pred: PrdIdx) -> Res<RedInfo> {
         self.check("before force true")?;
 
@@ -924,6 +1026,7 @@ impl<'a> PreInstance<'a> {
             debug_assert! { self.instance.clauses[* clause].preds_changed() }
         }
 
+        info += self.simplify_clauses_of(pred)?;
         info += self.simplify_clauses()?;
 
         self.check("after force true")?;
@@ -952,6 +1055,9 @@ impl<'a> PreInstance<'a> {
     /// - forces `pred` to be `exists qvars, pred_apps /\ terms`
     /// - simplifies all clauses impacted
     ///
+    /// Returns `None` and leaves the instance untouched if one of the resulting clauses would
+    /// have more variables than `--max_clause_vars`.
+    ///
     /// # Used by
     ///
     /// - `SimpleOneRhs`
@@ -961,7 +1067,7 @@ impl<'a> PreInstance<'a> {
         pred: PrdIdx,
         qvars: Quantfed,
         tterm_set: TTermSet,
-    ) -> Res<RedInfo> {
+    ) -> Res<Option<RedInfo>> {
         self.check("before `force_pred_left`")?;
 
         // let mut tterm_set = TTermSet::new() ;
@@ -971,7 +1077,11 @@ impl<'a> PreInstance<'a> {
         // }
 
         if tterm_set.is_empty() {
-            return self.force_true(pred);
+            return self.force_true(pred).map(Some);
+        }
+
+        if self.exceeds_var_budget(pred, true, qvars.len()) {
+            return Ok(None);
         }
 
         let mut info = RedInfo::new();
@@ -1079,11 +1189,56 @@ impl<'a> PreInstance<'a> {
         // Actually force the predicate.
         self.force_pred(pred, TTerms::conj(Quant::exists(qvars), tterm_set))?;
 
-        info += self.simplify_clauses()?;
+        info += self.simplify_clauses_of(pred)?;
+        info += self.simplify_and_normalize_clauses()?;
 
         self.check("after `force_pred_left`")?;
 
-        Ok(info)
+        Ok(Some(info))
+    }
+
+    /// True if unfolding `pred` would create a clause with more variables than
+    /// `--max_clause_vars`.
+    ///
+    /// If `lhs`, `pred` is unfolded in the clauses it appears in the lhs of, where each
+    /// application introduces `qvars` fresh variables. Otherwise it is unfolded in the clauses
+    /// it is the rhs of, each of which receives `qvars` fresh variables once.
+    pub fn exceeds_var_budget(&self, pred: PrdIdx, lhs: bool, qvars: usize) -> bool {
+        let max = if let Some(max) = conf.preproc.max_clause_vars {
+            max
+        } else {
+            return false;
+        };
+
+        let clauses = if lhs {
+            self.instance.lhs_clauses_of(pred)
+        } else {
+            self.instance.rhs_clauses_of(pred)
+        };
+
+        for clause in clauses {
+            let clause = &self.instance[*clause];
+            let fresh = if lhs {
+                clause
+                    .lhs_preds()
+                    .get(&pred)
+                    .map(|argss| argss.len())
+                    .unwrap_or(0)
+                    * qvars
+            } else {
+                qvars
+            };
+            let count = clause.vars().len() + fresh;
+            if count > max {
+                log! { @2 |
+                    "not unfolding {}: would create a clause with {} variables (max is {})",
+                    conf.emph(&self.instance[pred].name), count, max
+                }
+                return true;
+            }
+        }
+
+        false
     }
 
     /// Extends the lhs occurences of a predicate with some a term.
@@ -1186,6 +1341,97 @@ impl<'a> PreInstance<'a> {
         Ok(info)
     }
 
+    /// Adds a lemma to the lhs of a clause.
+    ///
+    /// A lemma is a term known to be valid, for instance an invariant discovered during a
+    /// previous run. Adding it to the lhs of a clause does not change the semantics of the
+    /// instance, but it can make the clause trivial or enable further simplifications.
+    ///
+    /// Fails if the lemma is not a boolean term, or if it mentions a variable that is not an
+    /// active variable of the clause, or with the wrong type.
+    ///
+    /// Simplifies before returning.
+    ///
+    /// # Consequences
+    ///
+    /// - simplifies the clause, which can remove it and thus change clause indices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hoice::{ common::*, parse, preproc::PreInstance };
+    /// let mut instance = parse::instance("
+    ///   (declare-fun p_1 ( Int ) Bool)
+    ///   (assert
+    ///     (forall ( (n Int) )
+    ///       (=> (and (p_1 n) (> n 0)) false)
+    ///     )
+    ///   )
+    /// ");
+    /// let mut instance = PreInstance::new(& mut instance).unwrap();
+    /// let clause: ClsIdx = 0.into();
+    ///
+    /// // Variable `v_1` does not exist in the clause.
+    /// let lemma = term::ge( term::int_var(1), term::int(0) );
+    /// assert! { instance.strengthen_clause(clause, lemma).is_err() }
+    /// // Variable `v_0` is an integer.
+    /// let lemma = term::bool_var(0);
+    /// assert! { instance.strengthen_clause(clause, lemma).is_err() }
+    /// assert_eq! { instance.clauses().len(), 1 }
+    ///
+    /// // `n <= 0` contradicts the lhs, the clause is trivial.
+    /// let lemma = term::le( term::int_var(0), term::int(0) );
+    /// let info = instance.strengthen_clause(clause, lemma).unwrap();
+    /// assert_eq! { info.clauses_rmed, 1 }
+    /// assert! { instance.clauses().is_empty() }
+    /// ```
+    pub fn strengthen_clause(&mut self, clause: ClsIdx, lemma: Term) -> Res<RedInfo> {
+        self.check("before `strengthen_clause`")?;
+
+        if !lemma.typ().is_bool() {
+            bail!(
+                "illegal lemma `{}` of type {} for clause #{}",
+                lemma,
+                lemma.typ(),
+                clause
+            )
+        }
+
+        let mut bad_var = None;
+        {
+            let vars = self[clause].vars();
+            lemma.iter(|term| {
+                if let Some(var) = term.var_idx() {
+                    let legal = var < vars.len() && vars[var].active && vars[var].typ == term.typ();
+                    if !legal && bad_var.is_none() {
+                        bad_var = Some((var, term.typ()))
+                    }
+                }
+            })
+        }
+        if let Some((var, typ)) = bad_var {
+            bail!(
+                "lemma `{}` mentions variable {}: {}, \
+                 which is not an active variable of clause #{}",
+                lemma,
+                var.default_str(),
+                typ,
+                clause
+            )
+        }
+
+        log! { @4 "adding lemma {} to clause #{}", lemma, clause }
+
+        debug_assert! { self.clauses_to_simplify.is_empty() }
+        self.instance.clause_add_lhs_term(clause, lemma);
+        self.clauses_to_simplify.push(clause);
+        let info = self.simplify_clauses()?;
+
+        self.check("after `strengthen_clause`")?;
+
+        Ok(info)
+    }
+
     /// Forces all lhs occurrences of a predicate to be replaced by a DNF.
     ///
     /// - only legal if `pred` does not appear in any rhs
@@ -1374,6 +1620,44 @@ impl<'a> PreInstance<'a> {
     /// - forces `pred` to be `forall qvars, pred_app \/ (not /\ pred_apps) \/
     ///   (not /\ terms)`
     ///
+    /// Returns `None` and leaves the instance untouched if one of the resulting clauses would
+    /// have more variables than `--max_clause_vars`.
+    ///
+    /// The top-level lhs disjunctions the definition introduces in the impacted clauses are split
+    /// if clause splitting is active.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, preproc::PreInstance };
+    /// let mut instance = hoice::parse::instance("
+    ///   (declare-fun P (Int) Bool)
+    ///   (declare-fun Q (Int) Bool)
+    ///   (assert (forall ((x Int)) (=> (= x 0) (Q x))))
+    ///   (assert (forall ((x Int)) (=> (Q x) (P x))))
+    ///   (assert (forall ((x Int)) (=> (and (P x) (or (> x 7) (< x 0))) false)))
+    /// ");
+    /// let p: PrdIdx = 0.into();
+    /// let mut negated = TTermSet::new();
+    /// negated.insert_term(
+    ///     term::or(vec![
+    ///         term::gt(term::int_var(0), term::int(7)),
+    ///         term::lt(term::int_var(0), term::int(0)),
+    ///     ])
+    /// );
+    /// {
+    ///     let mut instance = PreInstance::new(&mut instance).unwrap();
+    ///     instance.force_pred_right(p, Quantfed::new(), None, negated).unwrap().unwrap();
+    ///     instance.check("in test").unwrap();
+    ///     instance.destroy().unwrap();
+    /// }
+    /// // `(Q x) /\ (or (> x 7) (< x 0)) => false` is split in two.
+    /// assert_eq! { instance.clauses().len(), 3 }
+    /// for clause in instance.clauses() {
+    ///     assert! { clause.lhs_terms().iter().all(|term| term.disj_inspect().is_none()) }
+    /// }
+    /// ```
+    ///
     /// # Used by
     ///
     /// - `SimpleOneLhs`
@@ -1383,9 +1667,13 @@ impl<'a> PreInstance<'a> {
         qvars: Quantfed,
         pred_app: Option<(PrdIdx, VarTerms)>,
         negated: TTermSet,
-    ) -> Res<RedInfo> {
+    ) -> Res<Option<RedInfo>> {
         self.check("before `force_pred_right`")?;
 
+        if self.exceeds_var_budget(pred, false, qvars.len()) {
+            return Ok(None);
+        }
+
         let mut info = RedInfo::new();
 
         let quant = Quant::forall(qvars);
@@ -1469,7 +1757,7 @@ impl<'a> PreInstance<'a> {
             )
         }
 
-        info += self.simplify_clauses()?;
+        info += self.simplify_and_normalize_clauses()?;
 
         let clause_to_rm = self
             .rm_only_lhs_clause_of(pred)
@@ -1486,7 +1774,7 @@ impl<'a> PreInstance<'a> {
 
         self.check("after `force_pred_right`")?;
 
-        Ok(info)
+        Ok(Some(info))
     }
 
     /// Unrolls some predicates.
@@ -1653,6 +1941,70 @@ impl<'a> PreInstance<'a> {
         Ok(info)
     }
 
+    /// Splits the top-level disjunctions in the lhs of the clauses.
+    ///
+    /// A clause `(or t_1 t_2) /\ lhs => rhs` becomes `t_1 /\ lhs => rhs` and `t_2 /\ lhs => rhs`,
+    /// see [`split_clause`].
+    ///
+    /// [`split_clause`]: #method.split_clause (split_clause function)
+    pub fn split_clauses(&mut self, max: usize) -> Res<RedInfo> {
+        let mut info = RedInfo::new();
+
+        // Only clauses mentioning a disjunction can be split. Go through them in reverse so that
+        // swap removes are safe.
+        let mut clauses: Vec<_> = self
+            .instance
+            .clauses_mentioning(Op::Or)
+            .into_iter()
+            .collect();
+        clauses.sort_unstable();
+        while let Some(clause) = clauses.pop() {
+            info += self.split_clause(clause, max)?;
+            conf.check_timeout()?
+        }
+
+        self.check("after clause split")?;
+
+        Ok(info)
+    }
+
+    /// Splits the top-level disjunction in the lhs of a clause.
+    ///
+    /// Splits on all the disjuncts using `split_on`, which simplifies the new clauses. Clauses
+    /// with a disjunction of more than `max` disjuncts are left untouched, and so are clauses with
+    /// several disjunctions since `split_on` only splits one of them at a time.
+    fn split_clause(&mut self, idx: ClsIdx, max: usize) -> Res<RedInfo> {
+        let mut has_disj = false;
+        for term in self.instance[idx].lhs_terms() {
+            if let Some(disj) = term.disj_inspect() {
+                if disj.len() > max {
+                    log! { @4 |
+                        "not splitting clause #{}, disjunction of {} terms", idx, disj.len()
+                    }
+                    return Ok(RedInfo::new());
+                }
+                has_disj = true
+            }
+        }
+        if has_disj {
+            self.split_on(idx, |_| true)
+        } else {
+            Ok(RedInfo::new())
+        }
+    }
+
+    /// Splits the top-level lhs disjunctions of all clauses, if clause splitting is active.
+    ///
+    /// Runs in `simplify_all`, forcing a predicate only splits the clauses it impacts, see
+    /// `simplify_and_normalize_clauses`.
+    fn normalize_clauses(&mut self) -> Res<RedInfo> {
+        if conf.preproc.active && conf.preproc.clause_split {
+            self.split_clauses(MAX_SPLIT)
+        } else {
+            Ok(RedInfo::new())
+        }
+    }
+
     /// Removes some arguments for a predicate.
     ///
     /// Returns `true` if something happened.
@@ -1674,7 +2026,7 @@ impl<'a> PreInstance<'a> {
         }
 
         log! { @4 |
-            "working on {} ({}/{})", self[pred], to_keep.len(), self[pred].sig.len()
+            "working on {} ({}/{})", self[pred], to_keep.len(), self[pred].arity()
         }
 
         let mut rmed = 0;
@@ -1759,9 +2111,9 @@ impl<'a> PreInstance<'a> {
 
         // Remove args from applications in clauses.
         for (pred, to_keep) in to_keep {
-            debug_assert!(to_keep.len() <= self[pred].sig.len());
+            debug_assert!(to_keep.len() <= self[pred].arity());
             log! { @4 | "- {}", self[pred] }
-            if to_keep.len() == self[pred].sig.len() {
+            if to_keep.len() == self[pred].arity() {
                 log! { @4 | "skipping" }
                 continue;
             }
@@ -1800,6 +2152,81 @@ impl<'a> PreInstance<'a> {
         Ok(info)
     }
 
+    /// Drops the facts of the instance from the LHS of the clauses.
+    ///
+    /// A *fact* is a clause with an empty LHS and a RHS of the form `(p c_1 ... c_n)` where all
+    /// the `c_i`s are constants. Since `(p c_1 ... c_n)` is known to hold, it can be dropped from
+    /// the LHS of all the clauses it appears in. Dropping applications can create new facts, so
+    /// this function runs until it reaches a fixed point.
+    ///
+    /// Runs simplifications on the clauses modified.
+    pub fn saturate_with_unit_clauses(&mut self) -> Res<RedInfo> {
+        debug_assert! { self.clauses_to_simplify.is_empty() }
+        let mut info = RedInfo::new();
+        // Facts already propagated.
+        let mut known = PredApps::new();
+
+        loop {
+            let mut facts = vec![];
+            for clause in self.instance.clauses() {
+                if !clause.lhs_preds().is_empty() || !clause.lhs_terms().is_empty() {
+                    continue;
+                }
+                if let Some((pred, args)) = clause.rhs() {
+                    if args.iter().all(|arg| arg.val().is_some())
+                        && known.insert_pred_app(pred, args.clone())
+                    {
+                        facts.push((pred, args.clone()))
+                    }
+                }
+            }
+
+            if facts.is_empty() {
+                break;
+            }
+
+            for (pred, args) in facts {
+                log! { @4 "propagating fact ({} {})", self[pred], args }
+                let clauses: Vec<ClsIdx> = self.instance.pred_to_clauses[pred]
+                    .0
+                    .iter()
+                    .cloned()
+                    .collect();
+
+                for clause in clauses {
+                    let mentions_fact = self.instance[clause]
+                        .lhs_preds()
+                        .get(&pred)
+                        .map(|argss| argss.contains(&args))
+                        .unwrap_or(false);
+                    if !mentions_fact {
+                        continue;
+                    }
+
+                    let mut argss = match self.instance[clause].drop_lhs_pred(pred) {
+                        Some(argss) => argss,
+                        None => bail!("inconsistent predicate application in clause LHS"),
+                    };
+                    argss.remove(&args);
+                    if argss.is_empty() {
+                        let was_there = self.instance.pred_to_clauses[pred].0.remove(&clause);
+                        debug_assert! { was_there }
+                    } else {
+                        for args in argss {
+                            self.instance[clause].insert_pred_app(pred, args);
+                        }
+                    }
+
+                    info.apps_rmed += 1;
+                    self.clauses_to_simplify.push(clause)
+                }
+            }
+        }
+
+        info += self.simplify_clauses()?;
+        Ok(info)
+    }
+
     /// Checks whether some partial definitions for the predicate constitute a model.
     pub fn check_pred_partial_defs<'b, I>(&mut self, defs: I) -> Res<bool>
     where
@@ -1877,7 +2304,7 @@ impl<'a> PreInstance<'a> {
                 log! { @4 "got unknown while checking partial definitions" }
             }
 
-            crate::smt::preproc_reset(solver)?;
+            crate::smt::preproc_reset(solver, instance)?;
 
             if sat != Some(false) {
                 return Ok(false);
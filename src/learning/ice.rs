@@ -57,7 +57,7 @@ use crate::{
         var_to::vals::VarValsMap,
         *,
     },
-    data::LrnData,
+    data::{Data, LrnData},
 };
 
 pub mod data;
@@ -84,7 +84,7 @@ impl Launcher {
     }
 }
 
-impl Learner for Launcher {
+impl msg::Learner for Launcher {
     fn run(&self, core: MsgCore, instance: Arc<Instance>, data: LrnData, mine: bool) {
         match Self::launch(&core, instance, data, mine) {
             Ok(()) => core.exit(),
@@ -96,6 +96,163 @@ impl Learner for Launcher {
     }
 }
 
+/// ICE learner driven directly by a custom teacher.
+///
+/// Wraps an [`IceLearner`] and its learning data so that it can be used without the teacher, the
+/// assistant or any message passing. The caller is the teacher: it feeds classified samples and
+/// implication constraints through [`add_data`], and asks for candidates with
+/// [`next_candidates`]. The learner does not check candidates against the clauses, the caller is
+/// responsible for this.
+///
+/// The learner does not spawn any process: the caller provides the solver it uses to check the
+/// legality of the decision trees it builds, see [`new`].
+///
+/// The instance is expected to be [finalized].
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::{ common::*, learning::ice::Learner };
+/// let mut instance = hoice::parse::instance("
+///   (declare-fun ice_learner_pred (Int) Bool)
+///   (assert (forall ((x Int)) (=> (= x 0) (ice_learner_pred x))))
+/// ");
+/// instance.finalize().expect("while finalizing instance");
+/// let pred: PrdIdx = 0.into();
+///
+/// let solver = Solver::new(conf.solver.conf(), ()).expect("while spawning solver");
+/// let mut learner = Learner::new(Arc::new(instance), solver).expect("while creating learner");
+/// // Sample `(ice_learner_pred 0)` is positive.
+/// learner.add_data(
+///     0.into(), vec![], Some((pred, vec![ val::int(0) ].into()))
+/// ).expect("while adding positive sample");
+/// let candidates = learner.next_candidates().expect("during learning").expect("unknown");
+/// let candidate = candidates[pred].as_ref().expect("no candidate for predicate");
+/// let model: VarMap<Val> = vec![ val::int(0) ].into();
+/// assert_eq! { candidate.bool_eval(&model).unwrap(), Some(true) }
+/// ```
+///
+/// [`IceLearner`]: struct.IceLearner.html (IceLearner struct)
+/// [`add_data`]: #method.add_data (add_data function)
+/// [`next_candidates`]: #method.next_candidates (next_candidates function)
+/// [`new`]: #method.new (new function)
+/// [finalized]: ../../instance/struct.Instance.html#method.finalize
+/// (finalize function of Instance)
+pub struct Learner {
+    /// Underlying ICE learner.
+    ice: IceLearner<'static>,
+    /// Learning data.
+    data: Data,
+    /// Receives the messages of the ICE learner.
+    from_ice: Receiver<Msg>,
+    /// Channel to the ICE learner, kept alive so that the learner does not think it should exit.
+    _to_ice: Sender<FromTeacher>,
+}
+impl Learner {
+    /// Constructor.
+    ///
+    /// The solver should be fresh, the learner declares the datatypes and functions of the
+    /// instance itself.
+    pub fn new(instance: Arc<Instance>, mut solver: Solver<()>) -> Res<Self> {
+        smt::init(&mut solver, &instance).chain_err(|| "while initializing the solver")?;
+        let (to_teacher, from_ice) = Msg::channel();
+        let (_to_ice, from_teacher) = FromTeacher::channel();
+        let core = MsgCore::new_learner(0.into(), to_teacher, from_teacher);
+        let data = Data::new(instance.clone());
+        let ice = IceLearner::with_core(
+            CoreRef::Owned(Box::new(core)),
+            instance,
+            data.to_lrn_data(),
+            true,
+            solver,
+        )
+        .chain_err(|| "while creating ice learner")?;
+        Ok(Learner {
+            ice,
+            data,
+            from_ice,
+            _to_ice,
+        })
+    }
+
+    /// Adds some learning data.
+    ///
+    /// Same semantics as [`Data::add_data`]: no `lhs` yields a positive sample, a single `lhs`
+    /// sample and no `rhs` yields a negative sample, anything else is an implication constraint.
+    ///
+    /// Returns `true` if something new was added.
+    ///
+    /// [`Data::add_data`]: ../../data/struct.Data.html#method.add_data
+    /// (add_data function for Data)
+    pub fn add_data(
+        &mut self,
+        clause: ClsIdx,
+        lhs: Vec<(PrdIdx, RVarVals)>,
+        rhs: Option<(PrdIdx, RVarVals)>,
+    ) -> Res<bool> {
+        self.data.add_data(clause, lhs, rhs)
+    }
+
+    /// Learns new candidates for all predicates from the data added so far.
+    ///
+    /// Returns `None` if the learner could not reach a conclusion. Fails with an [unsat] error if
+    /// the data is contradictory.
+    ///
+    /// [unsat]: ../../errors/enum.ErrorKind.html#variant.Unsat (ErrorKind's Unsat variant)
+    pub fn next_candidates(&mut self) -> Res<Option<Candidates>> {
+        self.data.propagate()?;
+        let res = self.ice.step(self.data.to_lrn_data());
+        self.drain_msgs();
+        match res {
+            Ok(Some(candidates)) => {
+                self.ice.candidates_done();
+                Ok(Some(candidates))
+            }
+            Ok(None) => Ok(None),
+            Err(ref e) if e.is_unknown() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// (De)activates candidate sharing between predicates with the same signature.
+    ///
+    /// Overrides `--share_cands`.
+    pub fn share_candidates(&mut self, active: bool) {
+        self.ice.share_cands = active
+    }
+
+    /// Logs and discards the messages of the ICE learner.
+    fn drain_msgs(&mut self) {
+        while let Ok(msg) = self.from_ice.try_recv() {
+            if let MsgKind::Msg(s) = msg.msg {
+                log! { @debug "{}", s }
+            }
+        }
+    }
+}
+
+/// Core of an ICE learner.
+///
+/// Borrowed when the learner is launched by the teacher, owned when it is driven directly through
+/// a [`Learner`].
+///
+/// [`Learner`]: struct.Learner.html (Learner struct)
+enum CoreRef<'core> {
+    /// Borrowed core.
+    Borrowed(&'core MsgCore),
+    /// Owned core.
+    Owned(Box<MsgCore>),
+}
+impl<'core> ::std::ops::Deref for CoreRef<'core> {
+    type Target = MsgCore;
+    fn deref(&self) -> &MsgCore {
+        match *self {
+            CoreRef::Borrowed(core) => core,
+            CoreRef::Owned(ref core) => core,
+        }
+    }
+}
+
 /// A branch of a decision tree.
 ///
 /// Boolean is `false` if the term should be negated.
@@ -117,12 +274,17 @@ pub struct IceLearner<'core> {
     pub qualifiers: NuQuals,
     /// Synthesizer.
     synth_sys: PrdMap<SynthSys>,
+    /// Predicates qualifier synthesis is deactivated for, see [`Instance::no_synth_preds`].
+    ///
+    /// [`Instance::no_synth_preds`]: ../../instance/struct.Instance.html#method.no_synth_preds
+    /// (no_synth_preds function of Instance)
+    no_synth: PrdSet,
     /// Current data.
     data: LrnData,
     /// Solver used to check if the constraints are respected.
     solver: Solver<()>,
     /// Learner core.
-    core: &'core MsgCore,
+    core: CoreRef<'core>,
     /// Branches of the tree, used when constructing a decision tree.
     finished: Vec<Branch>,
     /// Branches to construct later, used when constructing a decision tree.
@@ -154,6 +316,16 @@ pub struct IceLearner<'core> {
     gain_pivot_synth: Option<f64>,
     /// Learn step counter.
     count: usize,
+    /// Shares candidates between predicates with the same signature, see `--share_cands`.
+    share_cands: bool,
+    /// Pairs of predicates `(from, to)` such that the qualifiers of `from` were copied to `to`.
+    seeded: HashSet<(PrdIdx, PrdIdx)>,
+    /// Last candidate of each predicate, with the [revision] of the data it was learned from.
+    ///
+    /// Only reused for predicates with no unclassified samples.
+    ///
+    /// [revision]: ../../data/struct.Data.html#method.revision (revision function of Data)
+    cached: PrdMap<Option<(usize, Term)>>,
 }
 impl<'core> IceLearner<'core> {
     /// Ice learner constructor.
@@ -164,21 +336,46 @@ impl<'core> IceLearner<'core> {
         mine: bool, // synth_solver: Slver
     ) -> Res<Self> {
         let solver = conf.solver.spawn("ice_learner", (), &instance)?;
+        Self::with_core(CoreRef::Borrowed(core), instance, data, mine, solver)
+    }
 
+    /// Ice learner constructor, with a borrowed or owned core and an initialized solver.
+    fn with_core(
+        core: CoreRef<'core>,
+        instance: Arc<Instance>,
+        data: LrnData,
+        mine: bool,
+        solver: Solver<()>,
+    ) -> Res<Self> {
         profile! { |core._profiler| tick "mining" }
-        let qualifiers =
-            NuQuals::new(&instance, mine).chain_err(|| "while creating qualifier structure")?;
+        let qualifiers = if let Some(path) = conf.ice.qual_hints.as_ref() {
+            let hints = ::std::fs::read_to_string(path)
+                .chain_err(|| format!("while reading qualifier hints from `{}`", path))?;
+            NuQuals::new_with_user_hints(&instance, mine, hints.lines())
+        } else {
+            NuQuals::new(&instance, mine)
+        }
+        .chain_err(|| "while creating qualifier structure")?;
         profile! { |core._profiler| mark "mining" }
 
         let dec_mem = vec![HashSet::with_capacity(103); instance.preds().len()].into();
         let candidate = vec![None; instance.preds().len()].into();
         let predicates = Vec::with_capacity(instance.preds().len());
+        let cached = vec![None; instance.preds().len()].into();
 
+        let difference_logic = instance.is_difference_logic();
         let mut synth_sys = PrdMap::with_capacity(instance.preds().len());
         for (pred, _) in instance.preds().index_iter() {
-            synth_sys.push(SynthSys::new(&instance[pred].sig))
+            let mut synth = SynthSys::new(&instance[pred].sig);
+            synth.set_constants(instance.constants_of(pred));
+            if difference_logic {
+                synth.prefer_difference_logic()
+            }
+            synth_sys.push(synth)
         }
 
+        let no_synth = instance.no_synth_preds();
+
         let mut using_rec_funs = false;
 
         fun::iter(|_| {
@@ -192,14 +389,13 @@ impl<'core> IceLearner<'core> {
             (conf.ice.gain_pivot, conf.ice.gain_pivot_synth)
         };
 
-        use rand::SeedableRng;
-
         Ok(IceLearner {
             instance,
             qualifiers,
             data,
             solver, // synth_solver,
             synth_sys,
+            no_synth,
             core,
             finished: Vec::with_capacity(103),
             unfinished: Vec::with_capacity(103),
@@ -207,15 +403,18 @@ impl<'core> IceLearner<'core> {
             dec_mem,
             candidate,
             predicates,
-            sort_rng_1: { Rng::from_seed([42; 16]) },
-            sort_rng_2: { Rng::from_seed([79; 16]) },
-            simple_rng: { Rng::from_seed([107; 16]) },
-            pre_skip_rng: { Rng::from_seed([245; 16]) },
+            sort_rng_1: new_rng(conf.seed, 42),
+            sort_rng_2: new_rng(conf.seed, 79),
+            simple_rng: new_rng(conf.seed, 107),
+            pre_skip_rng: new_rng(conf.seed, 245),
             luby: if mine { None } else { Some(LubyCount::new()) },
             known_quals: TermSet::new(),
             gain_pivot,
             gain_pivot_synth,
             count: 0,
+            share_cands: conf.ice.share_cands,
+            seeded: HashSet::new(),
+            cached,
         })
     }
 
@@ -237,25 +436,10 @@ impl<'core> IceLearner<'core> {
               |self.core._profiler| wrap { self.recv() } "waiting"
             ) {
                 Ok(data) => {
-                    self.count += 1;
-                    if self.count % 50 == 0 {
-                        smt::reset(&mut self.solver, &self.instance)?
-                    }
-                    profile! { self "learn steps" => add 1 }
-                    if let Some(candidates) = profile!(
-                      |self.core._profiler| wrap {
-                        self.solver.push(1) ? ;
-                        let res = self.learn(data) ;
-                        self.solver.pop(1) ? ;
-                        res
-                      } "learning"
-                    )? {
+                    if let Some(candidates) = self.step(data)? {
                         self.send_cands(candidates)
                             .chain_err(|| "while sending candidates")?;
-                        if self.restart() {
-                            profile! { self "restarts" => add 1 }
-                            self.qualifiers.wipe()
-                        }
+                        self.candidates_done()
                     } else {
                         return Ok(());
                     }
@@ -265,6 +449,40 @@ impl<'core> IceLearner<'core> {
         }
     }
 
+    /// Runs a single learning step on some data.
+    ///
+    /// Resets the solver every 50 steps, and runs [`learn`] inside a solver frame.
+    ///
+    /// [`learn`]: #method.learn (learn function)
+    fn step(&mut self, data: LrnData) -> Res<Option<Candidates>> {
+        self.count += 1;
+        if self.count % 50 == 0 {
+            smt::reset(&mut self.solver, &self.instance)?
+        }
+        profile! { self "learn steps" => add 1 }
+        profile!(
+          |self.core._profiler| wrap {
+            self.solver.push(1) ? ;
+            let res = self.learn(data) ;
+            self.solver.pop(1) ? ;
+            res
+          } "learning"
+        )
+    }
+
+    /// Called once the candidates of a learning step have been sent.
+    ///
+    /// Clears declaration memory and wipes the qualifiers on restarts.
+    fn candidates_done(&mut self) {
+        for set in self.dec_mem.iter_mut() {
+            set.clear()
+        }
+        if self.restart() {
+            profile! { self "restarts" => add 1 }
+            self.qualifiers.wipe()
+        }
+    }
+
     /// Finalizes the learning process and exits.
     #[cfg(not(feature = "bench"))]
     pub fn finalize(mut self) -> Res<()> {
@@ -281,22 +499,12 @@ impl<'core> IceLearner<'core> {
     }
 
     /// Sends some candidates.
-    ///
-    /// Also resets the solver and clears declaration memory.
     fn send_cands(&mut self, candidates: Candidates) -> Res<()> {
         profile!(
             | self._profiler | wrap {
                 self.send_candidates(candidates)
             } "sending"
-        )?;
-        // // Reset and clear declaration memory.
-        // smt::reset(& mut self.solver).chain_err(
-        //   || "during solver reset"
-        // ) ? ;
-        for set in self.dec_mem.iter_mut() {
-            set.clear()
-        }
-        Ok(())
+        )
     }
 
     /// Looks for a classifier.
@@ -308,6 +516,23 @@ impl<'core> IceLearner<'core> {
         ::std::mem::swap(&mut data, &mut self.data);
         self.core.merge_set_prof("data", data.destroy());
 
+        self.qualifiers.new_step();
+        if let Some(budget) = conf.teacher.mem_budget {
+            let footprint = self.data.footprint() + self.qualifiers.real_qual_count();
+            if footprint > budget {
+                let evicted = self
+                    .qualifiers
+                    .evict_older_than(conf.teacher.mem_budget_age);
+                profile! { self "quals evicted" => add evicted }
+                if evicted > 0 {
+                    msg! { force self =>
+                        "warning: over memory budget ({} > {}), dropped {} qualifier(s)",
+                        footprint, budget, evicted
+                    }
+                }
+            }
+        }
+
         if self.count % conf.ice.gain_pivot_mod == 0 {
             self.gain_pivot += conf.ice.gain_pivot_inc;
             if self.gain_pivot > 0.999 {
@@ -377,6 +602,8 @@ impl<'core> IceLearner<'core> {
 
         self.check_exit()?;
 
+        let accepted = self.accepted_candidates();
+
         while let Some((_unc, _cla, pred)) = self.predicates.pop() {
             msg! {
                 debug self =>
@@ -384,6 +611,19 @@ impl<'core> IceLearner<'core> {
                 self.instance[pred], _unc, _cla
             }
 
+            // The projected data did not change since the last candidate was learned, reuse it.
+            // Only done when there are no unclassified samples: learning classifies them and
+            // propagates the result to the other predicates, reusing the candidate would not.
+            let revision = self.data.revision(pred);
+            if let Some((rev, ref term)) = self.cached[pred] {
+                if rev == revision && self.data.map()[pred].is_empty() {
+                    msg! { debug self => "reusing last candidate for {}", self.instance[pred] }
+                    profile! { self "candidates reused" => add 1 }
+                    self.candidate[pred] = Some(term.clone());
+                    continue;
+                }
+            }
+
             let data = profile!(
                 |self.core._profiler| wrap {
                     self.data.data_of(pred)
@@ -391,11 +631,15 @@ impl<'core> IceLearner<'core> {
             );
             self.check_exit()?;
 
-            if let Some(term) = self.pred_learn(pred, data, simple)? {
-                self.candidate[pred] = Some(term)
+            let term = if let Some(term) = self.shared_candidate(pred, &data, &accepted)? {
+                term
+            } else if let Some(term) = self.pred_learn(pred, data, simple)? {
+                term
             } else {
                 return Ok(None);
-            }
+            };
+            self.cached[pred] = Some((revision, term.clone()));
+            self.candidate[pred] = Some(term);
             self.check_exit()?;
         }
 
@@ -406,6 +650,74 @@ impl<'core> IceLearner<'core> {
         Ok(Some(candidates))
     }
 
+    /// Candidates of the previous learning step that were not refuted.
+    ///
+    /// A candidate is considered accepted by all the clauses mentioning its predicate if the
+    /// [revision] of the data of the predicate did not change since then. Always empty when not
+    /// sharing candidates.
+    ///
+    /// [revision]: ../../data/struct.Data.html#method.revision (revision function of Data)
+    fn accepted_candidates(&self) -> Vec<(PrdIdx, Term)> {
+        let mut res = vec![];
+        if !self.share_cands {
+            return res;
+        }
+        for (pred, cached) in self.cached.index_iter() {
+            if let Some((revision, ref term)) = *cached {
+                if self.data.revision(pred) == revision {
+                    res.push((pred, term.clone()))
+                }
+            }
+        }
+        res
+    }
+
+    /// Looks for an accepted candidate of another predicate with the same signature that respects
+    /// the data of a predicate.
+    ///
+    /// Seeds the qualifiers of `pred` with the ones of the predicates with an accepted candidate
+    /// and the same signature. The candidate is only reused if `pred` has no unclassified data,
+    /// and if it is true (false) on all positive (negative) samples.
+    fn shared_candidate(
+        &mut self,
+        pred: PrdIdx,
+        data: &CData,
+        accepted: &[(PrdIdx, Term)],
+    ) -> Res<Option<Term>> {
+        'accepted: for &(other, ref term) in accepted {
+            if other == pred || self.instance[other].sig != self.instance[pred].sig {
+                continue 'accepted;
+            }
+
+            if self.seeded.insert((other, pred)) {
+                let _count = self.qualifiers.copy_quals(other, pred)?;
+                profile! { self "quals shared" => add _count }
+            }
+
+            if !data.unc().is_empty() {
+                continue 'accepted;
+            }
+            for sample in data.pos() {
+                if term.bool_eval(sample.get())? != Some(true) {
+                    continue 'accepted;
+                }
+            }
+            for sample in data.neg() {
+                if term.bool_eval(sample.get())? != Some(false) {
+                    continue 'accepted;
+                }
+            }
+
+            msg! {
+                @verb self =>
+                "reusing candidate of {} for {}", self.instance[other], self.instance[pred]
+            }
+            profile! { self "candidates shared" => add 1 }
+            return Ok(Some(term.clone()));
+        }
+        Ok(None)
+    }
+
     /// Prepares the predicates for sorting.
     fn predicate_stats(&mut self, skip_prelim: bool) -> Res<()> {
         debug_assert! { self.predicates.is_empty() }
@@ -421,8 +733,8 @@ impl<'core> IceLearner<'core> {
                     continue;
                 }
 
-                let pos_len = self.data.pos[pred].len();
-                let neg_len = self.data.neg[pred].len();
+                let pos_len = self.data.pos_count_for(pred);
+                let neg_len = self.data.neg_count_for(pred);
                 let unc_len = self.data.map()[pred].len();
 
                 if !skip_prelim {
@@ -1196,7 +1508,12 @@ impl<'core> IceLearner<'core> {
 
         self.qualifiers.maximize(pred, bias, |qual| {
             if conf.ice.qual_step {
-                let _ = core.msg(format!("evaluating {} (simple gain)", qual));
+                let _ = core.msg(format!(
+                    "evaluating {} (simple gain, size {}, depth {})",
+                    qual,
+                    qual.size(),
+                    qual.depth()
+                ));
             }
             let res = data.simple_gain(qual, false)?;
             if conf.ice.qual_step {
@@ -1228,7 +1545,12 @@ impl<'core> IceLearner<'core> {
 
         qualifiers.maximize(pred, bias, |qual| {
             if conf.ice.qual_step {
-                let _ = core.msg(format!("evaluating {} (gain)", qual));
+                let _ = core.msg(format!(
+                    "evaluating {} (gain, size {}, depth {})",
+                    qual,
+                    qual.size(),
+                    qual.depth()
+                ));
             }
             let res = data.gain(pred, all_data, qual, &core._profiler, false)?;
             if conf.ice.qual_step {
@@ -1308,6 +1630,8 @@ impl<'core> IceLearner<'core> {
     }
 
     /// Qualifier synthesis.
+    ///
+    /// Does nothing for predicates synthesis is deactivated for.
     pub fn synthesize(
         &mut self,
         pred: PrdIdx,
@@ -1315,6 +1639,15 @@ impl<'core> IceLearner<'core> {
         best: &mut Option<(Term, f64)>,
         simple: bool,
     ) -> Res<Option<()>> {
+        if self.no_synth.contains(&pred) {
+            msg! { self => "synthesis is deactivated for {}", self.instance[pred] }
+            return Ok(Some(()));
+        }
+        if !self.synth_sys[pred].has_type(&self.instance[pred].sig) {
+            msg! { self => "no synthesizer for the signature of {}", self.instance[pred] }
+            return Ok(Some(()));
+        }
+
         scoped! {
           let self_data = & self.data ;
           let quals = & mut self.qualifiers ;
@@ -1341,7 +1674,10 @@ impl<'core> IceLearner<'core> {
             } else {
               if conf.ice.qual_step || conf.ice.qual_synth_step {
                 let _ = self_core.msg(
-                  format!("synth evaluating {}", term)
+                  format!(
+                    "synth evaluating {} (size {}, depth {})",
+                    term, term.size(), term.depth()
+                  )
                 ) ;
               }
               let gain = if simple {
@@ -266,6 +266,10 @@ fn qual_of_term(term: &Term, map: &VarHMap<Term>) -> Option<Term> {
 
 /// Extracts some qualifiers from a clause.
 ///
+/// Only looks at the left-hand side terms: a term right-hand side is negated and moved to the
+/// left-hand side during parsing, so clause `P(x) /\ x > 10 => x > y` is really
+/// `P(x) /\ x > 10 /\ x <= y => false`.
+///
 /// # TO DO
 ///
 /// - write an explanation of what actually happens
@@ -451,6 +455,14 @@ fn mine_instance(instance: &Instance, quals: &mut NuQuals) -> Res<()> {
 pub struct NuQuals {
     instance: Arc<Instance>,
     quals: PrdMap<VarHMap<TermSet>>,
+    /// Last step each qualifier was inserted or selected by [`maximize`][max].
+    ///
+    /// [max]: #method.maximize (maximize function)
+    last_used: PrdMap<TermMap<usize>>,
+    /// Current step, see [`new_step`][new step].
+    ///
+    /// [new step]: #method.new_step (new_step function)
+    step: usize,
     rng: Rng,
 }
 impl NuQuals {
@@ -569,17 +581,37 @@ impl NuQuals {
     }
 
     /// Constructor.
+    ///
+    /// If `mine`, mines the instance for qualifiers. Note that term right-hand sides of clauses
+    /// are negated and moved to the left-hand side during parsing, so the negation of a
+    /// right-hand side atom is mined like any other left-hand side term.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, learning::ice::quals::NuQuals };
+    /// let instance = hoice::parse::instance("
+    /// (declare-fun P (Int Int) Bool)
+    /// (assert (forall ((x Int) (y Int)) (=> (and (P y x) (> x 10)) (> x y))))
+    /// ");
+    /// let quals = NuQuals::new(&Arc::new(instance), true).unwrap();
+    /// let p: PrdIdx = 0.into();
+    /// // `x` is `v_1` and `y` is `v_0` in `P`.
+    /// let qual = term::not(term::gt(term::int_var(1), term::int_var(0)));
+    /// let qual = qual.rm_neg().unwrap_or(qual);
+    /// assert! { quals.quals_of_contains(p, &qual) }
+    /// ```
     pub fn new(instance: &Arc<Instance>, mine: bool) -> Res<Self> {
-        use rand::SeedableRng;
-
         let mut quals = PrdMap::with_capacity(instance.preds().len());
+        let mut last_used = PrdMap::with_capacity(instance.preds().len());
         for _ in 0..instance.preds().len() {
-            quals.push(VarHMap::new())
+            quals.push(VarHMap::new());
+            last_used.push(TermMap::new())
         }
         let mut quals = NuQuals {
             quals,
+            last_used,
+            step: 0,
             instance: instance.clone(),
-            rng: Rng::from_seed([42; 16]),
+            rng: new_rng(conf.seed, 42),
         };
 
         if mine {
@@ -625,6 +657,156 @@ impl NuQuals {
             mine_instance(instance, &mut quals).chain_err(|| "during qualifier mining")?
         }
 
+        // Qualifiers from previous checks.
+        if let Some(warm) = instance.warm() {
+            for pred in instance.pred_indices() {
+                if !warm.is_compatible(instance, pred) {
+                    continue;
+                }
+                if let Some(terms) = warm.quals_of(pred) {
+                    for term in terms {
+                        quals.insert(term.clone(), pred)?;
+                    }
+                }
+            }
+        }
+
+        // Hints from the user.
+        for pred in instance.pred_indices() {
+            if instance[pred].is_defined() {
+                continue;
+            }
+            for hint in instance[pred].hints() {
+                quals.insert(hint, pred)?;
+            }
+        }
+
+        Ok(quals)
+    }
+
+    /// Constructor seeding the qualifiers with some user hints.
+    ///
+    /// Same as [`new`][new], but also parses each hint as a boolean term where variable `v_i`
+    /// is the `i`th argument of a predicate (see [`parse::sig_term`][sig term]), and inserts it
+    /// for all the (undefined) predicates whose signature is compatible. Empty hints and hints
+    /// starting with `;` are ignored. Hints that are compatible with no predicate, typically
+    /// because they are malformed, produce a warning.
+    ///
+    /// Hints are written against the [original signature] of the predicates, as declared by the
+    /// user. They are translated to the current signature, and dropped for the predicates where
+    /// they mention an argument removed by pre-processing.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, learning::ice::quals::NuQuals };
+    /// let instance = hoice::parse::instance("
+    /// (declare-fun P (Int Int) Bool)
+    /// (declare-fun Q (Bool) Bool)
+    /// ");
+    /// let hints = vec![
+    ///     "; comment", "(<= v_0 (+ v_1 4096))", "v_0", "", "(>= v_0 ",
+    /// ];
+    /// let quals = NuQuals::new_with_user_hints(&Arc::new(instance), false, hints).unwrap();
+    /// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+    /// let qual = term::le(term::int_var(0), term::add2(term::int_var(1), term::int(4096)));
+    /// assert! {  quals.quals_of_contains(p, &qual) }
+    /// assert! { !quals.quals_of_contains(q, &qual) }
+    /// assert! {  quals.quals_of_contains(q, &term::bool_var(0)) }
+    /// assert! { !quals.quals_of_contains(p, &term::bool_var(0)) }
+    /// ```
+    ///
+    /// Argument reduction removes the first argument of `P` below.
+    ///
+    /// ```rust
+    /// use hoice::{
+    ///     common::*, learning::ice::quals::NuQuals, preproc::{ ArgRed, PreInstance, RedStrat },
+    /// };
+    /// let mut instance = hoice::parse::instance("
+    /// (declare-fun P (Int Int) Bool)
+    /// (assert (forall ((x Int) (y Int)) (=> (= y 0) (P x y))))
+    /// (assert (forall ((x Int) (y Int)) (=> (and (P x y) (< y 0)) false)))
+    /// (assert (forall ((x Int) (y Int) (z Int)) (=> (P x y) (P z (+ y 1)))))
+    /// ");
+    /// {
+    ///     let mut arg_red = ArgRed::new(&instance);
+    ///     let mut pre_instance = PreInstance::new(&mut instance).unwrap();
+    ///     arg_red.apply(&mut pre_instance).unwrap();
+    ///     pre_instance.destroy().unwrap();
+    /// }
+    /// let p: PrdIdx = 0.into();
+    /// assert_eq! { instance[p].sig().len(), 1 }
+    ///
+    /// let hints = vec![ "(>= v_1 7)", "(<= v_0 3)" ];
+    /// let quals = NuQuals::new_with_user_hints(&Arc::new(instance), false, hints).unwrap();
+    /// // `v_1` is now `v_0`.
+    /// assert! {  quals.quals_of_contains(p, &term::ge(term::int_var(0), term::int(7))) }
+    /// // The original `v_0` does not exist anymore.
+    /// assert! { !quals.quals_of_contains(p, &term::le(term::int_var(0), term::int(3))) }
+    /// ```
+    ///
+    /// [new]: #method.new (new function)
+    /// [sig term]: ../../../parse/fn.sig_term.html (sig_term function)
+    /// [original signature]: ../../../info/struct.Pred.html#method.original_sig
+    /// (original_sig function of Pred)
+    pub fn new_with_user_hints<'a, Hints>(
+        instance: &Arc<Instance>,
+        mine: bool,
+        hints: Hints,
+    ) -> Res<Self>
+    where
+        Hints: IntoIterator<Item = &'a str>,
+    {
+        let mut quals = Self::new(instance, mine)?;
+
+        // Maps the variables of the original signatures to the current ones.
+        let mut sig_maps: PrdMap<VarHMap<Term>> = PrdMap::with_capacity(instance.preds().len());
+        for info in instance.preds() {
+            let mut map = VarHMap::new();
+            for (var, old_var) in info.original_sig_map().index_iter() {
+                map.insert(*old_var, term::var(var, info.sig()[var].clone()));
+            }
+            sig_maps.push(map)
+        }
+
+        for hint in hints {
+            let hint = hint.trim();
+            if hint.is_empty() || hint.starts_with(';') {
+                continue;
+            }
+
+            let mut compatible = false;
+            let mut last_err = None;
+            for pred in instance.pred_indices() {
+                if instance[pred].is_defined() {
+                    continue;
+                }
+                match crate::parse::sig_term(hint, instance[pred].original_sig(), instance) {
+                    Ok(term) => {
+                        compatible = true;
+                        // Fails if the hint mentions a removed argument.
+                        if let Some((term, _)) = term.subst_total(&sig_maps[pred]) {
+                            quals.insert(term, pred)?;
+                        }
+                    }
+                    Err(e) => last_err = Some(e),
+                }
+            }
+
+            if !compatible {
+                let reason = last_err
+                    .map(|e| {
+                        e.iter()
+                            .map(|e| e.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    })
+                    .unwrap_or_else(|| "no predicate to apply it to".into());
+                warn! {
+                    "ignoring qualifier hint `{}`", hint ;
+                    "{}", reason
+                }
+            }
+        }
+
         Ok(quals)
     }
 
@@ -634,6 +816,7 @@ impl NuQuals {
             .entry(var_count.into())
             .or_insert_with(|| TermSet::with_capacity(103));
 
+        self.last_used[pred].insert(term.clone(), self.step);
         let is_new = set.insert(term);
         Ok(is_new)
     }
@@ -678,6 +861,119 @@ impl NuQuals {
         &self.quals[pred]
     }
 
+    /// Copies the qualifiers of a predicate to another predicate with the same signature.
+    ///
+    /// Returns the number of qualifiers that were new for `to`.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, learning::ice::quals::NuQuals };
+    /// let instance = hoice::parse::instance("
+    /// (declare-fun P (Int) Bool)
+    /// (declare-fun Q (Int) Bool)
+    /// ");
+    /// let mut quals = NuQuals::new(&Arc::new(instance), false).unwrap();
+    /// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+    /// let qual = term::ge(term::int_var(0), term::int(7));
+    /// quals.insert(qual.clone(), p).unwrap();
+    /// assert_eq! { quals.copy_quals(p, q).unwrap(), 1 }
+    /// assert! { quals.quals_of_contains(q, &qual) }
+    /// assert_eq! { quals.copy_quals(p, q).unwrap(), 0 }
+    /// ```
+    pub fn copy_quals(&mut self, from: PrdIdx, to: PrdIdx) -> Res<usize> {
+        if self.instance[from].sig != self.instance[to].sig {
+            bail!(
+                "cannot copy qualifiers of {} to {}: signatures differ",
+                self.instance[from],
+                self.instance[to]
+            )
+        }
+        let terms: Vec<Term> = self.quals[from]
+            .values()
+            .flat_map(|terms| terms.iter().cloned())
+            .collect();
+        let mut count = 0;
+        for term in terms {
+            if self.insert(term, to)? {
+                count += 1
+            }
+        }
+        Ok(count)
+    }
+
+    /// Iterator over the negations of the qualifiers of a predicate.
+    ///
+    /// Lazy, only yields the negation of the qualifiers whose negation is not already a
+    /// qualifier of the predicate.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, learning::ice::quals::NuQuals };
+    /// let instance = hoice::parse::instance("
+    /// (declare-fun P (Bool Bool) Bool)
+    /// ");
+    /// let mut quals = NuQuals::new(&Arc::new(instance), false).unwrap();
+    /// let p: PrdIdx = 0.into();
+    /// let (v_0, v_1) = (term::bool_var(0), term::bool_var(1));
+    /// quals.insert(v_0.clone(), p).unwrap();
+    /// quals.insert(term::not(v_0.clone()), p).unwrap();
+    /// quals.insert(v_1.clone(), p).unwrap();
+    ///
+    /// let negs: Vec<_> = quals.neg_qualifiers(p).collect();
+    /// assert_eq! { negs, vec![ term::not(v_1) ] }
+    /// ```
+    pub fn neg_qualifiers<'a>(&'a self, pred: PrdIdx) -> impl Iterator<Item = Term> + 'a {
+        self.quals[pred]
+            .values()
+            .flat_map(|terms| terms.iter())
+            .map(|term| term::not(term.clone()))
+            .filter(move |neg| !self.quals_of_contains(pred, neg))
+    }
+
+    /// Evaluates all the qualifiers of a predicate on a sample.
+    ///
+    /// Qualifiers that do not evaluate to a boolean on the sample, because it is partial for
+    /// instance, are not in the result. Each qualifier appears at most once.
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate hoice;
+    /// use hoice::{ common::*, learning::ice::quals::NuQuals };
+    /// # fn main() {
+    /// let instance = hoice::parse::instance("
+    /// (declare-fun P (Int Int) Bool)
+    /// ");
+    /// let mut quals = NuQuals::new(&Arc::new(instance), false).unwrap();
+    /// let p: PrdIdx = 0.into();
+    /// let (v_0, v_1) = (term::int_var(0), term::int_var(1));
+    /// quals.insert(term::ge(v_0.clone(), term::int(0)), p).unwrap();
+    /// quals.insert(term::eq(v_0.clone(), v_1.clone()), p).unwrap();
+    /// quals.insert(term::gt(v_1.clone(), term::int(7)), p).unwrap();
+    ///
+    /// let sample = var_vals!((int 3) (int 3));
+    /// let mut res = quals.eval_all(p, &sample).unwrap();
+    /// res.sort();
+    /// let mut expected = vec![
+    ///     (term::ge(v_0.clone(), term::int(0)), true),
+    ///     (term::eq(v_0.clone(), v_1.clone()), true),
+    ///     (term::gt(v_1.clone(), term::int(7)), false),
+    /// ];
+    /// expected.sort();
+    /// assert_eq! { res, expected }
+    /// # }
+    /// ```
+    pub fn eval_all(&self, pred: PrdIdx, sample: &VarVals) -> Res<Vec<(Term, bool)>> {
+        let mut res = Vec::with_capacity(self.quals[pred].values().map(|terms| terms.len()).sum());
+        for terms in self.quals[pred].values() {
+            for term in terms {
+                if let Some(value) = term
+                    .evaluate(sample.get())
+                    .chain_err(|| format!("while evaluating qualifier {}", term))?
+                {
+                    res.push((term.clone(), value))
+                }
+            }
+        }
+        Ok(res)
+    }
+
     /// Returns the qualifier that maximized the input criterion in a non-zero
     /// fashion, if any. Early-returns if the criterion is `>=` to the gain pivot
     /// defined in the configuration at some point.
@@ -745,6 +1041,7 @@ impl NuQuals {
 
                 if let Some(value) = crit(term)? {
                     best = if value > 0.9999 {
+                        self.last_used[pred].insert(term.clone(), self.step);
                         return Ok(Some((term.clone(), value)));
                     } else if let Some((best, best_value)) = best {
                         let diff = value - best_value;
@@ -760,6 +1057,89 @@ impl NuQuals {
             }
         }
 
+        if let Some((term, _)) = best.as_ref() {
+            self.last_used[pred].insert((*term).clone(), self.step);
+        }
+
         Ok(best.map(|(t, v)| (t.clone(), v)))
     }
+
+    /// Starts a new step.
+    ///
+    /// Qualifiers inserted or selected by [`maximize`][max] during a step are stamped with it,
+    /// see [`evict_older_than`][evict].
+    ///
+    /// [max]: #method.maximize (maximize function)
+    /// [evict]: #method.evict_older_than (evict_older_than function)
+    pub fn new_step(&mut self) {
+        self.step += 1
+    }
+
+    /// Last step a qualifier was inserted or selected by [`maximize`][max], if any.
+    ///
+    /// [max]: #method.maximize (maximize function)
+    pub fn last_used(&self, pred: PrdIdx, term: &Term) -> Option<usize> {
+        self.last_used[pred].get(term).cloned()
+    }
+
+    /// Removes the qualifiers that were not inserted or selected by [`maximize`][max] during the
+    /// last `age` steps.
+    ///
+    /// Qualifiers inserted or selected during the current step are never removed. Used when the
+    /// learner is over the memory budget. Returns the number of qualifiers removed.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, learning::ice::quals::NuQuals };
+    /// let instance = hoice::parse::instance("
+    /// (declare-fun P (Int) Bool)
+    /// ");
+    /// let mut quals = NuQuals::new(&Arc::new(instance), false).unwrap();
+    /// let p: PrdIdx = 0.into();
+    /// let (ge, le) = (
+    ///     term::ge(term::int_var(0), term::int(7)),
+    ///     term::le(term::int_var(0), term::int(3)),
+    /// );
+    /// quals.insert(ge.clone(), p).unwrap();
+    /// quals.insert(le.clone(), p).unwrap();
+    ///
+    /// quals.new_step();
+    /// let best = quals.maximize(
+    ///     p, None, |qual| Ok( Some( if qual == &le { 1.0 } else { 0.5 } ) )
+    /// ).unwrap();
+    /// assert_eq! { best, Some((le.clone(), 1.0)) }
+    /// assert_eq! { quals.last_used(p, &le), Some(1) }
+    /// assert_eq! { quals.last_used(p, &ge), Some(0) }
+    ///
+    /// // Everything was used during the current step.
+    /// assert_eq! { quals.evict_older_than(0), 0 }
+    ///
+    /// quals.new_step();
+    /// let eq = term::eq(term::int_var(0), term::int(5));
+    /// quals.insert(eq.clone(), p).unwrap();
+    /// assert_eq! { quals.evict_older_than(1), 1 }
+    /// assert! { quals.quals_of_contains(p, &le) }
+    /// assert! { quals.quals_of_contains(p, &eq) }
+    /// assert! { !quals.quals_of_contains(p, &ge) }
+    /// assert_eq! { quals.last_used(p, &ge), None }
+    ///
+    /// assert_eq! { quals.evict_older_than(0), 1 }
+    /// assert! { !quals.quals_of_contains(p, &le) }
+    /// assert! { quals.quals_of_contains(p, &eq) }
+    /// ```
+    ///
+    /// [max]: #method.maximize (maximize function)
+    pub fn evict_older_than(&mut self, age: usize) -> usize {
+        let step = self.step;
+        let mut count = 0;
+        for (pred, sets) in self.quals.index_iter_mut() {
+            let last_used = &mut self.last_used[pred];
+            last_used.retain(|_, used| step - *used <= age);
+            for terms in sets.values_mut() {
+                let len = terms.len();
+                terms.retain(|term| last_used.contains_key(term));
+                count += len - terms.len()
+            }
+        }
+        count
+    }
 }
@@ -10,6 +10,8 @@ use crate::common::*;
 #[macro_use]
 pub mod helpers;
 pub mod adt;
+pub mod boolean;
+pub mod enumeration;
 pub mod int;
 pub mod real;
 
@@ -57,15 +59,42 @@ pub trait TheoSynth {
 }
 
 use self::adt::AdtSynth;
+use self::boolean::BoolSynth;
+use self::enumeration::EnumSynth;
 use self::int::IntSynth;
 use self::real::RealSynth;
 
+/// Identifies a theory synthesizer of a [`SynthSys`].
+///
+/// [`SynthSys`]: struct.SynthSys.html (SynthSys struct)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Theory {
+    /// Integer synthesis.
+    Int,
+    /// Real synthesis.
+    Real,
+    /// Synthesis for the `n`th datatype.
+    Adt(usize),
+    /// Synthesis for the `n`th enumeration datatype.
+    Enum(usize),
+    /// Boolean synthesis.
+    Bool,
+}
+
 /// Manages theory synthesizers.
 pub struct SynthSys {
     int: Option<IntSynth>,
     real: Option<RealSynth>,
     adt: Vec<AdtSynth>,
+    enums: Vec<EnumSynth>,
+    boolean: Option<BoolSynth>,
     cross_synth: TermMap<Val>,
+    /// Theories of the synthesizers, in their default order.
+    theories: Vec<Theory>,
+    /// Number of calls to [`sample_synth`][sample synth], decides which theory goes first.
+    ///
+    /// [sample synth]: #method.sample_synth (sample_synth function)
+    rotation: usize,
 }
 impl SynthSys {
     /// Constructor.
@@ -87,6 +116,8 @@ impl SynthSys {
         }
 
         let mut adt: Vec<AdtSynth> = Vec::new();
+        let mut enums: Vec<EnumSynth> = Vec::new();
+        let mut boolean = None;
         for typ in sig {
             match **typ {
                 typ::RTyp::Int => set!(int),
@@ -103,25 +134,145 @@ impl SynthSys {
                         }
                         adt.push(synth)
                     }
+                    if enums.iter().all(|enm| enm.typ() != typ) {
+                        if let Some(synth) = EnumSynth::new(typ.clone()) {
+                            enums.push(synth)
+                        }
+                    }
+                }
+
+                typ::RTyp::Bool => {
+                    if boolean.is_none() {
+                        boolean = Some(BoolSynth::new())
+                    }
                 }
 
-                typ::RTyp::Bool | typ::RTyp::Array { .. } | typ::RTyp::Unk => (),
+                typ::RTyp::Array { .. } | typ::RTyp::Unk => (),
             }
         }
 
+        let mut theories = Vec::with_capacity(3 + adt.len() + enums.len());
+        if int.is_some() {
+            theories.push(Theory::Int)
+        }
+        if real.is_some() {
+            theories.push(Theory::Real)
+        }
+        theories.extend((0..adt.len()).map(Theory::Adt));
+        theories.extend((0..enums.len()).map(Theory::Enum));
+        if boolean.is_some() {
+            theories.push(Theory::Bool)
+        }
+
         SynthSys {
             int,
             real,
             adt,
+            enums,
+            boolean,
             cross_synth: TermMap::new(),
+            theories,
+            rotation: 0,
+        }
+    }
+
+    /// Sets the constants the arithmetic synthesizers relate variables to.
+    ///
+    /// Typically the constant pool of the predicate, see
+    /// [`Instance::constants_of`][csts].
+    ///
+    /// [csts]: ../../../instance/struct.Instance.html#method.constants_of
+    /// (constants_of function of Instance)
+    pub fn set_constants(&mut self, constants: &[Val]) {
+        if let Some(int) = self.int.as_mut() {
+            int.set_constants(constants)
+        }
+        if let Some(real) = self.real.as_mut() {
+            real.set_constants(constants)
+        }
+    }
+
+    /// Makes integer synthesis prefer difference bounds.
+    ///
+    /// Used when the instance is in difference logic, see
+    /// [`Instance::is_difference_logic`][dl]. The first level of integer synthesis then only
+    /// generates bounds and differences of two variables. Sums of two variables come with the
+    /// next level, and the levels after that are unchanged.
+    ///
+    /// [dl]: ../../../instance/struct.Instance.html#method.is_difference_logic
+    /// (is_difference_logic function of Instance)
+    pub fn prefer_difference_logic(&mut self) {
+        if let Some(int) = self.int.as_mut() {
+            int.prefer_difference_logic()
         }
     }
 
+    /// Order in which the next call to [`sample_synth`][sample synth] runs the theories.
+    ///
+    /// The default order is int, real, the datatypes, the enumerations and bool. Each call to
+    /// [`sample_synth`][sample synth] rotates this order by one, so that all theories regularly
+    /// get to go first.
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, learning::ice::synth::{ SynthSys, Theory } };
+    /// fn main() {
+    ///     let sig: Sig = vec![ typ::int(), typ::real() ].into();
+    ///     let mut synth = SynthSys::new(&sig);
+    ///     let profiler = Profiler::new();
+    ///     let sample = var_vals!( (int 7) (real 3) );
+    ///
+    ///     assert_eq! { synth.next_order(), vec![ Theory::Int, Theory::Real ] }
+    ///     synth.sample_synth(&sample, |_| Ok(true), &profiler).unwrap();
+    ///     assert_eq! { synth.next_order(), vec![ Theory::Real, Theory::Int ] }
+    ///     synth.sample_synth(&sample, |_| Ok(true), &profiler).unwrap();
+    ///     assert_eq! { synth.next_order(), vec![ Theory::Int, Theory::Real ] }
+    /// }
+    /// ```
+    ///
+    /// [sample synth]: #method.sample_synth (sample_synth function)
+    pub fn next_order(&self) -> Vec<Theory> {
+        let len = self.theories.len();
+        (0..len)
+            .map(|count| self.theories[(self.rotation + count) % len])
+            .collect()
+    }
+
+    /// True if one of the synthesizers works on a type appearing in a signature.
+    ///
+    /// When false, synthesis cannot produce anything for this signature.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, learning::ice::synth::SynthSys };
+    /// let sig: Sig = vec![ typ::bool(), typ::int() ].into();
+    /// assert! { SynthSys::new(&sig).has_type(&sig) }
+    /// let sig: Sig = vec![ typ::bool(), typ::array(typ::int(), typ::int()) ].into();
+    /// assert! { SynthSys::new(&sig).has_type(&sig) }
+    /// let sig: Sig = vec![ typ::array(typ::int(), typ::int()) ].into();
+    /// assert! { !SynthSys::new(&sig).has_type(&sig) }
+    /// ```
+    pub fn has_type(&self, sig: &Sig) -> bool {
+        sig.iter().any(|typ| {
+            self.int.as_ref().map(|i| i.typ() == typ).unwrap_or(false)
+                || self.real.as_ref().map(|r| r.typ() == typ).unwrap_or(false)
+                || self.adt.iter().any(|a| a.typ() == typ)
+                || self.enums.iter().any(|e| e.typ() == typ)
+                || self
+                    .boolean
+                    .as_ref()
+                    .map(|b| b.typ() == typ)
+                    .unwrap_or(false)
+        })
+    }
+
     /// True if all synthesizers are done.
     pub fn is_done(&self) -> bool {
         self.int.as_ref().map(|i| i.is_done()).unwrap_or(true)
             && self.real.as_ref().map(|r| r.is_done()).unwrap_or(true)
             && self.adt.iter().all(|a| a.is_done())
+            && self.enums.iter().all(|e| e.is_done())
+            && self.boolean.as_ref().map(|b| b.is_done()).unwrap_or(true)
     }
 
     /// Increments all synthesizers.
@@ -135,6 +286,12 @@ impl SynthSys {
         for a in &mut self.adt {
             a.increment()
         }
+        for e in &mut self.enums {
+            e.increment()
+        }
+        if let Some(b) = self.boolean.as_mut() {
+            b.increment()
+        }
     }
 
     /// Restarts all synthesizers.
@@ -148,21 +305,57 @@ impl SynthSys {
         for a in &mut self.adt {
             a.restart()
         }
+        for e in &mut self.enums {
+            e.restart()
+        }
+        if let Some(b) = self.boolean.as_mut() {
+            b.restart()
+        }
     }
 
     /// Synthesizes qualifiers for a sample, stops if input function returns
     /// `true`.
     ///
+    /// The theories run in the order given by [`next_order`][next order], which changes after
+    /// each call.
+    ///
+    /// Qualifiers with more nodes than `--synth_max_size` are discarded before reaching `f`.
+    ///
     /// Returns `true` iff `f` returned true at some point.
+    ///
+    /// [next order]: #method.next_order (next_order function)
     pub fn sample_synth<F>(&mut self, sample: &VarVals, mut f: F, _prof: &Profiler) -> Res<bool>
     where
         F: FnMut(Term) -> Res<bool>,
     {
-        let done = self.int_synth(sample, &mut f, _prof)?
-            || self.real_synth(sample, &mut f, _prof)?
-            || self.adt_synth(sample, &mut f, _prof)?;
+        let max_size = conf.ice.synth_max_size;
+        let mut f = |term: Term| {
+            if max_size.map(|max| term.size() > max).unwrap_or(false) {
+                log_debug! { "discarding qualifier of size {}: {}", term.size(), term }
+                Ok(false)
+            } else {
+                f(term)
+            }
+        };
 
-        Ok(done)
+        let len = self.theories.len();
+        let start = self.rotation;
+        self.rotation = if len == 0 { 0 } else { (start + 1) % len };
+
+        for count in 0..len {
+            let done = match self.theories[(start + count) % len] {
+                Theory::Int => self.int_synth(sample, &mut f, _prof)?,
+                Theory::Real => self.real_synth(sample, &mut f, _prof)?,
+                Theory::Adt(idx) => self.one_adt_synth(idx, sample, &mut f, _prof)?,
+                Theory::Enum(idx) => self.one_enum_synth(idx, sample, &mut f, _prof)?,
+                Theory::Bool => self.bool_synth(sample, &mut f, _prof)?,
+            };
+            if done {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
     }
 
     /// Runs integer synthesis.
@@ -253,7 +446,27 @@ impl SynthSys {
     where
         F: FnMut(Term) -> Res<bool>,
     {
-        for adt_synth in &mut self.adt {
+        for idx in 0..self.adt.len() {
+            if self.one_adt_synth(idx, sample, &mut f, _profiler)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Runs synthesis for the `idx`th datatype.
+    fn one_adt_synth<F>(
+        &mut self,
+        idx: usize,
+        sample: &VarVals,
+        mut f: F,
+        _profiler: &Profiler,
+    ) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        if let Some(adt_synth) = self.adt.get_mut(idx) {
             if !adt_synth.is_done() {
                 self.cross_synth.clear();
 
@@ -288,4 +501,78 @@ impl SynthSys {
 
         Ok(false)
     }
+
+    /// Runs enumeration datatype synthesis.
+    pub fn enum_synth<F>(&mut self, sample: &VarVals, mut f: F, _profiler: &Profiler) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        for idx in 0..self.enums.len() {
+            if self.one_enum_synth(idx, sample, &mut f, _profiler)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Runs synthesis for the `idx`th enumeration datatype.
+    fn one_enum_synth<F>(
+        &mut self,
+        idx: usize,
+        sample: &VarVals,
+        mut f: F,
+        _profiler: &Profiler,
+    ) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        if let Some(enum_synth) = self.enums.get_mut(idx) {
+            if !enum_synth.is_done() {
+                self.cross_synth.clear();
+
+                profile! { |_profiler| tick "learning", "qual", "synthesis", "enum" }
+                let done = enum_synth.synth(&mut f, sample, &mut self.cross_synth, _profiler);
+                profile! { |_profiler| mark "learning", "qual", "synthesis", "enum" }
+                if done? {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Runs boolean synthesis.
+    ///
+    /// The int synthesizer projects some int atoms to relate the boolean variables to.
+    pub fn bool_synth<F>(&mut self, sample: &VarVals, mut f: F, _profiler: &Profiler) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        if let Some(bool_synth) = self.boolean.as_mut() {
+            if !bool_synth.is_done() {
+                self.cross_synth.clear();
+
+                if let Some(int_synth) = self.int.as_mut() {
+                    profile! (
+                      |_profiler| wrap {
+                        int_synth.project(
+                          sample, bool_synth.typ(), & mut self.cross_synth
+                        )
+                      } "learning", "qual", "synthesis", "int project"
+                    )?
+                }
+
+                profile! { |_profiler| tick "learning", "qual", "synthesis", "bool" }
+                let done = bool_synth.synth(&mut f, sample, &mut self.cross_synth, _profiler);
+                profile! { |_profiler| mark "learning", "qual", "synthesis", "bool" }
+                if done? {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
 }
@@ -64,6 +64,15 @@ impl AdtSynth {
 
         let var = term::var(var, self.typ.clone());
 
+        // Size of the value, only defined in z3.
+        if typ.is_int() && self.typ.is_mono_dtyp() && conf.solver.is_z3() {
+            let size = val.dtyp_size()?;
+            if size.is_known() {
+                let prev = map.insert(term::dtyp_size(var.clone()), size);
+                debug_assert! { prev.is_none() }
+            }
+        }
+
         // Apply unary functions from `self.typ` to `typ`.
         for fun in &self.funs.from_typ {
             if &fun.typ != typ {
@@ -161,7 +170,13 @@ impl AdtSynth {
     }
 
     /// True if the synthesizer can project values to int.
+    ///
+    /// Always true for datatypes without type parameters when the solver is z3, through their
+    /// size.
     pub fn can_project_to_int(&self) -> bool {
+        if self.typ.is_mono_dtyp() && conf.solver.is_z3() {
+            return true;
+        }
         for fun in &self.funs.from_typ {
             if fun.typ.is_int() {
                 return true;
@@ -0,0 +1,146 @@
+//! Boolean qualifier synthesis.
+
+use crate::common::*;
+
+use super::{TermVals, TheoSynth};
+
+/// Boolean qualifier synthesizer.
+///
+/// Level 0 generates the boolean variables and their negation. Level 1 generates equivalences
+/// and xors between pairs of boolean variables, and between boolean variables and the atoms
+/// projected by the other synthesizers.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::common::*;
+/// use hoice::learning::ice::synth::{boolean::BoolSynth, TheoSynth};
+/// let mut synth = BoolSynth::new();
+///
+/// let sample = var_to::vals::new(vec![val::bool(true), val::int(7), val::bool(false)]);
+/// let mut quals = vec![];
+/// synth.synth(
+///     |qual| { quals.push(qual); Ok(false) },
+///     &sample, &mut TermMap::new(), &Profiler::new(),
+/// ).unwrap();
+/// // The two boolean variables and their negation.
+/// assert_eq! { quals.len(), 4 }
+///
+/// synth.increment();
+/// let mut others = TermMap::new();
+/// others.insert(term::ge(term::int_var(1), term::int(0)), val::bool(true));
+/// quals.clear();
+/// synth.synth(
+///     |qual| { quals.push(qual); Ok(false) },
+///     &sample, &mut others, &Profiler::new(),
+/// ).unwrap();
+/// // Equivalence and xor between the two variables, and between each variable and the atom.
+/// assert_eq! { quals.len(), 6 }
+///
+/// synth.increment();
+/// assert! { synth.is_done() }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BoolSynth {
+    /// Expressivity level.
+    expressivity: usize,
+    /// The bool type.
+    typ: Typ,
+}
+impl Default for BoolSynth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoolSynth {
+    /// Creates a new boolean synthesizer.
+    pub fn new() -> Self {
+        BoolSynth {
+            expressivity: 0,
+            typ: typ::bool(),
+        }
+    }
+}
+
+impl TheoSynth for BoolSynth {
+    fn typ(&self) -> &Typ {
+        &self.typ
+    }
+
+    fn is_done(&self) -> bool {
+        self.expressivity > 1
+    }
+
+    fn restart(&mut self) {
+        self.expressivity = 0
+    }
+
+    fn increment(&mut self) {
+        self.expressivity += 1
+    }
+
+    fn synth<F>(
+        &mut self,
+        mut f: F,
+        sample: &VarVals,
+        others: &mut TermVals,
+        _profiler: &Profiler,
+    ) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        let vars = sample
+            .index_iter()
+            .filter(|(_, val)| val.typ() == self.typ)
+            .map(|(var, _)| term::var(var, self.typ.clone()));
+
+        match self.expressivity {
+            0 => {
+                for var in vars {
+                    if f(var.clone())? || f(term::not(var))? {
+                        return Ok(true);
+                    }
+                }
+            }
+
+            1 => {
+                let mut previous: Vec<Term> = Vec::with_capacity(sample.len());
+                for var in vars {
+                    for other in &previous {
+                        if eq_xor_synth(&var, other, &mut f)? {
+                            return Ok(true);
+                        }
+                    }
+                    previous.push(var)
+                }
+
+                for (atom, _) in others.drain() {
+                    for var in &previous {
+                        if eq_xor_synth(var, &atom, &mut f)? {
+                            return Ok(true);
+                        }
+                    }
+                }
+            }
+
+            _ => (),
+        }
+
+        Ok(false)
+    }
+
+    /// Booleans do not project to other types.
+    fn project(&self, _: &VarVals, _: &Typ, _: &mut TermVals) -> Res<()> {
+        Ok(())
+    }
+}
+
+/// Generates the equivalence and the xor of two boolean terms.
+fn eq_xor_synth<F>(lhs: &Term, rhs: &Term, mut f: F) -> Res<bool>
+where
+    F: FnMut(Term) -> Res<bool>,
+{
+    let eq = term::eq(lhs.clone(), rhs.clone());
+    Ok(f(eq.clone())? || f(term::not(eq))?)
+}
@@ -0,0 +1,122 @@
+//! Enumeration datatype qualifier synthesis.
+
+use crate::common::*;
+
+use super::{TermVals, TheoSynth};
+
+/// Synthesizer for enumeration datatypes, *i.e.* datatypes with nullary constructors only.
+///
+/// Generates equalities between the variables of the type and each constructor.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::common::*;
+/// use hoice::learning::ice::synth::{enumeration::EnumSynth, TheoSynth};
+/// hoice::parse::fun_dtyp(
+///     "(declare-datatypes ( (Light 0) ) ( ( on off ) ) )"
+/// );
+/// let light = typ::dtyp(dtyp::get("Light").unwrap(), vec![].into());
+/// assert! { EnumSynth::new(typ::int()).is_none() }
+/// let mut synth = EnumSynth::new(light.clone()).unwrap();
+///
+/// let sample = var_to::vals::new(vec![
+///     val::int(7), val::dtyp_new(light.clone(), "off".into(), vec![]),
+/// ]);
+/// let mut quals = vec![];
+/// synth.synth(
+///     |qual| { quals.push(qual); Ok(false) },
+///     &sample, &mut TermMap::new(), &Profiler::new(),
+/// ).unwrap();
+/// // One qualifier per constructor, only `v_1 = off` holds on the sample.
+/// assert_eq! { quals.len(), 2 }
+/// let true_count = quals
+///     .iter()
+///     .filter(|qual| qual.bool_eval(sample.get()).unwrap() == Some(true))
+///     .count();
+/// assert_eq! { true_count, 1 }
+/// ```
+#[derive(Clone, Debug)]
+pub struct EnumSynth {
+    /// Expressivity level.
+    expressivity: usize,
+    /// Type this synthesizer handles.
+    typ: Typ,
+    /// Constructors of the type.
+    constructors: Vec<Term>,
+}
+
+impl EnumSynth {
+    /// Constructor.
+    ///
+    /// Returns `None` if the type is not an enumeration datatype.
+    pub fn new(typ: Typ) -> Option<Self> {
+        if !typ.is_dtyp() || !typ.is_finite_domain() {
+            return None;
+        }
+        let constructors = if let Some((dtyp, _)) = typ.dtyp_inspect() {
+            dtyp.news
+                .keys()
+                .map(|name| term::dtyp_new(typ.clone(), name.clone(), vec![]))
+                .collect()
+        } else {
+            return None;
+        };
+        Some(EnumSynth {
+            expressivity: 0,
+            typ,
+            constructors,
+        })
+    }
+}
+
+impl TheoSynth for EnumSynth {
+    fn typ(&self) -> &Typ {
+        &self.typ
+    }
+
+    fn is_done(&self) -> bool {
+        self.expressivity > 0
+    }
+
+    fn restart(&mut self) {
+        self.expressivity = 0
+    }
+
+    fn increment(&mut self) {
+        self.expressivity += 1
+    }
+
+    fn synth<F>(
+        &mut self,
+        mut f: F,
+        sample: &VarVals,
+        _others: &mut TermVals,
+        _profiler: &Profiler,
+    ) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        if self.expressivity > 0 {
+            return Ok(false);
+        }
+
+        for (var, val) in sample.index_iter() {
+            if val.typ() == self.typ {
+                let var = term::var(var, self.typ.clone());
+                for constructor in &self.constructors {
+                    if f(term::eq(var.clone(), constructor.clone()))? {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Enumerations do not project to other types.
+    fn project(&self, _: &VarVals, _: &Typ, _: &mut TermVals) -> Res<()> {
+        Ok(())
+    }
+}
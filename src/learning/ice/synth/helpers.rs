@@ -328,6 +328,119 @@ where
     Ok(false)
 }
 
+/// Relates the variables of a sample to some constants.
+///
+/// Generates `v >= c` and `v <= c` for all the variables `v` of type `typ` with a known value in
+/// the sample, and all the constants `c` of type `typ` in `csts`.
+pub fn cst_pool_synth<F>(sample: &VarVals, typ: &Typ, csts: &[Val], mut f: F) -> Res<bool>
+where
+    F: FnMut(Term) -> Res<bool>,
+{
+    let csts: Vec<Term> = csts
+        .iter()
+        .filter(|cst| cst.typ() == *typ)
+        .filter_map(|cst| cst.to_term())
+        .collect();
+    if csts.is_empty() {
+        return Ok(false);
+    }
+
+    for (var_idx, val) in sample.index_iter() {
+        if val.typ() == *typ && val.is_known() {
+            let var = term::var(var_idx, typ.clone());
+            for cst in &csts {
+                if f(term::ge(var.clone(), cst.clone()))? || f(term::le(var.clone(), cst.clone()))?
+                {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Small coefficients used to relate cross-theory terms to native variables.
+const SMALL_COEFS: [i64; 4] = [1, -1, 2, -2];
+
+/// Small coefficient as a value of some arithmetic type.
+fn small_coef(typ: &Typ, coef: i64) -> Val {
+    if typ.is_int() {
+        val::int(coef)
+    } else {
+        val::real(Rat::from_integer(coef.into()))
+    }
+}
+
+/// Enriches projected terms with scaled versions and sums with native variables.
+///
+/// For each term `t` in `terms`, adds `2 * t` and `t + v` for all variables `v` of type `typ`
+/// in the sample. `typ` is the type of the terms, their values are obtained by evaluation on the
+/// sample. Unknown values are ignored.
+pub fn project_combinations(
+    sample: &VarVals,
+    typ: &Typ,
+    terms: Vec<Term>,
+    map: &mut TermVals,
+) -> Res<()> {
+    for term in terms {
+        let mut combinations = vec![term::cmul(small_coef(typ, 2).get().clone(), term.clone())];
+        for (var_idx, val) in sample.index_iter() {
+            if val.typ() == *typ && val.is_known() {
+                combinations.push(term::add2(term.clone(), term::var(var_idx, typ.clone())))
+            }
+        }
+
+        for combination in combinations {
+            let val = combination.eval(sample.get())?;
+            if val.is_known() {
+                map.entry(combination).or_insert(val);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Relates cross-theory terms to native variables with small coefficients.
+///
+/// For each cross-theory term `t` and each variable `v` of type `typ` in the sample, synthesizes
+/// `t - c * v <op> n`, where `c` ranges over `1`, `-1`, `2` and `-2`, `<op>` is `=`, `>=` or `<=`
+/// and `n` is the value of `t - c * v` on the sample. Does not drain `others`.
+pub fn cross_coef_synth<F>(sample: &VarVals, others: &TermVals, typ: &Typ, mut f: F) -> Res<bool>
+where
+    F: FnMut(Term) -> Res<bool>,
+{
+    for (term, val) in others {
+        if !val.is_known() {
+            continue;
+        }
+        for (var_idx, var_val) in sample.index_iter() {
+            if var_val.typ() != *typ || !var_val.is_known() {
+                continue;
+            }
+            let var = term::var(var_idx, typ.clone());
+
+            for coef in &SMALL_COEFS {
+                let coef = small_coef(typ, *coef);
+                let lhs = term::sub(vec![
+                    term.clone(),
+                    term::cmul(coef.get().clone(), var.clone()),
+                ]);
+                let rhs = if let Some(rhs) = val.sub(&coef.mul(var_val)?)?.to_term() {
+                    rhs
+                } else {
+                    bail!("unexpected non-value in synthesis")
+                };
+
+                apply! { f to term::eq(lhs.clone(), rhs.clone()) }
+                apply! { f to term::ge(lhs.clone(), rhs.clone()) }
+                apply! { f to term::le(lhs, rhs) }
+            }
+        }
+    }
+    Ok(false)
+}
+
 /// Arith sum/diff synth.
 pub fn sum_diff_synth<F>(
     term: &(Term, Val),
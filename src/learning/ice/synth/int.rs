@@ -1,6 +1,9 @@
 //! Qualifier synthesis in the theory of integers.
 
-use super::{helpers::n_term_arith_synth, TermVals, TheoSynth};
+use super::{
+    helpers::{cross_coef_synth, cst_pool_synth, n_term_arith_synth, project_combinations},
+    TermVals, TheoSynth,
+};
 use crate::common::*;
 
 /// Integer qualifier synthesizer.
@@ -11,6 +14,12 @@ pub struct IntSynth {
     typ: Typ,
     /// True if the synth is done.
     done: bool,
+    /// Constants of the clauses of the predicate, see [`set_constants`][set csts].
+    ///
+    /// [set csts]: #method.set_constants (set_constants function)
+    constants: Vec<Val>,
+    /// True if level 0 should stick to difference bounds.
+    difference_logic: bool,
 }
 impl Default for IntSynth {
     fn default() -> Self {
@@ -25,8 +34,30 @@ impl IntSynth {
             expressivity: 0,
             typ: typ::int(),
             done: false,
+            constants: vec![],
+            difference_logic: false,
         }
     }
+
+    /// Sets the constants level 0 relates the variables of the sample to.
+    ///
+    /// Typically the constant pool of the predicate, see [`Instance::constants_of`][csts].
+    /// Constants that are not integers are ignored.
+    ///
+    /// [csts]: ../../../../instance/struct.Instance.html#method.constants_of
+    /// (constants_of function of Instance)
+    pub fn set_constants(&mut self, constants: &[Val]) {
+        self.constants = constants
+            .iter()
+            .filter(|cst| cst.typ() == self.typ)
+            .cloned()
+            .collect()
+    }
+
+    /// Makes level 0 stick to difference bounds, postponing sums of two variables to level 1.
+    pub fn prefer_difference_logic(&mut self) {
+        self.difference_logic = true
+    }
 }
 impl TheoSynth for IntSynth {
     fn typ(&self) -> &Typ {
@@ -57,14 +88,41 @@ impl TheoSynth for IntSynth {
         F: FnMut(Term) -> Res<bool>,
     {
         self.done = false;
+
+        // Only level 1 is allowed to produce non-linear terms.
+        let expressivity = self.expressivity;
+        let mut f = |term: Term| {
+            debug_assert! {
+                expressivity == 1 || term.is_linear(),
+                "int synthesis (level {}) produced non-linear term {}", expressivity, term
+            }
+            f(term)
+        };
+
         match self.expressivity {
+            // Difference bounds first, sums of two terms are postponed to level 1.
+            0 if self.difference_logic => profile!(
+              |_profiler| wrap {
+                let done = n_term_arith_synth(
+                  sample, others, & self.typ, 1, & mut f
+                ) ? || diff_synth(sample, & self.typ, & mut f) ? ;
+                if ! done {
+                  cst_pool_synth(sample, & self.typ, & self.constants, f)
+                } else {
+                  Ok(true)
+                }
+              } "learning", "qual", "synthesis", "int", "level 0"
+            ),
+
             0 => profile!(
               |_profiler| wrap {
                 let done = n_term_arith_synth(
                   sample, others, & self.typ, 1, & mut f
+                ) ? || n_term_arith_synth(
+                  sample, others, & self.typ, 2, & mut f
                 ) ? ;
                 if ! done {
-                  n_term_arith_synth(sample, others, & self.typ, 2, f)
+                  cst_pool_synth(sample, & self.typ, & self.constants, f)
                 } else {
                   Ok(true)
                 }
@@ -73,7 +131,16 @@ impl TheoSynth for IntSynth {
 
             1 => profile!(
               |_profiler| wrap {
-                non_lin_int_synth(sample, others, f)
+                let done = (
+                  self.difference_logic && n_term_arith_synth(
+                    sample, others, & self.typ, 2, & mut f
+                  ) ?
+                ) || cross_coef_synth(sample, others, & self.typ, & mut f) ? ;
+                if ! done {
+                  non_lin_int_synth(sample, others, f)
+                } else {
+                  Ok(true)
+                }
               } "learning", "qual", "synthesis", "int", "level 1"
             ),
 
@@ -90,21 +157,79 @@ impl TheoSynth for IntSynth {
         }
     }
 
-    /// Only generates reals for now (using `to_real`).
+    /// Generates reals (using `to_real`) and booleans.
+    ///
+    /// For reals, also generates scaled casts and sums of casts with the real variables of the
+    /// sample, see [`project_combinations`]. For booleans, generates the atoms `x >= 0` and
+    /// `x >= v` for all int variables `x` of value `v` in the sample.
+    ///
+    /// [`project_combinations`]: ../helpers/fn.project_combinations.html
+    /// (project_combinations function)
     fn project(&self, sample: &VarVals, typ: &Typ, map: &mut TermVals) -> Res<()> {
-        if typ.is_real() {
+        if typ.is_bool() {
+            for (var, val) in sample.index_iter() {
+                if let val::RVal::I(ref i) = val.get() {
+                    let var = term::var(var, typ::int());
+                    for bound in &[term::int(0), term::int(i.clone())] {
+                        let atom = term::ge(var.clone(), bound.clone());
+                        let val = atom.eval(sample.get())?;
+                        map.insert(atom, val);
+                    }
+                }
+            }
+        } else if typ.is_real() {
+            let mut casts = vec![];
             for (var, val) in sample.index_iter() {
                 if let val::RVal::I(_) = val.get() {
                     let val = Op::ToReal.eval(vec![val.clone()])?;
-                    let prev = map.insert(term::to_real(term::var(var, typ::int())), val);
-                    debug_assert_eq!(prev, None)
+                    let cast = term::to_real(term::var(var, typ::int()));
+                    let prev = map.insert(cast.clone(), val);
+                    debug_assert_eq!(prev, None);
+                    casts.push(cast)
                 }
             }
+            project_combinations(sample, typ, casts, map)?
         }
         Ok(())
     }
 }
 
+/// Difference bounds synthesis.
+///
+/// Generates `x - y = c`, `x - y >= c` and `x - y <= c` for all pairs of variables `x`, `y` of
+/// type `typ` of the sample, where `c` is the value of `x - y` in the sample.
+pub fn diff_synth<F>(sample: &VarVals, typ: &Typ, mut f: F) -> Res<bool>
+where
+    F: FnMut(Term) -> Res<bool>,
+{
+    let mut previous: Vec<(Term, Val)> = Vec::with_capacity(sample.len());
+
+    for (var_idx, val) in sample.index_iter() {
+        if val.typ() == *typ && val.is_known() {
+            let var = term::var(var_idx, typ.clone());
+
+            for (other, other_val) in &previous {
+                let diff = term::sub2(var.clone(), other.clone());
+                let cst = if let Some(cst) = val.sub(other_val)?.to_term() {
+                    cst
+                } else {
+                    bail!("unexpected non-value in synthesis")
+                };
+                let done = f(term::eq(diff.clone(), cst.clone()))?
+                    || f(term::ge(diff.clone(), cst.clone()))?
+                    || f(term::le(diff, cst))?;
+                if done {
+                    return Ok(true);
+                }
+            }
+
+            previous.push((var, val.clone()))
+        }
+    }
+
+    Ok(false)
+}
+
 /// Non-linear int synthesis.
 pub fn non_lin_int_synth<F>(sample: &VarVals, others: &mut TermVals, mut f: F) -> Res<bool>
 where
@@ -1,6 +1,9 @@
 //! Qualifier synthesis in the theory of reals.
 
-use super::{helpers::n_term_arith_synth, TermVals, TheoSynth};
+use super::{
+    helpers::{cross_coef_synth, cst_pool_synth, n_term_arith_synth, project_combinations},
+    TermVals, TheoSynth,
+};
 use crate::common::*;
 
 /// Real qualifier synthesizer.
@@ -11,6 +14,10 @@ pub struct RealSynth {
     typ: Typ,
     /// True if the synth is done.
     done: bool,
+    /// Constants of the clauses of the predicate, see [`set_constants`][set csts].
+    ///
+    /// [set csts]: #method.set_constants (set_constants function)
+    constants: Vec<Val>,
 }
 impl Default for RealSynth {
     fn default() -> Self {
@@ -25,8 +32,24 @@ impl RealSynth {
             expressivity: 0,
             typ: typ::real(),
             done: false,
+            constants: vec![],
         }
     }
+
+    /// Sets the constants level 0 relates the variables of the sample to.
+    ///
+    /// Typically the constant pool of the predicate, see [`Instance::constants_of`][csts].
+    /// Constants that are not reals are ignored.
+    ///
+    /// [csts]: ../../../../instance/struct.Instance.html#method.constants_of
+    /// (constants_of function of Instance)
+    pub fn set_constants(&mut self, constants: &[Val]) {
+        self.constants = constants
+            .iter()
+            .filter(|cst| cst.typ() == self.typ)
+            .cloned()
+            .collect()
+    }
 }
 impl TheoSynth for RealSynth {
     fn typ(&self) -> &Typ {
@@ -62,9 +85,11 @@ impl TheoSynth for RealSynth {
               |_profiler| wrap {
                 let done = n_term_arith_synth(
                   sample, others, & self.typ, 1, & mut f
+                ) ? || n_term_arith_synth(
+                  sample, others, & self.typ, 2, & mut f
                 ) ? ;
                 if ! done {
-                  n_term_arith_synth(sample, others, & self.typ, 2, f)
+                  cst_pool_synth(sample, & self.typ, & self.constants, f)
                 } else {
                   Ok(true)
                 }
@@ -73,7 +98,12 @@ impl TheoSynth for RealSynth {
 
             1 => profile!(
               |_profiler| wrap {
-                non_lin_real_synth(sample, others, f)
+                let done = cross_coef_synth(sample, others, & self.typ, & mut f) ? ;
+                if ! done {
+                  non_lin_real_synth(sample, others, f)
+                } else {
+                  Ok(true)
+                }
               } "learning", "qual", "synthesis", "real", "level 1"
             ),
 
@@ -91,15 +121,25 @@ impl TheoSynth for RealSynth {
     }
 
     /// Only generates ints for now (using `to_int`).
+    ///
+    /// Also generates scaled casts and sums of casts with the integer variables of the sample, see
+    /// [`project_combinations`].
+    ///
+    /// [`project_combinations`]: ../helpers/fn.project_combinations.html
+    /// (project_combinations function)
     fn project(&self, sample: &VarVals, typ: &Typ, map: &mut TermVals) -> Res<()> {
         if let typ::RTyp::Int = **typ {
+            let mut casts = vec![];
             for (var, val) in sample.index_iter() {
                 if let val::RVal::R(ref r) = val.get() {
                     let val = Op::ToInt.eval(vec![val::real(r.clone())])?;
-                    let prev = map.insert(term::to_int(term::var(var, typ::real())), val);
-                    debug_assert_eq!(prev, None)
+                    let cast = term::to_int(term::var(var, typ::real()));
+                    let prev = map.insert(cast.clone(), val);
+                    debug_assert_eq!(prev, None);
+                    casts.push(cast)
                 }
             }
+            project_combinations(sample, typ, casts, map)?
         }
         Ok(())
     }
@@ -71,11 +71,16 @@ use crate::{common::*, consts::keywords, info::VarInfo};
 mod ptterms;
 pub use self::ptterms::*;
 
+#[cfg(test)]
+mod test;
+
 /// Result yielded by the parser.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Parsed {
     /// Check-sat.
     CheckSat,
+    /// Check-sat-assuming, the clauses deactivated are stored in the instance.
+    CheckSatAssuming,
     /// Get-model.
     GetModel,
     /// Get unsat core.
@@ -88,6 +93,8 @@ pub enum Parsed {
     Items,
     /// Reset.
     Reset,
+    /// Reset-assertions.
+    ResetAssertions,
     /// End of file.
     Eof,
 }
@@ -221,6 +228,104 @@ impl<T: ::std::io::BufRead> ItemRead for T {
     }
 }
 
+/// Parses SMT-LIB 2 items from a `BufRead`er, one item at a time.
+///
+/// Created by [`Instance::parse_stream`]. Each call to `next` reads exactly one item, parses it
+/// and updates the instance. The item buffer is cleared after each item, so peak memory is bounded
+/// by the size of the largest item rather than that of the whole input. Line offsets are
+/// maintained across items so that parse errors still mention the right line.
+///
+/// Yields the result of parsing each item. A parse error does not stop the iteration. A
+/// `(reset)` resets both the parser context and the instance before being yielded. Iteration ends
+/// on `eof`, or after an `(exit)` is yielded.
+///
+/// [`Instance::parse_stream`]: ../common/struct.Instance.html#method.parse_stream
+/// (Instance's parse_stream function)
+pub struct ParseStream<'a, R> {
+    /// Reader the items come from.
+    reader: R,
+    /// Instance the items are parsed into.
+    instance: &'a mut Instance,
+    /// Item buffer.
+    buf: String,
+    /// Parser context.
+    cxt: ParserCxt,
+    /// Line offset of the parser.
+    line_off: usize,
+    /// Profiler.
+    profiler: Profiler,
+    /// True if iteration is over.
+    done: bool,
+}
+impl<'a, R: ::std::io::BufRead> ParseStream<'a, R> {
+    /// Constructor.
+    pub fn new(reader: R, instance: &'a mut Instance) -> Self {
+        ParseStream {
+            reader,
+            instance,
+            buf: String::with_capacity(2000),
+            cxt: ParserCxt::new(),
+            line_off: 0,
+            profiler: Profiler::new(),
+            done: false,
+        }
+    }
+
+    /// Number of lines read so far.
+    pub fn line_off(&self) -> usize {
+        self.line_off
+    }
+}
+impl<'a, R: ::std::io::BufRead> Iterator for ParseStream<'a, R> {
+    type Item = Res<Parsed>;
+    fn next(&mut self) -> Option<Res<Parsed>> {
+        while !self.done {
+            self.buf.clear();
+            let lines_parsed = match self
+                .reader
+                .read_item(&mut self.buf)
+                .chain_err(|| "while reading input")
+            {
+                Ok(lines_parsed) => lines_parsed,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+
+            if lines_parsed == 0 {
+                self.done = true;
+                break;
+            }
+
+            let res = self
+                .cxt
+                .parser(&self.buf, self.line_off, &self.profiler)
+                .parse(self.instance);
+            self.line_off += lines_parsed;
+
+            match res {
+                // Only whitespace and comments, keep going.
+                Ok(Parsed::Eof) => continue,
+                Ok(Parsed::Reset) => {
+                    if let Err(e) = self.cxt.reset() {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                    *self.instance = Instance::new()
+                }
+                Ok(Parsed::ResetAssertions) => self.instance.reset_assertions(),
+                Ok(Parsed::Exit) => self.done = true,
+                _ => (),
+            }
+
+            return Some(res);
+        }
+
+        None
+    }
+}
+
 /// String cursor.
 type Cursor = usize;
 
@@ -360,6 +465,24 @@ pub struct ParserCxt {
     mem: Vec<Cursor>,
     /// Map from predicate names to predicate indices.
     pred_name_map: BTreeMap<String, PrdIdx>,
+    /// Datatypes declared so far, forgotten on reset.
+    dtyps: Vec<String>,
+    /// Functions defined so far, forgotten on reset.
+    funs: Vec<String>,
+    /// Function signatures registered so far, forgotten on reset.
+    ///
+    /// Signatures are consumed when the function is defined, the ones left belong to definitions
+    /// that failed to parse.
+    sigs: Vec<String>,
+    /// Datatypes referenced in datatype declarations but not declared yet.
+    ///
+    /// Stores the name of the datatype referenced, the name of the datatype referencing it, and
+    /// the line of the reference.
+    undeclared_dtyps: Vec<(String, String, usize)>,
+    /// Variables declared with `declare-var` so far, in declaration order, forgotten on reset.
+    ///
+    /// These are the implicitly universally quantified variables of `rule`s.
+    vars: Vec<(String, Typ)>,
 }
 impl ParserCxt {
     /// Constructor.
@@ -368,6 +491,11 @@ impl ParserCxt {
             term_stack: Vec::with_capacity(17),
             mem: Vec::with_capacity(17),
             pred_name_map: BTreeMap::new(),
+            dtyps: vec![],
+            funs: vec![],
+            sigs: vec![],
+            undeclared_dtyps: vec![],
+            vars: vec![],
         }
     }
 
@@ -385,14 +513,37 @@ impl ParserCxt {
             cursor: 0,
             line_off,
             bindings: Vec::with_capacity(7),
+            var_scope: 0,
             functions: BTreeMap::new(),
             _profiler,
         }
     }
 
     /// Resets the parser.
-    pub fn reset(&mut self) {
-        self.pred_name_map.clear()
+    ///
+    /// Forgets the predicates declared and the functions defined so far, including the
+    /// signatures of functions whose definition failed. Also forgets the datatypes declared,
+    /// unless `--keep_dtyps_on_reset` is active.
+    pub fn reset(&mut self) -> Res<()> {
+        self.term_stack.clear();
+        self.mem.clear();
+        self.pred_name_map.clear();
+        self.undeclared_dtyps.clear();
+        self.vars.clear();
+        for fun in self.funs.drain(0..) {
+            let _ = fun::forget(&fun);
+        }
+        for sig in self.sigs.drain(0..) {
+            let _ = fun::forget_sig(&sig);
+        }
+        if conf.keep_dtyps_on_reset {
+            self.dtyps.clear()
+        } else {
+            for dtyp in self.dtyps.drain(0..) {
+                dtyp::forget(&dtyp).chain_err(|| "while resetting")?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -440,6 +591,12 @@ pub struct Parser<'cxt, 's> {
     line_off: usize,
     /// Stack of bindings.
     bindings: Vec<BTreeMap<&'s str, PTTerms>>,
+    /// Number of binding scopes opened before the variables of the current clause were declared.
+    ///
+    /// Bindings of these scopes do not shadow clause variables, see [`get_bind_of`].
+    ///
+    /// [`get_bind_of`]: #method.get_bind_of (get_bind_of function)
+    var_scope: usize,
     /// Functions we are currently parsing.
     ///
     /// Only used when parsing a `define-funs-rec`.
@@ -465,6 +622,21 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.error(pos, msg)
     }
 
+    /// Line of a position, taking the line offset into account.
+    fn line_of(&self, pos: Pos) -> usize {
+        let mut char_pos = *pos;
+        let mut line_count = self.line_off;
+        for line in self.string.lines() {
+            line_count += 1;
+            if char_pos <= line.len() {
+                break;
+            } else {
+                char_pos -= line.len() + 1
+            }
+        }
+        line_count
+    }
+
     /// Generates a parse error at the given position.
     fn error<S: Into<String>>(&self, char_pos: Pos, msg: S) -> ErrorKind {
         let mut char_pos = *char_pos;
@@ -672,22 +844,31 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         let ident_start_pos = self.pos();
         if let Some(char) = self.next() {
             if char == "|" {
-                let (mut legal_unquoted, mut is_first) = (true, true);
-                while let Some(char) = self.next() {
-                    if char == "|" {
-                        return Ok(Some(if legal_unquoted {
-                            &self.string[*ident_start_pos + 1..self.cursor - 1]
-                        } else {
-                            &self.string[*ident_start_pos..self.cursor]
-                        }));
-                    } else {
+                // Quoted identifiers can contain anything but `|`, including `;`, newlines and
+                // non-ascii characters, so look for the closing `|` directly.
+                let content_start = self.cursor;
+                if let Some(len) = self.string[content_start..].find('|') {
+                    let content = &self.string[content_start..content_start + len];
+                    self.cursor = content_start + len + 1;
+
+                    let (mut legal_unquoted, mut is_first) = (true, true);
+                    let mut buf = [0; 4];
+                    for char in content.chars() {
+                        let char: &str = char.encode_utf8(&mut buf);
                         legal_unquoted = legal_unquoted
                             && ((!is_first && char.is_alphanumeric())
                                 || (is_first && char.is_alphabetic())
                                 || id_special_chars.contains(char));
                         is_first = false;
                     }
+
+                    return Ok(Some(if legal_unquoted {
+                        content
+                    } else {
+                        &self.string[*ident_start_pos..self.cursor]
+                    }));
                 }
+                self.cursor = self.string.len();
                 bail!(self.error(
                     ident_start_pos,
                     "expected `|` closing this quoted identifier, \
@@ -742,17 +923,138 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         }
     }
 
+    /// Skips an s-expression, an atom or a parenthesized list of s-expressions.
+    fn skip_sexpr(&mut self) -> Res<()> {
+        let start_pos = self.pos();
+        let mut depth = 0;
+        loop {
+            self.ws_cmt();
+            match self.next() {
+                Some("(") => depth += 1,
+                Some(")") if depth > 0 => depth -= 1,
+                Some(")") => {
+                    self.move_back(1);
+                    bail!(self.error_here("expected s-expression"))
+                }
+                Some("|") => {
+                    if !self.eat_until('|', true) {
+                        bail!(self.error_here("expected closing `|`, found <eof>"))
+                    }
+                }
+                Some("\"") => {
+                    if !self.eat_until('"', true) {
+                        bail!(self.error_here("expected closing `\"`, found <eof>"))
+                    }
+                }
+                Some(_) => {
+                    while let Some(char) = self.peek() {
+                        if char == "(" || char == ")" || char == ";" || char.trim().is_empty() {
+                            break;
+                        }
+                        self.cursor += 1
+                    }
+                }
+                None => {
+                    self.backtrack_to(start_pos);
+                    bail!(self.error_here("could not find the end of this s-expression"))
+                }
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Parses the opening of an annotation `(! <term> <attribute>*)`, if any.
+    fn annot_opt(&mut self) -> bool {
+        let start_pos = self.pos();
+        if self.tag_opt("(") {
+            self.ws_cmt();
+            if self.tag_opt("!") {
+                return true;
+            }
+        }
+        self.backtrack_to(start_pos);
+        false
+    }
+
+    /// Parses the attributes of an annotation and its closing parenthesis.
+    ///
+    /// Only `:named` is meaningful, this function returns its value if any. Other attributes,
+    /// such as `:pattern`, are skipped along with their value.
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// let instance = hoice::parse::instance("
+    ///   (declare-fun P (Int) Bool)
+    ///   (assert (!
+    ///     (forall ((n Int)) (! (=> (P n) (P (+ n 1))) :pattern ((P n)) :weight 3 :skolemid))
+    ///     :pattern ((P 0)) :named step
+    ///   ))
+    /// ");
+    /// assert_eq! { instance.clauses().len(), 1 }
+    /// assert! { instance.old_clause_of_name("step").is_some() }
+    /// ```
+    fn attributes(&mut self) -> Res<Option<&'s str>> {
+        let mut name = None;
+        loop {
+            self.ws_cmt();
+            if self.tag_opt(")") {
+                return Ok(name);
+            }
+
+            self.tag_err(":", "expected an attribute or `)`")?;
+            let (_, key) = self.ident()?;
+            self.ws_cmt();
+
+            if key == "named" {
+                let (pos, ident) = self
+                    .ident()
+                    .chain_err(|| "expected identifier after `:named` tag")?;
+                if name.is_some() {
+                    bail!(self.error(pos, "illegal second `:named` attribute"))
+                }
+                name = Some(ident)
+            } else {
+                log_debug! { "ignoring attribute `:{}`", key }
+                match self.peek() {
+                    Some(":") | Some(")") | None => (),
+                    Some(_) => self.skip_sexpr()?,
+                }
+            }
+        }
+    }
+
     /// Parses a set-info.
-    fn set_info(&mut self) -> Res<bool> {
+    ///
+    /// Only `:hint` and `:status` are meaningful, see [`hint`][hint] for the former. The latter
+    /// stores the expected status on the instance, checked against the result in
+    /// `--check_status` mode. All other infos are ignored.
+    ///
+    /// [hint]: #method.hint (hint function)
+    fn set_info(&mut self, instance: &mut Instance) -> Res<bool> {
         if !self.word_opt("set-info") {
             return Ok(false);
         }
         self.ws_cmt();
         self.tag(":")?;
         self.ws_cmt();
-        let _ = self.ident()?;
+        let (_, key) = self.ident()?;
         self.ws_cmt();
-        if self.tag_opt("\"") {
+        if key == "hint" {
+            self.hint(instance)?
+        } else if key == "status" {
+            let (pos, status) = self.ident()?;
+            match status {
+                "sat" => instance.set_expected_status(true),
+                "unsat" => instance.set_expected_status(false),
+                "unknown" => (),
+                _ => bail!(self.error(
+                    pos,
+                    format!("expected `sat`, `unsat` or `unknown`, found `{}`", status)
+                )),
+            }
+        } else if self.tag_opt("\"") {
             let found_it = self.eat_until('"', true);
             if !found_it {
                 bail!(self.error_here("expected closing `\"`, found <eof>"))
@@ -763,8 +1065,63 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         Ok(true)
     }
 
+    /// Parses a hint `(<pred> <term>)` for a predicate.
+    ///
+    /// The term is a boolean term over the signature of the predicate, where the `i`th argument is
+    /// `v_i`. Hints are used as qualifiers, and as the initial candidate for the predicate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, parse::ParserCxt };
+    /// let mut instance = Instance::new();
+    /// let mut cxt = ParserCxt::new();
+    /// let profiler = Profiler::new();
+    /// cxt.parser("\
+    ///     (declare-fun Inv (Int Int) Bool)
+    ///     (set-info :hint (Inv (>= v_0 v_1)))
+    /// ", 0, &profiler).parse(&mut instance).expect("during parsing");
+    /// let pred: PrdIdx = 0.into();
+    /// assert_eq! {
+    ///     instance[pred].hints(), vec![ term::ge(term::int_var(0), term::int_var(1)) ]
+    /// }
+    /// ```
+    fn hint(&mut self, instance: &mut Instance) -> Res<()> {
+        self.tag("(")?;
+        self.ws_cmt();
+
+        let (pred_pos, name) = self.ident()?;
+        let pred = if let Some(pred) = self.cxt.pred_name_map.get(name).cloned() {
+            pred
+        } else {
+            bail!(self.error(pred_pos, format!("unknown predicate `{}`", conf.bad(name))))
+        };
+        self.ws_cmt();
+
+        let var_infos = sig_var_infos(instance[pred].sig());
+        let map = var_map_of(&var_infos);
+        let term_pos = self.pos();
+        let hint = if let Some(hint) = self.term_opt(&var_infos, &map, instance)? {
+            hint
+        } else {
+            bail!(self.error(term_pos, "expected term"))
+        };
+        if !hint.typ().is_bool() {
+            bail!(self.error(
+                term_pos,
+                format!("expected boolean term, found term of sort {}", hint.typ())
+            ))
+        }
+
+        self.ws_cmt();
+        self.tag(")")?;
+
+        instance.add_hint(pred, hint);
+        Ok(())
+    }
+
     /// Set-option.
-    fn set_option(&mut self) -> Res<Option<(&'s str, &'s str)>> {
+    fn set_option(&mut self) -> Res<Option<(&'s str, Pos, &'s str)>> {
         let start_pos = self.pos();
         if !self.word_opt("set-option") {
             return Ok(None);
@@ -773,14 +1130,17 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.tag(":")?;
         let key = self.ident()?.1;
         self.ws_cmt();
+        let val_pos = self.pos();
         let val = if self.tag_opt("|") {
-            if let Some(res) = self.get_until('|', true) {
+            if let Some(res) = self.get_until('|', false) {
+                self.tag("|")?;
                 res
             } else {
                 bail!(self.error_here("could not find closing `|` opened"))
             }
         } else if self.tag_opt("\"") {
-            if let Some(res) = self.get_until('"', true) {
+            if let Some(res) = self.get_until('"', false) {
+                self.tag("\"")?;
                 res
             } else {
                 bail!(self.error_here("could not find closing `\"` opened"))
@@ -791,7 +1151,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             self.backtrack_to(start_pos);
             bail!(self.error_here("could not find closing `)` for this set-option"))
         };
-        Ok(Some((key, val)))
+        Ok(Some((key, val_pos, val)))
     }
 
     /// Parses an echo.
@@ -910,6 +1270,9 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     }
 
     /// Tries to parse a sort given some optional type parameters.
+    ///
+    /// Malformed array sorts and builtin sorts between parentheses yield errors pointing at the
+    /// opening paren of the sort.
     fn inner_sort_opt(
         &mut self,
         type_params: Option<&BTreeMap<&'s str, dtyp::TPrmIdx>>,
@@ -957,6 +1320,14 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                     } else {
                         None
                     }
+                } else if let Some(builtin) = ["Int", "Real", "Bool"]
+                    .iter()
+                    .find(|builtin| self.word_opt(builtin))
+                {
+                    bail!(self.error(
+                        current_pos,
+                        format!("unexpected parentheses around sort {}", conf.bad(builtin))
+                    ))
                 } else if let Some((pos, name)) = self.ident_opt()? {
                     stack.push(CTyp::DTyp {
                         name,
@@ -1007,6 +1378,11 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                             stack.push(CTyp::ArraySrc { pos, src });
                             // Need to parse the domain now.
                             continue 'go_down;
+                        } else if self.peek() == Some(")") {
+                            bail!(self.error(
+                                pos,
+                                "array sort expects exactly two sort parameters, got none"
+                            ))
                         } else {
                             Err::<_, Error>(self.error(pos, "while parsing this array sort").into())
                                 .chain_err(|| self.error(current_pos, "expected index sort"))?
@@ -1020,14 +1396,23 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                             // Parse closing paren.
                             self.ws_cmt();
                             if !self.tag_opt(")") {
+                                if self.inner_sort_opt(type_params)?.is_some() {
+                                    bail!(self.error(
+                                        pos,
+                                        "array sort expects exactly two sort parameters, got more"
+                                    ))
+                                }
                                 let err: Error =
                                     self.error(pos, "while parsing this array sort").into();
-                                Err(err).chain_err(|| {
-                                    self.error(current_pos, "expected expected closing paren")
-                                })?
+                                Err(err).chain_err(|| self.error_here("expected closing paren"))?
                             }
 
                             continue 'go_up;
+                        } else if self.peek() == Some(")") {
+                            bail!(self.error(
+                                pos,
+                                "array sort expects exactly two sort parameters, got one"
+                            ))
                         } else {
                             Err::<_, Error>(self.error(pos, "while parsing this array sort").into())
                                 .chain_err(|| self.error(current_pos, "expected domain sort"))?
@@ -1151,6 +1536,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
 
         let fun = FunSig::new(name, args, typ);
         fun::register_sig(fun.clone())?;
+        self.cxt.sigs.push(fun.name.clone());
 
         let prev = self
             .functions
@@ -1262,6 +1648,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
 
             let fun = FunSig::new(name, args, typ);
             fun::register_sig(fun.clone())?;
+            self.cxt.sigs.push(fun.name.clone());
 
             // Check this is the first time we see this function and populate
             // dependencies.
@@ -1338,7 +1725,9 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                 bail!(e.chain_err(|| self.error(pos, "declared here")))
             };
 
-            fun::new(def).chain_err(|| self.error(pos, "while registering this function"))?;
+            let fun =
+                fun::new(def).chain_err(|| self.error(pos, "while registering this function"))?;
+            self.cxt.funs.push(fun.name().clone())
         }
 
         Ok(())
@@ -1468,9 +1857,12 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             return Ok(false);
         }
 
+        self.ws_cmt();
+
         let (dtyp_pos, dtyp_ident) = self
             .ident()
             .chain_err(|| "while parsing datatype declaration")?;
+        self.ws_cmt();
 
         let mut dtyp = dtyp::RDTyp::new(dtyp_ident);
 
@@ -1484,7 +1876,56 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             )
         })?;
 
-        Ok(true)
+        match dtyp::new_recs_unchecked(vec![dtyp]) {
+            Err((_, err)) => bail!(err.chain_err(
+                || self.error(dtyp_pos, "while parsing the declaration for this datatype")
+            )),
+            Ok(dtyps) => {
+                self.register_dtyps(dtyps);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Registers some datatypes that were just declared.
+    ///
+    /// Remembers the datatypes they reference that are not declared yet, and forgets the ones
+    /// they declare. See [`check_undeclared_dtyps`].
+    ///
+    /// [`check_undeclared_dtyps`]: #method.check_undeclared_dtyps
+    /// (check_undeclared_dtyps function)
+    fn register_dtyps(&mut self, dtyps: Vec<DTyp>) {
+        for dtyp in dtyps {
+            for (pos, name) in dtyp.undeclared() {
+                let line = self.line_of(pos);
+                self.cxt
+                    .undeclared_dtyps
+                    .push((name, dtyp.name.clone(), line))
+            }
+            self.cxt.dtyps.push(dtyp.name.clone())
+        }
+        self.cxt
+            .undeclared_dtyps
+            .retain(|(name, _, _)| dtyp::get(name).is_err())
+    }
+
+    /// Fails if some datatype declarations reference datatypes that are still not declared.
+    ///
+    /// Datatypes can reference datatypes declared in later commands, this is checked at
+    /// `check-sat` time.
+    fn check_undeclared_dtyps(&mut self) -> Res<()> {
+        if let Some((name, dtyp, line)) = self.cxt.undeclared_dtyps.first().cloned() {
+            let e: Error = format!(
+                "undeclared datatype `{}` referenced at line {}, in the declaration of `{}`",
+                conf.bad(&name),
+                line,
+                conf.emph(&dtyp)
+            )
+            .into();
+            let pos = self.pos();
+            bail!(e.chain_err(|| self.error(pos, "while checking the datatypes declared so far")))
+        }
+        Ok(())
     }
 
     /// Multiple datatype declaration.
@@ -1550,12 +1991,15 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.tag(")")
             .chain_err(|| "closing the list of datatype declaration")?;
 
-        match dtyp::new_recs(final_dtyps, |pos, blah| self.error(pos, blah)) {
+        match dtyp::new_recs_unchecked(final_dtyps) {
             Err((index, err)) => bail!(err.chain_err(|| self.error(
                 dtyps_pos[index],
                 "while parsing the declaration for this datatype"
             ))),
-            Ok(_) => Ok(true),
+            Ok(dtyps) => {
+                self.register_dtyps(dtyps);
+                Ok(true)
+            }
         }
     }
 
@@ -1568,6 +2012,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.ws_cmt();
         let (pos, ident) = self.ident()?;
         self.ws_cmt();
+        self.no_par("predicates")?;
         self.tag("(")?;
 
         let mut sorts = Vec::with_capacity(11);
@@ -1600,6 +2045,30 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         Ok(true)
     }
 
+    /// Fails on a `(par ...)` polymorphic declaration.
+    ///
+    /// Polymorphic predicates and functions are not supported, the error points at the `par`
+    /// keyword. Does not consume anything otherwise.
+    fn no_par(&mut self, what: &str) -> Res<()> {
+        let start = self.pos();
+        if self.tag_opt("(") {
+            self.ws_cmt();
+            let par_pos = self.pos();
+            if self.word_opt("par") {
+                bail!(self.error(
+                    par_pos,
+                    format!(
+                        "polymorphic {} are not supported (`{}` declaration)",
+                        what,
+                        conf.bad("par")
+                    )
+                ))
+            }
+        }
+        self.backtrack_to(start);
+        Ok(())
+    }
+
     /// Parses some arguments `( (<id> <ty>) ... )`.
     pub fn args(
         &mut self,
@@ -1665,6 +2134,31 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         None
     }
 
+    /// Finds the binding an identifier refers to, if any.
+    ///
+    /// Scoping rules for identifiers, from highest to lowest priority:
+    ///
+    /// - let-bindings opened after the variables of the clause were declared, innermost first;
+    /// - clause variables (in `map`);
+    /// - let-bindings opened before the variables of the clause were declared;
+    /// - predicates, and then datatype constructors.
+    ///
+    /// So a let-binding shadows predicates, functions and clause variables, unless it is outside
+    /// of the quantifier declaring the clause variable. This function only handles the
+    /// let-bindings: if it returns `None` and `id` is in `map`, it is a clause variable.
+    fn get_bind_of(&self, id: &str, map: &BTreeMap<&str, VarIdx>) -> Option<&PTTerms> {
+        if map.contains_key(id) {
+            for bindings in self.bindings[self.var_scope..].iter().rev() {
+                if let Some(tterms) = bindings.get(id) {
+                    return Some(tterms);
+                }
+            }
+            None
+        } else {
+            self.get_bind(id)
+        }
+    }
+
     /// Parses the end of some consecutive let-bindings.
     #[inline]
     fn close_let_bindings(&mut self, count: LetCount) -> Res<()> {
@@ -1688,7 +2182,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     fn let_bindings(
         &mut self,
         var_map: &VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         instance: &Instance,
     ) -> Res<LetCount> {
         let mut n = 0;
@@ -1846,25 +2340,46 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         }
     }
 
+    /// Parses the `-` of a negative literal, *i.e.* a `-` directly followed by a digit.
+    ///
+    /// SMT-LIB requires `(- 5)`, but a lot of hand-written files use `-5`. Since `-` is a legal
+    /// identifier character, this must run before identifier parsing.
+    fn literal_minus_opt(&mut self) -> bool {
+        let start_pos = self.pos();
+        if self.tag_opt("-") {
+            if self.peek().map(|char| char.is_numeric()).unwrap_or(false) {
+                return true;
+            }
+            self.backtrack_to(start_pos)
+        }
+        false
+    }
+
     /// Integer parser (numeral not followed by a `.`).
+    ///
+    /// Also accepts negative literals `-<numeral>`.
     pub fn int(&mut self) -> Option<Int> {
         let start_pos = self.pos();
+        let negative = self.literal_minus_opt();
         let num = self.numeral();
-        if num.is_some() && self.peek() == Some(".") {
+        if num.is_none() || self.peek() == Some(".") {
             self.backtrack_to(start_pos);
             return None;
         }
-        num
+        num.map(|num| if negative { -num } else { num })
     }
 
     /// Real parser.
     ///
-    /// Decimal or fraction.
+    /// Decimal or fraction. Also accepts negative decimals `-<decimal>`.
     pub fn real(&mut self) -> Res<Option<Rat>> {
         let start_pos = self.pos();
 
+        let negative = self.literal_minus_opt();
         if let Some(res) = self.decimal() {
-            return Ok(Some(res));
+            return Ok(Some(if negative { -res } else { res }));
+        } else if negative {
+            self.backtrack_to(start_pos)
         }
 
         if self.tag_opt("(") {
@@ -1937,7 +2452,21 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         debug_assert_eq! { args_pos.len(), args.len() }
 
         match term::try_app(op, args) {
-            Ok(term) => Ok((term, op_pos)),
+            Ok(term) => {
+                // Machine integers, the results of integer arithmetic operations wrap around.
+                let term = match (op, conf.instance.int_width) {
+                    (Op::Add, Some(width))
+                    | (Op::Sub, Some(width))
+                    | (Op::Mul, Some(width))
+                    | (Op::CMul, Some(width))
+                        if term.typ().is_int() =>
+                    {
+                        term::wrap_int(term, width)
+                    }
+                    _ => term,
+                };
+                Ok((term, op_pos))
+            }
             Err(TypError::Typ {
                 expected,
                 obtained,
@@ -2018,6 +2547,15 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         //   )
         // } ;
 
+        // Integer defaults are allowed for arrays of reals, as in
+        // `((as const (Array Int Real)) 0)`.
+        let arg = if tgt.is_real() && arg.typ().is_int() {
+            term::to_real(arg)
+        } else {
+            arg
+        };
+
+        // Casting takes care of datatype defaults with unknown parameters, like `nil`.
         let default = match arg.cast(tgt) {
             Ok(None) => arg,
             Ok(Some(term)) => term,
@@ -2265,6 +2803,8 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                     Some(Op::IDiv)
                 } else if self.word_opt("istinct") {
                     Some(Op::Distinct)
+                } else if self.word_opt("typ-size") {
+                    Some(Op::DTypSize)
                 } else {
                     None
                 }
@@ -2311,7 +2851,20 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                 }
             }
             Some("+") => Some(Op::Add),
-            Some("-") => Some(Op::Sub),
+            Some("-") => {
+                // `(-5)`, most likely a parenthesized negative literal.
+                if let Some(num) = self.numeral() {
+                    bail!(self.error(
+                        start_pos,
+                        format!(
+                            "illegal application of negative literal `-{}`, \
+                             write `-{}` or `(- {})` instead",
+                            num, num, num
+                        )
+                    ))
+                }
+                Some(Op::Sub)
+            }
             Some("*") => Some(Op::Mul),
             Some("/") => Some(Op::Div),
             Some(_) => None,
@@ -2329,7 +2882,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     pub fn term_opt(
         &mut self,
         var_map: &VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         instance: &Instance,
     ) -> Res<Option<Term>> {
         let start_pos = self.pos();
@@ -2345,6 +2898,41 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         res
     }
 
+    /// Parses a negated numeral or decimal `(- <cst>)` as a constant.
+    ///
+    /// Backtracks and returns `None` if the input is not of this form, in which case the `-`
+    /// application goes through the usual operator path.
+    fn neg_cst_opt(&mut self) -> Option<Term> {
+        let start_pos = self.pos();
+
+        if self.tag_opt("(") {
+            self.ws_cmt();
+            if self.tag_opt("-") {
+                let op_end = self.pos();
+                self.ws_cmt();
+                // `-` must be followed by whitespace, otherwise it's an identifier.
+                if op_end != self.pos() {
+                    let cst = if let Some(int) = self.int() {
+                        Some(term::int(val::machine_int(-int)))
+                    } else if let Some(real) = self.decimal() {
+                        Some(term::real(-real))
+                    } else {
+                        None
+                    };
+                    if let Some(cst) = cst {
+                        self.ws_cmt();
+                        if self.tag_opt(")") {
+                            return Some(cst);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.backtrack_to(start_pos);
+        None
+    }
+
     /// Parses a token from a term.
     ///
     /// Returns a term when the next token was a constant or a variable. Returns `None` when
@@ -2352,19 +2940,18 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     fn inner_term_token(
         &mut self,
         var_map: &VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         bind_count: LetCount,
     ) -> Res<TermTokenRes> {
         let term = if let Some(int) = self.int() {
-            term::int(int)
+            term::int(val::machine_int(int))
         } else if let Some(real) = self.real()? {
             term::real(real)
         } else if let Some(b) = self.bool() {
             term::bool(b)
         } else if let Some((pos, id)) = self.ident_opt()? {
-            if let Some(idx) = map.get(id) {
-                term::var(*idx, var_map[*idx].typ.clone())
-            } else if let Some(ptterms) = self.get_bind(id) {
+            // See `get_bind_of` for the scoping rules.
+            if let Some(ptterms) = self.get_bind_of(id, map) {
                 if let Some(term) = ptterms
                     .to_term()
                     .chain_err(|| format!("while retrieving binding for {}", conf.emph(id)))?
@@ -2374,6 +2961,8 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                     // Not in a legal term.
                     return Ok(TermTokenRes::NotATerm);
                 }
+            } else if let Some(idx) = map.get(id) {
+                term::var(*idx, var_map[*idx].typ.clone())
             } else if self.cxt.pred_name_map.get(id).is_some() {
                 // Identifier is a predicate, we're not in a legal term.
                 return Ok(TermTokenRes::NotATerm);
@@ -2401,6 +2990,8 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             } else {
                 bail!(self.error(pos, format!("unknown identifier `{}`", conf.bad(id))))
             }
+        } else if let Some(cst) = self.neg_cst_opt() {
+            cst
         } else if self.tag_opt("(") {
             self.ws_cmt();
             let op_pos = self.pos();
@@ -2521,7 +3112,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     fn inner_term_opt(
         &mut self,
         var_map: &VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         instance: &Instance,
     ) -> Res<Option<Term>> {
         if !self.cxt.term_stack.is_empty() {
@@ -2662,6 +3253,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
 
         let (name_pos, name) = self.ident()?;
         self.ws_cmt();
+        self.no_par("functions")?;
 
         let mut var_info = VarInfos::new();
         let mut map = BTreeMap::new();
@@ -2700,9 +3292,9 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         if let Some(term) = body.to_term()? {
             use crate::fun::FunSig;
             let fun = FunSig::new(name, var_info, out_sort).into_fun(term);
-            let _ = fun::new(fun)
+            let fun = fun::new(fun)
                 .chain_err(|| self.error(name_pos, "while registering this function"))?;
-            ()
+            self.cxt.funs.push(fun.name().clone())
         } else {
             let prev = instance.add_define_fun(name, var_info, body);
 
@@ -2718,7 +3310,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     fn ptterm_args(
         &mut self,
         var_map: &VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         instance: &Instance,
     ) -> Res<VarMap<(Pos, PTTerms)>> {
         let mut res = VarMap::with_capacity(11);
@@ -2748,7 +3340,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         pred: PrdIdx,
         pred_pos: Pos,
         var_map: &VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         instance: &Instance,
     ) -> Res<Option<PTTerms>> {
         let mut args = VarMap::with_capacity(11);
@@ -2820,7 +3412,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     fn top_term(
         &mut self,
         var_map: &VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         instance: &Instance,
     ) -> Res<PTTerms> {
         if let Some(res) = self.top_term_opt(var_map, map, instance)? {
@@ -2833,7 +3425,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     fn top_term_opt(
         &mut self,
         var_map: &VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         instance: &Instance,
     ) -> Res<Option<PTTerms>> {
         conf.check_timeout()?;
@@ -2848,7 +3440,11 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             .ident_opt()
             .chain_err(|| "while trying to parse a top term (1)")?
         {
-            if let Some(idx) = self.cxt.pred_name_map.get(id) {
+            // Clause variables are handled by `term_opt` above. See `get_bind_of` for the
+            // scoping rules.
+            if let Some(ptterms) = self.get_bind_of(id, map) {
+                Ok(Some(ptterms.clone()))
+            } else if let Some(idx) = self.cxt.pred_name_map.get(id) {
                 let idx = *idx;
                 if instance[idx].sig.is_empty() {
                     Ok(Some(PTTerms::TTerm(TTerm::P {
@@ -2862,12 +3458,10 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                             "illegal nullary application of predicate `{}`, \
                              this predicate takes {} arguments",
                             conf.bad(&instance[idx].name),
-                            instance[idx].sig.len()
+                            instance[idx].arity()
                         )
                     ))
                 }
-            } else if let Some(ptterms) = self.get_bind(id) {
-                Ok(Some(ptterms.clone()))
             } else {
                 bail!(self.error(pos, format!("unknown ident `{}`", conf.bad(id))))
             }
@@ -2955,7 +3549,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     fn parse_ptterms(
         &mut self,
         var_map: &VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         instance: &Instance,
     ) -> Res<PTTerms> {
         enum Frame {
@@ -3189,14 +3783,25 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             }
         }
 
+        // Bindings opened from now on shadow the clause variables.
+        let var_scope = ::std::mem::replace(&mut self.var_scope, self.bindings.len());
+
         self.ws_cmt();
         let outter_bind_count = self.let_bindings(&var_map, &hash_map, instance)?;
 
+        self.ws_cmt();
+        let annotated = self.annot_opt();
+
         self.ws_cmt();
         let idx = self.parse_clause(var_map, &hash_map, instance, false)?;
 
+        if annotated {
+            self.body_attributes(&idx, instance)?
+        }
+
         self.ws_cmt();
         self.close_let_bindings(outter_bind_count)?;
+        self.var_scope = var_scope;
 
         for _ in 0..closing_parens {
             self.ws_cmt();
@@ -3273,9 +3878,20 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             }
         }
 
+        // Bindings opened from now on shadow the clause variables.
+        let var_scope = ::std::mem::replace(&mut self.var_scope, self.bindings.len());
+
+        self.ws_cmt();
+        let annotated = self.annot_opt();
+
         self.ws_cmt();
         let idx = self.parse_clause(var_map, &hash_map, instance, true)?;
 
+        if annotated {
+            self.body_attributes(&idx, instance)?
+        }
+
+        self.var_scope = var_scope;
         self.ws_cmt();
         self.close_let_bindings(outter_bind_count)?;
 
@@ -3287,18 +3903,46 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         Ok(Some(idx))
     }
 
+    /// Parses the attributes of an annotated quantifier body, see [`attributes`][attributes].
+    ///
+    /// [attributes]: #method.attributes (attributes function)
+    fn body_attributes(&mut self, idx: &ClauseRes, instance: &mut Instance) -> Res<()> {
+        if let Some(name) = self.attributes()? {
+            if let ClauseRes::Added(idx) = *idx {
+                instance.set_old_clause_name(idx, name.into())?
+            }
+        }
+        Ok(())
+    }
+
     fn parse_clause(
         &mut self,
         var_map: VarInfos,
-        map: &BTreeMap<&'s str, VarIdx>,
+        map: &BTreeMap<&str, VarIdx>,
         instance: &mut Instance,
         negated: bool,
     ) -> Res<ClauseRes> {
         profile! { self tick "parsing", "clause" }
+        let res = match self.parse_clause_ptterms(&var_map, map, instance, negated) {
+            Ok(ptterms) => self.add_clauses(instance, var_map, ptterms),
+            Err(e) => Err(e),
+        };
+        profile! { self mark "parsing", "clause" }
+        res
+    }
+
+    /// Parses the body of a clause, negated if `negated`.
+    fn parse_clause_ptterms(
+        &mut self,
+        var_map: &VarInfos,
+        map: &BTreeMap<&str, VarIdx>,
+        instance: &Instance,
+        negated: bool,
+    ) -> Res<PTTerms> {
         self.ws_cmt();
 
         let start_pos = self.pos();
-        let mut ptterms = self.parse_ptterms(&var_map, &map, instance)?;
+        let mut ptterms = self.parse_ptterms(var_map, map, instance)?;
         if !ptterms.typ().is_bool() {
             err_chain! {
               "while parsing clause terms"
@@ -3312,7 +3956,16 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         if negated {
             ptterms = PTTerms::not(ptterms)?
         }
+        Ok(ptterms)
+    }
 
+    /// Adds the clauses corresponding to the body of a clause.
+    fn add_clauses(
+        &mut self,
+        instance: &mut Instance,
+        var_map: VarInfos,
+        ptterms: PTTerms,
+    ) -> Res<ClauseRes> {
         let (mut at_least_one, idx) = (false, instance.next_clause_index());
 
         let mut clauses = ptterms.into_clauses()?.into_iter();
@@ -3328,8 +3981,6 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             }
         }
 
-        profile! { self mark "parsing", "clause" }
-
         if at_least_one {
             Ok(ClauseRes::Added(idx))
         } else {
@@ -3387,19 +4038,8 @@ impl<'cxt, 's> Parser<'cxt, 's> {
 
         self.ws_cmt();
 
-        let start_pos = self.pos();
-        let tagged = if self.tag_opt("(") {
-            self.ws_cmt();
-            if self.tag_opt("!") {
-                self.ws_cmt();
-                true
-            } else {
-                self.backtrack_to(start_pos);
-                false
-            }
-        } else {
-            false
-        };
+        let tagged = self.annot_opt();
+        self.ws_cmt();
 
         let bind_count = self.let_bindings(&VarMap::new(), &BTreeMap::new(), instance)?;
 
@@ -3424,20 +4064,142 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.close_let_bindings(bind_count)?;
 
         if tagged {
-            self.ws_cmt();
-            self.word(":named").chain_err(|| "unexpected tag")?;
-            self.ws_cmt();
-            let (_, ident) = self
-                .ident()
-                .chain_err(|| "expected identifier after `:named` tag")?;
+            if let Some(name) = self.attributes()? {
+                if let Some(idx) = idx.into_option() {
+                    instance.set_old_clause_name(idx, name.into())?
+                }
+            }
+        }
+
+        profile! { self mark "parsing", "assert" }
+
+        Ok(true)
+    }
+
+    /// Parses a `declare-var`, a variable declaration for the `rule`s that follow.
+    ///
+    /// Part of the Datalog-style format, see [`rule`][rule].
+    ///
+    /// [rule]: #method.rule (rule function)
+    fn declare_var(&mut self) -> Res<bool> {
+        if !self.word_opt("declare-var") {
+            return Ok(false);
+        }
+
+        self.ws_cmt();
+        let (pos, ident) = self.ident()?;
+        self.ws_cmt();
+        let sort = self.sort()?;
+
+        if self.cxt.vars.iter().any(|(name, _)| name == ident) {
+            bail!(self.error(
+                pos,
+                format!("variable `{}` is already declared", conf.bad(ident))
+            ))
+        }
+        self.cxt.vars.push((ident.into(), sort));
+
+        Ok(true)
+    }
+
+    /// Variables declared with `declare-var`, indexed in the order they were declared in.
+    fn declared_var_infos(&self) -> VarInfos {
+        let mut var_infos = VarInfos::with_capacity(self.cxt.vars.len());
+        for (name, typ) in &self.cxt.vars {
+            let idx = var_infos.next_index();
+            var_infos.push(VarInfo::new(name.clone(), typ.clone(), idx))
+        }
+        var_infos
+    }
+
+    /// Parses a `rule`.
+    ///
+    /// Part of the Datalog-style format: `(rule <body> <name>?)` is a clause, the free variables
+    /// of which are the ones declared with `declare-var`. The clause only mentions the variables
+    /// actually appearing in `<body>`. An optional name is treated as the name of a `:named`
+    /// assert.
+    fn rule(&mut self, instance: &mut Instance) -> Res<bool> {
+        if !self.word_opt("rule") {
+            return Ok(false);
+        }
+
+        self.ws_cmt();
+        let declared = self.declared_var_infos();
+        let hash_map = var_map_of(&declared);
+
+        // Bindings opened from now on shadow the clause variables.
+        let var_scope = ::std::mem::replace(&mut self.var_scope, self.bindings.len());
+        let ptterms = self.parse_clause_ptterms(&declared, &hash_map, instance, false);
+        self.var_scope = var_scope;
+
+        // Only keep the declared variables the clause mentions.
+        let mut used = VarSet::new();
+        let ptterms = ptterms?;
+        ptterms.vars(&mut used);
+        let mut var_map = VarInfos::with_capacity(used.len());
+        let mut subst = VarMap::with_capacity(declared.len());
+        for info in declared.iter() {
+            let var = if used.contains(&info.idx) {
+                let idx = var_map.next_index();
+                var_map.push(VarInfo::new(info.name.clone(), info.typ.clone(), idx));
+                term::var(idx, info.typ.clone())
+            } else {
+                // Does not appear in the clause.
+                term::var(info.idx, info.typ.clone())
+            };
+            subst.push(PTTerms::tterm(TTerm::T(var)))
+        }
+        let ptterms = ptterms.subst_total(&subst)?;
+        let idx = self.add_clauses(instance, var_map, ptterms)?;
+
+        self.ws_cmt();
+        if let Some((_, name)) = self.ident_opt()? {
             if let Some(idx) = idx.into_option() {
-                instance.set_old_clause_name(idx, ident.into())?
+                instance.set_old_clause_name(idx, name.into())?
             }
+        }
+
+        Ok(true)
+    }
+
+    /// Parses a `query`.
+    ///
+    /// Part of the Datalog-style format: `(query <pred>)` is the clause stating that `<pred>` is
+    /// false for all its arguments. Options such as `:print-certificate true` are ignored.
+    fn query(&mut self, instance: &mut Instance) -> Res<bool> {
+        if !self.word_opt("query") {
+            return Ok(false);
+        }
+
+        self.ws_cmt();
+        let (pos, ident) = self.ident()?;
+        let pred = if let Some(pred) = self.cxt.pred_name_map.get(ident) {
+            *pred
+        } else {
+            bail!(self.error(pos, format!("unknown predicate `{}`", conf.bad(ident))))
+        };
+
+        self.ws_cmt();
+        while self.peek() == Some(":") {
+            self.skip_sexpr()?;
+            self.ws_cmt();
+            self.skip_sexpr()?;
             self.ws_cmt();
-            self.tag(")")?;
         }
 
-        profile! { self mark "parsing", "assert" }
+        let sig = &instance[pred].sig;
+        let mut var_map = VarInfos::with_capacity(sig.len());
+        let mut args = VarMap::with_capacity(sig.len());
+        for (var, typ) in sig.index_iter() {
+            var_map.push(VarInfo::new(var.default_str(), typ.clone(), var));
+            args.push(term::var(var, typ.clone()))
+        }
+
+        let lhs = vec![TTerm::P {
+            pred,
+            args: args.into(),
+        }];
+        self.add_clause(instance, var_map, lhs, TTerm::T(term::fls()))?;
 
         Ok(true)
     }
@@ -3447,6 +4209,76 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.word_opt(keywords::cmd::check_sat)
     }
 
+    /// Parses a check-sat-assuming.
+    ///
+    /// The literals are clause names given by `:named` asserts, or their negation. A negative
+    /// literal deactivates the clause it mentions, other clauses are active. The clauses
+    /// deactivated are stored in the instance, see [`Instance::take_deactivated_clauses`].
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, parse::{ ParserCxt, Parsed } };
+    /// let mut instance = Instance::new();
+    /// let mut cxt = ParserCxt::new();
+    /// let profiler = Profiler::new();
+    /// let res = cxt.parser("\
+    /// (declare-fun P (Int) Bool)
+    /// (assert (! (forall ((n Int)) (P n)) :named g1))
+    /// (assert (! (forall ((n Int)) (=> (P n) false)) :named g2))
+    /// (check-sat-assuming (g1 (not g2)))
+    /// ", 0, &profiler).parse(&mut instance).unwrap();
+    /// assert_eq! { res, Parsed::CheckSatAssuming }
+    /// let g2 = instance.old_clause_of_name("g2").unwrap();
+    /// let deactivated = instance.take_deactivated_clauses();
+    /// assert_eq! { deactivated, vec![g2].into_iter().collect() }
+    /// ```
+    ///
+    /// [`Instance::take_deactivated_clauses`]: ../instance/struct.Instance.html#method.take_deactivated_clauses
+    /// (take_deactivated_clauses function over Instance)
+    fn check_sat_assuming(&mut self, instance: &mut Instance) -> Res<bool> {
+        if !self.word_opt(keywords::cmd::check_sat_assuming) {
+            return Ok(false);
+        }
+
+        let mut deactivated = ClsSet::new();
+
+        self.ws_cmt();
+        self.tag("(")?;
+        self.ws_cmt();
+
+        while !self.tag_opt(")") {
+            let negated = if self.tag_opt("(") {
+                self.ws_cmt();
+                if self.word_opt(keywords::op::not_) {
+                    true
+                } else {
+                    bail!(self.error_here("expected `not` or a clause name"))
+                }
+            } else {
+                false
+            };
+            self.ws_cmt();
+
+            let (pos, name) = self.ident()?;
+            let clause = if let Some(clause) = instance.old_clause_of_name(name) {
+                clause
+            } else {
+                bail!(self.error(pos, format!("unknown clause name `{}`", conf.bad(name))))
+            };
+
+            if negated {
+                self.ws_cmt();
+                self.tag(")").chain_err(|| "closing negated clause name")?;
+                deactivated.insert(clause);
+            }
+
+            self.ws_cmt();
+        }
+
+        instance.set_deactivated_clauses(deactivated);
+
+        Ok(true)
+    }
+
     /// Parses a get-model.
     fn get_model(&mut self) -> bool {
         self.word_opt(keywords::cmd::get_model)
@@ -3472,6 +4304,11 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.word_opt(keywords::cmd::reset)
     }
 
+    /// Parses a reset-assertions command.
+    fn reset_assertions(&mut self) -> bool {
+        self.word_opt(keywords::cmd::reset_assertions)
+    }
+
     /// Parses items, returns true if it found a check-sat.
     pub fn parse(mut self, instance: &mut Instance) -> Res<Parsed> {
         self.ws_cmt();
@@ -3486,15 +4323,23 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             )?;
             self.ws_cmt();
 
-            let start_pos = self.pos();
+            let par_pos = self.pos();
+            if self.word_opt("par") {
+                bail!(self.error(
+                    par_pos,
+                    format!(
+                        "polymorphic declarations are not supported (`{}` item)",
+                        conf.bad("par")
+                    )
+                ))
+            }
 
-            res = if self.set_info()? {
+            res = if self.set_info(instance)? {
                 Parsed::Items
-            } else if let Some((key, val)) = self.set_option()? {
-                instance.set_option(key, val).chain_err(|| {
-                    self.backtrack_to(start_pos);
-                    self.error_here("in this set-option")
-                })?;
+            } else if let Some((key, val_pos, val)) = self.set_option()? {
+                if let Err(e) = instance.set_option(key, val) {
+                    bail!(self.error(val_pos, format!("{}", e)))
+                }
                 Parsed::Items
             } else if self.set_logic()?
                 || self.pred_dec(instance)?
@@ -3502,12 +4347,19 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                 || self.define_fun_rec(instance)?
                 || self.define_funs_rec(instance)?
                 || self.assert(instance)?
+                || self.declare_var()?
+                || self.rule(instance)?
+                || self.query(instance)?
                 || self.dtyp_dec_item()?
                 || self.dtyp_decs_item()?
             {
                 Parsed::Items
             } else if self.check_sat() {
+                self.check_undeclared_dtyps()?;
                 Parsed::CheckSat
+            } else if self.check_sat_assuming(instance)? {
+                self.check_undeclared_dtyps()?;
+                Parsed::CheckSatAssuming
             } else if self.get_model() {
                 Parsed::GetModel
             } else if self.get_unsat_core() {
@@ -3516,10 +4368,12 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                 Parsed::GetProof
             } else if self.exit() {
                 Parsed::Exit
+            } else if self.reset_assertions() {
+                Parsed::ResetAssertions
             } else if self.reset() {
                 Parsed::Reset
             } else if let Some(blah) = self.echo()? {
-                println!("{}", blah);
+                println_side!("{}", blah);
                 Parsed::Items
             } else {
                 bail!(self.error_here("expected top-level item"))
@@ -3544,6 +4398,137 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     }
 }
 
+impl<'cxt, 's> Parser<'cxt, 's> {
+    /// Parses a model for the predicates of an instance.
+    ///
+    /// Expects a `(model (define-fun <pred> <args> Bool <body>) ...)`, the `model` keyword is
+    /// optional. Definitions for functions that are already defined, typically companion
+    /// functions, are ignored. The terms are expressed over the **original** signature of the
+    /// predicates. Fails if a predicate is not defined, or defined more than once.
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, parse::ParserCxt };
+    /// let instance = hoice::parse::mc_91();
+    /// let model = "\
+    /// (model
+    ///   (define-fun mc91 ((n Int) (r Int)) Bool
+    ///     (or (= r 91) (> n 100))
+    ///   )
+    /// )";
+    /// let mut cxt = ParserCxt::new();
+    /// let profiler = Profiler::new();
+    /// let model = cxt.parser(model, 0, &profiler).parse_model(&instance).unwrap();
+    /// assert_eq! { model.len(), 1 }
+    /// let (n, r) = (term::int_var(0), term::int_var(1));
+    /// assert_eq! {
+    ///     model[0.into()],
+    ///     term::or(vec![
+    ///         term::eq(r, term::int(91)),
+    ///         term::gt(n, term::int(100)),
+    ///     ])
+    /// }
+    /// ```
+    pub fn parse_model(mut self, instance: &Instance) -> Res<PrdMap<Term>> {
+        let mut defs: PrdMap<Option<Term>> = vec![None; instance.preds().len()].into();
+
+        self.ws_cmt();
+        self.tag("(")?;
+        self.ws_cmt();
+        if self.word_opt("model") {
+            self.ws_cmt()
+        }
+
+        while self.tag_opt("(") {
+            self.ws_cmt();
+            if !self.word_opt(keywords::cmd::def_fun) {
+                bail!(self.error_here(format!("expected `{}`", conf.emph(keywords::cmd::def_fun))))
+            }
+            self.ws_cmt();
+
+            let (name_pos, name) = self.ident()?;
+            self.ws_cmt();
+
+            let mut var_info = VarInfos::new();
+            let mut map = BTreeMap::new();
+            let args_pos = self.pos();
+            self.args(&mut var_info, &mut map)?;
+            self.ws_cmt();
+
+            let sort_pos = self.pos();
+            let out_sort = self.sort()?;
+            self.ws_cmt();
+
+            let pred = if let Some(pred) = instance.preds().iter().find(|pred| pred.name == name) {
+                pred
+            } else if fun::get(name).is_some() {
+                // Already defined, skip the body.
+                let body_pos = self.pos();
+                if self.term_opt(&var_info, &map, instance)?.is_none() {
+                    bail!(self.error(body_pos, "expected term"))
+                }
+                self.ws_cmt();
+                self.tag(")")?;
+                self.ws_cmt();
+                continue;
+            } else {
+                bail!(self.error(name_pos, format!("unknown predicate `{}`", conf.bad(name))))
+            };
+
+            if out_sort != typ::bool() {
+                bail!(self.error(sort_pos, format!("expected sort {}", conf.emph("Bool"))))
+            }
+            let sig_ok = var_info.len() == pred.original_sig().len()
+                && var_info
+                    .iter()
+                    .zip(pred.original_sig().iter())
+                    .all(|(info, typ)| &info.typ == typ);
+            if !sig_ok {
+                bail!(self.error(
+                    args_pos,
+                    format!(
+                        "arguments do not match the signature of `{}`",
+                        conf.emph(name)
+                    )
+                ))
+            }
+
+            let body_pos = self.pos();
+            let body = if let Some(body) = self.term_opt(&var_info, &map, instance)? {
+                body
+            } else {
+                bail!(self.error(body_pos, "expected term"))
+            };
+            if !body.typ().is_bool() {
+                bail!(self.error(body_pos, "expected boolean term"))
+            }
+
+            if defs[pred.idx].is_some() {
+                bail!(self.error(name_pos, format!("redefinition of `{}`", conf.bad(name))))
+            }
+            defs[pred.idx] = Some(body);
+
+            self.ws_cmt();
+            self.tag(")")?;
+            self.ws_cmt()
+        }
+
+        self.tag(")")?;
+
+        let mut model = PrdMap::with_capacity(defs.len());
+        for (pred, def) in defs.into_index_iter() {
+            if let Some(def) = def {
+                model.push(def)
+            } else {
+                bail!(
+                    "no definition for predicate `{}` in model",
+                    conf.bad(&instance[pred].name)
+                )
+            }
+        }
+        Ok(model)
+    }
+}
+
 /// If input expression is an error, prints it and panics.
 macro_rules! print_err {
     ($e:expr, $blah:expr) => {
@@ -3607,6 +4592,74 @@ pub fn term(s: &str, var_infos: &VarInfos, instance: &Instance) -> Term {
     }
 }
 
+/// Variables of a signature, where the `i`th variable is named `v_i`.
+fn sig_var_infos(sig: &Sig) -> VarInfos {
+    let mut var_infos = VarInfos::with_capacity(sig.len());
+    for (var, typ) in sig.index_iter() {
+        var_infos.push(VarInfo::new(var.default_str(), typ.clone(), var))
+    }
+    var_infos
+}
+
+/// Maps the names of some variables to their index.
+fn var_map_of(var_infos: &VarInfos) -> BTreeMap<&str, VarIdx> {
+    var_infos
+        .iter()
+        .map(|info| (info.name.as_str(), info.idx))
+        .collect()
+}
+
+/// Parses a boolean term over a signature, where the `i`th argument is `v_i`.
+///
+/// Used for the qualifier hints given with `--qual_hints`. Fails if the string is not exactly
+/// one boolean term that type-checks with the signature.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::common::*;
+/// let instance = Instance::new();
+/// let sig: Sig = vec![typ::int(), typ::int()].into();
+/// let term = hoice::parse::sig_term("(>= v_0 (+ v_1 4096))", &sig, &instance).unwrap();
+/// assert_eq! {
+///     term, term::ge(term::int_var(0), term::add2(term::int_var(1), term::int(4096)))
+/// }
+/// // Unknown variable for this signature.
+/// assert! { hoice::parse::sig_term("(>= v_2 0)", &sig, &instance).is_err() }
+/// // Not a boolean term.
+/// assert! { hoice::parse::sig_term("(+ v_0 1)", &sig, &instance).is_err() }
+/// // Trailing garbage.
+/// assert! { hoice::parse::sig_term("(>= v_0 0) )", &sig, &instance).is_err() }
+/// ```
+pub fn sig_term(s: &str, sig: &Sig, instance: &Instance) -> Res<Term> {
+    let profiler = Profiler::new();
+    let mut cxt = ParserCxt::new();
+    let mut parser = cxt.parser(s, 0, &profiler);
+
+    parser.ws_cmt();
+    let var_infos = sig_var_infos(sig);
+    let map = var_map_of(&var_infos);
+    let term_pos = parser.pos();
+    let term = if let Some(term) = parser.term_opt(&var_infos, &map, instance)? {
+        term
+    } else {
+        bail!(parser.error(term_pos, "expected term"))
+    };
+    if !term.typ().is_bool() {
+        bail!(parser.error(
+            term_pos,
+            format!("expected boolean term, found term of sort {}", term.typ())
+        ))
+    }
+
+    parser.ws_cmt();
+    if parser.has_next() {
+        bail!(parser.error_here("unexpected input after term"))
+    }
+
+    Ok(term)
+}
+
 /// Parses an instance from an SMT 2 string.
 ///
 /// Stops at the end of the string or at the first non-declaration non-assert non-definition
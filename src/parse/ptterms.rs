@@ -45,6 +45,25 @@ impl PTTerms {
         PTTerms::TTerm(TTerm::fls())
     }
 
+    /// Inserts the variables appearing in the top terms in a set.
+    pub fn vars(&self, vars: &mut VarSet) {
+        match self {
+            PTTerms::And(ref kids) | PTTerms::Or(ref kids) => {
+                for kid in kids {
+                    kid.vars(vars)
+                }
+            }
+            PTTerms::NTTerm(ref tterm) | PTTerms::TTerm(ref tterm) => match tterm {
+                TTerm::P { ref args, .. } => {
+                    for arg in args.iter() {
+                        vars.extend(term::vars(arg))
+                    }
+                }
+                TTerm::T(ref term) => vars.extend(term::vars(term)),
+            },
+        }
+    }
+
     /// Total substitution over top terms.
     ///
     /// # TODO
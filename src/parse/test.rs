@@ -0,0 +1,523 @@
+//! Tests for the parser.
+
+use crate::{common::*, parse::*};
+
+/// Parses a sort and returns the message, line and column of the parse error it produces.
+fn sort_error(s: &str) -> (String, Option<usize>, usize) {
+    let profiler = Profiler::new();
+    let mut cxt = ParserCxt::new();
+    let err = cxt
+        .parser(s, 0, &profiler)
+        .sort_opt()
+        .expect_err(&format!("parsing sort `{}` should fail", s));
+    match err.kind() {
+        ErrorKind::ParseError(data) => (data.msg.clone(), data.line, data.pref.len() + 1),
+        _ => panic!("expected a parse error on `{}`, got {}", s, err),
+    }
+}
+
+#[test]
+fn array_sort_missing_params() {
+    let (msg, line, col) = sort_error("(Array Int)");
+    assert_eq!(
+        msg,
+        "array sort expects exactly two sort parameters, got one"
+    );
+    assert_eq!(line, Some(1));
+    assert_eq!(col, 1);
+
+    let (msg, line, col) = sort_error("  (Array )");
+    assert_eq!(
+        msg,
+        "array sort expects exactly two sort parameters, got none"
+    );
+    assert_eq!(line, Some(1));
+    assert_eq!(col, 3);
+}
+
+#[test]
+fn array_sort_too_many_params() {
+    let (msg, line, col) = sort_error("\n(Array Int Int Bool)");
+    assert_eq!(
+        msg,
+        "array sort expects exactly two sort parameters, got more"
+    );
+    assert_eq!(line, Some(2));
+    assert_eq!(col, 1);
+}
+
+#[test]
+fn array_sort_nested() {
+    let (msg, line, col) = sort_error("(Array Int (Array Int))");
+    assert_eq!(
+        msg,
+        "array sort expects exactly two sort parameters, got one"
+    );
+    assert_eq!(line, Some(1));
+    assert_eq!(col, 12);
+}
+
+#[test]
+fn parenthesized_builtin_sort() {
+    let (msg, line, col) = sort_error("(Array (Int) Int)");
+    assert!(msg.starts_with("unexpected parentheses around sort"));
+    assert!(msg.contains("Int"));
+    assert_eq!(line, Some(1));
+    assert_eq!(col, 8);
+
+    let (msg, _, col) = sort_error("( Bool )");
+    assert!(msg.contains("Bool"));
+    assert_eq!(col, 1);
+}
+
+/// Parses some items and returns the message, line and column of the parse error they produce.
+fn items_error(s: &str) -> (String, Option<usize>, usize) {
+    let profiler = Profiler::new();
+    let mut cxt = ParserCxt::new();
+    let mut instance = Instance::new();
+    let err = cxt
+        .parser(s, 0, &profiler)
+        .parse(&mut instance)
+        .expect_err(&format!("parsing `{}` should fail", s));
+    match err.kind() {
+        ErrorKind::ParseError(data) => (data.msg.clone(), data.line, data.pref.len() + 1),
+        _ => panic!("expected a parse error on `{}`, got {}", s, err),
+    }
+}
+
+#[test]
+fn polymorphic_declarations() {
+    let (msg, line, col) = items_error("(declare-fun P (par (T) (T Int)) Bool)");
+    assert!(msg.starts_with("polymorphic predicates are not supported"));
+    assert!(msg.contains("par"));
+    assert_eq!(line, Some(1));
+    assert_eq!(col, 17);
+
+    let (msg, line, col) =
+        items_error("(declare-fun P (Int) Bool)\n(define-fun f ( par (T) ((x T)) T x))");
+    assert!(msg.starts_with("polymorphic functions are not supported"));
+    assert_eq!(line, Some(2));
+    assert_eq!(col, 17);
+
+    let (msg, line, col) =
+        items_error("(declare-fun P (Int) Bool)\n(par (T) (declare-fun Q (T) Bool))");
+    assert!(msg.starts_with("polymorphic declarations are not supported"));
+    assert_eq!(line, Some(2));
+    assert_eq!(col, 2);
+
+    // Non-polymorphic declarations are left untouched.
+    let instance = crate::parse::instance("(declare-fun P ( Int ) Bool)");
+    assert_eq!(instance.preds().len(), 1);
+}
+
+#[test]
+fn hint_unknown_pred() {
+    let (msg, line, col) =
+        items_error("(declare-fun Inv (Int Int) Bool)\n(set-info :hint (Ivn (>= v_0 v_1)))");
+    assert!(msg.starts_with("unknown predicate"));
+    assert!(msg.contains("Ivn"));
+    assert_eq!(line, Some(2));
+    assert_eq!(col, 18);
+}
+
+#[test]
+fn hint_not_bool() {
+    let (msg, line, col) =
+        items_error("(declare-fun Inv (Int Int) Bool)\n(set-info :hint (Inv (+ v_0 v_1)))");
+    assert!(msg.starts_with("expected boolean term"));
+    assert_eq!(line, Some(2));
+    assert_eq!(col, 22);
+}
+
+#[test]
+fn status_info() {
+    let instance = crate::parse::instance("(set-info :status sat)");
+    assert_eq!(instance.expected_status(), Some(true));
+    let instance = crate::parse::instance("(set-info :status unsat)");
+    assert_eq!(instance.expected_status(), Some(false));
+    let instance = crate::parse::instance("(set-info :status unknown)");
+    assert_eq!(instance.expected_status(), None);
+    let instance = crate::parse::instance("(set-info :source |some benchmark|)");
+    assert_eq!(instance.expected_status(), None);
+
+    let (msg, line, col) = items_error("(set-info :status maybe)");
+    assert!(msg.starts_with("expected `sat`, `unsat` or `unknown`"));
+    assert_eq!(line, Some(1));
+    assert_eq!(col, 19);
+}
+
+#[test]
+fn annotation_unknown_attributes() {
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (assert (not (exists ((n Int)) (! (and (P n) (< n 0)) :pattern ((P n)) :qid neg))))",
+    );
+    assert_eq!(instance.clauses().len(), 1);
+    assert!(!instance.has_old_clause_names());
+}
+
+#[test]
+fn annotation_two_names() {
+    let (msg, line, _) = items_error(
+        "(declare-fun P (Int) Bool)\n(assert (! (forall ((n Int)) (P n)) :named a :named b))",
+    );
+    assert!(msg.contains("second `:named`"));
+    assert_eq!(line, Some(2));
+}
+
+#[test]
+fn set_option_bad_value() {
+    let (msg, line, col) = items_error("(set-logic HORN)\n(set-option :produce-proofs 7)");
+    assert!(msg.contains("expected boolean `true/false`, got `7`"));
+    assert_eq!(line, Some(2));
+    assert_eq!(col, 29);
+}
+
+#[test]
+fn set_option_quoted_value() {
+    let instance =
+        crate::parse::instance("(set-option :no-inlining-preds |P Q|)\n(declare-fun P (Int) Bool)");
+    assert_eq!(instance.no_inlining_preds().len(), 2);
+    assert!(instance.no_inlining_preds().contains("Q"));
+}
+
+/// Predicate and arguments of the rhs of the only clause of an instance.
+fn only_rhs(instance: &Instance) -> (String, Vec<Term>) {
+    assert_eq!(instance.clauses().len(), 1);
+    let (pred, args) = instance[ClsIdx::from(0)]
+        .rhs()
+        .expect("expected a clause with a rhs");
+    (instance[pred].name.clone(), args.iter().cloned().collect())
+}
+
+#[test]
+fn shadowing_let_over_pred() {
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (declare-fun Q (Int) Bool)\n\
+         (assert (forall ((n Int)) (let ((P 3)) (Q P))))",
+    );
+    assert_eq!(only_rhs(&instance), ("Q".into(), vec![term::int(3)]));
+
+    // Let-binding to a predicate application, used as a top term.
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (declare-fun Q (Int) Bool)\n\
+         (assert (forall ((n Int)) (let ((P (Q n))) (=> P (Q (+ n 1))))))",
+    );
+    let (name, _) = only_rhs(&instance);
+    assert_eq!(name, "Q");
+    let clause = &instance[ClsIdx::from(0)];
+    assert_eq!(clause.lhs_preds().len(), 1);
+    for pred in clause.lhs_preds().keys() {
+        assert_eq!(instance[*pred].name, "Q")
+    }
+}
+
+#[test]
+fn shadowing_let_over_clause_var() {
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (assert (forall ((n Int)) (let ((n 3)) (P n))))",
+    );
+    assert_eq!(only_rhs(&instance), ("P".into(), vec![term::int(3)]));
+
+    // A let-binding outside of the quantifier does not shadow the clause variable.
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (assert (let ((n 3)) (forall ((n Int)) (P n))))",
+    );
+    let (name, args) = only_rhs(&instance);
+    assert_eq!(name, "P");
+    assert_eq!(args.len(), 1);
+    assert!(args[0].var_idx().is_some());
+}
+
+#[test]
+fn shadowing_clause_var_over_pred() {
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (declare-fun Q (Int) Bool)\n\
+         (assert (forall ((P Int)) (Q P)))",
+    );
+    let (name, args) = only_rhs(&instance);
+    assert_eq!(name, "Q");
+    assert_eq!(args.len(), 1);
+    assert!(args[0].var_idx().is_some());
+}
+
+#[test]
+fn negative_literals() {
+    for input in &["-5", "(- 5)"] {
+        let instance = crate::parse::instance(&format!(
+            "(declare-fun P (Int) Bool)\n(assert (P {}))",
+            input
+        ));
+        assert_eq!(only_rhs(&instance), ("P".into(), vec![term::int(-5)]));
+    }
+
+    let instance = crate::parse::instance(
+        "(declare-fun P (Real) Bool)\n\
+         (assert (P -1.5))",
+    );
+    assert_eq!(
+        only_rhs(&instance),
+        (
+            "P".into(),
+            vec![term::real(Rat::new((-3).into(), 2.into()))]
+        )
+    );
+
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (assert (forall ((x Int)) (=> (= x -5) (P x))))",
+    );
+    let holds = |x: i64| {
+        let model: VarMap<Val> = vec![val::int(x)].into();
+        instance[ClsIdx::from(0)]
+            .lhs_terms()
+            .iter()
+            .all(|term| term.bool_eval(&model).expect("evaluation failed") == Some(true))
+    };
+    assert!(holds(-5));
+    assert!(!holds(5));
+
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (assert (forall ((x Int)) (P (- x 5))))",
+    );
+    let val = eval_only_rhs_arg(&instance, vec![val::int(7)].into());
+    assert_eq!(val, val::int(2));
+
+    let (msg, line, col) = items_error(
+        "(declare-fun P (Int) Bool)\n\
+         (assert (P (-5)))",
+    );
+    assert!(msg.starts_with("illegal application of negative literal `-5`"));
+    assert_eq!(line, Some(2));
+    assert_eq!(col, 13);
+}
+
+#[test]
+fn shadowing_let_over_define_fun() {
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (define-fun shadowed_fun ((x Int)) Int (+ x 1))\n\
+         (assert (forall ((n Int)) (let ((shadowed_fun 3)) (P shadowed_fun))))",
+    );
+    assert_eq!(only_rhs(&instance), ("P".into(), vec![term::int(3)]));
+}
+
+/// Evaluates the only argument of the rhs of the only clause of an instance.
+fn eval_only_rhs_arg(instance: &Instance, model: VarMap<Val>) -> Val {
+    let (_, args) = only_rhs(instance);
+    assert_eq!(args.len(), 1);
+    args[0].eval(&model).expect("evaluation failed")
+}
+
+#[test]
+fn const_array_real() {
+    let instance = crate::parse::instance(
+        "(declare-fun P (Real) Bool)\n\
+         (assert (forall ((n Int)) (P (select ((as const (Array Int Real)) 0.0) n))))",
+    );
+    let val = eval_only_rhs_arg(&instance, vec![val::int(3)].into());
+    assert_eq!(val, val::real_of(0.));
+
+    // Integer default, cast to a real.
+    let instance = crate::parse::instance(
+        "(declare-fun P (Real) Bool)\n\
+         (assert (forall ((n Int)) (P (select ((as const (Array Int Real)) 7) n))))",
+    );
+    let val = eval_only_rhs_arg(&instance, vec![val::int(3)].into());
+    assert_eq!(val, val::real_of(7.));
+}
+
+#[test]
+fn const_array_real_non_constant_default() {
+    let instance = crate::parse::instance(
+        "(declare-fun P (Real) Bool)\n\
+         (assert (forall ((n Int) (r Real)) (P (select ((as const (Array Int Real)) r) n))))",
+    );
+    let val = eval_only_rhs_arg(&instance, vec![val::int(3), val::real_of(1.5)].into());
+    assert_eq!(val, val::real_of(1.5));
+}
+
+#[test]
+fn const_array_dtyp() {
+    let list = typ::dtyp(dtyp::get("List").unwrap(), vec![typ::int()].into());
+    let instance = crate::parse::instance(
+        "(declare-fun P ((List Int)) Bool)\n\
+         (assert (forall ((n Int)) (P (select ((as const (Array Int (List Int))) nil) n))))",
+    );
+    let val = eval_only_rhs_arg(&instance, vec![val::int(3)].into());
+    assert_eq!(val, val::dtyp_new(list, "nil".into(), vec![]));
+}
+
+#[test]
+fn dtyp_forward_reference() {
+    crate::parse::fun_dtyp(
+        "(declare-datatype ParseTestTree ( (tree_leaf) (tree_node (tree_kids ParseTestTrees)) ))\n\
+         (declare-datatype ParseTestTrees ( (trees_nil) (trees_cons (trees_head ParseTestTree) (trees_tail ParseTestTrees)) ))\n\
+         (check-sat)",
+    );
+    let tree = dtyp::get("ParseTestTree").unwrap();
+    assert!(tree.undeclared().is_empty());
+    assert!(tree.dtyp_deps().contains("ParseTestTrees"));
+}
+
+#[test]
+fn dtyp_undeclared_reference() {
+    let mut instance = Instance::new();
+    let mut cxt = ParserCxt::new();
+    let profiler = Profiler::new();
+    let err = cxt
+        .parser(
+            "(declare-datatype ParseTestOrphan ( (orphan_nil) (orphan_cons (orphan_tail ParseTestUnknown)) ))\n\
+             (check-sat)",
+            0,
+            &profiler,
+        )
+        .parse(&mut instance)
+        .expect_err("undeclared datatype should be an error");
+    let blah = format!("{}", err.iter().last().unwrap());
+    assert!(blah.contains("undeclared datatype"));
+    assert!(blah.contains("ParseTestUnknown"));
+    assert!(blah.contains("referenced at line 1"));
+}
+
+#[test]
+fn quoted_idents_with_comments_and_newlines() {
+    let instance = crate::parse::instance(
+        "(declare-fun |a ; b| (Int) Bool)\n\
+         (declare-fun |multi\nline| (Int) Bool)\n\
+         (assert (forall ((n Int)) (=> (|a ; b| n) (|multi\nline| n))))",
+    );
+    let names: Vec<_> = instance
+        .preds()
+        .iter()
+        .map(|info| info.name.clone())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["|a ; b|".to_string(), "|multi\nline|".to_string()]
+    );
+    let (name, _) = only_rhs(&instance);
+    assert_eq!(name, "|multi\nline|");
+}
+
+#[test]
+fn read_quoted_idents_with_comments_and_newlines() {
+    let input = "(declare-fun |a ; b) (| (Int) Bool) ; comment (\n\
+                 (declare-fun |multi\n(line| (Int) Bool)\n";
+    let mut reader = ::std::io::Cursor::new(input);
+    let mut buf = String::new();
+
+    assert_eq!(reader.read_item(&mut buf).unwrap(), 1);
+    assert!(buf.contains("|a ; b) (|"));
+
+    buf.clear();
+    assert_eq!(reader.read_item(&mut buf).unwrap(), 2);
+    assert_eq!(buf, "(declare-fun |multi\n(line| (Int) Bool)\n");
+}
+
+/// Parses an instance and returns the message of the parse error it produces.
+fn instance_error(s: &str) -> String {
+    let mut instance = Instance::new();
+    let mut cxt = ParserCxt::new();
+    let profiler = Profiler::new();
+    let err = cxt
+        .parser(s, 0, &profiler)
+        .parse(&mut instance)
+        .expect_err(&format!("parsing `{}` should fail", s));
+    match err.kind() {
+        ErrorKind::ParseError(data) => data.msg.clone(),
+        _ => panic!("expected a parse error on `{}`, got {}", s, err),
+    }
+}
+
+#[test]
+fn op_arity() {
+    let msg = instance_error(
+        "(declare-fun P (Real) Bool)\n\
+         (assert (forall ((r Real)) (P (/ r 2.0 3.0))))",
+    );
+    assert!(msg.contains("expected at most 2"));
+
+    let msg = instance_error(
+        "(declare-fun P (Bool) Bool)\n\
+         (assert (forall ((n Int)) (P (= n))))",
+    );
+    assert!(msg.contains("expected at least 2"));
+}
+
+#[test]
+fn clause_printing_uses_var_names() {
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int Int) Bool)\n\
+         (assert (forall ((counter Int) (|x y| Int)) (=> (> counter |x y|) (P counter |x y|))))",
+    );
+    let clause = &instance[ClsIdx::from(0)];
+    let s = clause.to_string_info(instance.preds()).unwrap();
+    assert!(s.contains("(P counter |x y|)"));
+    assert!(!s.contains("v_0"));
+}
+
+#[test]
+fn datalog_rules() {
+    let datalog = crate::parse::instance(
+        "(declare-fun P (Int Int) Bool)\n\
+         (declare-fun Q (Int) Bool)\n\
+         (declare-var x Int)\n\
+         (declare-var y Int)\n\
+         (declare-var |z z| Int)\n\
+         (rule (=> (= y 0) (P 0 y)))\n\
+         (rule (=> (and (P x y) (< x 10)) (P (+ x 1) (+ y 2))) step)\n\
+         (assert (forall ((n Int) (m Int)) (=> (and (P n m) (>= m n)) (Q m))))\n\
+         (rule (=> (and (Q |z z|) (> |z z| 30)) false))\n\
+         (query Q :print-certificate true)",
+    );
+    let standard = crate::parse::instance(
+        "(declare-fun P (Int Int) Bool)\n\
+         (declare-fun Q (Int) Bool)\n\
+         (assert (forall ((y Int)) (=> (= y 0) (P 0 y))))\n\
+         (assert (! (forall ((x Int) (y Int))\n\
+            (=> (and (P x y) (< x 10)) (P (+ x 1) (+ y 2)))\n\
+         ) :named step))\n\
+         (assert (forall ((n Int) (m Int)) (=> (and (P n m) (>= m n)) (Q m))))\n\
+         (assert (forall ((|z z| Int)) (=> (and (Q |z z|) (> |z z| 30)) false)))\n\
+         (assert (forall ((v_0 Int)) (=> (Q v_0) false)))",
+    );
+
+    assert_eq!(datalog.clauses().len(), standard.clauses().len());
+    for (idx, clause) in datalog.clauses().index_iter() {
+        assert_eq!(
+            clause.to_string_info(datalog.preds()).unwrap(),
+            standard[idx].to_string_info(standard.preds()).unwrap()
+        );
+        assert_eq!(clause.vars().len(), standard[idx].vars().len());
+        assert_eq!(
+            datalog.name_of_old_clause(idx),
+            standard.name_of_old_clause(idx)
+        );
+    }
+    assert_eq!(
+        datalog.name_of_old_clause(1.into()).map(String::as_str),
+        Some("step")
+    );
+}
+
+#[test]
+fn datalog_rule_vars() {
+    // `x` is shadowed by the let-binding, the clause only mentions `y`.
+    let instance = crate::parse::instance(
+        "(declare-fun P (Int) Bool)\n\
+         (declare-var x Int)\n\
+         (declare-var y Int)\n\
+         (rule (=> (let ((x 1)) (> y x)) (P y)))",
+    );
+    assert_eq!(instance.clauses().len(), 1);
+    let clause = &instance[ClsIdx::from(0)];
+    assert_eq!(clause.vars().len(), 1);
+    assert_eq!(clause.vars()[VarIdx::from(0)].name, "y");
+}
@@ -19,15 +19,26 @@ pub mod utils;
 pub mod arg_red;
 pub mod bias_unroll;
 pub mod cfg_red;
+pub mod clause_split;
 pub mod fun_preds;
 pub mod one_lhs;
 pub mod one_rhs;
 pub mod strict_neg_clauses;
+pub mod unit_prop;
 pub mod unroll;
 
 pub use self::{
-    arg_red::ArgRed, bias_unroll::BiasedUnroll, cfg_red::CfgRed, fun_preds::FunPreds,
-    one_lhs::OneLhs, one_rhs::OneRhs, strict_neg_clauses::StrictNeg, unroll::RUnroll,
+    arg_red::ArgRed,
+    bias_unroll::BiasedUnroll,
+    cfg_red::CfgRed,
+    clause_split::ClauseSplit,
+    fun_preds::FunPreds,
+    one_lhs::OneLhs,
+    one_rhs::OneRhs,
+    strict_neg_clauses::StrictNeg,
+    unit_prop::UnitProp,
+    unroll::RUnroll,
+    utils::{SkipReason, SkipReasons},
 };
 pub use crate::instance::PreInstance;
 
@@ -43,6 +54,18 @@ pub type PredExtension = (TermSet, Vec<(Quantfed, Term)>);
 ///
 /// Finalizes the instance.
 pub fn work(instance: &mut Instance, profiler: &Profiler) -> Res<()> {
+    let duplicates = profile! {
+      |profiler| wrap {
+        instance.remove_duplicate_clauses() ?
+      } "preproc", "duplicate removal"
+    };
+    if duplicates > 0 {
+        log_verb! { "removed {} duplicate clause(s)", duplicates }
+        profile! {
+            |profiler|
+            "clauses |      duplicates" => add duplicates
+        }
+    }
     let res = {
         let instance = profile! {
           |profiler| wrap {
@@ -266,8 +289,12 @@ pub struct Reductor<'a> {
     instance: PreInstance<'a>,
     /// Preinstance simplification.
     simplify: Option<Simplify>,
+    /// Optional fact propagation.
+    unit_prop: Option<UnitProp>,
     /// Optional predicate argument reduction pre-processor.
     arg_red: Option<ArgRed>,
+    /// Optional lhs disjunction splitter.
+    clause_split: Option<ClauseSplit>,
     /// Optional one rhs pre-processor.
     one_rhs: Option<OneRhs>,
     /// Optional one lhs pre-processor.
@@ -311,7 +338,9 @@ impl<'a> Reductor<'a> {
         }
 
         let simplify = Some(Simplify::new(&instance));
+        let unit_prop = some_new! { UnitProp if active and unit_prop };
         let arg_red = some_new! { ArgRed if active and arg_red };
+        let clause_split = some_new! { ClauseSplit if active and clause_split };
 
         let one_rhs = some_new! {
           OneRhs if active and one_rhs
@@ -340,7 +369,9 @@ impl<'a> Reductor<'a> {
         Ok(Reductor {
             instance,
             simplify,
+            unit_prop,
             arg_red,
+            clause_split,
             one_rhs,
             one_lhs,
             cfg_red,
@@ -399,7 +430,7 @@ impl<'a> Reductor<'a> {
             run! { simplify };
         }
 
-        if !conf.preproc.active || self.instance.track_samples() {
+        if !conf.preproc.active {
             return Ok(());
         }
 
@@ -413,9 +444,11 @@ impl<'a> Reductor<'a> {
             }
             conf.check_timeout()?;
 
+            run! { unit_prop };
+
             run! { arg_red };
 
-            let changed = false;
+            let changed = run! { clause_split };
 
             if changed {
                 changed_since_cfg_red = true;
@@ -477,6 +510,10 @@ impl<'a> Reductor<'a> {
 
         run! { strict_neg };
 
+        if conf.preproc.explain_preproc {
+            self.instance.skip_reasons().print(&self.instance)
+        }
+
         utils::register_final_stats(&self.instance, _profiler)?;
 
         Ok(())
@@ -180,7 +180,7 @@ impl ArgReductor {
 
         'all_preds: for (pred, argss) in clause.lhs_preds() {
             let pred = *pred;
-            if self.keep[pred].len() == instance[pred].sig.len() {
+            if self.keep[pred].len() == instance[pred].arity() {
                 continue 'all_preds;
             }
 
@@ -3,7 +3,7 @@
 
 use crate::{
     common::*,
-    preproc::{utils, utils::ExtractionCxt, PreInstance, RedStrat},
+    preproc::{utils, utils::ExtractionCxt, PreInstance, RedStrat, SkipReason},
     var_to::terms::VarTermsSet,
 };
 
@@ -1156,6 +1156,15 @@ impl CfgRed {
             }
 
             conf.check_timeout()?;
+
+            // Each lhs application receives the variables of one of the disjuncts.
+            let qvars = def.iter().map(|(qvars, _)| qvars.len()).max().unwrap_or(0);
+            if instance.exceeds_var_budget(pred, true, qvars) {
+                instance.skip(self.name(), pred, SkipReason::VarBudget);
+                continue;
+            }
+
+            info.preds += 1;
             info += instance.rm_rhs_clauses_of(pred)?;
 
             if_log! { @5
@@ -1264,11 +1273,20 @@ impl RedStrat for CfgRed {
             // let breaking_duration = Instant::now() - start ;
             // println!("breaking time: {}", breaking_duration.to_str()) ;
 
+            // Remember why predicates are kept, only when explaining.
+            let breaks_cycle = if conf.preproc.explain_preproc {
+                to_keep.clone()
+            } else {
+                PrdSet::new()
+            };
+            let mut no_inlining_set = PrdSet::new();
+
             let no_inlining = instance.no_inlining();
             let no_inlining_preds = instance.no_inlining_preds();
             for p in instance.preds() {
                 if no_inlining || no_inlining_preds.contains(&p.name) {
                     to_keep.insert(p.idx);
+                    no_inlining_set.insert(p.idx);
                 }
             }
 
@@ -1279,12 +1297,23 @@ impl RedStrat for CfgRed {
                 .graph
                 .inline(instance, &mut to_keep, self.upper_bound)?;
 
+            if conf.preproc.explain_preproc {
+                for pred in &to_keep {
+                    let reason = if no_inlining_set.contains(pred) {
+                        SkipReason::NoInlining
+                    } else if breaks_cycle.contains(pred) {
+                        SkipReason::BreaksCycle
+                    } else {
+                        SkipReason::BlowupGuard
+                    };
+                    instance.skip(self.name(), *pred, reason)
+                }
+            }
+
             if pred_defs.is_empty() {
                 break;
             }
 
-            info.preds += pred_defs.len();
-
             self.graph.check(&instance)?;
             if_log! { @verb
                 log! { @verb | "inlining {} predicate(s)", pred_defs.len() }
@@ -1294,6 +1323,7 @@ impl RedStrat for CfgRed {
             }
 
             if pred_defs.len() == instance.active_pred_count() {
+                info.preds += pred_defs.len();
                 let (is_sat, this_info) = instance.force_all_preds(pred_defs, false)?;
                 info += this_info;
                 if !is_sat {
@@ -0,0 +1,63 @@
+//! Clause splitting.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Maximum number of clauses a single clause can be split into.
+///
+/// Splitting a clause on a disjunction of `k` disjuncts yields `k` clauses, clauses with larger
+/// disjunctions are left as they are.
+pub const MAX_SPLIT: usize = 8;
+
+/// Splits the top-level disjunctions in the lhs of the clauses.
+///
+/// Disjunctions can appear in the lhs of a clause after the parser has normalized it, or after a
+/// predicate has been replaced by a definition containing a disjunction. Splitting them up front
+/// yields simpler clauses for the other preprocessors and the learners.
+///
+/// Clauses that would yield more than [`MAX_SPLIT`] clauses are not split, and neither are clauses
+/// with several lhs disjunctions.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ parse, preproc::{ PreInstance, RedStrat, ClauseSplit } };
+/// let mut instance = parse::instance("
+///   (declare-fun p_1 ( Int ) Bool)
+///   (assert
+///     (forall ( (n Int) )
+///       (=>
+///         (and (or (> n 7) (< n 0)) (p_1 n))
+///         false
+///       )
+///     )
+///   )
+/// ");
+/// assert_eq! { instance.clauses().len(), 1 }
+///
+/// let mut clause_split = ClauseSplit::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = clause_split.apply(& mut instance).unwrap();
+/// assert_eq! { info.clauses_rmed, 1 }
+/// assert_eq! { info.clauses_added, 2 }
+/// assert_eq! { instance.clauses().len(), 2 }
+/// ```
+///
+/// [`MAX_SPLIT`]: constant.MAX_SPLIT.html (MAX_SPLIT constant)
+pub struct ClauseSplit;
+
+impl RedStrat for ClauseSplit {
+    fn name(&self) -> &'static str {
+        "clause_split"
+    }
+
+    fn new(_: &Instance) -> Self {
+        ClauseSplit
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        instance.split_clauses(MAX_SPLIT)
+    }
+}
@@ -520,7 +520,7 @@ impl FunDef {
 
                 // Target invariant (negated).
                 let neg_objective = {
-                    let invariant = if self.sig.len() < instance[self.pred].sig().len() {
+                    let invariant = if self.sig.len() < instance[self.pred].arity() {
                         subst.insert(self.res_idx, branch!().value.clone());
                         // println!("subst ({}):", self.res_idx);
                         // for (key, val) in &subst {
@@ -573,7 +573,7 @@ impl FunDef {
                         } else {
                             bail!("ill-formed function reconstruction context")
                         }
-                        if self.sig.len() < instance[self.pred].sig().len() {
+                        if self.sig.len() < instance[self.pred].arity() {
                             subst.insert(self.res_idx, term::var(*var, self.typ.clone()));
                         }
                         // println!("subst:");
@@ -777,7 +777,7 @@ impl FunDef {
         let mut invs = Vec::with_capacity(self.invars.len());
         if !self.invars.is_empty() {
             let mut subst = VarHMap::new();
-            if self.sig.len() < instance[self.pred].sig().len() {
+            if self.sig.len() < instance[self.pred].arity() {
                 subst.insert(
                     self.res_idx,
                     term::fun(
@@ -797,7 +797,7 @@ impl FunDef {
                 //     println!("  {} -> {}", key.default_str(), val)
                 // }
                 // println!("inv: {}", inv);
-                let inv = if self.sig.len() == instance[self.pred].sig().len() {
+                let inv = if self.sig.len() == instance[self.pred].arity() {
                     let mut args: Vec<Term> = Vec::with_capacity(self.sig.len()).into();
                     for info in &self.sig {
                         args.push(term::var(info.idx, info.typ.clone()))
@@ -928,9 +928,9 @@ impl FunPreds {
         let mut var_infos = VarInfos::new();
 
         let args_len = if use_all_args {
-            instance[pred].sig.len()
+            instance[pred].arity()
         } else {
-            instance[pred].sig.len() - 1
+            instance[pred].arity() - 1
         };
 
         for typ in &instance[pred].sig[0..args_len] {
@@ -938,7 +938,7 @@ impl FunPreds {
             let var_info = VarInfo::new(idx.default_str(), typ.clone(), idx);
             var_infos.push(var_info)
         }
-        let res_idx: VarIdx = (instance[pred].sig.len() - 1).into();
+        let res_idx: VarIdx = (instance[pred].arity() - 1).into();
         let last: Option<VarIdx> = if use_all_args { None } else { Some(res_idx) };
 
         // println!(
@@ -1101,8 +1101,8 @@ impl RedStrat for FunPreds {
                 // }
 
                 // `pred` has only one dtyp argument (ignoring the last argument)
-                if info.sig.len() <= 1
-                    || info.sig[0..info.sig.len() - 1]
+                if info.arity() <= 1
+                    || info.sig[0..info.arity() - 1]
                         .iter()
                         .filter(|typ| typ.is_dtyp())
                         .count()
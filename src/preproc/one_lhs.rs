@@ -2,7 +2,7 @@
 
 use crate::{
     common::*,
-    preproc::{utils::ExtractRes, PreInstance, RedStrat},
+    preproc::{utils::ExtractRes, PreInstance, RedStrat, SkipReason},
 };
 
 /// Tries to reduce predicates that appear as an antecedent in exactly one
@@ -205,10 +205,20 @@ impl OneLhs {
                     instance.force_false(pred)?
                 } else {
                     self.log_extraction(instance, &qualfed, &pred_app, &tterms);
-                    instance.force_pred_right(pred, qualfed, pred_app, tterms)?
+                    if let Some(info) =
+                        instance.force_pred_right(pred, qualfed, pred_app, tterms)?
+                    {
+                        info
+                    } else {
+                        instance.skip(self.name(), pred, SkipReason::VarBudget);
+                        return Ok(None);
+                    }
                 }
             }
-            Failed => return Ok(None),
+            Failed => {
+                instance.skip(self.name(), pred, SkipReason::ExtractionFailed);
+                return Ok(None);
+            }
         };
 
         Ok(Some(info))
@@ -228,7 +238,11 @@ impl RedStrat for OneLhs {
         let mut red_info = RedInfo::new();
 
         'all_preds: for pred in instance.pred_indices() {
-            if instance[pred].is_defined() || instance.clauses_of(pred).0.len() > 1 {
+            if instance[pred].is_defined() {
+                continue 'all_preds;
+            }
+            if instance.clauses_of(pred).0.len() > 1 {
+                instance.skip(self.name(), pred, SkipReason::MultipleLhs);
                 continue 'all_preds;
             }
 
@@ -253,6 +267,7 @@ impl RedStrat for OneLhs {
                 .map(|argss| argss.len() != 1)
                 .unwrap_or(true)
             {
+                instance.skip(self.name(), pred, SkipReason::MultipleLhsApps);
                 continue 'all_preds;
             }
 
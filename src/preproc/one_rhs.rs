@@ -2,7 +2,7 @@
 
 use crate::{
     common::*,
-    preproc::{utils::ExtractRes, PreInstance, RedStrat},
+    preproc::{utils::ExtractRes, PreInstance, RedStrat, SkipReason},
 };
 
 /// Works on predicates that appear in only one rhs.
@@ -158,10 +158,18 @@ impl OneRhs {
 
             Success((qvars, tterms)) => {
                 self.log_extraction(instance, &qvars, &tterms);
-                instance.force_pred_left(pred, qvars, tterms)?
+                if let Some(info) = instance.force_pred_left(pred, qvars, tterms)? {
+                    info
+                } else {
+                    instance.skip(self.name(), pred, SkipReason::VarBudget);
+                    return Ok(None);
+                }
             }
 
-            Failed => return Ok(None),
+            Failed => {
+                instance.skip(self.name(), pred, SkipReason::ExtractionFailed);
+                return Ok(None);
+            }
         };
 
         Ok(Some(info))
@@ -181,7 +189,11 @@ impl RedStrat for OneRhs {
         let mut red_info = RedInfo::new();
 
         'all_preds: for pred in instance.pred_indices() {
-            if instance[pred].is_defined() || instance.clauses_of(pred).1.len() > 1 {
+            if instance[pred].is_defined() {
+                continue 'all_preds;
+            }
+            if instance.clauses_of(pred).1.len() > 1 {
+                instance.skip(self.name(), pred, SkipReason::MultipleRhs);
                 continue 'all_preds;
             }
 
@@ -285,7 +285,7 @@ impl StrictNeg {
         let mut defs = Vec::with_capacity(self.partial_defs.len());
 
         for (pred, def) in self.partial_defs.drain() {
-            let mut fresh: VarIdx = instance[pred].sig().len().into();
+            let mut fresh: VarIdx = instance[pred].arity().into();
             let def = def.unwrap();
             let mut qvars = VarHMap::new();
             let mut term = term::tru();
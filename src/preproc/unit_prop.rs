@@ -0,0 +1,79 @@
+//! Propagates facts.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Propagates facts.
+///
+/// A *fact* is a clause with an empty LHS and a RHS of the form `(p c_1 ... c_n)` where the
+/// `c_i`s are constants. This preprocessor drops such applications from the LHS of all the
+/// clauses. It runs first since dropping applications enables other reductions.
+///
+/// See [`PreInstance::saturate_with_unit_clauses`].
+///
+/// [`PreInstance::saturate_with_unit_clauses`]: ../struct.PreInstance.html#method.saturate_with_unit_clauses
+/// (saturate_with_unit_clauses function of PreInstance)
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, UnitProp } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int Int ) Bool)
+///   (declare-fun q ( Int ) Bool)
+///   (assert
+///     (forall ( (x Int) )
+///       (p 1 2)
+///     )
+///   )
+///   (assert
+///     (forall ( (x Int) )
+///       (=>
+///         (and (p 1 2) (p x 3) (> x 0))
+///         (q x)
+///       )
+///     )
+///   )
+///   (assert
+///     (forall ( (x Int) )
+///       (=>
+///         (and (q x) (p x 3))
+///         false
+///       )
+///     )
+///   )
+/// ");
+///
+/// let p: PrdIdx = 0.into();
+/// assert_eq! { "p", & instance[p].name }
+///
+/// let mut unit_prop = UnitProp::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = unit_prop.apply(& mut instance).unwrap();
+/// assert_eq! { info.apps_rmed, 1 }
+///
+/// for clause in instance.clauses() {
+///     if let Some(argss) = clause.lhs_preds().get(& p) {
+///         for args in argss {
+///             assert! { args.iter().any(|arg| arg.val().is_none()) }
+///         }
+///     }
+/// }
+/// ```
+pub struct UnitProp;
+
+impl RedStrat for UnitProp {
+    fn name(&self) -> &'static str {
+        "unit_prop"
+    }
+
+    fn new(_: &Instance) -> Self {
+        UnitProp
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        instance.saturate_with_unit_clauses()
+    }
+}
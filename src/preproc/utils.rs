@@ -4,6 +4,104 @@ use crate::{common::*, var_to::terms::VarTermsSet};
 
 use super::{PreInstance, RedStrat};
 
+/// Reason why a pre-processor did not reduce a predicate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SkipReason {
+    /// The predicate appears in more than one rhs.
+    MultipleRhs,
+    /// The predicate appears in more than one lhs.
+    MultipleLhs,
+    /// The predicate is applied more than once in the only lhs it appears in.
+    MultipleLhsApps,
+    /// The definition of the predicate could not be extracted from its clause.
+    ExtractionFailed,
+    /// The predicate was kept to break a cycle in the predicate dependency graph.
+    BreaksCycle,
+    /// Inlining the predicate is forbidden by the user.
+    NoInlining,
+    /// Inlining the predicate would create too many clauses, or its definition could not be
+    /// constructed.
+    BlowupGuard,
+    /// Unfolding the predicate would create a clause with too many variables.
+    VarBudget,
+}
+impl SkipReason {
+    /// Description of the reason.
+    pub fn describe(self) -> &'static str {
+        match self {
+            SkipReason::MultipleRhs => "appears in more than one rhs",
+            SkipReason::MultipleLhs => "appears in more than one lhs",
+            SkipReason::MultipleLhsApps => "applied more than once in its only lhs",
+            SkipReason::ExtractionFailed => "extraction failed",
+            SkipReason::BreaksCycle => "breaks a dependency cycle",
+            SkipReason::NoInlining => "inlining deactivated",
+            SkipReason::BlowupGuard => "inlining would blow up or failed",
+            SkipReason::VarBudget => "unfolding exceeds the variable budget",
+        }
+    }
+}
+
+/// Records why pre-processors did not reduce predicates, see `--explain_preproc`.
+///
+/// Only the last reason given by a pre-processor for a predicate is kept, since pre-processors
+/// run several times before reaching the fixed point.
+#[derive(Default)]
+pub struct SkipReasons {
+    /// Maps pre-processor names to the last reason they gave for each predicate.
+    reasons: BTreeMap<&'static str, PrdHMap<SkipReason>>,
+}
+impl SkipReasons {
+    /// Constructor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the reason why a pre-processor did not reduce a predicate.
+    pub fn record(&mut self, preproc: &'static str, pred: PrdIdx, reason: SkipReason) {
+        self.reasons
+            .entry(preproc)
+            .or_insert_with(PrdHMap::new)
+            .insert(pred, reason);
+    }
+
+    /// Counts the reasons of each pre-processor, ignoring predicates that are now defined.
+    pub fn summary(&self, instance: &Instance) -> BTreeMap<(&'static str, SkipReason), usize> {
+        let mut res = BTreeMap::new();
+        for (preproc, reasons) in &self.reasons {
+            for (pred, reason) in reasons {
+                if !instance[*pred].is_defined() {
+                    *res.entry((*preproc, *reason)).or_insert(0) += 1
+                }
+            }
+        }
+        res
+    }
+
+    /// Prints the summary of the reasons as a table.
+    pub fn print(&self, instance: &Instance) {
+        let summary = self.summary(instance);
+        println_side!("; pre-processing skip reasons {{");
+        if summary.is_empty() {
+            println_side!(";   none")
+        }
+        let width = summary
+            .keys()
+            .map(|(preproc, _)| preproc.len())
+            .max()
+            .unwrap_or(0);
+        for ((preproc, reason), count) in summary {
+            println_side!(
+                ";   {:<width$} | {:>5} | {}",
+                preproc,
+                count,
+                reason.describe(),
+                width = width
+            )
+        }
+        println_side!("; }}")
+    }
+}
+
 /// Result of extracting the terms for a predicate application in a clause.
 #[derive(Clone, PartialEq, Eq)]
 #[allow(dead_code)]
@@ -270,7 +368,7 @@ impl ExtractionCxt {
 
         log! { @5 | "terms of app to {}", args }
 
-        let mut app_vars = VarSet::with_capacity(instance[pred].sig.len());
+        let mut app_vars = VarSet::with_capacity(instance[pred].arity());
         let mut terms = TermSet::with_capacity(7);
 
         // Will store the arguments that are not a variable or a constant.
@@ -396,6 +494,26 @@ impl ExtractionCxt {
             return Ok(ExtractRes::Failed);
         }
 
+        // Each term holds in the other ones.
+        if tterms.terms().len() > 1 {
+            let conj = tterms.terms().clone();
+            for term in &conj {
+                let nu_term = term::simplify::conj_context_simpl(term, &conj);
+                if nu_term == *term {
+                    continue;
+                }
+                log! { @6 "{} ~> {}", term, nu_term }
+                tterms.terms_mut().remove(term);
+                match nu_term.bool() {
+                    Some(true) => (),
+                    Some(false) => return Ok(ExtractRes::Trivial),
+                    None => {
+                        tterms.terms_mut().insert(nu_term);
+                    }
+                }
+            }
+        }
+
         Ok(ExtractRes::Success((tterms, app_vars, pred_argss)))
     }
 
@@ -606,7 +724,7 @@ pub fn register_stats(instance: &Instance, _profiler: &Profiler, count: usize) -
                 let mut args = 0 ;
                 for info in instance.preds() {
                     if ! instance[info.idx].is_defined() {
-                        args += info.sig.len()
+                        args += info.arity()
                     }
                 }
                 args
@@ -650,6 +768,16 @@ pub fn register_info(
         "{:>10}    arg red", preproc
       ) => add _red_info.args_rmed
     }
+    profile! {
+      |_profiler| format!(
+        "{:>10}    app red", preproc
+      ) => add _red_info.apps_rmed
+    }
+    profile! {
+      |_profiler| format!(
+        "{:>10}  term simpl", preproc
+      ) => add _red_info.terms_simplified
+    }
     log! { @verb
       "{}: {}", conf.emph( preproc ), _red_info
     }
@@ -703,7 +831,7 @@ pub fn register_final_stats(instance: &Instance, _profiler: &Profiler) -> Res<()
           let mut args = 0 ;
           for info in instance.preds() {
             if ! instance[info.idx].is_defined() {
-              args += info.sig.len()
+              args += info.arity()
             }
           }
           args
@@ -784,6 +912,9 @@ pub fn run_preproc<Strat: RedStrat>(
     let red_info = red_info?;
 
     process_red_info(instance, _profiler, preproc.name(), count, &red_info)?;
+    if red_info.non_zero() {
+        instance.register_preproc_info(preproc.name(), &red_info)
+    }
 
     if check_solved(instance, _profiler)? {
         Ok(None)
@@ -7,6 +7,7 @@
 //! dropping some negative clauses.
 
 use crate::common::*;
+use crate::data::{SampleCounts, WarmData};
 use crate::unsat_core::UnsatRes;
 
 /// Splits the instance if asked to do so, and solves it.
@@ -17,11 +18,23 @@ use crate::unsat_core::UnsatRes;
 /// - `None` if not in `infer` mode,
 /// - an [`UnsatRes`] if unsat.
 ///
+/// Fails with [`ErrorKind::PartialModel`] if the iteration limit (`--max_iter`) was reached.
+///
 /// Assumes the instance is **already pre-processed**.
 ///
+/// The learning data is recorded in `warm` if it is not `None`, see [`WarmData`]. The number of
+/// samples of each learned predicate is added to `counts` if it is not `None`, see
+/// [`SampleCounts`].
+///
 /// [`UnsatRes`]: ../unsat_core/enum.UnsatRes.html (UnsatRes struct)
+/// [`WarmData`]: ../data/struct.WarmData.html (WarmData struct)
+/// [`SampleCounts`]: ../data/struct.SampleCounts.html (SampleCounts struct)
+/// [`ErrorKind::PartialModel`]: ../errors/enum.ErrorKind.html#variant.PartialModel
+/// (PartialModel variant of ErrorKind)
 pub fn work(
     real_instance: &Arc<Instance>,
+    mut warm: Option<&mut WarmData>,
+    mut counts: Option<&mut SampleCounts>,
     _profiler: &Profiler,
 ) -> Res<Option<Either<ConjCandidates, UnsatRes>>> {
     let mut model = ConjCandidates::new();
@@ -31,7 +44,7 @@ pub fn work(
     'split_loop: while let Some(preproc_res) = {
         if_not_bench! {
           if let Some((clause, handled, total)) = splitter.info() {
-            log! { conf.stats || conf.split_step, || @info
+            log! { conf.show_stats || conf.split_step, || @info
               "\n{}{}{}{}{} Splitting on negative clause #{} ({} of {})",
               conf.emph("|"),
               conf.happy("="),
@@ -65,7 +78,14 @@ pub fn work(
             }
         };
 
-        match run_on(_profiler, instance, &model)? {
+        match run_on(
+            _profiler,
+            real_instance,
+            instance,
+            &model,
+            warm.as_mut().map(|warm| &mut **warm),
+            counts.as_mut().map(|counts| &mut **counts),
+        )? {
             Some(Either::Left(this_model)) => add_submodel(&real_instance, &mut model, this_model),
 
             Some(Either::Right(reason)) => return Ok(Some(Either::Right(reason))),
@@ -82,10 +102,19 @@ pub fn work(
 }
 
 /// Runs on a pre-processed instance.
+///
+/// Fails with [`ErrorKind::PartialModel`] if the teacher reached its iteration limit, the partial
+/// model is `model` augmented with the last candidates of the learner.
+///
+/// [`ErrorKind::PartialModel`]: ../errors/enum.ErrorKind.html#variant.PartialModel
+/// (PartialModel variant of ErrorKind)
 fn run_on(
     _profiler: &Profiler,
+    real_instance: &Arc<Instance>,
     mut instance: Arc<Instance>,
     model: &ConjCandidates,
+    warm: Option<&mut WarmData>,
+    counts: Option<&mut SampleCounts>,
 ) -> Res<Option<Either<Model, UnsatRes>>> {
     if !conf.infer {
         if conf.split_step {
@@ -103,13 +132,20 @@ fn run_on(
 
     let res = profile!(
       |_profiler| wrap {
-        run_teacher(instance.clone(), & model)
+        run_teacher(instance.clone(), & model, warm, counts)
       } "solving"
     )?;
 
     match res {
-        TeachRes::Model(candidates) => {
+        TeachRes::Model(mut candidates) => {
             log_info! { "sat\n\n" }
+            if conf.simplify_model {
+                profile!(
+                  |_profiler| wrap {
+                    instance.simplify_candidates(&mut candidates)
+                  } "model simplification"
+                )?
+            }
             let mut this_model = instance.model_of(candidates)?;
             if let Some(instance) = Arc::get_mut(&mut instance) {
                 instance.simplify_pred_defs(&mut this_model)?
@@ -119,6 +155,14 @@ fn run_on(
         }
 
         TeachRes::Unsat(reason) => Ok(Some(Either::Right(reason))),
+
+        TeachRes::Partial(candidates) => {
+            log_info! { "unknown, iteration limit reached\n\n" }
+            let this_model = instance.model_of(candidates)?;
+            let mut partial = model.clone();
+            add_submodel(real_instance, &mut partial, this_model);
+            bail!(ErrorKind::PartialModel(partial))
+        }
     }
 }
 
@@ -144,9 +188,14 @@ fn add_submodel(instance: &Arc<Instance>, model: &mut ConjCandidates, submodel:
 }
 
 /// Runs the teacher on an instance.
-fn run_teacher(instance: Arc<Instance>, model: &ConjCandidates) -> Res<TeachRes> {
+fn run_teacher(
+    instance: Arc<Instance>,
+    model: &ConjCandidates,
+    warm: Option<&mut WarmData>,
+    counts: Option<&mut SampleCounts>,
+) -> Res<TeachRes> {
     let teacher_profiler = Profiler::new();
-    let solve_res = crate::teacher::start_class(instance, model, &teacher_profiler);
+    let solve_res = crate::teacher::start_class(instance, model, warm, counts, &teacher_profiler);
     print_stats("teacher", teacher_profiler);
     solve_res
 }
@@ -11,7 +11,7 @@ use crate::{
         smt::{FullParser as Parser, SmtTerm},
         *,
     },
-    data::Data,
+    data::{Data, SampleCounts, WarmData},
     unsat_core::UnsatRes,
 };
 
@@ -21,13 +21,22 @@ use self::assistant::Assistant;
 
 pub use self::cex_bias::CexBias;
 
+/// Number of learning rounds between two prunings of the samples no constraint mentions anymore.
+const PRUNE_PERIOD: usize = 10;
+
 /// Starts the teaching process.
 ///
 /// The partial model stores conjunction of top terms for some of the top
 /// terms, and is expressed in terms of the predicates' original signatures.
+///
+/// If `warm` is not `None` and the instance is sat, the learning data and the model are recorded
+/// in it for later checks. If `counts` is not `None`, the number of samples of each predicate is
+/// added to it.
 pub fn start_class(
     instance: Arc<Instance>,
     partial_model: &ConjCandidates,
+    warm: Option<&mut WarmData>,
+    counts: Option<&mut SampleCounts>,
     profiler: &Profiler,
 ) -> Res<TeachRes> {
     log! { @debug
@@ -60,6 +69,13 @@ pub fn start_class(
         },
     };
 
+    if let (Some(warm), Ok(TeachRes::Model(candidates))) = (warm, res.as_ref()) {
+        warm.record(&teacher.data, candidates)
+    }
+    if let Some(counts) = counts {
+        counts.record(&teacher.data)
+    }
+
     teacher.finalize()?;
     res
 }
@@ -81,6 +97,9 @@ pub fn teach(teacher: &mut Teacher) -> Res<TeachRes> {
     // None at the beginning (broadcast).
     let mut learner: Option<LrnIdx> = None;
 
+    // Number of candidates checked so far, see `conf.teacher.max_iter`.
+    let mut iter = 0;
+
     loop {
         log_verb! {
           "all learning data:\n{}", teacher.data.string_do(
@@ -121,9 +140,18 @@ pub fn teach(teacher: &mut Teacher) -> Res<TeachRes> {
             // Got a candidate.
             Either::Left((idx, candidates)) => {
                 learner = Some(idx);
+                iter += 1;
+                let last = match conf.teacher.max_iter {
+                    Some(max) if iter >= max => Some(candidates.clone()),
+                    _ => None,
+                };
                 if let Some(res) = teacher.handle_candidates(candidates, idx)? {
                     return Ok(res);
                 }
+                if let Some(candidates) = last {
+                    log_info! { "reached iteration limit ({})", iter }
+                    return Ok(TeachRes::Partial(teacher.model_of_candidates(candidates)));
+                }
             }
         }
     }
@@ -221,7 +249,12 @@ impl<'a> Teacher<'a> {
 
         let learners = LrnMap::with_capacity(2);
         let (to_teacher, from_learners) = Msg::channel();
-        let data = Data::new(instance.clone());
+        let mut data = Data::new(instance.clone());
+        if let Some(warm) = instance.warm() {
+            if data.add_warm_data(warm)? {
+                profile! { |profiler| "warm start" => add 1 }
+            }
+        }
 
         let assistant = if conf.teacher.assistant {
             Some(
@@ -305,7 +338,8 @@ impl<'a> Teacher<'a> {
 
         log_debug! { "generating data from initial cex..." }
         let nu_stuff = self.instance.cexs_to_data(&mut self.data, cexs)?;
-        if !nu_stuff {
+        // Data from previous checks might already contain the initial cexs.
+        if !nu_stuff && self.data.metrics() == (0, 0, 0) {
             bail! { "translation of initial cexs to data generated no new data" }
         }
         self.run_assistant()?;
@@ -593,9 +627,36 @@ impl<'a> Teacher<'a> {
         profile! { self mark "data", "propagation" }
         profile! { self mark "data" }
 
+        self.data.new_round();
+        if self.data.round() % PRUNE_PERIOD == 0 {
+            let _pruned = self.data.prune_unreferenced_samples();
+            log_verb! { "pruned {} unreferenced sample(s)", _pruned }
+        }
+        if let Some(budget) = conf.teacher.mem_budget {
+            self.enforce_mem_budget(budget)?
+        }
+
         Ok(None)
     }
 
+    /// Drops old constraints if the learning data is over the memory budget.
+    ///
+    /// See `Data::evict_constraints`.
+    fn enforce_mem_budget(&mut self, budget: usize) -> Res<()> {
+        let footprint = self.data.footprint();
+        if footprint <= budget {
+            return Ok(());
+        }
+        let evicted = self.data.evict_constraints(conf.teacher.mem_budget_age)?;
+        if evicted > 0 {
+            warn! {
+                "over memory budget ({} > {})", footprint, budget ;
+                "dropped {} old constraint(s)", evicted
+            }
+        }
+        Ok(())
+    }
+
     /// Waits for some candidates.
     ///
     /// Returns `None` when there are no more kids. Otherwise, the second
@@ -637,9 +698,9 @@ impl<'a> Teacher<'a> {
                         Id::Learner(idx) => conf.emph(&self.learners[idx].1),
                         Id::Assistant => conf.emph("assistant"),
                     };
-                    println!(";");
+                    println_side!(";");
                     for _line in _s.lines() {
-                        println!("; {} | {}", id, _line)
+                        println_side!("; {} | {}", id, _line)
                     }
                 }
 
@@ -698,7 +759,8 @@ impl<'a> Teacher<'a> {
             .chain_err(|| "while getting unsat proof in teacher")
     }
 
-    /// Initial check, where all candidates are `true`.
+    /// Initial check, where the candidate of a predicate is the conjunction of its hints, `true`
+    /// if it has none.
     ///
     /// Drops the copy of the `Sender` end of the channel used to communicate
     /// with the teacher (`self.to_teacher`). This entails that attempting to
@@ -731,7 +793,8 @@ impl<'a> Teacher<'a> {
             //   }
             //   cands.push( Some( term::or(cand_dnf) ) )
             } else {
-                cands.push(Some(term::tru()))
+                // Try the user's hints first, if any.
+                cands.push(Some(term::and(self.instance[pred].hints())))
             }
         }
 
@@ -936,6 +999,17 @@ impl<'a> Teacher<'a> {
         bias: bool,
     ) -> Res<()> {
         if !self.clauses_to_ignore.contains(&clause) {
+            if self.instance.ground_clauses().contains(&clause) {
+                if let Some(cexs) = self.eval_ground_clause(cands, clause)? {
+                    profile! { self "ground clauses evaluated" => add 1 }
+                    if !cexs.is_empty() {
+                        let prev = map.insert(clause, cexs);
+                        debug_assert_eq!(prev, None)
+                    }
+                    return Ok(());
+                }
+            }
+
             if self.restart_on_cex {
                 self.define_preds(cands)?
             } else {
@@ -975,6 +1049,100 @@ impl<'a> Teacher<'a> {
         )
     }
 
+    /// Bias of an unbiased counterexample for a clause.
+    ///
+    /// Left for positive clauses, right on the only predicate application for strict negative
+    /// clauses, none otherwise.
+    fn unbiased_bias(&self, clause: ClsIdx) -> Bias {
+        if self.instance[clause].is_positive() {
+            Bias::Lft
+        } else if self.instance[clause].is_strict_neg() {
+            let (pred, args) = self.instance[clause]
+                .lhs_preds()
+                .iter()
+                .next()
+                .map(|(pred, argss)| (*pred, argss.iter().next().unwrap().clone()))
+                .unwrap();
+            Bias::NuRgt(pred, args)
+        } else {
+            Bias::Non
+        }
+    }
+
+    /// Checks a ground clause by evaluating the candidates on its arguments.
+    ///
+    /// Returns `None` if evaluation is inconclusive, in which case the clause must be checked by
+    /// the solver. Otherwise, the result is empty if the clause holds, and contains the only
+    /// counterexample for the clause if it does not.
+    fn eval_ground_clause(&self, cands: &Candidates, clause: ClsIdx) -> Res<Option<Vec<BCex>>> {
+        let cex = Cex::of_model(
+            self.instance[clause].vars(),
+            Vec::<(VarIdx, (), Val)>::new(),
+            false,
+        )?;
+
+        // Evaluates a candidate on the arguments of a predicate application.
+        let eval_app = |pred: PrdIdx, args: &VarTerms| -> Res<Option<bool>> {
+            let cand = if let Some(cand) = cands[pred].as_ref() {
+                cand
+            } else {
+                return Ok(None);
+            };
+            let mut model = VarMap::with_capacity(args.len());
+            for arg in args.iter() {
+                model.push(arg.eval(&cex)?)
+            }
+            cand.bool_eval(&model)
+        };
+
+        for term in self.instance[clause].lhs_terms() {
+            match term.bool_eval(&cex)? {
+                Some(true) => (),
+                Some(false) => return Ok(Some(vec![])),
+                None => return Ok(None),
+            }
+        }
+
+        for (pred, argss) in self.instance[clause].lhs_preds() {
+            for args in argss {
+                match eval_app(*pred, args)? {
+                    Some(true) => (),
+                    Some(false) => return Ok(Some(vec![])),
+                    None => return Ok(None),
+                }
+            }
+        }
+
+        if let Some((pred, args)) = self.instance[clause].rhs() {
+            match eval_app(pred, args)? {
+                Some(true) => return Ok(Some(vec![])),
+                Some(false) => (),
+                None => return Ok(None),
+            }
+        }
+
+        log! { @debug | "  ground clause #{} is falsified", clause }
+        let bias = self.unbiased_bias(clause);
+        Ok(Some(vec![(cex, bias)]))
+    }
+
+    /// String representation of a counterexample for a clause.
+    ///
+    /// Uses the names of the variables of the clause, and ignores inactive variables.
+    fn cex_to_string(&self, clause: ClsIdx, cex: &Cex) -> String {
+        let vars = self.instance[clause].vars();
+        let mut s = String::new();
+        for (var, val) in cex.index_iter() {
+            if var < vars.len() && vars[var].active {
+                if !s.is_empty() {
+                    s.push(' ')
+                }
+                s.push_str(&format!("({} {})", vars[var].smt_name(), val))
+            }
+        }
+        s
+    }
+
     /// Check-sats given an optional bias.
     fn check_sat_cex(
         &mut self,
@@ -997,7 +1165,7 @@ impl<'a> Teacher<'a> {
                 log! { @debug | "  sat, getting cex" }
                 profile! { self mark "cexs", "biased check-sat" }
                 let cex = self.get_bias_cex(clause, &bias)?;
-                log! { @debug | "  {}", cex }
+                log! { @debug | "  {}", self.cex_to_string(clause, &cex) }
                 self.solver.de_actlit(actlit)?;
                 Ok(Some((cex, bias)))
             } else {
@@ -1050,21 +1218,9 @@ impl<'a> Teacher<'a> {
 
             if sat {
                 log! { @debug | "  sat, getting cex" }
-                let bias = if self.instance[clause].is_positive() {
-                    Bias::Lft
-                } else if self.instance[clause].is_strict_neg() {
-                    let (pred, args) = self.instance[clause]
-                        .lhs_preds()
-                        .iter()
-                        .next()
-                        .map(|(pred, argss)| (*pred, argss.iter().next().unwrap().clone()))
-                        .unwrap();
-                    Bias::NuRgt(pred, args)
-                } else {
-                    Bias::Non
-                };
+                let bias = self.unbiased_bias(clause);
                 let cex = self.get_bias_cex(clause, &bias)?;
-                log! { @debug "  {}", cex }
+                log! { @debug "  {}", self.cex_to_string(clause, &cex) }
                 Ok(Some((cex, bias)))
             } else {
                 log! { @debug | "  unsat" }
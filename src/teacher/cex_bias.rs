@@ -114,7 +114,7 @@ impl CexBias {
 
         let res = if let Some((pred, args)) = instance[clause].rhs() {
             log! { @5 "-> {}", instance[pred] }
-            if data.neg[pred].is_empty() {
+            if data.neg_count_for(pred) == 0 {
                 // No negative data...
                 log! { @5 "   no negative data" }
                 None
@@ -166,7 +166,7 @@ impl CexBias {
             let pred = *pred;
             log! { @5 "for {} ({})", instance[pred], argss.len() }
 
-            if data.pos[pred].is_empty() {
+            if data.pos_count_for(pred) == 0 {
                 log! { @5 "  no positive data" }
                 self.lhs_non_pos.insert(pred, argss.clone());
                 continue;
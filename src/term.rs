@@ -68,6 +68,7 @@ mod leaf_iter;
 pub mod simplify;
 mod tterms;
 pub mod typ;
+pub mod visitor;
 mod zip;
 
 pub use self::bindings::Bindings;
@@ -76,6 +77,7 @@ pub use self::leaf_iter::LeafIter;
 pub use self::op::*;
 pub use self::tterms::*;
 pub use self::typ::Typ;
+pub use self::visitor::Visitor;
 
 #[cfg(test)]
 mod test;
@@ -105,6 +107,8 @@ pub enum RTerm {
     CArray {
         /// Depth of this term.
         depth: usize,
+        /// Size of this term.
+        size: usize,
         /// Type of **the indices** (not the array).
         typ: Typ,
         /// Default term of the array.
@@ -115,6 +119,8 @@ pub enum RTerm {
     App {
         /// Depth of this term.
         depth: usize,
+        /// Size of this term.
+        size: usize,
         /// Type of the application.
         typ: Typ,
         /// The operator.
@@ -127,6 +133,8 @@ pub enum RTerm {
     DTypNew {
         /// Depth of this term.
         depth: usize,
+        /// Size of this term.
+        size: usize,
         /// Type of the application.
         typ: Typ,
         /// Name of the constructor.
@@ -139,6 +147,8 @@ pub enum RTerm {
     DTypSlc {
         /// Depth of this term.
         depth: usize,
+        /// Size of this term.
+        size: usize,
         /// Type of the application.
         typ: Typ,
         /// Name of the selector.
@@ -151,6 +161,8 @@ pub enum RTerm {
     DTypTst {
         /// Depth of this term.
         depth: usize,
+        /// Size of this term.
+        size: usize,
         /// Type of the term (always bool).
         typ: Typ,
         /// Name of the tester.
@@ -163,6 +175,8 @@ pub enum RTerm {
     Fun {
         /// Depth of this term.
         depth: usize,
+        /// Size of this term.
+        size: usize,
         /// Type of this term.
         typ: Typ,
         /// Function being applied.
@@ -179,8 +193,10 @@ impl RTerm {
         let depth = args
             .iter()
             .fold(1, |acc, term| ::std::cmp::max(acc, term.depth() + 1));
+        let size = args.iter().fold(1, |acc, term| acc + term.size());
         RTerm::App {
             depth,
+            size,
             typ,
             op,
             args,
@@ -195,9 +211,11 @@ impl RTerm {
         let depth = args
             .iter()
             .fold(1, |acc, term| ::std::cmp::max(acc, term.depth() + 1));
+        let size = args.iter().fold(1, |acc, term| acc + term.size());
         let name = name.into();
         RTerm::Fun {
             depth,
+            size,
             typ,
             name,
             args,
@@ -210,6 +228,7 @@ impl RTerm {
     fn new_carray(typ: Typ, term: Term) -> Self {
         RTerm::CArray {
             depth: term.depth() + 1,
+            size: term.size() + 1,
             typ,
             term,
         }
@@ -223,6 +242,7 @@ impl RTerm {
         let name = name.into();
         RTerm::DTypSlc {
             depth: term.depth() + 1,
+            size: term.size() + 1,
             typ,
             name,
             term,
@@ -237,6 +257,7 @@ impl RTerm {
         let name = name.into();
         RTerm::DTypTst {
             depth: term.depth() + 1,
+            size: term.size() + 1,
             typ,
             name,
             term,
@@ -252,8 +273,10 @@ impl RTerm {
         let depth = args
             .iter()
             .fold(1, |acc, term| ::std::cmp::max(acc, term.depth() + 1));
+        let size = args.iter().fold(1, |acc, term| acc + term.size());
         RTerm::DTypNew {
             depth,
+            size,
             typ,
             name,
             args,
@@ -263,7 +286,34 @@ impl RTerm {
 
 /// Accessors and testers.
 impl RTerm {
-    /// Size of a term: number of subterms.
+    /// Size of a term: number of nodes in its tree representation.
+    ///
+    /// Subterms appearing several times are counted several times. Computed when the term is
+    /// created, constant time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::term;
+    /// assert_eq! { term::int_var(0).size(), 1 }
+    /// let sum = term::add(vec![term::int_var(0), term::int_var(1)]);
+    /// assert_eq! { sum.size(), 3 }
+    /// let term = term::ge(sum.clone(), sum);
+    /// assert! { term.size() <= 7 }
+    /// ```
+    pub fn size(&self) -> usize {
+        match self {
+            RTerm::Var(_, _) | RTerm::Cst(_) => 1,
+            RTerm::CArray { size, .. }
+            | RTerm::App { size, .. }
+            | RTerm::DTypNew { size, .. }
+            | RTerm::DTypSlc { size, .. }
+            | RTerm::DTypTst { size, .. }
+            | RTerm::Fun { size, .. } => *size,
+        }
+    }
+
+    /// Depth of a term.
     ///
     /// # Examples
     ///
@@ -878,6 +928,92 @@ impl RTerm {
         res.is_err()
     }
 
+    /// True if the term is linear in all its variables.
+    ///
+    /// A term is **not** linear if it contains
+    ///
+    /// - a multiplication where at least two arguments mention variables,
+    /// - a division or modulo where the divisor mentions variables,
+    /// - a constant multiplication by something else than `1` or `-1` of a non-constant term.
+    ///
+    /// Linear in time in the size of the term.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let t = term::ge(
+    ///     term::sub(vec![term::int_var(0), term::int_var(1)]), term::int(7)
+    /// );
+    /// # println!("{}", t);
+    /// assert! { t.is_linear() }
+    ///
+    /// let t = term::idiv(vec![term::int_var(0), term::int(2)]);
+    /// # println!("{}", t);
+    /// assert! { t.is_linear() }
+    ///
+    /// let t = term::idiv(vec![term::int(2), term::int_var(0)]);
+    /// # println!("{}", t);
+    /// assert! { !t.is_linear() }
+    ///
+    /// let t = term::mul(vec![term::int_var(0), term::int_var(1)]);
+    /// # println!("{}", t);
+    /// assert! { !t.is_linear() }
+    ///
+    /// let t = term::modulo(term::int_var(0), term::add2(term::int_var(1), term::int(1)));
+    /// # println!("{}", t);
+    /// assert! { !t.is_linear() }
+    ///
+    /// let t = term::cmul(3, term::int_var(0));
+    /// # println!("{}", t);
+    /// assert! { !t.is_linear() }
+    /// ```
+    pub fn is_linear(&self) -> bool {
+        use self::zip::*;
+
+        // Yields `true` if the sub-term mentions a variable. Will be `Err(())` as soon as something
+        // non-linear is found.
+        let res = zip(
+            &self.to_hcons(),
+            |term| {
+                if let Some((val, kid)) = term.cmul_inspect() {
+                    if !(val.is_one() || val.is_minus_one()) && kid.val().is_none() {
+                        return Err(());
+                    }
+                }
+                Ok(None)
+            },
+            |zip_null| match zip_null {
+                ZipNullary::Cst(_) => Ok(false),
+                ZipNullary::Var(_, _) => Ok(true),
+            },
+            |zip_op, _, args: Vec<bool>| {
+                let has_vars = args.iter().any(|b| *b);
+                match zip_op {
+                    ZipOp::Op(Op::Mul) if args.iter().filter(|b| **b).count() > 1 => Err(()),
+                    ZipOp::Op(Op::IDiv)
+                    | ZipOp::Op(Op::Div)
+                    | ZipOp::Op(Op::Mod)
+                    | ZipOp::Op(Op::Rem)
+                        if args.iter().skip(1).any(|b| *b) =>
+                    {
+                        Err(())
+                    }
+                    _ => Ok(ZipDoTotal::Upp { yielded: has_vars }),
+                }
+            },
+            |mut frame| {
+                let nu_term = frame
+                    .rgt_args
+                    .next()
+                    .expect("illegal call to `partial_op`: empty `rgt_args` (is_linear)");
+                Ok(ZipDo::Trm { nu_term, frame })
+            },
+        );
+
+        res.is_ok()
+    }
+
     /// The kids of this term, if any.
     ///
     /// # Examples
@@ -1638,64 +1774,10 @@ impl RTerm {
                     }
                 }
             },
-            |zip_op, typ, mut acc| {
-                let yielded = match zip_op {
-                    ZipOp::Op(op) => term::app(op, acc),
-                    ZipOp::New(name) => term::dtyp_new(typ.clone(), name.clone(), acc),
-
-                    ZipOp::Slc(name) => {
-                        if let Some(kid) = acc.pop() {
-                            if !acc.is_empty() {
-                                panic!(
-                                    "illegal application of datatype selector {} to {} arguments",
-                                    conf.bad(name),
-                                    acc.len() + 1
-                                )
-                            }
-                            term::dtyp_slc(typ.clone(), name.clone(), kid)
-                        } else {
-                            panic!(
-                                "illegal application of datatype selector {} to 0 arguments",
-                                conf.bad(name)
-                            )
-                        }
-                    }
-
-                    ZipOp::Tst(name) => {
-                        if let Some(kid) = acc.pop() {
-                            if !acc.is_empty() {
-                                panic!(
-                                    "illegal application of datatype tester {} to {} arguments",
-                                    conf.bad(name),
-                                    acc.len() + 1
-                                )
-                            }
-                            term::dtyp_tst(name.clone(), kid)
-                        } else {
-                            panic!(
-                                "illegal application of datatype tester {} to 0 arguments",
-                                conf.bad(name)
-                            )
-                        }
-                    }
-
-                    ZipOp::CArray => {
-                        if let Some(kid) = acc.pop() {
-                            if !acc.is_empty() {
-                                panic!(
-                                    "illegal constant array application to {} arguments",
-                                    acc.len() + 1
-                                )
-                            }
-                            term::cst_array(typ.clone(), kid)
-                        } else {
-                            panic!("illegal constant array application to 0 arguments")
-                        }
-                    }
-                    ZipOp::Fun(name) => term::fun(name.clone(), acc),
-                };
-
-                Ok(ZipDoTotal::Upp { yielded })
+            |zip_op, typ, acc| {
+                Ok(ZipDoTotal::Upp {
+                    yielded: zip_op.rebuild(typ, acc),
+                })
             },
             |mut frame| {
                 let nu_term = frame
@@ -1866,6 +1948,112 @@ impl RTerm {
         }
     }
 
+    /// Polarity of the occurrences of a variable in a term.
+    ///
+    /// Returns `Some(true)` if `var` only appears under an even number of negations, `Some(false)`
+    /// if it only appears under an odd number of negations, and `None` if it appears under both
+    /// or does not appear at all.
+    ///
+    /// The left-hand side of an implication counts as a negation. Occurrences in the condition of
+    /// an `ite`, in an equality or disequality between booleans, or in the arguments of a
+    /// function application have both polarities.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let (v_0, v_1) = (term::bool_var(0), term::bool_var(1));
+    /// let t = term::and(vec![ v_0.clone(), term::not(v_1.clone()) ]);
+    /// # println!("{}", t);
+    /// assert_eq! { t.occurs_positively(0.into()), Some(true) }
+    /// assert_eq! { t.occurs_positively(1.into()), Some(false) }
+    /// assert_eq! { t.occurs_positively(2.into()), None }
+    ///
+    /// let t = term::and(vec![
+    ///     v_0.clone(), term::not( term::and(vec![ v_0.clone(), v_1.clone() ]) )
+    /// ]);
+    /// # println!("{}", t);
+    /// assert_eq! { t.occurs_positively(0.into()), None }
+    /// assert_eq! { t.occurs_positively(1.into()), Some(false) }
+    /// ```
+    pub fn occurs_positively(&self, var: VarIdx) -> Option<bool> {
+        use self::RTerm::*;
+        let (mut pos, mut neg) = (false, false);
+        // `None` polarity means both polarities.
+        let mut stack = vec![(self, Some(true))];
+
+        while let Some((term, polarity)) = stack.pop() {
+            match term {
+                Var(_, idx) => {
+                    if *idx == var {
+                        match polarity {
+                            Some(true) => pos = true,
+                            Some(false) => neg = true,
+                            None => return None,
+                        }
+                    }
+                }
+
+                Cst(_) => (),
+
+                App {
+                    op: Op::Not, args, ..
+                } => stack.extend(args.iter().map(|arg| (arg.get(), polarity.map(|b| !b)))),
+
+                App {
+                    op: Op::Impl, args, ..
+                } => {
+                    let mut args = args.iter();
+                    if let Some(lhs) = args.next() {
+                        stack.push((lhs.get(), polarity.map(|b| !b)))
+                    }
+                    stack.extend(args.map(|arg| (arg.get(), polarity)))
+                }
+
+                App {
+                    op: Op::Ite, args, ..
+                } => {
+                    let mut args = args.iter();
+                    if let Some(cnd) = args.next() {
+                        stack.push((cnd.get(), None))
+                    }
+                    stack.extend(args.map(|arg| (arg.get(), polarity)))
+                }
+
+                App {
+                    op: Op::Eql, args, ..
+                }
+                | App {
+                    op: Op::Distinct,
+                    args,
+                    ..
+                } if args.iter().any(|arg| arg.typ().is_bool()) => {
+                    stack.extend(args.iter().map(|arg| (arg.get(), None)))
+                }
+
+                App { args, .. } | DTypNew { args, .. } => {
+                    stack.extend(args.iter().map(|arg| (arg.get(), polarity)))
+                }
+
+                Fun { args, .. } => stack.extend(args.iter().map(|arg| (arg.get(), None))),
+
+                CArray { term, .. } | DTypSlc { term, .. } | DTypTst { term, .. } => {
+                    stack.push((term.get(), polarity))
+                }
+            }
+
+            if pos && neg {
+                return None;
+            }
+        }
+
+        match (pos, neg) {
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            _ => None,
+        }
+    }
+
     /// Boolean a constant boolean term evaluates to.
     ///
     /// # Examples
@@ -1975,6 +2163,10 @@ impl RTerm {
 
     /// The highest variable index appearing in the term.
     ///
+    /// Walks the whole term, prefer [`term::max_var`] on hashconsed terms as it is cached.
+    ///
+    /// [`term::max_var`]: fn.max_var.html (max_var function)
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -2082,64 +2274,10 @@ impl RTerm {
                 ZipNullary::Cst(val) => Ok(cst(val.clone())),
                 ZipNullary::Var(typ, var) => Ok(term::var(var, typ.clone())),
             },
-            |zip_op, typ, mut acc| {
-                let yielded = match zip_op {
-                    ZipOp::Op(op) => term::app(op, acc),
-                    ZipOp::New(name) => term::dtyp_new(typ.clone(), name.clone(), acc),
-
-                    ZipOp::Slc(name) => {
-                        if let Some(kid) = acc.pop() {
-                            if !acc.is_empty() {
-                                panic!(
-                                    "illegal application of datatype selector {} to {} arguments",
-                                    conf.bad(name),
-                                    acc.len() + 1
-                                )
-                            }
-                            term::dtyp_slc(typ.clone(), name.clone(), kid)
-                        } else {
-                            panic!(
-                                "illegal application of datatype selector {} to 0 arguments",
-                                conf.bad(name)
-                            )
-                        }
-                    }
-
-                    ZipOp::Tst(name) => {
-                        if let Some(kid) = acc.pop() {
-                            if !acc.is_empty() {
-                                panic!(
-                                    "illegal application of datatype tester {} to {} arguments",
-                                    conf.bad(name),
-                                    acc.len() + 1
-                                )
-                            }
-                            term::dtyp_tst(name.clone(), kid)
-                        } else {
-                            panic!(
-                                "illegal application of datatype tester {} to 0 arguments",
-                                conf.bad(name)
-                            )
-                        }
-                    }
-
-                    ZipOp::CArray => {
-                        if let Some(kid) = acc.pop() {
-                            if !acc.is_empty() {
-                                panic!(
-                                    "illegal constant array application to {} arguments",
-                                    acc.len() + 1
-                                )
-                            }
-                            term::cst_array(typ.clone(), kid)
-                        } else {
-                            panic!("illegal constant array application to 0 arguments")
-                        }
-                    }
-                    ZipOp::Fun(name) => term::fun(name.clone(), acc),
-                };
-
-                Ok(ZipDoTotal::Upp { yielded })
+            |zip_op, typ, acc| {
+                Ok(ZipDoTotal::Upp {
+                    yielded: zip_op.rebuild(typ, acc),
+                })
             },
             |mut frame| {
                 let nu_term = frame.rgt_args.next().expect(
@@ -2278,6 +2416,25 @@ impl RTerm {
     ///     )
     /// }
     /// ```
+    ///
+    /// Constant multiplications are inverted by dividing over the reals. Over the integers, this
+    /// only works if the division is exact.
+    ///
+    /// ```rust
+    /// # use hoice::common::* ;
+    /// let term = term::cmul( Rat::new(3.into(), 1.into()), term::real_var(0) ) ;
+    /// assert_eq!{
+    ///     term.invert( term::real_var(1) ),
+    ///     Some( (0.into(), term::cmul( Rat::new(1.into(), 3.into()), term::real_var(1) ) ) )
+    /// }
+    /// let term = term::cmul( 3, term::int_var(0) ) ;
+    /// assert_eq!{ term.invert( term::int_var(1) ), None }
+    /// assert_eq!{
+    ///     term.invert( term::cmul( 6, term::int_var(1) ) ),
+    ///     Some( (0.into(), term::cmul( 2, term::int_var(1) ) ) )
+    /// }
+    /// assert_eq!{ term.invert( term::cmul( 4, term::int_var(1) ) ), None }
+    /// ```
     pub fn invert(&self, term: Term) -> Option<(VarIdx, Term)> {
         let mut solution = term;
         let mut term = self;
@@ -2310,24 +2467,29 @@ impl RTerm {
                         Op::CMul => {
                             if args.len() == 2 {
                                 if let Some(val) = args[0].val() {
-                                    if val
-                                        .minus()
-                                        .expect("illegal c_mul application found in `invert`")
-                                        .is_one()
-                                    {
-                                        solution = term::u_minus(solution);
-                                        term = &args[1];
-                                        continue;
-                                    } else {
-                                        return None;
-                                    }
+                                    solution = invert_cmul(&val, solution, &args[1].typ())?;
+                                    term = &args[1];
+                                    continue;
                                 }
                             }
 
                             panic!("illegal c_mul application found in `invert`")
                         }
+                        Op::Mul => {
+                            if args.len() == 2 {
+                                if let Some(val) = args[0].val() {
+                                    solution = invert_cmul(&val, solution, &args[1].typ())?;
+                                    term = &args[1];
+                                    continue;
+                                } else if let Some(val) = args[1].val() {
+                                    solution = invert_cmul(&val, solution, &args[0].typ())?;
+                                    term = &args[0];
+                                    continue;
+                                }
+                            }
+                            return None;
+                        }
                         // Op::Div => (Op::Mul, false),
-                        // Op::Mul => (Op::Div, true),
                         Op::ToReal => {
                             solution = term::to_int(solution);
                             term = &args[0];
@@ -2372,6 +2534,49 @@ impl RTerm {
     }
 }
 
+/// Solves `solution = coef * x` for `x`, where `x` has type `typ`.
+///
+/// Over the reals, this is `x = solution / coef` for any non-zero `coef`. Over the integers, it
+/// only succeeds when `coef` is `1`, `-1`, or divides `solution` evenly, which requires
+/// `solution` to be a constant or a constant multiplication.
+fn invert_cmul(coef: &Val, solution: Term, typ: &Typ) -> Option<Term> {
+    if coef.is_zero() {
+        return None;
+    }
+    if coef.is_one() {
+        return Some(solution);
+    }
+    if coef.is_minus_one() {
+        return Some(term::u_minus(solution));
+    }
+
+    if typ.is_real() {
+        let coef = coef.to_real().ok()??;
+        Some(term::cmul(coef.recip(), solution))
+    } else if typ.is_int() {
+        let coef = coef.to_int().ok()??;
+        if let Some(val) = solution.val() {
+            let val = val.to_int().ok()??;
+            if (&val % &coef).is_zero() {
+                Some(term::int(val / coef))
+            } else {
+                None
+            }
+        } else if let Some((val, kid)) = solution.cmul_inspect() {
+            let val = val.to_int().ok()??;
+            if (&val % &coef).is_zero() {
+                Some(term::cmul(val / coef, kid.clone()))
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
 /// Term evaluation.
 impl RTerm {
     /// Term evaluation.
@@ -2397,6 +2602,26 @@ impl RTerm {
     /// ].into();
     /// assert! { t.eval(&ill_typed).is_err() }
     /// ```
+    ///
+    /// Applying a selector to a value built with a different constructor yields a non-value.
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// fun::test::create_length_fun();
+    /// let list = typ::dtyp(dtyp::get("List").unwrap(), vec![typ::int()].into());
+    /// let t = term::dtyp_slc(typ::int(), "head", term::var(0, list.clone()));
+    ///
+    /// let values: VarMap<_> = vec![ val::dtyp_new(list.clone(), "nil".into(), vec![]) ].into();
+    /// assert_eq! { t.eval(&values).unwrap(), val::none(typ::int()) }
+    ///
+    /// let values: VarMap<_> = vec![
+    ///     val::dtyp_new(
+    ///         list.clone(), "insert".into(),
+    ///         vec![ val::int(7), val::dtyp_new(list, "nil".into(), vec![]) ]
+    ///     )
+    /// ].into();
+    /// assert_eq! { t.eval(&values).unwrap(), val::int(7) }
+    /// ```
     pub fn eval<E: Evaluator>(&self, model: &E) -> Res<Val> {
         eval::eval(&factory::term(self.clone()), model)
     }
@@ -2632,6 +2857,6 @@ impl<'a> PebcakFmt<'a> for RTerm {
         w: &mut W,
         vars: &'a VarMap<crate::info::VarInfo>,
     ) -> IoRes<()> {
-        self.write(w, |w, var| w.write_all(vars[var].as_bytes()))
+        self.write(w, |w, var| vars[var].write_name(w))
     }
 }
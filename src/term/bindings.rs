@@ -110,11 +110,43 @@ impl Builder {
     }
 
     /// Builds bindings from the information it accumulated so far.
-    pub fn build(mut self, mut fresh: VarIdx) -> Option<Bindings> {
+    ///
+    /// Only binds the terms that appear more than twice.
+    pub fn build(self, fresh: VarIdx) -> Option<Bindings> {
+        self.build_min(fresh, 3)
+    }
+
+    /// Builds bindings for all the terms appearing more than once.
+    ///
+    /// Used when printing models, where single-use subterms stay inlined.
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// use hoice::term::bindings::Builder;
+    /// let shared = term::ge(term::int_var(0), term::int(7));
+    /// let single = term::ge(term::int_var(1), term::int(3));
+    /// let term = term::and(vec![
+    ///     term::or(vec![shared.clone(), term::bool_var(2)]),
+    ///     term::or(vec![shared.clone(), term::bool_var(3)]),
+    ///     term::or(vec![single.clone(), term::bool_var(4)]),
+    /// ]);
+    /// # println!("{}", term);
+    /// // Fresh variables must not collide with the ones of the term.
+    /// let bindings = Builder::new().scan_term(&term).build_shared(5.into()).unwrap();
+    /// assert_eq! { bindings.bindings().len(), 1 }
+    /// assert_eq! { bindings.bindings()[0].get(&shared), Some(&VarIdx::from(5)) }
+    /// assert! { !bindings.bindings()[0].contains_key(&single) }
+    /// ```
+    pub fn build_shared(self, fresh: VarIdx) -> Option<Bindings> {
+        self.build_min(fresh, 2)
+    }
+
+    /// Builds bindings for the terms appearing at least `min_count` times.
+    fn build_min(mut self, mut fresh: VarIdx, min_count: usize) -> Option<Bindings> {
         let first_fresh = fresh;
         let mut empty = 0;
         for term_map in self.depth_map.values_mut() {
-            term_map.retain(|_, count| *count > 2);
+            term_map.retain(|_, count| *count >= min_count);
             if term_map.is_empty() {
                 empty += 1
             }
@@ -68,48 +68,11 @@ fn total<'a>(
             .eval(values)
             .chain_err(|| format!("while evaluating operator `{}`", op))?,
 
-        ZipOp::New(name) => val::dtyp_new(typ.clone(), name.clone(), values),
+        ZipOp::New(name) => dtyp_new(typ, name, values)?,
 
         ZipOp::Slc(name) => {
             if values.len() == 1 {
-                let value = values.pop().unwrap();
-                if !value.is_known() {
-                    val::none(typ.clone())
-                } else if let Some((ty, constructor, values)) = value.dtyp_inspect() {
-                    if let Some((dtyp, _)) = ty.dtyp_inspect() {
-                        if let Some(selectors) = dtyp.news.get(constructor) {
-                            let mut res = None;
-                            for ((selector, _), value) in selectors.iter().zip(values.iter()) {
-                                if selector == name {
-                                    res = Some(value.clone())
-                                }
-                            }
-
-                            if let Some(res) = res {
-                                res
-                            } else {
-                                val::none(typ.clone())
-                            }
-                        } else {
-                            let e: Error = format!(
-                                "unknown selector `{}` for datatype {}",
-                                conf.bad(constructor),
-                                dtyp.name
-                            )
-                            .into();
-                            bail!(e.chain_err(|| dtyp::constructors_as_error(&dtyp.name)))
-                        }
-                    } else {
-                        bail!("inconsistent type {} for value {}", ty, value)
-                    }
-                } else {
-                    bail!(
-                        "illegal application of selector `{}` of `{}` to `{}`",
-                        conf.bad(&name),
-                        typ,
-                        value
-                    )
-                }
+                dtyp_slc(typ, name, values.pop().unwrap())?
             } else {
                 bail!(
                     "expected one value for datatype selection, found {}",
@@ -179,6 +142,110 @@ fn total<'a>(
     Ok(ZipDoTotal::Upp { yielded })
 }
 
+/// Evaluates a datatype constructor application.
+///
+/// Checks the constructor and its arity against the datatype before building the value.
+fn dtyp_new(typ: &Typ, name: &str, values: Vec<Val>) -> Res<Val> {
+    let (dtyp, _) = if let Some(res) = typ.dtyp_inspect() {
+        res
+    } else {
+        bail!(
+            "illegal application of constructor `{}` to type {}",
+            conf.bad(name),
+            typ
+        )
+    };
+    let cargs = if let Some(cargs) = dtyp.news.get(name) {
+        cargs
+    } else {
+        let e: Error = format!(
+            "unknown constructor `{}` for datatype {}",
+            conf.bad(name),
+            dtyp.name
+        )
+        .into();
+        bail!(e.chain_err(|| dtyp::constructors_as_error(&dtyp.name)))
+    };
+    if values.len() != cargs.len() {
+        bail!(
+            "illegal application of constructor `{}` for datatype {} to {} arguments, \
+             expected {}",
+            conf.bad(name),
+            dtyp.name,
+            values.len(),
+            cargs.len()
+        )
+    }
+    Ok(val::dtyp_new(typ.clone(), name.into(), values))
+}
+
+/// Evaluates a datatype selector application.
+///
+/// Yields `val::none(typ)` when the selector belongs to a different constructor than the one of
+/// the value, and fails if the selector does not exist at all.
+fn dtyp_slc(typ: &Typ, name: &str, value: Val) -> Res<Val> {
+    if !value.is_known() {
+        return Ok(val::none(typ.clone()));
+    }
+
+    let (ty, constructor, args) = if let Some(res) = value.dtyp_inspect() {
+        res
+    } else {
+        bail!(
+            "illegal application of selector `{}` of `{}` to `{}`",
+            conf.bad(name),
+            typ,
+            value
+        )
+    };
+
+    let dtyp = if let Some((dtyp, _)) = ty.dtyp_inspect() {
+        dtyp
+    } else {
+        bail!("inconsistent type {} for value {}", ty, value)
+    };
+
+    let selectors = if let Some(selectors) = dtyp.news.get(constructor) {
+        selectors
+    } else {
+        let e: Error = format!(
+            "unknown constructor `{}` for datatype {}",
+            conf.bad(constructor),
+            dtyp.name
+        )
+        .into();
+        bail!(e.chain_err(|| dtyp::constructors_as_error(&dtyp.name)))
+    };
+
+    for ((selector, _), arg) in selectors.iter().zip(args.iter()) {
+        if selector == name {
+            return Ok(arg.clone());
+        }
+    }
+
+    let other_constructor = dtyp
+        .news
+        .iter()
+        .find(|(_, selectors)| selectors.iter().any(|(selector, _)| selector == name));
+
+    if let Some((other, _)) = other_constructor {
+        if cfg!(debug_assertions) {
+            log! { @warn
+                "applying selector `{}` of constructor `{}` to value `{}`", name, other, value
+            }
+        }
+        Ok(val::none(typ.clone()))
+    } else {
+        let e: Error = format!(
+            "unknown selector `{}` for datatype {}",
+            conf.bad(name),
+            dtyp.name
+        )
+        .into();
+        bail!(e.chain_err(|| dtyp::constructors_as_error(&dtyp.name)))
+    }
+}
+
 fn partial<'a>(
     _: &BTreeMap<String, Fun>,
     Frame {
@@ -345,7 +412,7 @@ fn partial_op<'a>(
 
         Op::Store | Op::Select => (),
 
-        Op::Not | Op::ToInt | Op::ToReal => bail!(
+        Op::Not | Op::ToInt | Op::ToReal | Op::DTypSize => bail!(
             "partial application of unary operator ({}) makes no sense",
             op
         ),
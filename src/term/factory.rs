@@ -1,5 +1,7 @@
 //! Term creation functions.
 
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrd};
+
 use hashconsing::HashConsign;
 
 use crate::{
@@ -12,6 +14,101 @@ hashconsing::consign! {
     let factory = consign(conf.instance.term_capa) for RTerm ;
 }
 
+/// Number of calls to the term factory.
+static MK_CALLS: AtomicUsize = AtomicUsize::new(0);
+/// Number of terms created by the term factory, *i.e.* highest uid plus one.
+static MK_CREATED: AtomicUsize = AtomicUsize::new(0);
+/// Size of the largest term created by the term factory.
+static MK_LARGEST: AtomicUsize = AtomicUsize::new(0);
+
+/// Hashconses a term, updating the factory statistics in `--stats` mode.
+#[inline]
+fn mk(rterm: RTerm) -> Term {
+    let res = factory.mk(rterm);
+    if conf.stats {
+        MK_CALLS.fetch_add(1, AtomicOrd::Relaxed);
+        MK_CREATED.fetch_max(res.uid() as usize + 1, AtomicOrd::Relaxed);
+        MK_LARGEST.fetch_max(res.size(), AtomicOrd::Relaxed);
+    }
+    res
+}
+
+/// Hashconsing statistics of the term factory.
+///
+/// Produced by [`hcons_stats`], printed at the end of the run in `--stats` mode.
+///
+/// [`hcons_stats`]: fn.hcons_stats.html (hcons_stats function)
+#[derive(Debug, Clone)]
+pub struct HConsStats {
+    /// Number of terms currently stored in the factory.
+    ///
+    /// Includes terms that are not referenced anymore but have not been collected yet.
+    pub stored: usize,
+    /// Number of calls to the factory.
+    pub calls: usize,
+    /// Number of calls that created a new term.
+    pub misses: usize,
+    /// Size of the largest term ever created.
+    pub largest: usize,
+}
+impl HConsStats {
+    /// Number of calls that returned a term already in the factory.
+    pub fn hits(&self) -> usize {
+        self.calls - ::std::cmp::min(self.misses, self.calls)
+    }
+    /// Ratio of hits over calls, `0` if there was no call.
+    pub fn hit_ratio(&self) -> f64 {
+        if self.calls == 0 {
+            0.
+        } else {
+            (self.hits() as f64) / (self.calls as f64)
+        }
+    }
+}
+mylib::impl_fmt! {
+  HConsStats(self, fmt) {
+    writeln!(fmt, "terms stored:  {}", self.stored) ? ;
+    writeln!(
+      fmt, "factory calls: {} ({} hits, {} misses, hit ratio {:.2})",
+      self.calls, self.hits(), self.misses, self.hit_ratio()
+    ) ? ;
+    write!(fmt, "largest term:  {}", self.largest)
+  }
+}
+
+/// Hashconsing statistics of the term factory.
+///
+/// Only the number of terms stored is available outside of `--stats` mode, the other counters
+/// are not maintained and stay at zero.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let t = term::add(vec![term::int_var(0), term::int_var(1)]);
+/// let before = term::hcons_stats();
+/// assert! { before.stored > 0 }
+/// let _ = term::add(vec![term::int_var(0), term::int_var(1)]);
+/// let after = term::hcons_stats();
+/// if conf.stats {
+///     assert! { before.largest >= t.size() }
+///     assert! { after.calls > before.calls }
+///     assert! { after.hits() > before.hits() }
+/// } else {
+///     assert_eq! { after.calls, 0 }
+///     assert_eq! { after.largest, 0 }
+/// }
+/// ```
+pub fn hcons_stats() -> HConsStats {
+    let stored = factory.read().map(|factory| factory.len()).unwrap_or(0);
+    HConsStats {
+        stored,
+        calls: MK_CALLS.load(AtomicOrd::Relaxed),
+        misses: MK_CREATED.load(AtomicOrd::Relaxed),
+        largest: MK_LARGEST.load(AtomicOrd::Relaxed),
+    }
+}
+
 lazy_static! {
     /// Cache for terms' variables.
     static ref var_cache: RwLock< TermMap<VarSet> > = RwLock::new(
@@ -85,6 +182,101 @@ pub fn vars(t: &Term) -> VarSet {
         .clone()
 }
 
+lazy_static! {
+    /// Cache for terms' highest variable, indexed by uid.
+    static ref max_var_cache: RwLock< HashMap<u64, Option<VarIdx>> > = RwLock::new(
+        HashMap::with_capacity( conf.instance.term_capa )
+    ) ;
+}
+
+/// Highest variable index appearing in a term (cached).
+///
+/// Same as [`RTerm::highest_var`], but computed once per term and stored alongside the term's
+/// hashconsing uid. Checking whether a term mentions a variable above some index is thus
+/// constant-time after the first call.
+///
+/// [`RTerm::highest_var`]: enum.RTerm.html#method.highest_var (highest_var function over RTerm)
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let t = term::ge(
+///     term::add( vec![term::real_var(0), term::real_var(2), term::real_of(17.)] ),
+///     term::real_var(666)
+/// );
+/// assert_eq! { term::max_var(&t), Some(666.into()) }
+/// assert_eq! { term::max_var(&t), t.highest_var() }
+/// assert_eq! { term::max_var(&term::int(7)), None }
+/// ```
+pub fn max_var(t: &Term) -> Option<VarIdx> {
+    if let Some(max) = max_var_cache
+        .read()
+        .expect("max variable cache is corrupted...")
+        .get(&t.uid())
+    {
+        return *max;
+    }
+
+    let res = match t.get() {
+        RTerm::Var(_, idx) => Some(*idx),
+        RTerm::Cst(_) => None,
+        RTerm::App { args, .. } | RTerm::Fun { args, .. } | RTerm::DTypNew { args, .. } => {
+            args.iter().filter_map(max_var).max()
+        }
+        RTerm::CArray { term, .. } | RTerm::DTypSlc { term, .. } | RTerm::DTypTst { term, .. } => {
+            max_var(term)
+        }
+    };
+
+    max_var_cache
+        .write()
+        .expect("max variable cache is corrupted...")
+        .insert(t.uid(), res);
+    res
+}
+
+/// True if a term does not mention any variable (cached).
+///
+/// A term is ground if it has no highest variable, so this function relies on [`max_var`] and
+/// its cache. Ground terms can be evaluated without a model.
+///
+/// [`max_var`]: fn.max_var.html (max_var function)
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let term = term::le(term::int_var(0), term::int(7));
+/// assert! { !term::ground(&term) }
+/// let term = term::le(term::int(3), term::int(7));
+/// assert! { term::ground(&term) }
+/// ```
+pub fn ground(t: &Term) -> bool {
+    max_var(t).is_none()
+}
+
+/// Polarity of the occurrences of a variable in a term.
+///
+/// Same as [`RTerm::occurs_positively`]: `Some(true)` if `var` only appears positively,
+/// `Some(false)` if it only appears negatively, and `None` if it appears with both polarities or
+/// not at all.
+///
+/// [`RTerm::occurs_positively`]: enum.RTerm.html#method.occurs_positively
+/// (occurs_positively function over RTerm)
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let t = term::implies(term::bool_var(0), term::bool_var(1));
+/// assert_eq! { term::occurs_positively(&t, 0.into()), Some(false) }
+/// assert_eq! { term::occurs_positively(&t, 1.into()), Some(true) }
+/// ```
+pub fn occurs_positively(t: &Term, var: VarIdx) -> Option<bool> {
+    t.occurs_positively(var)
+}
+
 /// Iterator over the variables appearing in a term (cached).
 ///
 /// Each variable is visited only once.
@@ -141,6 +333,82 @@ where
     ()
 }
 
+/// Bottom-up map over the subterms of a term.
+///
+/// Calls `f` on each node of `t` after its kids have been transformed, and replaces the node
+/// with the result if it is `Some`. Otherwise the node, rebuilt over its transformed kids, is
+/// kept. Unlike [`RTerm::top_down_map`][top down], `f` sees the terms it produced for the kids.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let t = term::ge(
+///     term::add(vec![ term::int_var(0), term::cmul(2, term::int_var(1)) ]),
+///     term::int(3)
+/// );
+/// # println!("{}", t);
+/// // Replaces variable `v_1` with `v_0`, and then `(* 2 v_0)` with `v_2`.
+/// let res = term::term_map(&t, |term| {
+///     if term.var_idx() == Some(1.into()) {
+///         Some(term::int_var(0))
+///     } else if term == term::cmul(2, term::int_var(0)).get() {
+///         Some(term::int_var(2))
+///     } else {
+///         None
+///     }
+/// });
+/// assert_eq! {
+///     res,
+///     term::ge(term::add(vec![ term::int_var(0), term::int_var(2) ]), term::int(3))
+/// }
+///
+/// // No replacement yields the same term.
+/// assert_eq! { term::term_map(&t, |_| None), t }
+/// ```
+///
+/// [top down]: ../enum.RTerm.html#method.top_down_map (top_down_map function over RTerm)
+pub fn term_map<F>(t: &Term, f: F) -> Term
+where
+    F: FnMut(&RTerm) -> Option<Term>,
+{
+    use crate::term::zip::*;
+    use std::cell::RefCell;
+
+    // Both the leaf and the application functions need `f`.
+    let f = RefCell::new(f);
+    let apply = |term: Term| {
+        let nu_term = (&mut *f.borrow_mut())(term.get());
+        nu_term.unwrap_or(term)
+    };
+
+    let res: Res<Term> = zip(
+        t,
+        |_| Ok(None),
+        |zip_null| {
+            let leaf = match zip_null {
+                ZipNullary::Cst(val) => cst(val.clone()),
+                ZipNullary::Var(typ, var) => self::var(var, typ.clone()),
+            };
+            Ok(apply(leaf))
+        },
+        |zip_op, typ, acc| {
+            Ok(ZipDoTotal::Upp {
+                yielded: apply(zip_op.rebuild(typ, acc)),
+            })
+        },
+        |mut frame| {
+            let nu_term = frame
+                .rgt_args
+                .next()
+                .expect("illegal call to `partial_op`: empty `rgt_args` (term_map)");
+            Ok(ZipDo::Trm { nu_term, frame })
+        },
+    );
+
+    res.expect("term map can never fail")
+}
+
 /// Creates a term from a non-hconsed `RTerm`.
 ///
 /// This function is not really meant to be used outside of the term module, although it cannot do
@@ -163,7 +431,7 @@ where
 /// ```
 #[inline]
 pub fn term(t: RTerm) -> Term {
-    factory.mk(t)
+    mk(t)
 }
 
 /// Creates a variable.
@@ -185,7 +453,7 @@ pub fn term(t: RTerm) -> Term {
 /// ```
 #[inline]
 pub fn var<V: Into<VarIdx>>(v: V, typ: Typ) -> Term {
-    factory.mk(RTerm::Var(typ, v.into()))
+    mk(RTerm::Var(typ, v.into()))
 }
 
 /// Creates an integer variable.
@@ -257,7 +525,7 @@ pub fn cst<V: Into<Val>>(val: V) -> Term {
             val
         )
     }
-    factory.mk(RTerm::Cst(val))
+    mk(RTerm::Cst(val))
 }
 
 /// Creates an integer constant.
@@ -651,9 +919,9 @@ pub fn and(terms: Vec<Term>) -> Term {
 #[inline]
 pub fn cst_array(typ: Typ, default: Term) -> Term {
     if let Some(val) = default.val() {
-        factory.mk(RTerm::Cst(val::array(typ, val)))
+        mk(RTerm::Cst(val::array(typ, val)))
     } else {
-        factory.mk(RTerm::new_carray(typ, default))
+        mk(RTerm::new_carray(typ, default))
     }
 }
 
@@ -682,16 +950,36 @@ pub fn store(array: Term, idx: Term, val: Term) -> Term {
 /// let arr = term::store(arr, term::int(7), term::int(0));
 /// let select_2 = term::select(arr.clone(), term::int(2));
 /// assert_eq! {
-///     &format!("{}", select_2), "(select (store ((as const (Array Int Int)) v_2) 7 0) 2)"
+///     &format!("{}", select_2), "(select ((as const (Array Int Int)) v_2) 2)"
 /// }
 /// let select_7 = term::select(arr.clone(), term::int(7));
-/// assert_eq! {
-///     &format!("{}", select_7), "(select (store ((as const (Array Int Int)) v_2) 7 0) 7)"
-/// }
+/// assert_eq! { &format!("{}", select_7), "0" }
 /// let model: VarMap<_> = vec![ val::int(17), val::int(17), val::int(13) ].into();
 /// assert_eq! { select_2.eval(&model).unwrap(), val::int(13) }
 /// assert_eq! { select_7.eval(&model).unwrap(), val::int(0) }
 /// ```
+///
+/// Reads over writes are simplified away when the indices are equal, or distinct constants.
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let arr = term::var(0, typ::array(typ::int(), typ::int()));
+/// let arr = term::store(arr, term::int(7), term::int_var(1));
+/// let arr = term::store(arr, term::int_var(2), term::int(3));
+///
+/// let select = term::select(arr.clone(), term::int_var(2));
+/// assert_eq! { select, term::int(3) }
+///
+/// // `v_2` might be `7`, can't simplify.
+/// let select = term::select(arr.clone(), term::int(7));
+/// assert_eq! { &format!("{}", select), "(select (store (store v_0 7 v_1) v_2 3) 7)" }
+///
+/// let arr = term::store(arr, term::int(11), term::int(5));
+/// let select = term::select(arr.clone(), term::int(11));
+/// assert_eq! { select, term::int(5) }
+/// let select = term::select(arr.clone(), term::int(13));
+/// assert_eq! { &format!("{}", select), "(select (store (store v_0 7 v_1) v_2 3) 13)" }
+/// ```
 #[inline]
 pub fn select(array: Term, idx: Term) -> Term {
     app(Op::Select, vec![array, idx])
@@ -810,7 +1098,7 @@ where
         Ok(info.typ.clone())
     })
     .map(|typ| {
-        let term = factory.mk(RTerm::new_fun(typ, name, args));
+        let term = mk(RTerm::new_fun(typ, name, args));
         if all_args_constant {
             if let Ok(val) = term.eval(&()) {
                 cst(val)
@@ -875,7 +1163,7 @@ pub fn app(op: Op, mut args: Vec<Term>) -> Term {
 
 /// Creates a constant term.
 pub fn val(val: Val) -> Term {
-    factory.mk(RTerm::Cst(val))
+    mk(RTerm::Cst(val))
 }
 
 /// Creates a datatype constructor.
@@ -916,7 +1204,7 @@ where
     S: Into<String>,
 {
     let rterm = term::simplify::dtyp_new(typ, name.into(), args);
-    factory.mk(rterm)
+    mk(rterm)
 }
 
 /// Creates a new datatype selector.
@@ -959,7 +1247,7 @@ where
     S: Into<String>,
 {
     match term::simplify::dtyp_slc(typ, name.into(), term) {
-        Either::Left(rterm) => factory.mk(rterm),
+        Either::Left(rterm) => mk(rterm),
         Either::Right(term) => term,
     }
 }
@@ -1004,7 +1292,7 @@ where
     S: Into<String>,
 {
     let (rterm, positive) = term::simplify::dtyp_tst(name.into(), term);
-    let res = factory.mk(rterm);
+    let res = mk(rterm);
     if !positive {
         not(res)
     } else {
@@ -1229,6 +1517,309 @@ pub fn u_minus(kid: Term) -> Term {
     app(Op::Sub, vec![kid])
 }
 
+/// Normalizes the arithmetic negations of a term.
+///
+/// Rewrites `(- 0 x)` to `(- x)`, `(- (- x))` to `x`, and `(- 0 (- x y))` to `(- y x)`. Terms
+/// created by the factory are already normalized, this is only useful for terms created with
+/// [`term`], which bypasses normalization. The result is hashconsed with the term the factory
+/// would have created.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let (x, y) = (term::int_var(0), term::int_var(1));
+/// // Creates `(- kid_1 kid_2 ...)` without normalizing it.
+/// let raw_sub = |args: Vec<Term>| term::term(RTerm::App {
+///     depth: 1 + args.iter().map(|arg| arg.depth()).max().unwrap(),
+///     size: 1 + args.iter().map(|arg| arg.size()).sum::<usize>(),
+///     typ: typ::int(),
+///     op: Op::Sub,
+///     args,
+/// });
+///
+/// let t = raw_sub(vec![term::int(0), x.clone()]);
+/// # println!("{}", t);
+/// assert_eq! { term::abs_normalize(&t), term::u_minus(x.clone()) }
+///
+/// let t = raw_sub(vec![raw_sub(vec![x.clone()])]);
+/// # println!("{}", t);
+/// assert_eq! { term::abs_normalize(&t), x }
+///
+/// let t = raw_sub(vec![term::int(0), raw_sub(vec![x.clone(), y.clone()])]);
+/// # println!("{}", t);
+/// assert_eq! { term::abs_normalize(&t), term::sub2(y.clone(), x.clone()) }
+/// ```
+///
+/// [`term`]: fn.term.html (term creation function)
+pub fn abs_normalize(t: &Term) -> Term {
+    t.top_down_map(|_| None)
+}
+
+/// Flattens the arithmetic sums of a term into a canonical sum of products.
+///
+/// Every maximal sum, subtraction or multiplication by a constant is rewritten as a single
+/// application `(+ (* c_0 t_0) ... (* c_n t_n) k)`, where the `t_i`s are variables or non-linear
+/// sub-terms, which are themselves simplified. Zero coefficients and a zero constant are dropped,
+/// and unit coefficients are omitted.
+///
+/// The sum is built with [`add`], which sorts its arguments, so that two linear expressions equal
+/// modulo associativity and commutativity yield the same hashconsed term.
+///
+/// [`add`]: fn.add.html (add function)
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let (a, b, c, d) = (
+///     term::int_var(0), term::int_var(1), term::int_var(2), term::int_var(3)
+/// );
+/// let lft = term::add2(
+///     term::add2(a.clone(), b.clone()),
+///     term::add2(c.clone(), d.clone()),
+/// );
+/// let rgt = term::add2(
+///     d.clone(),
+///     term::add2(c.clone(), term::add2(b.clone(), a.clone())),
+/// );
+/// let lft = term::simplify_linear(&lft);
+/// assert_eq! { lft, term::add(vec![a.clone(), b.clone(), c.clone(), d.clone()]) }
+/// assert_eq! { lft, term::simplify_linear(&rgt) }
+///
+/// let t = term::sub2(
+///     term::cmul(3, term::add2(b.clone(), term::int(1))),
+///     term::sub2(a.clone(), term::int(7)),
+/// );
+/// let expected = term::add(vec![
+///     term::cmul(Int::from(-1), a.clone()), term::cmul(3, b.clone()), term::int(10)
+/// ]);
+/// assert_eq! { term::simplify_linear(&t), expected }
+///
+/// let t = term::sub2(term::add2(a.clone(), b.clone()), b.clone());
+/// assert_eq! { term::simplify_linear(&t), a }
+/// ```
+pub fn simplify_linear(term: &Term) -> Term {
+    term.top_down_map(|term| {
+        if term.typ().is_arith() {
+            linear_form(term)
+        } else {
+            None
+        }
+    })
+}
+
+/// Canonical sum of products of an arithmetic term, `None` if it is not linear at top-level.
+fn linear_form(term: &Term) -> Option<Term> {
+    match term.app_inspect() {
+        Some((Op::Add, _)) | Some((Op::Sub, _)) | Some((Op::CMul, _)) => (),
+        _ => return None,
+    }
+
+    let typ = term.typ();
+    let (mut sum, one) = if typ.is_int() {
+        (val::int(0), val::int(1))
+    } else {
+        (
+            val::real(Rat::new(0.into(), 1.into())),
+            val::real(Rat::new(1.into(), 1.into())),
+        )
+    };
+
+    let mut coefs = TermMap::<Val>::new();
+    let mut stack = vec![(one, term.clone())];
+
+    while let Some((coef, term)) = stack.pop() {
+        if let Some(val) = term.val() {
+            let val = val.mul(&coef).expect("during linear simplification");
+            sum = sum.add(&val).expect("during linear simplification");
+            continue;
+        }
+
+        match term.app_inspect() {
+            Some((Op::Add, args)) => {
+                for arg in args {
+                    stack.push((coef.clone(), arg.clone()))
+                }
+            }
+
+            Some((Op::Sub, args)) => {
+                let minus = coef.minus().expect("during linear simplification");
+                if args.len() == 1 {
+                    stack.push((minus, args[0].clone()))
+                } else {
+                    let mut args = args.iter();
+                    if let Some(first) = args.next() {
+                        stack.push((coef, first.clone()))
+                    }
+                    for arg in args {
+                        stack.push((minus.clone(), arg.clone()))
+                    }
+                }
+            }
+
+            Some((Op::CMul, _)) => {
+                let (val, kid) = term
+                    .cmul_inspect()
+                    .expect("illegal constant multiplication");
+                let coef = coef.mul(&val).expect("during linear simplification");
+                stack.push((coef, kid.clone()))
+            }
+
+            _ => {
+                let atom = simplify_linear(&term);
+                let is_linear = match atom.app_inspect() {
+                    Some((Op::Add, _)) | Some((Op::Sub, _)) | Some((Op::CMul, _)) => true,
+                    _ => atom.val().is_some(),
+                };
+                if is_linear {
+                    // Simplifying the sub-terms revealed some linear structure.
+                    stack.push((coef, atom))
+                } else {
+                    let entry = coefs.entry(atom).or_insert_with(|| {
+                        if typ.is_int() {
+                            val::int(0)
+                        } else {
+                            val::real(Rat::new(0.into(), 1.into()))
+                        }
+                    });
+                    *entry = entry.add(&coef).expect("during linear simplification")
+                }
+            }
+        }
+    }
+
+    let mut args: Vec<_> = coefs
+        .into_iter()
+        .filter(|(_, coef)| !coef.is_zero())
+        .map(|(atom, coef)| cmul(coef.get().clone(), atom))
+        .collect();
+    args.push(
+        sum.to_term()
+            .expect("illegal constant in linear simplification"),
+    );
+
+    Some(add(args))
+}
+
+/// Abstracts the complex arithmetic sub-terms of a term with fresh variables.
+///
+/// The sub-terms abstracted are the arithmetic ones that a linear solver cannot reason about:
+/// non-linear multiplications, divisions and modulos by non-constant terms, array selects,
+/// datatype selectors and function applications. Equal sub-terms are abstracted by the same
+/// variable. The sub-terms of an abstracted term are not looked at.
+///
+/// All the variables of `term` must be in `vars`. The fresh variables have indices starting at
+/// `vars.max() + 1`.
+///
+/// Returns the abstracted term and a map from variable indices to terms: the fresh indices map to
+/// the sub-term they abstract, and the others map to the variable with the same index. Indices
+/// that do not appear in `term` are given a boolean variable, [`concretize`] never looks at them.
+/// See [`concretize`] for the inverse operation.
+///
+/// [`concretize`]: fn.concretize.html (concretize function)
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let vars: VarSet = vec![0.into(), 1.into(), 2.into()].into_iter().collect();
+/// let (x, y) = (term::int_var(0), term::int_var(1));
+/// let a = term::var(2, typ::array(typ::int(), typ::int()));
+/// let x_y = term::mul(vec![x.clone(), y.clone()]);
+/// let a_x = term::select(a.clone(), x.clone());
+/// let t = term::ge(term::add2(x_y.clone(), term::cmul(2, a_x.clone())), term::int(0));
+///
+/// let (abstracted, map) = term::abstract_arith(&t, &vars);
+/// assert_eq! { map.len(), 5 }
+/// assert_eq! { (&map[0.into()], &map[1.into()], &map[2.into()]), (&x, &y, &a) }
+/// let abstracted_subterms: TermSet = vec![map[3.into()].clone(), map[4.into()].clone()]
+///     .into_iter().collect();
+/// let expected: TermSet = vec![x_y, a_x].into_iter().collect();
+/// assert_eq! { abstracted_subterms, expected }
+/// assert_eq! { term::vars(&abstracted).len(), 2 }
+///
+/// assert_eq! { term::concretize(&abstracted, &map), t }
+///
+/// // Linear terms are left as is.
+/// let t = term::ge(term::add2(term::cmul(3, x.clone()), y.clone()), term::int(7));
+/// let (abstracted, map) = term::abstract_arith(&t, &vars);
+/// assert_eq! { abstracted, t }
+/// assert_eq! { map.len(), 3 }
+///
+/// // Fresh indices are above the maximal index of `vars`.
+/// let z = term::int_var(7);
+/// let vars: VarSet = vec![0.into(), 7.into()].into_iter().collect();
+/// let t = term::ge(term::mul(vec![x.clone(), z.clone()]), term::int(0));
+/// let (abstracted, map) = term::abstract_arith(&t, &vars);
+/// assert_eq! { map.len(), 9 }
+/// assert_eq! { abstracted, term::ge(term::int_var(8), term::int(0)) }
+/// assert_eq! { term::concretize(&abstracted, &map), t }
+/// ```
+pub fn abstract_arith(term: &Term, vars: &VarSet) -> (Term, VarMap<Term>) {
+    debug_assert! { self::vars(term).is_subset(vars) }
+
+    let mut map = VarMap::new();
+    if let Some(max) = vars.iter().max() {
+        while map.next_index() <= *max {
+            let idx = map.next_index();
+            map.push(bool_var(idx))
+        }
+    }
+    term.iter(|term| {
+        if let RTerm::Var(typ, idx) = term {
+            map[*idx] = var(*idx, typ.clone())
+        }
+    });
+    let mut known = TermMap::new();
+
+    let abstracted = term.top_down_map(|term| {
+        if !is_complex_arith(term) {
+            return None;
+        }
+        if let Some(var) = known.get(term) {
+            return Some(Term::clone(var));
+        }
+        let fresh = var(map.next_index(), term.typ());
+        map.push(term.clone());
+        known.insert(term.clone(), fresh.clone());
+        Some(fresh)
+    });
+
+    (abstracted, map)
+}
+
+/// True if a term is abstracted by [`abstract_arith`].
+///
+/// [`abstract_arith`]: fn.abstract_arith.html (abstract_arith function)
+fn is_complex_arith(term: &Term) -> bool {
+    if !term.typ().is_arith() {
+        return false;
+    }
+    if let Some((op, args)) = term.app_inspect() {
+        match op {
+            Op::Mul => args.iter().filter(|arg| arg.val().is_none()).count() > 1,
+            Op::IDiv | Op::Div | Op::Mod | Op::Rem => {
+                args.iter().skip(1).any(|arg| arg.val().is_none())
+            }
+            Op::Select => true,
+            _ => false,
+        }
+    } else {
+        term.dtyp_slc_inspect().is_some() || term.fun_inspect().is_some()
+    }
+}
+
+/// Inverts [`abstract_arith`].
+///
+/// Replaces the variables of a term by the terms they map to in `map`. Variables that do not
+/// appear in `map` are left as is.
+///
+/// [`abstract_arith`]: fn.abstract_arith.html (abstract_arith function)
+pub fn concretize(term: &Term, map: &VarMap<Term>) -> Term {
+    term.subst(map).0
+}
+
 /// Creates a multiplication.
 ///
 /// # Examples
@@ -1262,6 +1853,9 @@ pub fn mul(kids: Vec<Term>) -> Term {
 /// assert_eq! { &format!("{}", t), "21" }
 /// let t = term::cmul( 7, term::add2(term::int_var(7), term::int(3)) );
 /// assert_eq! { &format!("{}", t), "(+ 21 (* 7 v_7))" }
+/// let t = term::cmul( 7, term::mul(vec![term::int_var(1), term::int_var(2)]) );
+/// let model: VarMap<Val> = vec![val::int(0), val::int(2), val::int(3)].into();
+/// assert_eq! { t.eval(&model).unwrap(), val::int(42) }
 /// ```
 #[inline]
 pub fn cmul<V>(cst: V, term: Term) -> Term
@@ -1303,6 +1897,16 @@ pub fn idiv(kids: Vec<Term>) -> Term {
 /// assert_eq! { &format!("{}", t), "(/ 7.0 v_3)" }
 /// let t = term::div( vec![ term::real_of(7.), term::real_of(3.) ] );
 /// assert_eq! { &format!("{}", t), "(/ 7 3)" }
+/// let t = term::div( vec![ term::real_var(3), term::real_of(1.) ] );
+/// assert_eq! { t, term::real_var(3) }
+/// let t = term::div( vec![ term::real_zero(), term::real_of(3.) ] );
+/// assert_eq! { t, term::real_zero() }
+/// // `v_3` might be zero.
+/// let t = term::div( vec![ term::real_zero(), term::real_var(3) ] );
+/// assert_eq! { &format!("{}", t), "(/ 0.0 v_3)" }
+/// // Division by zero is not simplified.
+/// let t = term::div( vec![ term::real_of(7.), term::real_zero() ] );
+/// assert_eq! { &format!("{}", t), "(/ 7.0 0.0)" }
 /// ```
 #[inline]
 pub fn div(kids: Vec<Term>) -> Term {
@@ -1325,6 +1929,34 @@ pub fn modulo(a: Term, b: Term) -> Term {
     app(Op::Mod, vec![a, b])
 }
 
+/// Wraps an integer term in the range of signed machine integers of some width.
+///
+/// Yields `(- (mod (+ term 2^(width-1)) 2^width) 2^(width-1))`, the term version of
+/// [`val::wrap_int`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let t = term::wrap_int( term::int_var(0), 8 );
+/// let model: VarMap<Val> = vec![ val::int(127) ].into();
+/// assert_eq! { t.eval(&model).unwrap(), val::int(127) }
+/// let model: VarMap<Val> = vec![ val::int(128) ].into();
+/// assert_eq! { t.eval(&model).unwrap(), val::int(-128) }
+/// assert_eq! { term::wrap_int( term::int(300), 8 ), term::int(44) }
+/// ```
+///
+/// [`val::wrap_int`]: ../val/fn.wrap_int.html (wrap_int function)
+pub fn wrap_int(term: Term, width: usize) -> Term {
+    debug_assert! { width > 0 }
+    let modulus = Int::one() << width;
+    let half = Int::one() << (width - 1);
+    sub2(
+        modulo(add2(term, int(half.clone())), int(modulus)),
+        int(half),
+    )
+}
+
 /// Creates a conversion from `Int` to `Real`.
 ///
 /// # Examples
@@ -1357,6 +1989,31 @@ pub fn to_int(real: Term) -> Term {
     app(Op::ToInt, vec![real])
 }
 
+/// Creates a datatype size application.
+///
+/// The term must be of a datatype sort without type parameters.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// hoice::parse::fun_dtyp("(declare-datatypes ( (Nat 0) ) ( ( Z (S (prev Nat)) ) ) )");
+/// let nat = typ::dtyp(dtyp::get("Nat").unwrap(), vec![].into());
+/// let t = term::dtyp_size( term::var(0, nat.clone()) );
+/// assert_eq! { &format!("{}", t), "(dtyp-size v_0)" }
+/// let t = term::dtyp_size(
+///     term::dtyp_new(nat.clone(), "S", vec![ term::var(0, nat.clone()) ])
+/// );
+/// assert_eq! { &format!("{}", t), "(+ (dtyp-size v_0) 1)" }
+/// let zero = term::dtyp_new(nat.clone(), "Z", vec![]);
+/// let t = term::dtyp_size( term::dtyp_new(nat.clone(), "S", vec![ zero ]) );
+/// assert_eq! { t, term::int(2) }
+/// ```
+#[inline]
+pub fn dtyp_size(term: Term) -> Term {
+    app(Op::DTypSize, vec![term])
+}
+
 /// Simplifies operator applications.
 ///
 /// This function is currently not strongly-normalizing.
@@ -1564,6 +2221,9 @@ fn normalize_app(mut op: Op, mut args: Vec<Term>, typ: Typ) -> NormRes {
         // Array operations.
         Op::Store => simplify::store(&mut args),
         Op::Select => simplify::select(&mut args),
+
+        // Datatype operations.
+        Op::DTypSize => simplify::dtyp_size(&mut args),
     };
 
     // print!("... ") ;
@@ -1577,6 +2237,15 @@ fn normalize_app(mut op: Op, mut args: Vec<Term>, typ: Typ) -> NormRes {
         //   print!(" {}", arg)
         // }
         // println!(")") ;
-        NormRes::Term(factory.mk(RTerm::new_app(typ, op, args)))
+        // Constant arguments need no model, evaluate the application right away.
+        if args.iter().all(|arg| arg.val().is_some()) {
+            let vals = args.iter().filter_map(|arg| arg.val()).collect();
+            if let Ok(val) = op.eval(vals) {
+                if val.is_known() {
+                    return NormRes::Term(self::val(val));
+                }
+            }
+        }
+        NormRes::Term(mk(RTerm::new_app(typ, op, args)))
     }
 }
@@ -58,6 +58,13 @@ pub enum Op {
     Store,
     /// Accessor for arrays.
     Select,
+
+    /// Structural size of a datatype value, *i.e.* its number of constructor nodes.
+    ///
+    /// Only legal on datatypes without type parameters, see [`RTyp::is_mono_dtyp`].
+    ///
+    /// [`RTyp::is_mono_dtyp`]: typ/enum.RTyp.html#method.is_mono_dtyp (is_mono_dtyp function)
+    DTypSize,
 }
 
 impl Op {
@@ -89,13 +96,62 @@ impl Op {
             Store => store_,
             Select => select_,
             Distinct => distinct_,
+            DTypSize => dtyp_size_,
         }
     }
 
+    /// Range of legal arities, `(min, max)` where a `max` of `None` means unbounded.
+    ///
+    /// Conjunctions and disjunctions can be empty, they stand for `true` and `false`
+    /// respectively. Unary subtraction is negation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// assert_eq! { Op::Not.arity_range(), (1, Some(1)) }
+    /// assert_eq! { Op::Ite.arity_range(), (3, Some(3)) }
+    /// assert_eq! { Op::Sub.arity_range(), (1, None) }
+    /// assert_eq! { Op::Impl.arity_range(), (2, Some(2)) }
+    /// assert_eq! { Op::Distinct.arity_range(), (2, None) }
+    /// assert_eq! { Op::And.arity_range(), (0, None) }
+    /// ```
+    pub fn arity_range(self) -> (usize, Option<usize>) {
+        use self::Op::*;
+        match self {
+            And | Or => (0, None),
+            Add | Sub | Mul => (1, None),
+            Eql | Distinct => (2, None),
+            Not | ToInt | ToReal | DTypSize => (1, Some(1)),
+            CMul | IDiv | Div | Rem | Mod | Gt | Ge | Le | Lt | Impl | Select => (2, Some(2)),
+            Ite | Store => (3, Some(3)),
+        }
+    }
+
+    /// True if the operator can be applied to `len` arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// assert! {  Op::Add.accepts_arity(7) }
+    /// assert! { !Op::Add.accepts_arity(0) }
+    /// assert! {  Op::Ite.accepts_arity(3) }
+    /// assert! { !Op::Ite.accepts_arity(4) }
+    /// ```
+    pub fn accepts_arity(self, len: usize) -> bool {
+        let (min, max) = self.arity_range();
+        min <= len && max.map(|max| len <= max).unwrap_or(true)
+    }
+
     /// Type checking.
     ///
     /// If there is an error, returns the type the spurious argument should have
     /// (if it is known) and the one found.
+    ///
+    /// Arity errors are reported as messages, see [`arity_range`].
+    ///
+    /// [`arity_range`]: #method.arity_range (arity_range function)
     pub fn type_check(self, args: &mut [Term]) -> Result<Typ, term::TypError> {
         use self::Op::*;
 
@@ -110,33 +166,27 @@ impl Op {
 
             (nullary) => (
                 err!(rgt
-                    "illegal nullary application of `{}`", self
+                    "illegal nullary application of `{}`, expected at least {} argument(s)",
+                    self, self.arity_range().0
                 )
             ) ;
         }
 
-        macro_rules! arity_check {
-            ( [ $min:tt, . ] => $e:expr ) => {
-                if args.len() < $min {
-                    err!(rgt
-                                        "illegal application of `{}` to {} arguments (> {})",
-                                        self, args.len(), $min
-                                    )
-                } else {
-                    $e
-                }
-            };
-
-            ( [ $min:tt, $max:tt ] => $e:expr ) => {
-                if args.len() > $max {
-                    err!(rgt
-                                        "illegal application of `{}` to {} arguments (> {})",
-                                        self, args.len(), $max
-                                    )
-                } else {
-                    arity_check!( [ $min, .] => $e )
-                }
-            };
+        let (min, max) = self.arity_range();
+        if args.is_empty() && min > 0 {
+            err!(nullary)
+        } else if args.len() < min {
+            err!(rgt
+                "illegal application of `{}` to {} argument(s), expected at least {}",
+                self, args.len(), min
+            )
+        } else if let Some(max) = max {
+            if args.len() > max {
+                err!(rgt
+                    "illegal application of `{}` to {} arguments, expected at most {}",
+                    self, args.len(), max
+                )
+            }
         }
 
         macro_rules! all_same {
@@ -218,84 +268,72 @@ impl Op {
 
         let res = match self {
             Add | Sub | Mul | Div | CMul => all_same!(arith),
-            IDiv | Rem | Mod => arity_check!(
-              [ 2, 2 ] => {
-                all_same!(typ::int())
-              }
-            ),
-            Gt | Ge | Le | Lt => arity_check!(
-              [ 2, 2 ] => {
-                all_same!(arith) ;
+            IDiv | Rem | Mod => all_same!(typ::int()),
+            Gt | Ge | Le | Lt => {
+                all_same!(arith);
                 typ::bool()
-              }
-            ),
+            }
 
             Distinct | Eql => {
                 all_same!();
                 typ::bool()
             }
-            Not => arity_check!(
-              [ 1, 1 ] => all_same!(typ::bool())
-            ),
+            Not => all_same!(typ::bool()),
             And | Or | Impl => all_same!(bool),
-            ToInt => arity_check!(
-              [ 1, 1 ] => {
-                all_same!(typ::real()) ;
+            ToInt => {
+                all_same!(typ::real());
                 typ::int()
-              }
-            ),
-            ToReal => arity_check!(
-              [ 1, 1 ] => {
-                all_same!(typ::int()) ;
+            }
+            ToReal => {
+                all_same!(typ::int());
                 typ::real()
-              }
-            ),
-            Ite => arity_check!(
-              [ 3, 3 ] => {
-                let mut args_iter = args.iter_mut().enumerate() ;
+            }
+            Ite => {
+                let mut args_iter = args.iter_mut().enumerate();
 
                 if let Some((index, cond)) = args_iter.next() {
-                  if ! cond.typ().is_bool() {
-                    err!(lft Some(typ::bool()), cond.typ(), index)
-                  }
-                  all_same!(sub args args_iter)
+                    if !cond.typ().is_bool() {
+                        err!(lft Some(typ::bool()), cond.typ(), index)
+                    }
+                    all_same!(sub args args_iter)
                 } else {
-                  err!(nullary)
+                    err!(nullary)
                 }
-              }
-            ),
-
-            Store => arity_check!(
-              [ 3, 3 ] => if let Some((src, tgt)) = args[0].typ().array_inspect() {
-                if args[1].typ() != * src {
-                  err!(
-                    lft Some(src.clone()), args[1].typ(), 1
-                  )
-                } else if args[2].typ() != * tgt {
-                  err!(
-                    lft Some(tgt.clone()), args[2].typ(), 2
-                  )
+            }
+
+            Store => {
+                if let Some((src, tgt)) = args[0].typ().array_inspect() {
+                    if args[1].typ() != *src {
+                        err!(lft Some(src.clone()), args[1].typ(), 1)
+                    } else if args[2].typ() != *tgt {
+                        err!(lft Some(tgt.clone()), args[2].typ(), 2)
+                    } else {
+                        args[0].typ()
+                    }
                 } else {
-                  args[0].typ()
+                    err!(lft None, args[0].typ(), 0)
                 }
-              } else {
-                err!(lft None, args[0].typ(), 0)
-              }
-            ),
-
-            Select => arity_check!(
-              [ 2, 2 ] => if let Some((src, tgt)) = args[0].typ().array_inspect() {
-                if args[1].typ() != * src {
-                  err!(
-                    lft Some(src.clone()), args[1].typ(), 1
-                  )
+            }
+
+            Select => {
+                if let Some((src, tgt)) = args[0].typ().array_inspect() {
+                    if args[1].typ() != *src {
+                        err!(lft Some(src.clone()), args[1].typ(), 1)
+                    } else {
+                        tgt.clone()
+                    }
+                } else {
+                    err!(lft None, args[0].typ(), 0)
+                }
+            }
+
+            DTypSize => {
+                if args[0].typ().is_mono_dtyp() {
+                    typ::int()
                 } else {
-                  tgt.clone()
+                    err!(lft None, args[0].typ(), 0)
                 }
-              } else {
-                err!(lft None, args[0].typ(), 0)
-              }
-            ),
+            }
         };
         Ok(res)
     }
@@ -341,6 +379,9 @@ impl Op {
             // Array operators.
             Store => eval::store(args),
             Select => eval::select(args),
+
+            // Datatype operators.
+            DTypSize => eval::dtyp_size(args),
         }
     }
 }
@@ -578,4 +619,13 @@ mod eval {
         } ;
     }
 
+    // Datatype operators.
+    eval_fun! {
+        // Size.
+        fn dtyp_size(args) {
+            arity!("dtyp-size" => args, 1) ;
+            args[0].dtyp_size()
+        } ;
+    }
+
 }
@@ -342,6 +342,40 @@ pub fn conj_term_insert(term: Term, set: &mut TermSet) -> bool {
     fls
 }
 
+/// Simplifies a term in the context of the conjunction it belongs to.
+///
+/// The strict subterms of `term` that are conjuncts of `conj` are replaced by `true`.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::common::*;
+/// use hoice::term::simplify::conj_context_simpl;
+///
+/// let cnd = term::ge(term::int_var(0), term::int(1));
+/// let term = term::ge(
+///     term::ite(cnd.clone(), term::int_var(0), term::int_var(1)),
+///     term::int(7),
+/// );
+/// let mut conj = TermSet::new();
+/// conj.insert(cnd.clone());
+/// conj.insert(term.clone());
+/// assert_eq! {
+///     conj_context_simpl(&term, &conj),
+///     term::ge(term::int_var(0), term::int(7))
+/// }
+/// assert_eq! { conj_context_simpl(&cnd, &conj), cnd }
+/// ```
+pub fn conj_context_simpl(term: &Term, conj: &TermSet) -> Term {
+    term::term_map(term, |sub| {
+        if sub != term.get() && conj.iter().any(|conjunct| conjunct.get() == sub) {
+            Some(term::tru())
+        } else {
+            None
+        }
+    })
+}
+
 /// Simplifies two boolean terms.
 ///
 /// Treats the pair of terms as a conjunction if `conj` is true, as a disjunction otherwise.
@@ -1608,18 +1642,15 @@ simpl_fun! {
     arity!("mod" => args, 2) ;
 
     if let Some(rhs) = args[1].val() {
-      if rhs.is_one() {
+      if rhs.is_one() || rhs.is_minus_one() {
         return Some(
           NormRes::Term( term::int(0) )
         )
       } else if let Some(lhs) = args[0].val() {
-        return Some(
-          NormRes::Term(
-            lhs.modulo(& rhs).expect(
-              "illegal application of `mod`"
-            ).to_term().unwrap()
-          )
-        )
+        // Modulo zero and unknown values are left as is.
+        if let Some(res) = lhs.modulo(& rhs).ok().and_then(|res| res.to_term()) {
+          return Some( NormRes::Term(res) )
+        }
       }
     }
 
@@ -1631,18 +1662,15 @@ simpl_fun! {
     arity!("rem" => args, 2) ;
 
     if let Some(rhs) = args[1].val() {
-      if rhs.is_one() {
+      if rhs.is_one() || rhs.is_minus_one() {
         return Some(
           NormRes::Term( term::int(0) )
         )
       } else if let Some(lhs) = args[0].val() {
-        return Some(
-          NormRes::Term(
-            lhs.modulo(& rhs).expect(
-              "illegal application of `rem`"
-            ).to_term().unwrap()
-          )
-        )
+        // Remainder by zero and unknown values are left as is.
+        if let Some(res) = lhs.rem(& rhs).ok().and_then(|res| res.to_term()) {
+          return Some( NormRes::Term(res) )
+        }
       }
     }
 
@@ -1655,7 +1683,14 @@ simpl_fun! {
 
     let (num, den) = (& args[0], & args[1]) ;
 
-    if num.is_zero() {
+    // Division by zero is left as is.
+    let den_non_zero = den.val().map(|den| den.is_known() && ! den.is_zero()) ;
+
+    if den_non_zero == Some(false) {
+
+      return None
+
+    } else if num.is_zero() && den_non_zero == Some(true) {
 
       return Some(
         NormRes::Term( term::real_zero() )
@@ -1677,13 +1712,11 @@ simpl_fun! {
 
     } else if let ( Some(num), Some(den) ) = ( num.val(), den.val() ) {
 
-      return Some(
-        NormRes::Term(
-          term::real(
-            num.div(& den).unwrap().to_real().unwrap().unwrap()
-          )
+      if let Ok(Some(res)) = num.div(& den).and_then(|res| res.to_real()) {
+        return Some(
+          NormRes::Term( term::real(res) )
         )
-      )
+      }
 
     }
 
@@ -1944,7 +1977,25 @@ simpl_fun! {
 
       match op {
 
-        Op::Add | Op::Mul | Op::Sub => return Some(
+        // Only the first factor of a product is multiplied.
+        Op::Mul => {
+          let mut args = args.iter() ;
+          let mut kids = vec![] ;
+          if let Some(first) = args.next() {
+            kids.push(
+              NormRes::App(
+                typ.clone(), Op::CMul, vec![
+                  NormRes::Term( cst.clone() ),
+                  NormRes::Term( first.clone() )
+                ]
+              )
+            )
+          }
+          kids.extend( args.map(|arg| NormRes::Term( arg.clone() )) ) ;
+          return Some( NormRes::App(typ.clone(), op, kids) )
+        },
+
+        Op::Add | Op::Sub => return Some(
           NormRes::App(
             typ.clone(), op, args.iter().map(
               |arg| {
@@ -2008,7 +2059,7 @@ simpl_fun! {
         },
 
         Op::IDiv | Op::Div | Op::Rem | Op::Mod |
-        Op::ToInt | Op::ToReal | Op::Store | Op::Select => (),
+        Op::ToInt | Op::ToReal | Op::Store | Op::Select | Op::DTypSize => (),
 
         Op::Gt | Op::Ge | Op::Le | Op::Lt | Op::Eql | Op::Distinct |
         Op::Impl | Op::Not | Op::And | Op::Or => panic!(
@@ -2107,12 +2158,12 @@ simpl_fun! {
 
 simpl_fun! {
 
-  // Real to int.
+  // Real to int, folds constants using the same rounding as evaluation (floor).
   fn to_int(args) {
     arity!("to-int" => args, 1) ;
 
     if let Some(r) = args[0].real() {
-      let i = r.to_integer() ;
+      let i = r.floor().to_integer() ;
       Some(
         NormRes::Term( term::int(i) )
       )
@@ -2121,7 +2172,7 @@ simpl_fun! {
     }
   } ;
 
-  // Int to real.
+  // Int to real, folds constants.
   fn to_real(args) {
     arity!("to-real" => args, 1) ;
 
@@ -2168,23 +2219,87 @@ simpl_fun! {
   fn select(args) {
     arity!("select" => args, 2) ;
 
-    match (
+    if let ( Some(array), Some(index) ) = (
       args[0].val(), args[1].val()
     ) {
-      ( Some(array), Some(index) ) => {
-        let result = array.select(index).to_term().unwrap_or_else(
-          || panic!(
-            "illegal select application (select {} {})", args[0], args[1]
+      let result = array.select(index).to_term().unwrap_or_else(
+        || panic!(
+          "illegal select application (select {} {})", args[0], args[1]
+        )
+      ) ;
+      return Some( NormRes::Term(result) )
+    }
+
+    // Read over write.
+    if let Some((Op::Store, store_args)) = args[0].app_inspect() {
+      arity!("store" => store_args, 3) ;
+      let (array, index, value) = (
+        & store_args[0], & store_args[1], & store_args[2]
+      ) ;
+
+      // `(select (store a i v) i)` is `v`.
+      if index == & args[1] {
+        return Some( NormRes::Term( value.clone() ) )
+      }
+
+      // `(select (store a i v) j)` is `(select a j)` if `i` and `j` are
+      // distinct constants.
+      if let ( Some(i), Some(j) ) = ( index.val(), args[1].val() ) {
+        if i.is_known() && j.is_known()
+        && ! i.is_composite() && ! j.is_composite()
+        && i != j {
+          return Some(
+            NormRes::App(
+              value.typ(), Op::Select, vec![
+                NormRes::Term( array.clone() ),
+                NormRes::Term( args[1].clone() ),
+              ]
+            )
           )
-        ) ;
-        Some( NormRes::Term(result) )
-      },
-      _ => None,
+        }
+      }
     }
+
+    None
   } ;
 
 }
 
+// Datatype operations.
+
+simpl_fun! {
+  // Size.
+  fn dtyp_size(args) {
+    arity!("dtyp-size" => args, 1) ;
+
+    if let Some(val) = args[0].val() {
+      if let Some(size) = val.dtyp_size().ok().and_then(|size| size.to_term()) {
+        return Some( NormRes::Term(size) )
+      }
+    } else if let Some((_, _, kids)) = args[0].dtyp_new_inspect() {
+      // `(dtyp-size (cons a_1 ... a_n))` is `1` plus the sizes of the `a_i`s of
+      // (monomorphic) datatype sort.
+      let mut sum = vec![ NormRes::Term( term::int(1) ) ] ;
+      for kid in kids {
+        if kid.typ().is_mono_dtyp() {
+          sum.push(
+            NormRes::App(
+              typ::int(), Op::DTypSize, vec![ NormRes::Term( kid.clone() ) ]
+            )
+          )
+        }
+      }
+      return if sum.len() == 1 {
+        sum.pop()
+      } else {
+        Some( NormRes::App(typ::int(), Op::Add, sum) )
+      }
+    }
+
+    None
+  } ;
+}
+
 /// Tries to create a constant datatype constructor.
 fn cst_dtyp_new<S>(typ: Typ, name: S, args: Vec<Term>) -> Either<Val, (Typ, String, Vec<Term>)>
 where
@@ -98,6 +98,47 @@ fn cst_mod() {
     )
 }
 
+#[test]
+fn cst_mod_rem_negative() {
+    let model: VarMap<Val> = model!();
+    for &(op, lhs, rhs, res) in &[
+        (Op::Mod, -7, 3, 2),
+        (Op::Rem, -7, 3, 2),
+        (Op::Mod, 7, -3, 1),
+        (Op::Rem, 7, -3, -1),
+        (Op::Mod, -7, -3, 2),
+        (Op::Rem, -7, -3, -2),
+    ] {
+        // Constant folding.
+        let term = term::app(op, vec![int(lhs), int(rhs)]);
+        assert_eq!(term, int(res), "({} {} {})", op, lhs, rhs);
+        // Evaluation.
+        let term = term::app(op, vec![term::int_var(0), term::int_var(1)]);
+        let model: VarMap<Val> = vec![val::int(lhs), val::int(rhs)].into();
+        assert_eq!(
+            term.eval(&model).unwrap(),
+            val::int(res),
+            "({} {} {})",
+            op,
+            lhs,
+            rhs
+        );
+    }
+
+    // Modulo one.
+    for op in &[Op::Mod, Op::Rem] {
+        let term = term::app(*op, vec![term::int_var(0), int(1)]);
+        assert_eq!(term, int(0));
+        let term = term::app(*op, vec![term::int_var(0), int(-1)]);
+        assert_eq!(term, int(0));
+    }
+
+    // Modulo zero is not simplified.
+    let term = term::app(Op::Mod, vec![int(7), int(0)]);
+    assert!(term.val().is_none());
+    assert!(term.eval(&model).is_err());
+}
+
 #[test]
 fn cst_gt_1() {
     init();
@@ -466,6 +507,57 @@ fn ite_1() {
     assert_eval!( real model => ite, 2.0 );
 }
 
+/// Creates a subtraction without normalizing it.
+fn raw_sub(args: Vec<Term>) -> Term {
+    let typ = args[0].typ();
+    let depth = 1 + args.iter().map(|arg| arg.depth()).max().unwrap_or(0);
+    let size = 1 + args.iter().map(|arg| arg.size()).sum::<usize>();
+    term::term(RTerm::App {
+        depth,
+        size,
+        typ,
+        op: Op::Sub,
+        args,
+    })
+}
+
+#[test]
+fn abs_normalize_zero_minus() {
+    init();
+    let v_0 = term::int_var(0);
+    let raw = raw_sub(vec![int(0), v_0.clone()]);
+    let normalized = term::abs_normalize(&raw);
+    let u_minus = term::u_minus(v_0.clone());
+    assert_eq!(normalized, u_minus);
+    assert_eq!(normalized.uid(), u_minus.uid());
+    // Going through the factory directly yields the same term.
+    let sub = term::sub(vec![int(0), v_0]);
+    assert_eq!(sub.uid(), u_minus.uid())
+}
+
+#[test]
+fn abs_normalize_double_minus() {
+    init();
+    let v_0 = term::real_var(0);
+    let raw = raw_sub(vec![raw_sub(vec![v_0.clone()])]);
+    let normalized = term::abs_normalize(&raw);
+    assert_eq!(normalized.uid(), v_0.uid());
+    let sub = term::u_minus(term::u_minus(v_0.clone()));
+    assert_eq!(sub.uid(), v_0.uid())
+}
+
+#[test]
+fn abs_normalize_zero_minus_sub() {
+    init();
+    let (v_0, v_1) = (term::int_var(0), term::int_var(1));
+    let raw = raw_sub(vec![int(0), raw_sub(vec![v_0.clone(), v_1.clone()])]);
+    let normalized = term::abs_normalize(&raw);
+    let expected = term::sub(vec![v_1.clone(), v_0.clone()]);
+    assert_eq!(normalized.uid(), expected.uid());
+    let sub = term::sub(vec![int(0), term::sub(vec![v_0, v_1])]);
+    assert_eq!(sub.uid(), expected.uid())
+}
+
 // The lazy evaluation tests rely on the order in which the terms are created.
 // This is not the case outside of these tests obviously. But here the goal is
 // to have the last term being illegal, usually a variable that's not defined
@@ -541,6 +633,76 @@ fn lazy_4() {
     assert_eval!( bool not model => distinct )
 }
 
+/// Size and depth of a term, computed recursively.
+fn size_and_depth(term: &Term) -> (usize, usize) {
+    let kids: Vec<Term> = match term.get() {
+        RTerm::Var(_, _) | RTerm::Cst(_) => vec![],
+        RTerm::App { args, .. } | RTerm::DTypNew { args, .. } | RTerm::Fun { args, .. } => {
+            args.clone()
+        }
+        RTerm::CArray { term, .. } | RTerm::DTypSlc { term, .. } | RTerm::DTypTst { term, .. } => {
+            vec![term.clone()]
+        }
+    };
+    kids.iter()
+        .map(size_and_depth)
+        .fold((1, 1), |(size, depth), (kid_size, kid_depth)| {
+            (size + kid_size, ::std::cmp::max(depth, kid_depth + 1))
+        })
+}
+
+#[test]
+fn size_depth_app() {
+    let v_0 = term::bool_var(0);
+    let v_1 = term::bool_var(1);
+    let t = term::and(vec![v_0, term::not(v_1.clone())]);
+    assert_eq!(t.size(), 4);
+    assert_eq!(t.depth(), 3);
+
+    let t = term::or(vec![t, term::not(term::and(vec![v_1, term::bool_var(2)]))]);
+    assert_eq!((t.size(), t.depth()), size_and_depth(&t));
+
+    let t = term::ge(
+        term::add(vec![
+            term::int_var(0),
+            term::mul(vec![term::int_var(1), term::int_var(2)]),
+        ]),
+        term::cmul(3, term::int_var(3)),
+    );
+    assert_eq!((t.size(), t.depth()), size_and_depth(&t));
+}
+
+#[test]
+fn size_depth_array() {
+    let array = term::cst_array(typ::int(), term::int_var(0));
+    assert_eq!(array.size(), 2);
+    assert_eq!(array.depth(), 2);
+
+    let t = term::select(
+        term::store(array, term::int_var(1), term::int_var(2)),
+        term::int_var(3),
+    );
+    assert_eq!((t.size(), t.depth()), size_and_depth(&t));
+}
+
+#[test]
+fn size_depth_dtyp() {
+    let list = typ::dtyp(dtyp::get("List").unwrap(), vec![typ::int()].into());
+    let nil = term::dtyp_new(list.clone(), "nil", vec![]);
+    assert_eq!((nil.size(), nil.depth()), (1, 1));
+
+    let one = term::dtyp_new(list.clone(), "insert", vec![term::int_var(0), nil]);
+    assert_eq!(one.size(), 3);
+    assert_eq!(one.depth(), 2);
+
+    let two = term::dtyp_new(list.clone(), "insert", vec![term::int_var(1), one]);
+    assert_eq!(two.size(), 5);
+    assert_eq!(two.depth(), 3);
+
+    let head = term::dtyp_slc(typ::int(), "head", two);
+    assert_eq!((head.size(), head.depth()), size_and_depth(&head));
+}
+
 // #[test]
 // fn models() {
 //   let v_1 = term::bool_var(0) ;
@@ -1034,3 +1196,52 @@ fn lazy_4() {
 //     }
 //   }
 // }
+
+#[test]
+fn cast_cst_folding() {
+    let is_cst = |term: &Term| match term.get() {
+        RTerm::Cst(_) => true,
+        _ => false,
+    };
+
+    let t = term::to_real(term::int(5));
+    assert!(is_cst(&t), "{} is not a constant", t);
+    assert_eq!(t, term::real(Rat::new(5.into(), 1.into())));
+
+    let t = term::to_real(term::int(-5));
+    assert!(is_cst(&t), "{} is not a constant", t);
+    assert_eq!(t, term::real(Rat::new((-5).into(), 1.into())));
+
+    let t = term::to_int(term::real(Rat::new(15.into(), 2.into())));
+    assert!(is_cst(&t), "{} is not a constant", t);
+    assert_eq!(t, term::int(7));
+
+    // Same rounding as evaluation.
+    let minus_seven_and_a_half = Rat::new((-15).into(), 2.into());
+    let t = term::to_int(term::real(minus_seven_and_a_half.clone()));
+    assert!(is_cst(&t), "{} is not a constant", t);
+    assert_eq!(t, term::int(-8));
+    let model = model!(val::real(minus_seven_and_a_half));
+    let to_int = term::to_int(term::real_var(0));
+    assert_eval!(
+        int model => to_int, -8
+    );
+}
+
+#[test]
+fn app_cst_folding() {
+    // Applications to constants are folded, whether the simplifier of the operator handles them
+    // or not.
+    let t = term::app(Op::Rem, vec![term::int(7), term::int(3)]);
+    assert_eq!(t, term::int(1));
+    let t = term::app(Op::IDiv, vec![term::int(7), term::int(2)]);
+    assert_eq!(t, term::int(3));
+    let t = term::app(Op::Mul, vec![term::int(2), term::int(3), term::int(7)]);
+    assert_eq!(t, term::int(42));
+    let t = term::app(Op::Distinct, vec![term::int(1), term::int(2)]);
+    assert_eq!(t, term::tru());
+
+    // Not constant.
+    let t = term::app(Op::Rem, vec![term::int_var(0), term::int(3)]);
+    assert!(t.val().is_none(), "{} is a constant", t);
+}
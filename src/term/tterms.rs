@@ -306,6 +306,19 @@ impl TTermSet {
         len
     }
 
+    /// Size of the elements, see [`RTerm::size`][size].
+    ///
+    /// A predicate application counts as one plus the size of its arguments.
+    ///
+    /// [size]: enum.RTerm.html#method.size (size function of RTerm)
+    pub fn size(&self) -> usize {
+        let mut size = 0;
+        for term in &self.terms {
+            size += term.size()
+        }
+        size + pred_apps_size(&self.preds)
+    }
+
     /// Collects all the functions mentioned in a TTerm.
     pub fn collect_funs(&self, set: &mut BTreeSet<String>) {
         for term in &self.terms {
@@ -891,6 +904,20 @@ impl TTerms {
         TTerms::Dnf { disj }.simplify()
     }
 
+    /// Size of the top terms, see [`TTermSet::size`][size].
+    ///
+    /// [size]: struct.TTermSet.html#method.size (size function of TTermSet)
+    pub fn size(&self) -> usize {
+        match self {
+            TTerms::True | TTerms::False => 1,
+            TTerms::Conj { tterms, .. } => tterms.size(),
+            TTerms::Disj {
+                tterms, neg_preds, ..
+            } => tterms.size() + pred_apps_size(neg_preds),
+            TTerms::Dnf { disj } => disj.iter().map(|(_, tterms)| tterms.size()).sum(),
+        }
+    }
+
     /// The predicate applications appearing in the top term.
     pub fn pred_apps(&self) -> Vec<&PredApps> {
         let mut res = vec![];
@@ -1652,3 +1679,17 @@ impl Quant {
         writeln!(w, " )")
     }
 }
+
+/// Size of some predicate applications, one plus the size of the arguments for each of them.
+fn pred_apps_size(apps: &PrdHMap<VarTermsSet>) -> usize {
+    let mut size = 0;
+    for argss in apps.values() {
+        for args in argss {
+            size += 1;
+            for arg in args.iter() {
+                size += arg.size()
+            }
+        }
+    }
+    size
+}
@@ -113,6 +113,26 @@ impl RTyp {
         }
     }
 
+    /// True if the type is a datatype without type parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// hoice::parse::fun_dtyp("(declare-datatypes ( (Nat 0) ) ( ( Z (S (prev Nat)) ) ) )");
+    /// let nat = typ::dtyp(dtyp::get("Nat").unwrap(), vec![].into());
+    /// assert! { nat.is_mono_dtyp() }
+    /// let int_list = typ::dtyp(dtyp::get("List").unwrap(), vec![typ::int()].into());
+    /// assert! { !int_list.is_mono_dtyp() }
+    /// assert! { !typ::int().is_mono_dtyp() }
+    /// ```
+    pub fn is_mono_dtyp(&self) -> bool {
+        match *self {
+            RTyp::DTyp { ref prms, .. } => prms.is_empty(),
+            _ => false,
+        }
+    }
+
     /// Inspects an array type.
     pub fn array_inspect(&self) -> Option<(&Typ, &Typ)> {
         if let RTyp::Array { src, tgt } = self {
@@ -168,6 +188,47 @@ impl RTyp {
         }
     }
 
+    /// True if the type has finitely many values.
+    ///
+    /// Only booleans and enumeration datatypes, *i.e.* datatypes with nullary constructors only,
+    /// are considered finite.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// hoice::parse::fun_dtyp(
+    ///     "(declare-datatypes ( (Color 0) ) ( ( red green blue ) ) )"
+    /// );
+    /// let color = typ::dtyp(dtyp::get("Color").unwrap(), vec![].into());
+    /// assert! { color.is_finite_domain() }
+    /// assert_eq! { color.domain_size(), Some(3) }
+    /// assert_eq! { typ::bool().domain_size(), Some(2) }
+    /// assert! { !typ::int().is_finite_domain() }
+    /// let list = typ::dtyp(dtyp::get("List").unwrap(), vec![typ::int()].into());
+    /// assert! { !list.is_finite_domain() }
+    /// ```
+    pub fn is_finite_domain(&self) -> bool {
+        self.domain_size().is_some()
+    }
+
+    /// Number of values of the type, if it is [finite].
+    ///
+    /// [finite]: #method.is_finite_domain (is_finite_domain function)
+    pub fn domain_size(&self) -> Option<usize> {
+        match self {
+            RTyp::Bool => Some(2),
+            RTyp::DTyp { dtyp, .. } => {
+                if dtyp.news.values().all(|args| args.is_empty()) {
+                    Some(dtyp.news.len())
+                } else {
+                    None
+                }
+            }
+            RTyp::Unk | RTyp::Int | RTyp::Real | RTyp::Array { .. } => None,
+        }
+    }
+
     /// Checks a type is legal.
     pub fn check(&self) -> Res<()> {
         match self {
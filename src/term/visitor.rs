@@ -0,0 +1,148 @@
+//! Visitor over the subterms of a term.
+//!
+//! A [`Visitor`] only overrides the `visit_*` functions it cares about, the others do nothing.
+//! [`Visitor::run`] traverses a term, calls the relevant `visit_*` function on all its subterms,
+//! and produces the result of the visitor.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use hoice::common::*;
+//! use hoice::term::{ Visitor, Op };
+//!
+//! /// Counts the additions in a term.
+//! struct AddCounter(usize);
+//! impl Visitor for AddCounter {
+//!     type Result = usize;
+//!     fn visit_app(&mut self, _: &Typ, op: Op, _: &[Term]) {
+//!         if op == Op::Add {
+//!             self.0 += 1
+//!         }
+//!     }
+//!     fn result(&mut self) -> usize {
+//!         ::std::mem::replace(&mut self.0, 0)
+//!     }
+//! }
+//!
+//! let term = term::ge(
+//!     term::add(vec![ term::int_var(0), term::int_var(1) ]), term::int_var(2)
+//! );
+//! # println!("{}", term);
+//! assert_eq! { AddCounter(0).run(&term), 1 }
+//! ```
+//!
+//! [`Visitor`]: trait.Visitor.html (Visitor trait)
+//! [`Visitor::run`]: trait.Visitor.html#method.run (run function of Visitor)
+
+use crate::common::*;
+
+/// Visitor over the subterms of a term.
+///
+/// All `visit_*` functions do nothing by default.
+pub trait Visitor {
+    /// Type of the result of a traversal.
+    type Result;
+
+    /// Produces the result of the visitor, called at the end of [`run`][run].
+    ///
+    /// [run]: #method.run (run function)
+    fn result(&mut self) -> Self::Result;
+
+    /// Visits a variable.
+    fn visit_var(&mut self, _typ: &Typ, _var: VarIdx) {}
+
+    /// Visits a constant.
+    fn visit_cst(&mut self, _val: &Val) {}
+
+    /// Visits a constant array.
+    fn visit_carray(&mut self, _typ: &Typ, _term: &Term) {}
+
+    /// Visits an operator application.
+    fn visit_app(&mut self, _typ: &Typ, _op: Op, _args: &[Term]) {}
+
+    /// Visits a datatype constructor application.
+    fn visit_dtyp_new(&mut self, _typ: &Typ, _name: &str, _args: &[Term]) {}
+
+    /// Visits a datatype selector application.
+    fn visit_dtyp_slc(&mut self, _typ: &Typ, _name: &str, _term: &Term) {}
+
+    /// Visits a datatype tester application.
+    fn visit_dtyp_tst(&mut self, _typ: &Typ, _name: &str, _term: &Term) {}
+
+    /// Visits a function application.
+    fn visit_fun(&mut self, _typ: &Typ, _name: &str, _args: &[Term]) {}
+
+    /// Calls the `visit_*` function corresponding to the top-level node of a term.
+    ///
+    /// Does not visit the subterms.
+    fn visit(&mut self, term: &RTerm) {
+        match term {
+            RTerm::Var(typ, var) => self.visit_var(typ, *var),
+            RTerm::Cst(val) => self.visit_cst(val),
+            RTerm::CArray { typ, term, .. } => self.visit_carray(typ, term),
+            RTerm::App { typ, op, args, .. } => self.visit_app(typ, *op, args),
+            RTerm::DTypNew {
+                typ, name, args, ..
+            } => self.visit_dtyp_new(typ, name, args),
+            RTerm::DTypSlc {
+                typ, name, term, ..
+            } => self.visit_dtyp_slc(typ, name, term),
+            RTerm::DTypTst {
+                typ, name, term, ..
+            } => self.visit_dtyp_tst(typ, name, term),
+            RTerm::Fun {
+                typ, name, args, ..
+            } => self.visit_fun(typ, name, args),
+        }
+    }
+
+    /// Visits all the subterms of a term and produces the result.
+    ///
+    /// Same traversal as [`RTerm::iter`][iter]: subterms appearing more than once are visited
+    /// more than once.
+    ///
+    /// [iter]: ../enum.RTerm.html#method.iter (iter function over RTerm)
+    fn run(&mut self, term: &RTerm) -> Self::Result {
+        term.iter(|term| self.visit(term));
+        self.result()
+    }
+}
+
+/// Collects the variables appearing in a term.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// use hoice::term::{ Visitor, visitor::FreeVarCollector };
+/// let term = term::ge(
+///     term::add(vec![ term::int_var(7), term::cmul(7, term::int_var(3)) ]), term::int_var(7)
+/// );
+/// # println!("{}", term);
+/// let expected: VarSet = vec![ 7.into(), 3.into() ].into_iter().collect();
+/// assert_eq! { FreeVarCollector::new().run(&term), expected }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FreeVarCollector {
+    /// Variables collected so far.
+    vars: VarSet,
+}
+impl FreeVarCollector {
+    /// Constructor.
+    pub fn new() -> Self {
+        FreeVarCollector {
+            vars: VarSet::new(),
+        }
+    }
+}
+impl Visitor for FreeVarCollector {
+    type Result = VarSet;
+
+    fn visit_var(&mut self, _: &Typ, var: VarIdx) {
+        self.vars.insert(var);
+    }
+
+    fn result(&mut self) -> VarSet {
+        ::std::mem::replace(&mut self.vars, VarSet::new())
+    }
+}
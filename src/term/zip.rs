@@ -94,6 +94,70 @@ impl<'a> ::std::fmt::Display for ZipOp<'a> {
         }
     }
 }
+impl<'a> ZipOp<'a> {
+    /// Applies the operator to some arguments, yielding a term of type `typ`.
+    ///
+    /// Panics if the operator is a datatype selector/tester or a constant array and there is
+    /// not exactly one argument.
+    pub fn rebuild(self, typ: &Typ, mut args: Vec<Term>) -> Term {
+        match self {
+            ZipOp::Op(op) => term::app(op, args),
+            ZipOp::New(name) => term::dtyp_new(typ.clone(), name.clone(), args),
+
+            ZipOp::Slc(name) => {
+                if let Some(kid) = args.pop() {
+                    if !args.is_empty() {
+                        panic!(
+                            "illegal application of datatype selector {} to {} arguments",
+                            conf.bad(name),
+                            args.len() + 1
+                        )
+                    }
+                    term::dtyp_slc(typ.clone(), name.clone(), kid)
+                } else {
+                    panic!(
+                        "illegal application of datatype selector {} to 0 arguments",
+                        conf.bad(name)
+                    )
+                }
+            }
+
+            ZipOp::Tst(name) => {
+                if let Some(kid) = args.pop() {
+                    if !args.is_empty() {
+                        panic!(
+                            "illegal application of datatype tester {} to {} arguments",
+                            conf.bad(name),
+                            args.len() + 1
+                        )
+                    }
+                    term::dtyp_tst(name.clone(), kid)
+                } else {
+                    panic!(
+                        "illegal application of datatype tester {} to 0 arguments",
+                        conf.bad(name)
+                    )
+                }
+            }
+
+            ZipOp::CArray => {
+                if let Some(kid) = args.pop() {
+                    if !args.is_empty() {
+                        panic!(
+                            "illegal constant array application to {} arguments",
+                            args.len() + 1
+                        )
+                    }
+                    term::cst_array(typ.clone(), kid)
+                } else {
+                    panic!("illegal constant array application to 0 arguments")
+                }
+            }
+
+            ZipOp::Fun(name) => term::fun(name.clone(), args),
+        }
+    }
+}
 
 // Nullary things the zipper can manipulate.
 #[derive(Clone, Copy, Debug)]
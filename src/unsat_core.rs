@@ -1,6 +1,8 @@
 //! Unsat core and proof extraction.
 //!
-//! Right now, only unsat proof in the form of [`entry_points`] is active.
+//! Right now, only unsat proof in the form of [`entry_points`] is active. The private
+//! `sample_graph` module, which tracks dependencies between term arguments and samples, is not
+//! used to produce proofs.
 //!
 //! [`entry_points`]: entry_points/index.html (entry_points module)
 
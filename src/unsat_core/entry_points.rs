@@ -11,6 +11,12 @@
 //! clauses that can be activated at the same time using the same samples (arguments for a
 //! predicate application).
 //!
+//! Dependencies only relate samples, *i.e.* the values of the arguments of predicate
+//! applications, never the argument terms of the clauses they come from. Reconstruction asks the
+//! solver for a model of a clause where the arguments of the rhs are equal to the sample, so
+//! clauses with non-variable arguments such as `(P (+ x 1))` never require to invert the
+//! evaluation of a term.
+//!
 //! [learning data]: ../../data/index.html (learning data module)
 //! [`EntryPoints` tracker]: struct.EntryPoints.html (EntryPoints struct)
 
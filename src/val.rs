@@ -76,9 +76,9 @@ pub fn array_of_fun(idx_typ: Typ, term: &Term) -> Res<Val> {
                 } else if let Some(var) = args[1].var_idx() {
                     debug_assert_eq! { * var, 0 }
                     args[0].clone()
-                } else if let Some((var, term)) = if term::vars(&args[1]).is_empty() {
+                } else if let Some((var, term)) = if term::ground(&args[1]) {
                     args[0].invert(args[1].clone())
-                } else if term::vars(&args[0]).is_empty() {
+                } else if term::ground(&args[0]) {
                     args[1].invert(args[0].clone())
                 } else {
                     break;
@@ -113,6 +113,42 @@ pub fn array_of_fun(idx_typ: Typ, term: &Term) -> Res<Val> {
 pub fn int<I: Into<Int>>(i: I) -> Val {
     new(RVal::I(i.into()))
 }
+/// Wraps an integer in the range of signed machine integers of some width.
+///
+/// Yields the only integer in `[-2^(width-1), 2^(width-1))` equal to `int` modulo `2^width`.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::common::*;
+/// assert_eq! { val::wrap_int(&Int::from(127), 8), Int::from(127) }
+/// assert_eq! { val::wrap_int(&Int::from(128), 8), Int::from(-128) }
+/// assert_eq! { val::wrap_int(&Int::from(-129), 8), Int::from(127) }
+/// assert_eq! { val::wrap_int(&Int::from(513), 8), Int::from(1) }
+/// ```
+pub fn wrap_int(int: &Int, width: usize) -> Int {
+    debug_assert! { width > 0 }
+    let modulus = Int::one() << width;
+    let half = Int::one() << (width - 1);
+    let res = ((int % &modulus) + &modulus) % &modulus;
+    if res >= half {
+        res - modulus
+    } else {
+        res
+    }
+}
+
+/// Wraps an integer if machine integers are active (`--int_width`), see [`wrap_int`].
+///
+/// [`wrap_int`]: fn.wrap_int.html (wrap_int function)
+pub fn machine_int(int: Int) -> Int {
+    if let Some(width) = conf.instance.int_width {
+        wrap_int(&int, width)
+    } else {
+        int
+    }
+}
+
 /// Creates a rational value.
 pub fn real<R: Into<Rat>>(r: R) -> Val {
     new(RVal::R(r.into()))
@@ -380,6 +416,55 @@ impl RVal {
         }
     }
 
+    /// Structural size of a datatype value, *i.e.* its number of constructor nodes.
+    ///
+    /// Only the arguments of constructors that are themselves of a datatype sort without type
+    /// parameters are counted. Yields a non-value if some of these arguments are not known.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// hoice::parse::fun_dtyp("(declare-datatypes ( (Nat 0) ) ( ( Z (S (prev Nat)) ) ) )");
+    /// let nat = typ::dtyp(dtyp::get("Nat").unwrap(), vec![].into());
+    /// let zero = val::dtyp_new(nat.clone(), "Z".into(), vec![]);
+    /// let two = val::dtyp_new(
+    ///     nat.clone(), "S".into(), vec![ val::dtyp_new(nat.clone(), "S".into(), vec![zero]) ]
+    /// );
+    /// assert_eq! { two.dtyp_size().unwrap(), val::int(3) }
+    /// let unknown = val::dtyp_new(nat.clone(), "S".into(), vec![ val::none(nat.clone()) ]);
+    /// assert_eq! { unknown.dtyp_size().unwrap(), val::none(typ::int()) }
+    /// assert! { val::int(7).dtyp_size().is_err() }
+    /// ```
+    pub fn dtyp_size(&self) -> Res<Val> {
+        if !self.typ().is_dtyp() {
+            bail!(
+                "expected datatype value, found value of type {}",
+                self.typ()
+            )
+        }
+
+        let mut size = 0usize;
+        let mut stack = vec![self];
+
+        while let Some(val) = stack.pop() {
+            match val {
+                RVal::DTypNew { args, .. } => {
+                    size += 1;
+                    for arg in args {
+                        if arg.typ().is_mono_dtyp() {
+                            stack.push(arg.get())
+                        }
+                    }
+                }
+                RVal::N(_) => return Ok(none(typ::int())),
+                val => bail!("unexpected value {} of type {} in datatype", val, val.typ()),
+            }
+        }
+
+        Ok(int(size))
+    }
+
     /// Returns the type of the value.
     pub fn typ(&self) -> Typ {
         use self::RVal::*;
@@ -408,6 +493,26 @@ impl RVal {
     }
 
     /// Attempts to cast a value.
+    ///
+    /// Integers are cast to reals exactly, reals are cast to integers following the semantics of
+    /// `to_int`, *i.e.* rounding towards negative infinity. Non-values are cast to non-values.
+    /// Fails on nonsensical casts, like booleans to integers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// assert_eq! { val::int(7).cast(&typ::real()).unwrap(), val::real_of(7.0) }
+    /// assert_eq! { val::real_of(3.5).cast(&typ::int()).unwrap(), val::int(3) }
+    /// assert_eq! { val::real_of(-3.5).cast(&typ::int()).unwrap(), val::int(-4) }
+    /// assert_eq! { val::real_of(-3.0).cast(&typ::int()).unwrap(), val::int(-3) }
+    /// assert_eq! { val::int(7).cast(&typ::int()).unwrap(), val::int(7) }
+    /// assert_eq! {
+    ///     val::none(typ::real()).cast(&typ::int()).unwrap(), val::none(typ::int())
+    /// }
+    /// assert! { val::bool(true).cast(&typ::int()).is_err() }
+    /// assert! { val::int(1).cast(&typ::bool()).is_err() }
+    /// ```
     pub fn cast(&self, typ: &Typ) -> Res<Val> {
         use crate::term::typ::RTyp;
 
@@ -420,6 +525,7 @@ impl RVal {
             (&RVal::I(ref num), &RTyp::Real) => Ok(real(Rat::new(num.clone(), Int::one()))),
 
             (&RVal::R(ref r), &RTyp::Real) => Ok(real(r.clone())),
+            (&RVal::R(_), &RTyp::Int) => self.real_to_int(),
 
             (&RVal::B(b), &RTyp::Bool) => Ok(bool(b)),
 
@@ -777,7 +883,24 @@ impl RVal {
     }
 
     /// Division.
+    ///
+    /// Exact rational division, the result is always a real. Fails if `other` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let res = val::int(7).div(&val::int(2)).unwrap();
+    /// assert_eq! { res, val::real(Rat::new(7.into(), 2.into())) }
+    /// let res = val::int(0).div(&val::real(Rat::new(1.into(), 3.into()))).unwrap();
+    /// assert_eq! { res, val::real(Rat::new(0.into(), 1.into())) }
+    /// let err = val::int(7).div(&val::int(0)).unwrap_err();
+    /// assert_eq! { &format!("{}", err), "division by zero in `(/ 7 0)`" }
+    /// ```
     pub fn div(&self, other: &Val) -> Res<Val> {
+        if other.is_zero() {
+            bail!("division by zero in `(/ {} {})`", self, other)
+        }
         let res = if self.is_zero() {
             real(Rat::new(0.into(), 1.into()))
         } else {
@@ -832,22 +955,53 @@ impl RVal {
     }
 
     /// Remainder.
+    ///
+    /// Follows z3: `(rem a b)` is `(mod a b)` when `b` is non-negative, and `(- (mod a b))`
+    /// otherwise. Unlike [`modulo`][modulo], the result can be negative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hoice::val;
+    /// assert_eq! { val::int(-7).rem(& val::int(3)).unwrap(), val::int(2) }
+    /// assert_eq! { val::int(7).rem(& val::int(-3)).unwrap(), val::int(-1) }
+    /// assert_eq! { val::int(-7).rem(& val::int(-3)).unwrap(), val::int(-2) }
+    /// assert_eq! { val::int(7).rem(& val::int(3)).unwrap(), val::int(1) }
+    /// assert! { val::int(7).rem(& val::int(0)).is_err() }
+    /// ```
+    ///
+    /// [modulo]: #method.modulo (modulo function)
     pub fn rem(&self, other: &Val) -> Res<Val> {
-        use num::Integer;
-        let b = try_val!(int self);
-        let res = if b.is_one() {
-            val::int(0)
+        let res = self.modulo(other)?;
+        let b = try_val!(int other);
+        if b.is_negative() {
+            res.minus()
         } else {
-            let a = try_val!(int other);
-            val::int(a.div_rem(&b).1)
-        };
-        Ok(res)
+            Ok(res)
+        }
     }
 
     /// Modulo.
+    ///
+    /// Euclidean modulo as in SMT-LIB: the result is always between `0` (inclusive) and the
+    /// absolute value of the divisor (exclusive).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hoice::val;
+    /// assert_eq! { val::int(-7).modulo(& val::int(3)).unwrap(), val::int(2) }
+    /// assert_eq! { val::int(7).modulo(& val::int(-3)).unwrap(), val::int(1) }
+    /// assert_eq! { val::int(-7).modulo(& val::int(-3)).unwrap(), val::int(2) }
+    /// assert_eq! { val::int(7).modulo(& val::int(3)).unwrap(), val::int(1) }
+    /// assert! { val::int(7).modulo(& val::int(0)).is_err() }
+    /// ```
     pub fn modulo(&self, other: &Val) -> Res<Val> {
         use num::Integer;
         let b = try_val!(int other);
+        if b.is_zero() {
+            bail!("division by zero, aborting...")
+        }
         let res = if b.is_one() {
             val::int(0)
         } else {
@@ -1010,14 +1164,20 @@ impl RVal {
     }
 
     /// Real to int conversion.
+    ///
+    /// Rounds towards negative infinity, as `to_int` in SMT-LIB.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::common::*;
+    /// assert_eq! { val::real_of(7.5).real_to_int().unwrap(), val::int(7) }
+    /// assert_eq! { val::real_of(-7.5).real_to_int().unwrap(), val::int(-8) }
+    /// assert_eq! { val::none(typ::real()).real_to_int().unwrap(), val::none(typ::int()) }
+    /// ```
     pub fn real_to_int(&self) -> Res<Val> {
         let res = if let Some(rat) = self.to_real()? {
-            let res = rat.denom() / rat.denom();
-            if rat.denom().is_negative() ^ rat.numer().is_negative() {
-                val::int(-res)
-            } else {
-                val::int(res)
-            }
+            val::int(rat.floor().to_integer())
         } else {
             val::none(typ::int())
         };
@@ -146,6 +146,34 @@ impl RVarVals {
         Ok(slf)
     }
 
+    /// SMT-LIB 2 argument list binding variables to their values.
+    ///
+    /// Produces `(<name_0> <val_0>) ... (<name_n> <val_n>)`, where the names come from `info`.
+    /// Unknown values are printed as `_`, which is not legal SMT-LIB 2.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::{ common::*, info::VarInfo };
+    /// let info: VarInfos = vec![
+    ///     VarInfo::new("n", typ::int(), 0.into()),
+    ///     VarInfo::new("b", typ::bool(), 1.into()),
+    /// ].into();
+    /// let vals = var_to::vals::new(vec![val::int(-7), val::bool(true)]);
+    /// assert_eq! { vals.to_smt2_args(&info), "(n (- 7)) (b true)" }
+    /// ```
+    pub fn to_smt2_args(&self, info: &VarInfos) -> String {
+        debug_assert_eq! { self.len(), info.len() }
+        let mut s = String::new();
+        for (var, val) in self.index_iter() {
+            if !s.is_empty() {
+                s.push(' ')
+            }
+            s.push_str(&format!("({} {})", info[var].name, val))
+        }
+        s
+    }
+
     /// Evaluates some arguments and yields the resulting `VarMap`.
     pub fn apply_to(&self, args: &VarMap<Term>) -> Res<Self> {
         let mut res = Self::with_capacity(args.len());
@@ -59,6 +59,138 @@ fn err() {
     run!(run_err())
 }
 
+#[test]
+fn model_simplification() {
+    run!(run_model_simplification())
+}
+
+#[test]
+fn incremental_check_sats() {
+    run!(run_incremental_check_sats())
+}
+
+#[test]
+fn check_sat_assuming() {
+    run!(run_check_sat_assuming())
+}
+
+#[test]
+fn reset() {
+    run!(run_reset("rsc/incremental/reset.smt2"))
+}
+
+#[test]
+fn reset_assertions() {
+    run!(run_reset("rsc/incremental/reset_assertions.smt2"))
+}
+
+#[test]
+fn reset_after_error() {
+    run!(run_reset_after_error())
+}
+
+#[test]
+fn many_constraints() {
+    run!(run_many_constraints(500))
+}
+
+/// Micro-benchmark, run with `cargo test --release -- --ignored many_constraints_bench`.
+#[test]
+#[ignore]
+fn many_constraints_bench() {
+    run!(run_many_constraints(5_000))
+}
+
+#[test]
+fn reproducible_runs() {
+    run!(run_reproducible_runs())
+}
+
+#[test]
+fn custom_teacher() {
+    run!(run_custom_teacher())
+}
+
+#[test]
+fn shared_candidates() {
+    run!(run_shared_candidates())
+}
+
+#[test]
+fn cached_candidates() {
+    run!(run_cached_candidates())
+}
+
+#[test]
+fn cross_theory_synth() {
+    run!(run_cross_theory_synth())
+}
+
+#[test]
+fn bool_synth() {
+    run!(run_bool_synth())
+}
+
+#[test]
+fn constant_pool_synth() {
+    run!(run_constant_pool_synth())
+}
+
+#[test]
+fn missing_solver() {
+    run!(run_missing_solver())
+}
+
+#[test]
+fn max_clauses() {
+    run!(run_max_clauses())
+}
+
+#[test]
+fn cfg_red_var_budget() {
+    run!(run_cfg_red_var_budget())
+}
+
+#[test]
+fn int_width() {
+    run!(run_int_width())
+}
+
+#[test]
+fn forced_preds_in_model() {
+    run!(run_forced_preds_in_model())
+}
+
+#[test]
+fn proof_complex_args() {
+    run!(run_proof_complex_args())
+}
+
+#[test]
+fn proof_sample_tracking() {
+    run!(run_proof_sample_tracking())
+}
+
+#[test]
+fn chc_comp() {
+    run!(run_chc_comp())
+}
+
+#[test]
+fn profile_to() {
+    run!(run_profile_to())
+}
+
+#[test]
+fn profile_json() {
+    run!(run_profile_json())
+}
+
+#[test]
+fn model_lets() {
+    run!(run_model_lets())
+}
+
 macro_rules! map_err {
   ($e:expr, $msg:expr) => (
     $e.map_err( |e| format!("{}:\n{}", $msg, e) ) ?
@@ -184,3 +316,989 @@ fn run_unsat() -> Res<()> {
 
     Ok(())
 }
+
+/// Number of nodes in a term.
+fn size(term: &Term) -> usize {
+    let mut size = 0;
+    term.iter(|_| size += 1);
+    size
+}
+
+fn run_model_simplification() -> Res<()> {
+    let instance = ::hoice::parse::instance("(declare-fun pred (Int Int) Bool)");
+    let (x, y) = (term::int_var(0), term::int_var(1));
+
+    // `x >= 0`.
+    let q_1 = term::ge(x.clone(), term::int(0));
+    // `y >= x`.
+    let q_2 = term::ge(y.clone(), x.clone());
+    // `x + y >= 0`, implied by `q_1 /\ q_2`.
+    let q_3 = term::ge(term::add2(x.clone(), y.clone()), term::int(0));
+
+    // Equivalent to `q_1 \/ q_2`.
+    let candidate = term::or(vec![
+        term::and(vec![q_1.clone(), q_2.clone(), q_3.clone()]),
+        term::and(vec![q_1.clone(), term::not(q_2.clone())]),
+        term::and(vec![term::not(q_1.clone()), q_2.clone()]),
+    ]);
+    let original_size = size(&candidate);
+
+    let mut candidates: Candidates = vec![Some(candidate)].into();
+    instance.simplify_candidates(&mut candidates)?;
+    let simplified = candidates[0.into()]
+        .clone()
+        .expect("simplification lost the candidate");
+    println!(
+        "{} ({} -> {})",
+        simplified,
+        original_size,
+        size(&simplified)
+    );
+
+    // Should be `q_1 \/ (not q_1 /\ q_2)` or `q_2 \/ (q_1 /\ not q_2)`.
+    let disj = simplified
+        .disj_inspect()
+        .expect("simplified candidate should be a disjunction");
+    assert_eq! { disj.len(), 2 }
+    let qual_count: usize = disj
+        .iter()
+        .map(|kid| kid.conj_inspect().map(|conj| conj.len()).unwrap_or(1))
+        .sum();
+    assert_eq! { qual_count, 3 }
+    assert! { size(&simplified) < original_size }
+
+    Ok(())
+}
+
+fn run_incremental_check_sats() -> Res<()> {
+    let file_name = "rsc/incremental/two_check_sats.smt2";
+    println!("looking at `{}`", file_name);
+    let file = OpenOptions::new()
+        .read(true)
+        .open(file_name)
+        .chain_err(|| format!("while opening file {}", file_name))?;
+    // Not a file input, so the first check-sat does not pre-process the instance the second
+    // assertion is added to.
+    let (model, instance) = read_and_work(file, false, false, true)
+        .chain_err(|| "while reading file and getting model")?;
+    if let Some(model) = model {
+        let mut buff: Vec<u8> = vec![];
+        instance
+            .write_model(&model, &mut buff)
+            .chain_err(|| "while writing model")?;
+        let buff = map_err!(
+            String::from_utf8(buff),
+            "converting model from bytes to utf8"
+        );
+        ::hoice::check::do_it_from_str(file_name, &buff).chain_err(|| "while checking model")?;
+        println!("- is okay");
+        Ok(())
+    } else {
+        Err(format!("got unsat on `{}`, expected sat", file_name).into())
+    }
+}
+
+fn run_check_sat_assuming() -> Res<()> {
+    let file_name = "rsc/incremental/check_sat_assuming.smt2";
+    println!("looking at `{}`", file_name);
+    let file = OpenOptions::new()
+        .read(true)
+        .open(file_name)
+        .chain_err(|| format!("while opening file {}", file_name))?;
+    // The first check is unsat, the second one deactivates the clause making it unsat.
+    let (model, _) = read_and_work(file, true, false, true)
+        .chain_err(|| "while reading file and getting model")?;
+    if model.is_some() {
+        println!("- is okay");
+        Ok(())
+    } else {
+        Err(format!("got unsat on `{}`, expected sat", file_name).into())
+    }
+}
+
+/// Runs a file where a reset separates an unsat check from a sat one.
+///
+/// Checks the result of both problems, the first one must not leak into the second one.
+fn run_reset(file_name: &str) -> Res<()> {
+    use std::process::Command;
+
+    println!("looking at `{}`", file_name);
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .arg(file_name)
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.contains("error") {
+        return Err(format!("unexpected failure {}:\n{}", output.status, stdout).into());
+    }
+    let results: Vec<_> = stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| ["sat", "unsat", "unknown"].contains(line))
+        .collect();
+    if results != ["unsat", "sat"] {
+        return Err(format!(
+            "expected `unsat` then `sat` on `{}`, got\n{}",
+            file_name, stdout
+        )
+        .into());
+    }
+    println!("- is okay");
+    Ok(())
+}
+
+/// Runs a file where a reset follows a problem that failed to parse.
+///
+/// The second problem is unsat, it is sat if state from the first problem is still around.
+fn run_reset_after_error() -> Res<()> {
+    let file_name = "rsc/incremental/reset_after_error.smt2";
+    println!("looking at `{}`", file_name);
+    let file = OpenOptions::new()
+        .read(true)
+        .open(file_name)
+        .chain_err(|| format!("while opening file {}", file_name))?;
+    // Keep going on errors, the first problem has one on purpose.
+    let (model, _) = read_and_work(file, true, false, false)
+        .chain_err(|| "while reading file and getting model")?;
+    if model.is_none() {
+        println!("- is okay");
+        Ok(())
+    } else {
+        Err(format!("got sat on `{}`, expected unsat", file_name).into())
+    }
+}
+
+/// Micro-benchmark for constraint subsumption.
+///
+/// Adds `cstr_count` negative constraints over three samples each, and one constraint with a
+/// subset of the lhs every ten constraints, making the larger constraint useless.
+fn run_many_constraints(cstr_count: i64) -> Res<()> {
+    use hoice::data::Data;
+    use std::time::Instant;
+
+    let instance = ::hoice::parse::mc_91();
+    let p_0: PrdIdx = 0.into();
+    let sample = |n: i64, m: i64| {
+        let args: hoice::var_to::vals::RVarVals = vec![val::int(n), val::int(m)].into();
+        (p_0, args)
+    };
+
+    let mut data = Data::new(Arc::new(instance));
+    let start = Instant::now();
+    for n in 0..cstr_count {
+        data.add_data(
+            2.into(),
+            vec![sample(n, 0), sample(n, 1), sample(n, 2)],
+            None,
+        )?;
+        if n % 10 == 0 {
+            data.add_data(2.into(), vec![sample(n, 0), sample(n, 1)], None)?;
+        }
+    }
+    data.propagate()?;
+    let elapsed = start.elapsed();
+    println!(
+        "added {} constraints in {}.{:0>3}s",
+        cstr_count + cstr_count / 10,
+        elapsed.as_secs(),
+        elapsed.subsec_millis()
+    );
+
+    let live = data
+        .constraints
+        .iter()
+        .filter(|cstr| !cstr.is_tautology())
+        .count();
+    if live as i64 != cstr_count {
+        Err(format!("expected {} constraints, got {}", cstr_count, live).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Solves a file and returns the model as a string.
+fn model_string_of(file_name: &str) -> Res<String> {
+    let file = OpenOptions::new()
+        .read(true)
+        .open(file_name)
+        .chain_err(|| format!("while opening file {}", file_name))?;
+    let (model, instance) = read_and_work(file, true, true, true)
+        .chain_err(|| "while reading file and getting model")?;
+    if let Some(model) = model {
+        let mut buff: Vec<u8> = vec![];
+        instance
+            .write_model(&model, &mut buff)
+            .chain_err(|| "while writing model")?;
+        Ok(map_err!(
+            String::from_utf8(buff),
+            "converting model from bytes to utf8"
+        ))
+    } else {
+        Err(format!("got unsat on `{}`, expected sat", file_name).into())
+    }
+}
+
+/// Output of hoice on `rsc/sat/cfg_red.smt2` with some seed, learning with random qualifier
+/// orders so that the seed matters.
+fn seeded_output(seed: u64) -> Res<String> {
+    use std::process::Command;
+
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .args(&["--rand_quals", "on", "--seed"])
+            .arg(seed.to_string())
+            .arg("rsc/sat/cfg_red.smt2")
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() || stdout.lines().next() != Some("sat") {
+        return Err(format!(
+            "unexpected result with seed {} ({}):\n{}",
+            seed, output.status, stdout
+        )
+        .into());
+    }
+    Ok(stdout)
+}
+
+/// Seeds used by `run_reproducible_runs`.
+static reproducible_seeds: &[u64] = &[0, 7, 42];
+
+fn run_reproducible_runs() -> Res<()> {
+    let mut models = Vec::with_capacity(reproducible_seeds.len());
+    for seed in reproducible_seeds {
+        println!("seed {}", seed);
+        let model_1 = seeded_output(*seed)?;
+        let model_2 = seeded_output(*seed)?;
+        if model_1 != model_2 {
+            return Err(format!(
+                "two runs with seed {} yield different models:\n{}\n{}",
+                seed, model_1, model_2
+            )
+            .into());
+        }
+        models.push(model_1)
+    }
+    // Different seeds may or may not yield the same model, they just have to be sat.
+    let distinct = models
+        .iter()
+        .enumerate()
+        .filter(|(idx, model)| !models[..*idx].contains(model))
+        .count();
+    println!(
+        "- is okay, {} distinct model(s) for {} seeds",
+        distinct,
+        reproducible_seeds.len()
+    );
+    Ok(())
+}
+
+/// Maximum number of learning steps in `run_custom_teacher`.
+static custom_teacher_max_steps: usize = 20;
+
+/// Drives the ICE learner with a toy teacher checking candidates on a range of integers.
+fn run_custom_teacher() -> Res<()> {
+    use hoice::learning::ice::Learner;
+
+    let mut instance = ::hoice::parse::instance(
+        "
+(declare-fun custom_teacher_pred (Int) Bool)
+(assert (forall ((x Int)) (=> (= x 0) (custom_teacher_pred x))))
+(assert (forall ((x Int)) (=> (and (custom_teacher_pred x) (< x 0)) false)))
+        ",
+    );
+    instance.finalize()?;
+    let pred: PrdIdx = 0.into();
+    let sample = |n: i64| {
+        let args: hoice::var_to::vals::RVarVals = vec![val::int(n)].into();
+        args
+    };
+
+    let solver = Solver::new(conf.solver.conf(), ())?;
+    let mut learner = Learner::new(Arc::new(instance), solver)?;
+
+    for step in 0..custom_teacher_max_steps {
+        let candidates = if let Some(candidates) = learner.next_candidates()? {
+            candidates
+        } else {
+            return Err(format!("learner returned unknown at step {}", step).into());
+        };
+        let candidate = if let Some(candidate) = candidates[pred].as_ref() {
+            candidate.clone()
+        } else {
+            return Err("learner produced no candidate".into());
+        };
+        println!("step {}: {}", step, candidate);
+
+        let mut okay = true;
+        for n in -10..=10 {
+            let model: VarMap<Val> = vec![val::int(n)].into();
+            let value = candidate.bool_eval(&model)?;
+            if n == 0 && value != Some(true) {
+                okay = false;
+                learner.add_data(0.into(), vec![], Some((pred, sample(n))))?;
+            } else if n < 0 && value != Some(false) {
+                okay = false;
+                learner.add_data(1.into(), vec![(pred, sample(n))], None)?;
+            }
+        }
+
+        if okay {
+            println!("- is okay");
+            return Ok(());
+        }
+    }
+
+    Err(format!(
+        "learner did not converge in {} steps",
+        custom_teacher_max_steps
+    )
+    .into())
+}
+
+/// Maximum number of learning steps in `shared_candidates_steps`.
+static shared_candidates_max_steps: usize = 50;
+
+/// Number of learning steps needed for two identical predicates to reach `x >= 0 /\ y >= 0` on
+/// the points of `[-3, 3]²`.
+///
+/// The toy teacher gives at most one counterexample per predicate and step. The first predicate
+/// starts with all the points, so its first candidate is right. The second one only starts with
+/// `(0, 0)`. Once it has one negative sample, the candidate of the first predicate respects its
+/// data. On its own, it needs at least two negative samples to learn the conjunction.
+fn shared_candidates_steps(share: bool) -> Res<usize> {
+    use hoice::learning::ice::Learner;
+
+    let mut instance = ::hoice::parse::instance(
+        "
+(declare-fun shared_cands_p (Int Int) Bool)
+(declare-fun shared_cands_q (Int Int) Bool)
+(assert (forall ((x Int) (y Int)) (=> (and (= x 0) (= y 0)) (shared_cands_p x y))))
+(assert (forall ((x Int) (y Int)) (=> (and (shared_cands_p x y) (< x 0)) false)))
+(assert (forall ((x Int) (y Int)) (=> (and (= x 0) (= y 0)) (shared_cands_q x y))))
+(assert (forall ((x Int) (y Int)) (=> (and (shared_cands_q x y) (< y 0)) false)))
+        ",
+    );
+    instance.finalize()?;
+    let preds: Vec<PrdIdx> = vec![0.into(), 1.into()];
+    let sample = |x: i64, y: i64| {
+        let args: hoice::var_to::vals::RVarVals = vec![val::int(x), val::int(y)].into();
+        args
+    };
+    let points: Vec<(i64, i64)> = (-3..=3)
+        .flat_map(|x| (-3..=3).map(move |y| (x, y)))
+        .collect();
+    // Adds a sample for `pred`, positive if `x >= 0 /\ y >= 0`, negative otherwise.
+    let add = |learner: &mut Learner, pred: PrdIdx, (x, y): (i64, i64)| -> Res<bool> {
+        if x >= 0 && y >= 0 {
+            learner.add_data(0.into(), vec![], Some((pred, sample(x, y))))
+        } else {
+            learner.add_data(1.into(), vec![(pred, sample(x, y))], None)
+        }
+    };
+
+    let solver = Solver::new(conf.solver.conf(), ())?;
+    let mut learner = Learner::new(Arc::new(instance), solver)?;
+    learner.share_candidates(share);
+
+    for point in &points {
+        add(&mut learner, preds[0], *point)?;
+    }
+    add(&mut learner, preds[1], (0, 0))?;
+
+    for step in 0..shared_candidates_max_steps {
+        let candidates = if let Some(candidates) = learner.next_candidates()? {
+            candidates
+        } else {
+            return Err(format!("learner returned unknown at step {}", step).into());
+        };
+
+        let mut okay = true;
+        for pred in &preds {
+            let candidate = if let Some(candidate) = candidates[*pred].as_ref() {
+                candidate.clone()
+            } else {
+                return Err("learner produced no candidate".into());
+            };
+            println!("step {}, {}: {}", step, pred, candidate);
+            for &(x, y) in &points {
+                let model: VarMap<Val> = vec![val::int(x), val::int(y)].into();
+                if candidate.bool_eval(&model)? != Some(x >= 0 && y >= 0) {
+                    okay = false;
+                    add(&mut learner, *pred, (x, y))?;
+                    break;
+                }
+            }
+        }
+
+        if okay {
+            return Ok(step + 1);
+        }
+    }
+
+    Err(format!(
+        "learner did not converge in {} steps",
+        shared_candidates_max_steps
+    )
+    .into())
+}
+
+/// Sharing candidates between predicates with the same signature saves learning steps.
+fn run_shared_candidates() -> Res<()> {
+    let without = shared_candidates_steps(false)?;
+    let with = shared_candidates_steps(true)?;
+    println!("steps without sharing: {}, with sharing: {}", without, with);
+    if with >= without {
+        Err(format!(
+            "sharing candidates took {} steps, not less than without sharing ({})",
+            with, without
+        )
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Number of learning steps in `run_cached_candidates`.
+static cached_candidates_steps: usize = 5;
+
+/// Candidates reused from a previous step must agree with the implications on the samples.
+///
+/// Only the data of `cached_cands_q` changes between steps, so the candidate for
+/// `cached_cands_p` is a reuse candidate. Sample `(cached_cands_p 3)` stays unclassified: the
+/// candidates are only consistent if the learner classifies it, and propagates the result to
+/// `cached_cands_q`, at every step.
+fn run_cached_candidates() -> Res<()> {
+    use hoice::learning::ice::Learner;
+
+    let mut instance = ::hoice::parse::instance(
+        "
+(declare-fun cached_cands_p (Int) Bool)
+(declare-fun cached_cands_q (Int) Bool)
+(assert (forall ((x Int)) (=> (= x 0) (cached_cands_p x))))
+(assert (forall ((x Int)) (=> (and (cached_cands_p x) (< x 0)) false)))
+(assert (forall ((x Int)) (=> (cached_cands_p x) (cached_cands_q x))))
+(assert (forall ((x Int)) (=> (and (cached_cands_q x) (< x 0)) false)))
+        ",
+    );
+    instance.finalize()?;
+    let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+    let sample = |n: i64| {
+        let args: hoice::var_to::vals::RVarVals = vec![val::int(n)].into();
+        args
+    };
+
+    let solver = Solver::new(conf.solver.conf(), ())?;
+    let mut learner = Learner::new(Arc::new(instance), solver)?;
+    learner.add_data(0.into(), vec![], Some((p, sample(0))))?;
+    learner.add_data(1.into(), vec![(p, sample(-1))], None)?;
+    learner.add_data(2.into(), vec![(p, sample(3))], Some((q, sample(3))))?;
+
+    for step in 0..cached_candidates_steps {
+        let candidates = if let Some(candidates) = learner.next_candidates()? {
+            candidates
+        } else {
+            return Err(format!("learner returned unknown at step {}", step).into());
+        };
+        let (p_cand, q_cand) = match (candidates[p].as_ref(), candidates[q].as_ref()) {
+            (Some(p_cand), Some(q_cand)) => (p_cand.clone(), q_cand.clone()),
+            _ => return Err("learner produced no candidate".into()),
+        };
+        println!("step {}: p = {}, q = {}", step, p_cand, q_cand);
+
+        let model: VarMap<Val> = vec![val::int(3)].into();
+        if p_cand.bool_eval(&model)? == Some(true) && q_cand.bool_eval(&model)? != Some(true) {
+            return Err(format!(
+                "step {}: candidates violate `(cached_cands_p 3) => (cached_cands_q 3)`",
+                step
+            )
+            .into());
+        }
+
+        // New data for `cached_cands_q` only.
+        learner.add_data(3.into(), vec![(q, sample(-(step as i64) - 1))], None)?;
+    }
+
+    Ok(())
+}
+
+/// Runs qualifier synthesis for `sig` on `sample`, with `constants` as the constant pool.
+///
+/// Returns true if `is_target` accepts one of the qualifiers synthesized in at most
+/// `max_increments` increments. Synthesis runs twice per increment so that each theory gets to go
+/// first.
+fn synthesizes<F>(
+    sig: &Sig,
+    constants: &[Val],
+    sample: &VarVals,
+    max_increments: usize,
+    mut is_target: F,
+) -> Res<bool>
+where
+    F: FnMut(&Term) -> Res<bool>,
+{
+    use hoice::learning::ice::synth::SynthSys;
+
+    let mut synth = SynthSys::new(sig);
+    synth.set_constants(constants);
+    let profiler = Profiler::new();
+
+    for increment in 0..=max_increments {
+        if increment > 0 {
+            synth.increment()
+        }
+        for _ in 0..2 {
+            if synth.sample_synth(sample, |qual| is_target(&qual), &profiler)? {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Qualifier synthesis should relate an `Int` and a `Real` argument within a few increments.
+fn run_cross_theory_synth() -> Res<()> {
+    let max_increments = 2;
+    let sig: Sig = vec![typ::int(), typ::real()].into();
+    let sample =
+        hoice::var_to::vals::new(vec![val::int(3), val::real(Rat::from_integer(3.into()))]);
+
+    // True if `qual` is equivalent to `(to_real x) <= r` on a few points.
+    let is_target = |qual: &Term| -> Res<bool> {
+        for x in -5..=5 {
+            for half_r in -10..=10 {
+                let r = Rat::new(half_r.into(), 2.into());
+                let target = Rat::from_integer(x.into()) <= r;
+                let model: VarMap<Val> = vec![val::int(x), val::real(r)].into();
+                if qual.bool_eval(&model)? != Some(target) {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    };
+
+    if synthesizes(&sig, &[], &sample, max_increments, is_target)? {
+        Ok(())
+    } else {
+        Err(format!(
+            "failed to synthesize `(to_real x) <= r` in {} increments",
+            max_increments
+        )
+        .into())
+    }
+}
+
+/// Relations with the constant pool are part of the first level of synthesis.
+fn run_constant_pool_synth() -> Res<()> {
+    let sig: Sig = vec![typ::int(), typ::int()].into();
+    let constants = [val::int(4096), val::real(Rat::from_integer(7.into()))];
+    let sample = hoice::var_to::vals::new(vec![val::int(3), val::int(-2)]);
+
+    // 4096 does not appear in the sample, only in the constant pool.
+    let target = term::le(term::int_var(0), term::int(4096));
+
+    if synthesizes(&sig, &constants, &sample, 0, |qual| Ok(*qual == target))? {
+        Ok(())
+    } else {
+        Err(format!("failed to synthesize `{}` without increments", target).into())
+    }
+}
+
+/// Qualifier synthesis should relate a `Bool` argument to an `Int` one within a few increments.
+fn run_bool_synth() -> Res<()> {
+    let max_increments = 2;
+    let sig: Sig = vec![typ::bool(), typ::int()].into();
+    let sample = hoice::var_to::vals::new(vec![val::bool(true), val::int(3)]);
+
+    // True if `qual` is equivalent to `b = (x >= 0)` on a few points.
+    let is_target = |qual: &Term| -> Res<bool> {
+        for b in &[true, false] {
+            for x in -5..=5 {
+                let target = *b == (x >= 0);
+                let model: VarMap<Val> = vec![val::bool(*b), val::int(x)].into();
+                if qual.bool_eval(&model)? != Some(target) {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    };
+
+    if synthesizes(&sig, &[], &sample, max_increments, is_target)? {
+        Ok(())
+    } else {
+        Err(format!(
+            "failed to synthesize `b = (x >= 0)` in {} increments",
+            max_increments
+        )
+        .into())
+    }
+}
+
+/// Running hoice with a solver command that does not exist fails right away with a dedicated exit
+/// code.
+fn run_missing_solver() -> Res<()> {
+    use std::process::Command;
+
+    let bogus = "hoice_test_no_such_solver";
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .args(&["--z3", bogus, "rsc/sat/ground_facts.smt2"])
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if output.status.code() != Some(3) {
+        return Err(format!("unexpected exit status {}:\n{}", output.status, stdout).into());
+    }
+    if !stdout.contains("could not run") || !stdout.contains(bogus) || !stdout.contains("--z3") {
+        return Err(format!("unexpected error message:\n{}", stdout).into());
+    }
+
+    // Parse-only mode does not need the solver.
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .args(&[
+                "--z3",
+                bogus,
+                "--parse_only",
+                "on",
+                "rsc/sat/ground_facts.smt2",
+            ])
+            .output(),
+        "while running hoice"
+    );
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Err(format!(
+            "parse-only mode failed with status {}:\n{}",
+            output.status, stdout
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Instances with more clauses than `--max_clauses` are not attempted.
+fn run_max_clauses() -> Res<()> {
+    use std::process::Command;
+
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .args(&["--max_clauses", "1", "rsc/sat/ground_facts.smt2"])
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if !output.status.success() {
+        return Err(format!("unexpected exit status {}:\n{}", output.status, stdout).into());
+    }
+    if stdout.lines().next() != Some("unknown") || !stdout.contains("instance too large") {
+        return Err(format!("unexpected output:\n{}", stdout).into());
+    }
+    Ok(())
+}
+
+/// `cfg_red` does not inline predicates when the result would exceed `--max_clause_vars`.
+fn run_cfg_red_var_budget() -> Res<()> {
+    use std::process::Command;
+
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .args(&[
+                "--max_clause_vars",
+                "1",
+                "--explain_preproc",
+                "on",
+                "rsc/sat/cfg_red_budget.smt2",
+            ])
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // The explanations are printed as comments before the result.
+    let result = stdout.lines().find(|line| !line.starts_with(';'));
+    if !output.status.success() || result != Some("sat") {
+        return Err(format!("unexpected result {}:\n{}", output.status, stdout).into());
+    }
+    let skipped = stdout.lines().any(|line| {
+        line.contains("cfg_red") && line.contains("unfolding exceeds the variable budget")
+    });
+    if !skipped {
+        return Err(format!("cfg_red did not hit the variable budget:\n{}", stdout).into());
+    }
+    Ok(())
+}
+
+/// First line of the output of hoice on `rsc/sat/int_overflow.smt2` with some integer width.
+fn int_width_result(width: &str) -> Res<String> {
+    use std::process::Command;
+
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .args(&["--int_width", width, "rsc/sat/int_overflow.smt2"])
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(format!("unexpected exit status {}:\n{}", output.status, stdout).into());
+    }
+    Ok(stdout.lines().next().unwrap_or("").into())
+}
+
+/// Overflows on machine integers make `rsc/sat/int_overflow.smt2` unsafe.
+fn run_int_width() -> Res<()> {
+    let unbounded = int_width_result("0")?;
+    if unbounded != "sat" {
+        return Err(format!("expected sat on mathematical integers, got `{}`", unbounded).into());
+    }
+    let bounded = int_width_result("8")?;
+    if bounded != "unsat" {
+        return Err(format!("expected unsat on 8-bit integers, got `{}`", bounded).into());
+    }
+    Ok(())
+}
+
+/// Predicates forced by pre-processing have a definition in the model.
+fn run_forced_preds_in_model() -> Res<()> {
+    let file_name = "rsc/sat/forced_preds.smt2";
+    println!("looking at `{}`", file_name);
+    let model = model_string_of(file_name)?;
+    for pred in &["inv", "src", "snk", "unused"] {
+        if !model.contains(&format!("(define-fun {}\n", pred)) {
+            return Err(format!("no definition for `{}` in model\n{}", pred, model).into());
+        }
+    }
+    Ok(())
+}
+
+/// Unsat proofs are produced when clauses apply predicates to non-variable arguments.
+fn run_proof_complex_args() -> Res<()> {
+    use std::process::Command;
+
+    let file_name = "rsc/unsat/proof_complex_args.smt2";
+    println!("looking at `{}`", file_name);
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .arg(file_name)
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if !output.status.success() || stdout.contains("error") {
+        return Err(format!("unexpected failure {}:\n{}", output.status, stdout).into());
+    }
+    let mut lines = stdout.lines();
+    if lines.next() != Some("unsat") {
+        return Err(format!("expected unsat:\n{}", stdout).into());
+    }
+    // The only possible entry point is the sample of the positive clause, and there must be one.
+    let proof: Vec<_> = lines
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let legal = proof.len() >= 3
+        && proof[0] == "("
+        && proof[proof.len() - 1] == ")"
+        && proof[1..proof.len() - 1]
+            .iter()
+            .all(|line| *line == "(inv 0 0)");
+    if !legal {
+        return Err(format!("unexpected proof:\n{}", stdout).into());
+    }
+    Ok(())
+}
+
+/// `(set-option :produce-proofs true)` turns on sample tracking, which yields a proof.
+fn run_proof_sample_tracking() -> Res<()> {
+    use std::process::Command;
+
+    let file_name = "rsc/unsat/core_1.smt2";
+    println!("looking at `{}`", file_name);
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .arg(file_name)
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if !output.status.success() || stdout.contains("error") {
+        return Err(format!("unexpected failure {}:\n{}", output.status, stdout).into());
+    }
+    let mut lines = stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    if lines.next() != Some("unsat") {
+        return Err(format!("expected unsat:\n{}", stdout).into());
+    }
+    // Skip the answer to `(get-unsat-core)`, the proof is the last s-expression.
+    let proof: Vec<_> = lines.skip_while(|line| *line != "(").collect();
+    let legal = proof.len() >= 3
+        && proof[proof.len() - 1] == ")"
+        && proof[1..proof.len() - 1]
+            .iter()
+            .all(|line| line.starts_with("(mc_91_out "));
+    if !legal {
+        return Err(format!("unexpected proof:\n{}", stdout).into());
+    }
+    Ok(())
+}
+
+/// Runs the binary in `--chc_comp` mode and returns its stdout.
+fn chc_comp_stdout(file_name: &str) -> Res<String> {
+    use std::process::Command;
+
+    println!("looking at `{}`", file_name);
+    // Verbose, warnings and logs must not reach stdout.
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .args(&["--chc_comp", "on", "-v", "--stats", "on", file_name])
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() {
+        return Err(format!("unexpected exit status {}:\n{}", output.status, stdout).into());
+    }
+    if stdout.contains('\u{1b}') {
+        return Err(format!("color codes on stdout:\n{}", stdout).into());
+    }
+    Ok(stdout)
+}
+
+/// In `--chc_comp` mode, stdout only contains the result and the model.
+fn run_chc_comp() -> Res<()> {
+    let stdout = chc_comp_stdout("rsc/unsat/mc_91.smt2")?;
+    if stdout != "unsat\n" {
+        return Err(format!("expected exactly `unsat`:\n{}", stdout).into());
+    }
+
+    let stdout = chc_comp_stdout("rsc/sat/clause_split.smt2")?;
+    let mut lines = stdout.lines();
+    if lines.next() != Some("sat") {
+        return Err(format!("expected `sat`:\n{}", stdout).into());
+    }
+    let model: Vec<_> = lines.collect();
+    let mut depth = 0;
+    for line in &model {
+        for c in line.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => (),
+            }
+        }
+    }
+    let legal = model.first() == Some(&"(model")
+        && model.last() == Some(&")")
+        && depth == 0
+        && model.iter().all(|line| !line.trim_start().starts_with(';'));
+    if !legal {
+        return Err(format!("unexpected model:\n{}", stdout).into());
+    }
+    Ok(())
+}
+
+/// `--profile_to` collects profiling information without `--stats`, and writes it without styling.
+fn run_profile_to() -> Res<()> {
+    use std::process::Command;
+
+    let path = ::std::env::temp_dir().join("hoice_test_profile_to.txt");
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .arg("--profile_to")
+            .arg(&path)
+            .arg("rsc/sat/ground_facts.smt2")
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(format!("unexpected exit status {}:\n{}", output.status, stdout).into());
+    }
+    if stdout.contains("; top {") {
+        return Err(format!("profiling information on stdout:\n{}", stdout).into());
+    }
+
+    let profile = map_err!(
+        ::std::fs::read_to_string(&path),
+        "while reading `{}`",
+        path.display()
+    );
+    if !profile.contains("; top {") || profile.contains('\u{1b}') {
+        return Err(format!("unexpected profiling information:\n{}", profile).into());
+    }
+    Ok(())
+}
+
+/// `--profile_json` collects profiling information without `--stats`, but does not print it.
+fn run_profile_json() -> Res<()> {
+    use std::process::Command;
+
+    let path = ::std::env::temp_dir().join("hoice_test_profile_json.json");
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .arg("--profile_json")
+            .arg(&path)
+            .arg("rsc/sat/ground_facts.smt2")
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(format!("unexpected exit status {}:\n{}", output.status, stdout).into());
+    }
+    if stdout.contains("; top {") {
+        return Err(format!("profiling information on stdout:\n{}", stdout).into());
+    }
+
+    let json = map_err!(
+        ::std::fs::read_to_string(&path),
+        "while reading `{}`",
+        path.display()
+    );
+    let json: String = json.chars().filter(|c| !c.is_whitespace()).collect();
+    if json == "{}" || !json.starts_with('{') {
+        return Err(format!("unexpected profiling information:\n{}", json).into());
+    }
+    Ok(())
+}
+
+/// `--model_lets on` factors the subterms repeated in a definition with let-bindings.
+fn run_model_lets() -> Res<()> {
+    use std::process::Command;
+
+    let file_name = "rsc/sat/model_lets.smt2";
+    println!("looking at `{}`", file_name);
+    let output = map_err!(
+        Command::new(env!("CARGO_BIN_EXE_hoice"))
+            .args(&["--model_lets", "on", file_name])
+            .output(),
+        "while running hoice"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.contains("error") {
+        return Err(format!("unexpected failure {}:\n{}", output.status, stdout).into());
+    }
+    if stdout.lines().next() != Some("sat") {
+        return Err(format!("expected sat:\n{}", stdout).into());
+    }
+    // The definition of `p` mentions `(>= x 7)` twice.
+    let model = &stdout[stdout.find("(define-fun p").unwrap_or(stdout.len())..];
+    if !model.contains("(let") {
+        return Err(format!("expected let-bindings in the model:\n{}", stdout).into());
+    }
+    Ok(())
+}